@@ -346,6 +346,15 @@ pub(crate) struct BadDeriveTarget {
 #[diag("building tests with panic=abort is not supported without `-Zpanic_abort_tests`")]
 pub(crate) struct TestsNotSupport {}
 
+// Ferrocene addition
+#[derive(Diagnostic)]
+#[diag("benchmarks are not supported by the `no_std` test harness")]
+#[note("the harness is enabled by `-Z no-std-test-harness`")]
+pub(crate) struct NoStdTestHarnessBench {
+    #[primary_span]
+    pub(crate) span: Span,
+}
+
 #[derive(Diagnostic)]
 #[diag("expected path to a trait, found literal", code = E0777)]
 pub(crate) struct BadDeriveLit {
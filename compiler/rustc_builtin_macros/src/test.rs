@@ -158,38 +158,43 @@ pub(crate) fn expand_test_or_bench(
         };
     }
 
+    // Ferrocene addition: the `no_std` harness lives in `core`, and doesn't run benchmarks.
+    let no_std_harness = cx.sess.opts.unstable_opts.no_std_test_harness;
+    if no_std_harness && is_bench {
+        cx.dcx().emit_err(diagnostics::NoStdTestHarnessBench { span: attr_sp });
+        return if is_stmt {
+            vec![Annotatable::Stmt(Box::new(cx.stmt_item(item.span, item)))]
+        } else {
+            vec![Annotatable::Item(item)]
+        };
+    }
+
     let sp = cx.with_def_site_ctxt(item.span);
     let ret_ty_sp = cx.with_def_site_ctxt(fn_.sig.decl.output.span());
     let attr_sp = cx.with_def_site_ctxt(attr_sp);
 
     let test_ident = Ident::new(sym::test, attr_sp);
 
+    // Ferrocene addition: the path of the harness items, `test` or, with the `no_std` harness,
+    // `test::ferrocene_test::harness` where `test` is `core`.
+    let mut harness = vec![test_ident];
+    if no_std_harness {
+        harness.push(Ident::new(sym::ferrocene_test, sp));
+        harness.push(Ident::from_str_and_span("harness", sp));
+    }
+    let harness_path = |span, names: &[&str]| {
+        let names = names.iter().map(|name| Ident::from_str_and_span(name, sp));
+        cx.path(span, harness.iter().copied().chain(names).collect())
+    };
+
     // creates test::$name
-    let test_path = |name| cx.path(ret_ty_sp, vec![test_ident, Ident::from_str_and_span(name, sp)]);
+    let test_path = |name| harness_path(ret_ty_sp, &[name]);
 
     // creates test::ShouldPanic::$name
-    let should_panic_path = |name| {
-        cx.path(
-            sp,
-            vec![
-                test_ident,
-                Ident::from_str_and_span("ShouldPanic", sp),
-                Ident::from_str_and_span(name, sp),
-            ],
-        )
-    };
+    let should_panic_path = |name| harness_path(sp, &["ShouldPanic", name]);
 
     // creates test::TestType::$name
-    let test_type_path = |name| {
-        cx.path(
-            sp,
-            vec![
-                test_ident,
-                Ident::from_str_and_span("TestType", sp),
-                Ident::from_str_and_span(name, sp),
-            ],
-        )
-    };
+    let test_type_path = |name| harness_path(sp, &["TestType", name]);
 
     // creates $name: $expr
     let field = |name, expr| cx.field_imm(sp, Ident::from_str_and_span(name, sp), expr);
@@ -378,8 +383,10 @@ pub(crate) fn expand_test_or_bench(
     test_const.vis.kind = ast::VisibilityKind::Public;
 
     // extern crate test
+    // Ferrocene addition: extern crate core as test, for the `no_std` harness
+    let test_crate = no_std_harness.then_some(sym::core);
     let test_extern =
-        cx.item(sp, ast::AttrVec::new(), ast::ItemKind::ExternCrate(None, test_ident));
+        cx.item(sp, ast::AttrVec::new(), ast::ItemKind::ExternCrate(test_crate, test_ident));
 
     let item = {
         let mut item = item;
@@ -65,7 +65,10 @@ pub fn inject(
     let test_runner = get_test_runner(sess, krate);
 
     if sess.is_test_crate() {
-        let panic_strategy = match (panic_strategy, sess.opts.unstable_opts.panic_abort_tests) {
+        // Ferrocene addition: the `no_std` harness never unwinds.
+        let panic_abort_tests = sess.opts.unstable_opts.panic_abort_tests
+            || sess.opts.unstable_opts.no_std_test_harness;
+        let panic_strategy = match (panic_strategy, panic_abort_tests) {
             (PanicStrategy::Abort | PanicStrategy::ImmediateAbort, true) => panic_strategy,
             (PanicStrategy::Abort | PanicStrategy::ImmediateAbort, false) => {
                 if panic_strategy == platform_panic_strategy {
@@ -236,7 +239,8 @@ fn generate_test_harness(
     let expn_id = ext_cx.resolver.expansion_for_ast_pass(
         DUMMY_SP,
         AstPass::TestHarness,
-        &[sym::test, sym::rustc_attrs, sym::coverage_attribute],
+        // Ferrocene addition: `ferrocene_test` for the harness of `-Z no-std-test-harness`
+        &[sym::test, sym::rustc_attrs, sym::coverage_attribute, sym::ferrocene_test],
         None,
     );
     let def_site = DUMMY_SP.with_def_site_ctxt(expn_id.to_expn_id());
@@ -287,17 +291,33 @@ fn generate_test_harness(
 /// [`TestCtxt::reexport_test_harness_main`] provides a different name for the `main`
 /// function and [`TestCtxt::test_runner`] provides a path that replaces
 /// `test::test_main_static`.
+///
+/// Ferrocene addition: with `-Z no-std-test-harness`, `test` is `core` and the runner is
+/// `test::ferrocene_test::harness::test_main_static`.
 fn mk_main(cx: &mut TestCtxt<'_>) -> Box<ast::Item> {
     let sp = cx.def_site;
     let ecx = &cx.ext_cx;
     let test_ident = Ident::new(sym::test, sp);
+    let no_std_harness = ecx.sess.opts.unstable_opts.no_std_test_harness;
 
     let runner_name =
         if cx.panic_strategy.unwinds() { "test_main_static" } else { "test_main_static_abort" };
 
     // test::test_main_static(...)
     let mut test_runner = cx.test_runner.clone().unwrap_or_else(|| {
-        ecx.path(sp, vec![test_ident, Ident::from_str_and_span(runner_name, sp)])
+        if no_std_harness {
+            ecx.path(
+                sp,
+                vec![
+                    test_ident,
+                    Ident::new(sym::ferrocene_test, sp),
+                    Ident::from_str_and_span("harness", sp),
+                    Ident::from_str_and_span("test_main_static", sp),
+                ],
+            )
+        } else {
+            ecx.path(sp, vec![test_ident, Ident::from_str_and_span(runner_name, sp)])
+        }
     });
 
     test_runner.span = sp;
@@ -307,9 +327,11 @@ fn mk_main(cx: &mut TestCtxt<'_>) -> Box<ast::Item> {
     let call_test_main = ecx.stmt_expr(call_test_main);
 
     // extern crate test
+    // Ferrocene addition: extern crate core as test, for the `no_std` harness
+    let test_crate = no_std_harness.then_some(sym::core);
     let test_extern_stmt = ecx.stmt_item(
         sp,
-        ecx.item(sp, ast::AttrVec::new(), ast::ItemKind::ExternCrate(None, test_ident)),
+        ecx.item(sp, ast::AttrVec::new(), ast::ItemKind::ExternCrate(test_crate, test_ident)),
     );
 
     // #[rustc_main]
@@ -218,7 +218,9 @@ pub(crate) fn target_machine_factory(
     let abi = SmallCStr::new(sess.target.llvm_abiname.desc());
     let trap_unreachable =
         sess.opts.unstable_opts.trap_unreachable.unwrap_or(sess.target.trap_unreachable);
-    let emit_stack_size_section = sess.opts.unstable_opts.emit_stack_sizes;
+    // Ferrocene addition: `-Z stack-usage-report` reads the frame sizes from this section.
+    let emit_stack_size_section = sess.opts.unstable_opts.emit_stack_sizes
+        || sess.opts.unstable_opts.stack_usage_report.is_some();
 
     let verbose_asm = sess.opts.unstable_opts.verbose_asm;
     let relax_elf_relocations =
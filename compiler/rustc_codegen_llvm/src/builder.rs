@@ -2182,4 +2182,45 @@ pub(crate) fn instrprof_increment(
     ) {
         self.call_intrinsic("llvm.instrprof.increment", &[], &[fn_name, hash, num_counters, index]);
     }
+
+    /// Emits a call to `llvm.instrprof.mcdc.parameters`, declaring the size of
+    /// the function's MC/DC test vector bitmap. (Ferrocene addition)
+    #[instrument(level = "debug", skip(self))]
+    pub(crate) fn mcdc_parameters(
+        &mut self,
+        fn_name: &'ll Value,
+        hash: &'ll Value,
+        bitmap_bits: &'ll Value,
+    ) {
+        self.call_intrinsic("llvm.instrprof.mcdc.parameters", &[], &[fn_name, hash, bitmap_bits]);
+    }
+
+    /// Emits a call to `llvm.instrprof.mcdc.tvbitmap.update`, recording the
+    /// test vector held in `mcdc_temp`. (Ferrocene addition)
+    #[instrument(level = "debug", skip(self))]
+    pub(crate) fn mcdc_tvbitmap_update(
+        &mut self,
+        fn_name: &'ll Value,
+        hash: &'ll Value,
+        bitmap_index: &'ll Value,
+        mcdc_temp: &'ll Value,
+    ) {
+        let args = &[fn_name, hash, bitmap_index, mcdc_temp];
+        self.call_intrinsic("llvm.instrprof.mcdc.tvbitmap.update", &[], args);
+    }
+
+    /// Resets the condition bitmap in `mcdc_temp`. (Ferrocene addition)
+    #[instrument(level = "debug", skip(self))]
+    pub(crate) fn mcdc_condbitmap_reset(&mut self, mcdc_temp: &'ll Value) {
+        self.store(self.const_i32(0), mcdc_temp, self.tcx.data_layout.i32_align);
+    }
+
+    /// Adds `cond_index` to the test vector index in `mcdc_temp`. (Ferrocene addition)
+    #[instrument(level = "debug", skip(self))]
+    pub(crate) fn mcdc_condbitmap_update(&mut self, cond_index: &'ll Value, mcdc_temp: &'ll Value) {
+        let align = self.tcx.data_layout.i32_align;
+        let current_tv_index = self.load(self.cx.type_i32(), mcdc_temp, align);
+        let new_tv_index = self.add(current_tv_index, cond_index);
+        self.store(new_tv_index, mcdc_temp, align);
+    }
 }
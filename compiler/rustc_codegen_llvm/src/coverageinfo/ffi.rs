@@ -98,3 +98,67 @@ pub(crate) struct BranchRegion {
     pub(crate) true_counter: Counter,
     pub(crate) false_counter: Counter,
 }
+
+// Ferrocene addition
+pub(crate) mod mcdc {
+    use rustc_middle::mir::coverage::{ConditionId, ConditionInfo, DecisionInfo};
+
+    /// Must match the layout of `LLVMRustMCDCDecisionParameters`.
+    #[derive(Clone, Copy, Debug, Default)]
+    #[repr(C)]
+    pub(crate) struct DecisionParameters {
+        bitmap_idx: u32,
+        num_conditions: u16,
+    }
+
+    /// Corresponds to `llvm::coverage::mcdc::ConditionID`, where `-1` means
+    /// that the outcome of the decision is known.
+    type LLVMConditionId = i16;
+
+    /// Must match the layout of `LLVMRustMCDCBranchParameters`.
+    #[derive(Clone, Copy, Debug, Default)]
+    #[repr(C)]
+    pub(crate) struct BranchParameters {
+        condition_id: LLVMConditionId,
+        /// The conditions evaluated next when this one is false and true.
+        condition_ids: [LLVMConditionId; 2],
+    }
+
+    impl From<ConditionInfo> for BranchParameters {
+        fn from(value: ConditionInfo) -> Self {
+            let to_llvm_cond_id = |cond_id: Option<ConditionId>| {
+                cond_id.and_then(|id| LLVMConditionId::try_from(id.as_usize()).ok()).unwrap_or(-1)
+            };
+            let ConditionInfo { condition_id, true_next_id, false_next_id } = value;
+            Self {
+                condition_id: to_llvm_cond_id(Some(condition_id)),
+                condition_ids: [to_llvm_cond_id(false_next_id), to_llvm_cond_id(true_next_id)],
+            }
+        }
+    }
+
+    impl From<DecisionInfo> for DecisionParameters {
+        fn from(info: DecisionInfo) -> Self {
+            let DecisionInfo { bitmap_idx, num_conditions } = info;
+            Self { bitmap_idx, num_conditions }
+        }
+    }
+}
+
+/// Must match the layout of `LLVMRustCoverageMCDCBranchRegion`. (Ferrocene addition)
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub(crate) struct MCDCBranchRegion {
+    pub(crate) cov_span: CoverageSpan,
+    pub(crate) true_counter: Counter,
+    pub(crate) false_counter: Counter,
+    pub(crate) mcdc_branch_params: mcdc::BranchParameters,
+}
+
+/// Must match the layout of `LLVMRustCoverageMCDCDecisionRegion`. (Ferrocene addition)
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub(crate) struct MCDCDecisionRegion {
+    pub(crate) cov_span: CoverageSpan,
+    pub(crate) mcdc_decision_params: mcdc::DecisionParameters,
+}
@@ -2198,6 +2198,10 @@ pub(crate) fn LLVMRustCoverageWriteFunctionMappingsToBuffer(
         NumExpansionRegions: size_t,
         BranchRegions: *const crate::coverageinfo::ffi::BranchRegion,
         NumBranchRegions: size_t,
+        MCDCBranchRegions: *const crate::coverageinfo::ffi::MCDCBranchRegion,
+        NumMCDCBranchRegions: size_t,
+        MCDCDecisionRegions: *const crate::coverageinfo::ffi::MCDCDecisionRegion,
+        NumMCDCDecisionRegions: size_t,
         BufferOut: &RustString,
     );
 
@@ -323,6 +323,13 @@ pub fn link_binary(
         sess.dcx().abort_if_errors();
     }
 
+    // Ferrocene addition: this must happen before linking, which may remove the object files.
+    if let Some(path) = &sess.opts.unstable_opts.stack_usage_report {
+        sess.time("stack_usage_report", || {
+            super::stack_usage::write_report(sess, &compiled_modules, &crate_info, path)
+        });
+    }
+
     for &crate_type in &crate_info.crate_types {
         // Ignore executable crates if we have -Z no-codegen, as they will error.
         if (sess.opts.unstable_opts.no_codegen || !sess.opts.output_types.should_codegen())
@@ -11,6 +11,8 @@
 pub mod metadata;
 pub mod rmeta_link;
 pub(crate) mod rpath;
+// Ferrocene addition
+mod stack_usage;
 mod symbol_edit;
 pub mod symbol_export;
 pub mod write;
@@ -0,0 +1,504 @@
+//! Ferrocene addition: the report written by `-Z stack-usage-report`.
+//!
+//! The frame size of each function is read from the `.stack_sizes` sections that LLVM emits with
+//! `-Z emit-stack-sizes`, which are only supported on ELF targets. They are read from the objects
+//! of the current crate and from the objects of the linked rlibs, and combined with the call graph
+//! computed by `rustc_monomorphize` (see [`StackUsageCallGraph`]) to find the worst-case stack
+//! usage of each mono root:
+//!
+//! ```json
+//! {
+//!   "roots": [
+//!     {
+//!       "function": "task_entry",
+//!       "stack_size": 96,
+//!       "bounded": true,
+//!       "worst_case_path": [
+//!         { "function": "task_entry", "frame_size": 32 },
+//!         { "function": "_ZN4task7process17h0123456789abcdefE", "frame_size": 64 }
+//!       ],
+//!       "recursive_functions": [],
+//!       "indirect_calls": [],
+//!       "unknown_frame_sizes": [],
+//!       "unknown_callees": [],
+//!       "inlined_functions": ["_ZN4task6helper17h0123456789abcdefE"]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! The backend calls functions that don't appear in MIR, e.g. `memcpy` for large copies or
+//! `__aeabi_uldivmod` for 64-bit divisions on 32-bit Arm, so the call graph is completed with the
+//! functions referenced by the relocations of the machine code of each function. Functions that
+//! were inlined into all their callers have no code of their own: their frame is accounted for by
+//! their callers' and they are listed in `inlined_functions`.
+//!
+//! The stack usage of a root is unbounded if a function reachable from it is recursive, or calls a
+//! function pointer or a trait object method. In that case `stack_size` is the usage of the deepest
+//! path that doesn't go through a recursive call, which is only a lower bound.
+//!
+//! Functions whose frame size is unknown, e.g. because their crate was compiled without
+//! `-Z emit-stack-sizes`, are counted as using no stack. Functions codegened outside the current
+//! crate, including the ones inserted by the backend, are listed as unknown callees, since we don't
+//! know whether they make indirect calls. Both are listed in the report, and also make
+//! `stack_size` a lower bound, so the root is only `bounded` if there are none.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write as _};
+use std::path::Path;
+
+use object::read::archive::ArchiveFile;
+use object::{
+    Object, ObjectSection, ObjectSymbol, RelocationTarget, SectionIndex, SectionKind, SymbolKind,
+};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexSet};
+use rustc_data_structures::graph::scc::Sccs;
+use rustc_data_structures::graph::{DirectedGraph, Successors};
+use rustc_data_structures::memmap::Mmap;
+use rustc_index::{IndexVec, newtype_index};
+use rustc_middle::mono::StackUsageCallGraph;
+use rustc_session::Session;
+
+use crate::back::link::each_linked_rlib;
+use crate::{CompiledModules, CrateInfo, diagnostics};
+
+pub(crate) fn write_report(
+    sess: &Session,
+    compiled_modules: &CompiledModules,
+    crate_info: &CrateInfo,
+    path: &Path,
+) {
+    let Some(call_graph) = &crate_info.stack_usage_call_graph else { return };
+
+    let mut functions = ObjectFunctions { local_objects_read: true, ..Default::default() };
+    let objects = compiled_modules
+        .modules
+        .iter()
+        .chain(&compiled_modules.allocator_module)
+        .filter_map(|module| module.object.as_deref());
+    for object in objects {
+        if !read_functions(sess, object, &mut functions) {
+            functions.local_objects_read = false;
+        }
+    }
+    // If the rlibs can't be found, the linker will report it. Their functions are then listed as
+    // having unknown frame sizes.
+    let _ = each_linked_rlib(crate_info, None, &mut |_, rlib| {
+        read_functions(sess, rlib, &mut functions);
+    });
+
+    let report = Report::compute(call_graph, &functions);
+    if let Err(error) = report.write_to(path) {
+        sess.dcx().emit_fatal(diagnostics::CouldntWriteStackUsageReport {
+            path,
+            error: error.to_string(),
+        });
+    }
+}
+
+/// What the object files tell about the functions they define.
+#[derive(Default)]
+struct ObjectFunctions {
+    /// The frame size of each function, from the `.stack_sizes` sections.
+    frame_sizes: FxHashMap<String, u64>,
+    /// The symbols referenced by the machine code of each function defined in the objects, from
+    /// the relocations of its section. Unlike the call graph built from MIR, these include the
+    /// calls inserted by the backend (e.g. to `memcpy` or `__aeabi_uldivmod`), and don't include
+    /// the functions that were inlined.
+    references: FxHashMap<String, FxIndexSet<String>>,
+    /// The symbols defined as data rather than as functions.
+    data: FxHashSet<String>,
+    /// Whether all the objects of the current crate were read. Functions of the current crate
+    /// missing from them were then inlined into all their callers.
+    local_objects_read: bool,
+}
+
+/// Reads the functions of an object file or an archive of object files, returning whether it
+/// succeeded.
+fn read_functions(sess: &Session, path: &Path, functions: &mut ObjectFunctions) -> bool {
+    match try_read_functions(path, functions) {
+        Ok(()) => true,
+        Err(error) => {
+            sess.dcx().emit_warn(diagnostics::CouldntReadStackSizes { path, error });
+            false
+        }
+    }
+}
+
+fn try_read_functions(path: &Path, functions: &mut ObjectFunctions) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let data = unsafe { Mmap::map(file) }.map_err(|e| e.to_string())?;
+    if data.starts_with(&object::archive::MAGIC) {
+        let archive = ArchiveFile::parse(&*data).map_err(|e| e.to_string())?;
+        for member in archive.members() {
+            let member_data =
+                member.and_then(|member| member.data(&*data)).map_err(|e| e.to_string())?;
+            // Skip the metadata and bitcode in rlibs, which are not object files.
+            if let Ok(object) = object::File::parse(member_data) {
+                read_object_functions(&object, functions)?;
+            }
+        }
+        Ok(())
+    } else {
+        let object = object::File::parse(&*data).map_err(|e| e.to_string())?;
+        read_object_functions(&object, functions)
+    }
+}
+
+fn read_object_functions(
+    object: &object::File<'_>,
+    functions: &mut ObjectFunctions,
+) -> Result<(), String> {
+    // Relocations against local functions are usually made against their section instead, so
+    // index the functions by their section and address. The lowest bit of the address is masked,
+    // since it's set for Thumb functions.
+    let mut by_address = FxHashMap::default();
+    let mut by_section: FxHashMap<SectionIndex, Vec<(u64, &str)>> = FxHashMap::default();
+    for symbol in object.symbols() {
+        let (Some(section), Ok(name)) = (symbol.section_index(), symbol.name()) else { continue };
+        match symbol.kind() {
+            SymbolKind::Text => {
+                by_address.insert((section, symbol.address() & !1), name);
+                by_section.entry(section).or_default().push((symbol.address() & !1, name));
+                functions.references.entry(name.to_owned()).or_default();
+            }
+            SymbolKind::Data | SymbolKind::Tls => {
+                functions.data.insert(name.to_owned());
+            }
+            _ => {}
+        }
+    }
+    for section_functions in by_section.values_mut() {
+        section_functions.sort_unstable();
+    }
+
+    let address_size = if object.is_64() { 8 } else { 4 };
+    for section in object.sections() {
+        if section.name().ok() != Some(".stack_sizes") {
+            continue;
+        }
+        let data = section.data().map_err(|e| e.to_string())?;
+        for (offset, relocation) in section.relocations() {
+            let RelocationTarget::Symbol(index) = relocation.target() else { continue };
+            let symbol = object.symbol_by_index(index).map_err(|e| e.to_string())?;
+            let Ok(offset) = usize::try_from(offset) else { continue };
+            let Some(address) = data.get(offset..offset + address_size) else { continue };
+            let Some(size) = read_uleb128(&data[offset + address_size..]) else {
+                return Err("truncated `.stack_sizes` section".to_owned());
+            };
+
+            let name = if symbol.kind() == SymbolKind::Section {
+                let addend = if relocation.has_implicit_addend() {
+                    read_address(address, object.is_little_endian())
+                } else {
+                    relocation.addend() as u64
+                };
+                let Some(section) = symbol.section_index() else { continue };
+                let Some(&name) = by_address.get(&(section, addend & !1)) else { continue };
+                name
+            } else {
+                symbol.name().map_err(|e| e.to_string())?
+            };
+
+            // Functions may be codegened in more than one object, with different optimizations.
+            let frame_size = functions.frame_sizes.entry(name.to_owned()).or_default();
+            *frame_size = (*frame_size).max(size);
+        }
+    }
+
+    for section in object.sections() {
+        if section.kind() != SectionKind::Text {
+            continue;
+        }
+        let Some(section_functions) = by_section.get(&section.index()) else { continue };
+        for (offset, relocation) in section.relocations() {
+            // The relocation belongs to the function starting last before it.
+            let position = section_functions.partition_point(|&(address, _)| address <= offset);
+            let Some(&(_, caller)) = position.checked_sub(1).map(|i| &section_functions[i]) else {
+                continue;
+            };
+
+            let RelocationTarget::Symbol(index) = relocation.target() else { continue };
+            let symbol = object.symbol_by_index(index).map_err(|e| e.to_string())?;
+            let callee = match symbol.kind() {
+                SymbolKind::Text | SymbolKind::Unknown => {
+                    symbol.name().map_err(|e| e.to_string())?.to_owned()
+                }
+                SymbolKind::Section => {
+                    let Some(target) = symbol.section_index() else { continue };
+                    let target_section =
+                        object.section_by_index(target).map_err(|e| e.to_string())?;
+                    if target_section.kind() != SectionKind::Text {
+                        continue;
+                    }
+                    let addend = relocation.addend() as u64;
+                    match by_section.get(&target).map(Vec::as_slice) {
+                        // Each function has its own section with `-Z function-sections`.
+                        Some(&[(_, name)]) => name.to_owned(),
+                        _ if target == section.index() => continue,
+                        // Implicit addends are encoded in the instruction, which we don't decode.
+                        _ => match by_address.get(&(target, addend & !1)) {
+                            Some(&name) if !relocation.has_implicit_addend() => name.to_owned(),
+                            // We don't know which function is referenced: record a callee we
+                            // know nothing about, which makes the stack usage unbounded.
+                            _ => {
+                                let section_name = target_section.name().unwrap_or("<unknown>");
+                                format!("{section_name}+{addend:#x}")
+                            }
+                        },
+                    }
+                }
+                _ => continue,
+            };
+            // References to the function itself are jumps within its body, and the GOT is not a
+            // function.
+            if callee == caller || callee == "_GLOBAL_OFFSET_TABLE_" {
+                continue;
+            }
+            functions.references.entry(caller.to_owned()).or_default().insert(callee);
+        }
+    }
+    Ok(())
+}
+
+fn read_address(bytes: &[u8], little_endian: bool) -> u64 {
+    let fold = |address: u64, &byte: &u8| (address << 8) | u64::from(byte);
+    if little_endian { bytes.iter().rev().fold(0, fold) } else { bytes.iter().fold(0, fold) }
+}
+
+fn read_uleb128(bytes: &[u8]) -> Option<u64> {
+    let mut value = 0;
+    for (i, &byte) in bytes.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+newtype_index! {
+    struct FnIdx {}
+}
+
+newtype_index! {
+    #[orderable]
+    struct SccIdx {}
+}
+
+struct CallGraph<'a> {
+    functions: FxIndexSet<&'a str>,
+    callees: IndexVec<FnIdx, Vec<FnIdx>>,
+}
+
+impl<'a> CallGraph<'a> {
+    /// The callees of a function are the ones called in its MIR, and the ones referenced by its
+    /// machine code. Functions inlined by the backend are then still visited, to find out whether
+    /// they make indirect calls, while the calls inserted by the backend are visited too.
+    fn new(call_graph: &'a StackUsageCallGraph, objects: &'a ObjectFunctions) -> Self {
+        let mut functions = FxIndexSet::default();
+        let mut callees: IndexVec<FnIdx, FxIndexSet<FnIdx>> = IndexVec::new();
+        let intern = |functions: &mut FxIndexSet<&'a str>, name: &'a str| {
+            let (index, _) = functions.insert_full(name);
+            FnIdx::from_usize(index)
+        };
+
+        for root in &call_graph.roots {
+            intern(&mut functions, root);
+        }
+        let mir_callees = call_graph
+            .functions
+            .iter()
+            .map(|(caller, callees)| (caller, callees.direct.iter().collect::<Vec<_>>()));
+        let object_callees = objects.references.iter().map(|(caller, references)| {
+            // References to data are not calls.
+            (caller, references.iter().filter(|name| !objects.data.contains(*name)).collect())
+        });
+        for (caller, caller_callees) in mir_callees.chain(object_callees) {
+            let caller = intern(&mut functions, caller);
+            let caller_callees = caller_callees
+                .into_iter()
+                .map(|callee| intern(&mut functions, callee))
+                .collect::<Vec<_>>();
+            callees.ensure_contains_elem(caller, FxIndexSet::default);
+            callees[caller].extend(caller_callees);
+        }
+        // Functions we know nothing about are leaves of the graph.
+        callees.ensure_contains_elem(FnIdx::from_usize(functions.len() - 1), FxIndexSet::default);
+
+        let callees = callees.into_iter().map(|callees| callees.into_iter().collect()).collect();
+        CallGraph { functions, callees }
+    }
+
+    fn name(&self, function: FnIdx) -> &'a str {
+        self.functions[function.index()]
+    }
+}
+
+impl DirectedGraph for CallGraph<'_> {
+    type Node = FnIdx;
+
+    fn num_nodes(&self) -> usize {
+        self.functions.len()
+    }
+}
+
+impl Successors for CallGraph<'_> {
+    fn successors(&self, function: FnIdx) -> impl Iterator<Item = FnIdx> {
+        self.callees[function].iter().copied()
+    }
+}
+
+#[derive(serde::Serialize)]
+struct Report<'a> {
+    roots: Vec<RootReport<'a>>,
+}
+
+#[derive(serde::Serialize)]
+struct RootReport<'a> {
+    function: &'a str,
+    stack_size: u64,
+    bounded: bool,
+    worst_case_path: Vec<PathEntry<'a>>,
+    recursive_functions: Vec<&'a str>,
+    indirect_calls: Vec<&'a str>,
+    unknown_frame_sizes: Vec<&'a str>,
+    unknown_callees: Vec<&'a str>,
+    inlined_functions: Vec<&'a str>,
+}
+
+#[derive(serde::Serialize)]
+struct PathEntry<'a> {
+    function: &'a str,
+    frame_size: Option<u64>,
+}
+
+impl<'a> Report<'a> {
+    fn compute(call_graph: &'a StackUsageCallGraph, objects: &'a ObjectFunctions) -> Self {
+        if call_graph.roots.is_empty() {
+            return Report { roots: Vec::new() };
+        }
+        let graph = CallGraph::new(call_graph, objects);
+        // Functions codegened in this crate but missing from its objects were inlined into all
+        // their callers, whose frame accounts for them.
+        let inlined = |function| {
+            let name = graph.name(function);
+            objects.local_objects_read
+                && call_graph.functions.contains_key(name)
+                && !objects.references.contains_key(name)
+        };
+        let frame_size = |function| match objects.frame_sizes.get(graph.name(function)) {
+            Some(&size) => Some(size),
+            None if inlined(function) => Some(0),
+            None => None,
+        };
+
+        // The SCCs are visited in post order, so the callees of a function are visited before it,
+        // unless they are part of the same cycle.
+        let sccs: Sccs<FnIdx, SccIdx> = Sccs::new(&graph);
+        let mut scc_members: IndexVec<SccIdx, Vec<FnIdx>> =
+            IndexVec::from_elem_n(Vec::new(), sccs.num_sccs());
+        for function in graph.iter_nodes() {
+            scc_members[sccs.scc(function)].push(function);
+        }
+
+        let mut recursive = IndexVec::from_elem_n(false, graph.num_nodes());
+        let mut stack_size = IndexVec::from_elem_n(0, graph.num_nodes());
+        let mut deepest_callee = IndexVec::from_elem_n(None, graph.num_nodes());
+        for scc in sccs.all_sccs() {
+            let members = &scc_members[scc];
+            let cyclic = members.len() > 1 || graph.callees[members[0]].contains(&members[0]);
+            for &function in members {
+                recursive[function] = cyclic;
+                // Calls within a cycle are not followed: the stack usage is unbounded anyway.
+                let deepest = graph.callees[function]
+                    .iter()
+                    .copied()
+                    .filter(|&callee| sccs.scc(callee) != scc)
+                    .max_by_key(|&callee| stack_size[callee]);
+                stack_size[function] = frame_size(function).unwrap_or(0)
+                    + deepest.map_or(0, |callee| stack_size[callee]);
+                deepest_callee[function] = deepest;
+            }
+        }
+
+        let mut roots = Vec::new();
+        for root in &call_graph.roots {
+            let root = FnIdx::from_usize(graph.functions.get_index_of(root.as_str()).unwrap());
+
+            let mut reachable = FxIndexSet::default();
+            reachable.insert(root);
+            let mut i = 0;
+            while let Some(&function) = reachable.get_index(i) {
+                reachable.extend(graph.successors(function));
+                i += 1;
+            }
+
+            let mut recursive_functions = Vec::new();
+            let mut indirect_calls = Vec::new();
+            let mut unknown_frame_sizes = Vec::new();
+            let mut unknown_callees = Vec::new();
+            let mut inlined_functions = Vec::new();
+            for &function in &reachable {
+                let name = graph.name(function);
+                if recursive[function] {
+                    recursive_functions.push(name);
+                }
+                match call_graph.functions.get(name) {
+                    Some(callees) if callees.indirect => indirect_calls.push(name),
+                    Some(_) => {}
+                    None => unknown_callees.push(name),
+                }
+                if frame_size(function).is_none() {
+                    unknown_frame_sizes.push(name);
+                }
+                if inlined(function) {
+                    inlined_functions.push(name);
+                }
+            }
+            for list in [
+                &mut recursive_functions,
+                &mut indirect_calls,
+                &mut unknown_frame_sizes,
+                &mut unknown_callees,
+                &mut inlined_functions,
+            ] {
+                list.sort_unstable();
+            }
+
+            let mut worst_case_path = Vec::new();
+            let mut next = Some(root);
+            while let Some(function) = next {
+                worst_case_path.push(PathEntry {
+                    function: graph.name(function),
+                    frame_size: frame_size(function),
+                });
+                next = deepest_callee[function];
+            }
+
+            roots.push(RootReport {
+                function: graph.name(root),
+                stack_size: stack_size[root],
+                bounded: recursive_functions.is_empty()
+                    && indirect_calls.is_empty()
+                    && unknown_frame_sizes.is_empty()
+                    && unknown_callees.is_empty(),
+                worst_case_path,
+                recursive_functions,
+                indirect_calls,
+                unknown_frame_sizes,
+                unknown_callees,
+                inlined_functions,
+            });
+        }
+
+        Report { roots }
+    }
+
+    fn write_to(&self, path: &Path) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut file, self)?;
+        writeln!(file)?;
+        file.flush()
+    }
+}
@@ -1068,6 +1068,14 @@ pub fn new(tcx: TyCtxt<'_>, target_cpu: String) -> CrateInfo {
             symbol_rename_suffix: format!(".rs{:x}", tcx.stable_crate_id(LOCAL_CRATE)),
             each_linked_rlib_file_for_lto: Default::default(),
             exported_symbols_for_lto: Default::default(),
+            // Ferrocene addition
+            stack_usage_call_graph: tcx
+                .sess
+                .opts
+                .unstable_opts
+                .stack_usage_report
+                .is_some()
+                .then(|| tcx.stack_usage_call_graph(()).clone()),
         };
 
         info.native_libraries.reserve(n_crates);
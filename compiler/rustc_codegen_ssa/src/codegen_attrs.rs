@@ -9,7 +9,7 @@
 use rustc_macros::Diagnostic;
 use rustc_middle::bug;
 use rustc_middle::middle::codegen_fn_attrs::ferrocene::{
-    Validated, ValidatedStatus, item_is_validated,
+    SAFETY_STANDARDS, ValidatedAttrError, ValidatedStatus, item_is_validated, validated_levels,
 };
 use rustc_middle::middle::codegen_fn_attrs::{
     CodegenFnAttrFlags, CodegenFnAttrs, InstrumentFnAttr, PatchableFunctionEntry, SanitizerFnAttrs,
@@ -48,6 +48,33 @@ fn try_fn_sig<'tcx>(
     }
 }
 
+// Ferrocene addition
+fn report_validated_attr_error(tcx: TyCtxt<'_>, err: ValidatedAttrError) {
+    match err {
+        ValidatedAttrError::Malformed { span } => {
+            tcx.dcx().emit_err(diagnostics::MalformedPrevalidated { span });
+        }
+        ValidatedAttrError::UnknownStandard { span, standard } => {
+            let known = SAFETY_STANDARDS.iter().map(|(name, _)| format!("`{name}`"));
+            tcx.dcx().emit_err(diagnostics::UnknownSafetyStandard {
+                span,
+                standard,
+                known: known.collect::<Vec<_>>().join(", "),
+            });
+        }
+        ValidatedAttrError::UnknownLevel { span, standard, level } => {
+            let (_, levels) = SAFETY_STANDARDS.iter().find(|(name, _)| *name == standard).unwrap();
+            let known = levels.iter().map(|name| format!("`{name}`"));
+            tcx.dcx().emit_err(diagnostics::UnknownSafetyLevel {
+                span,
+                standard,
+                level,
+                known: known.collect::<Vec<_>>().join(", "),
+            });
+        }
+    }
+}
+
 /// Spans that are collected when processing built-in attributes,
 /// that are useful for emitting diagnostics later.
 #[derive(Default)]
@@ -71,7 +98,14 @@ fn process_builtin_attrs(
 
     // Ferrocene addition
     if let ValidatedStatus::Validated { .. } = item_is_validated(tcx, did.into()) {
-        codegen_fn_attrs.validated = Some(Validated {});
+        // Closures share the attributes of their parent; only report errors once.
+        let report_errors = tcx.typeck_root_def_id(did.into()) == did.to_def_id();
+        let validated = validated_levels(tcx, did.into(), |err| {
+            if report_errors {
+                report_validated_attr_error(tcx, err);
+            }
+        });
+        codegen_fn_attrs.validated = Some(validated);
     }
 
     let parsed_attrs = attrs
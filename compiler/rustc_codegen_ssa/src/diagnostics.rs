@@ -1377,3 +1377,100 @@ pub(crate) fn new(libname: &'a str, verbatim: bool) -> Self {
 pub(crate) struct SuggestLibraryName<'a> {
     suggested_name: &'a str,
 }
+
+// Ferrocene addition
+#[derive(Diagnostic)]
+#[diag("malformed `ferrocene::prevalidated` attribute")]
+#[help(
+    "expected `#[ferrocene::prevalidated]` or `#[ferrocene::prevalidated(standard = \"...\", level = \"...\")]`"
+)]
+pub(crate) struct MalformedPrevalidated {
+    #[primary_span]
+    pub span: Span,
+}
+
+// Ferrocene addition
+#[derive(Diagnostic)]
+#[diag("unknown safety standard `{$standard}`")]
+#[note("the known standards are: {$known}")]
+pub(crate) struct UnknownSafetyStandard {
+    #[primary_span]
+    pub span: Span,
+    pub standard: Symbol,
+    pub known: String,
+}
+
+// Ferrocene addition
+#[derive(Diagnostic)]
+#[diag("unknown {$standard} level `{$level}`")]
+#[note("the known {$standard} levels are: {$known}")]
+pub(crate) struct UnknownSafetyLevel {
+    #[primary_span]
+    pub span: Span,
+    pub standard: &'static str,
+    pub level: Symbol,
+    pub known: String,
+}
+
+// Ferrocene addition
+#[derive(Diagnostic)]
+#[diag("failed to read the validation manifest `{$path}`: {$error}")]
+pub(crate) struct CouldntReadValidationManifest<'a> {
+    pub path: &'a Path,
+    pub error: String,
+}
+
+// Ferrocene addition
+#[derive(Diagnostic)]
+#[diag("malformed validation manifest `{$path}`: {$error}")]
+pub(crate) struct MalformedValidationManifest<'a> {
+    pub path: &'a Path,
+    pub error: String,
+}
+
+// Ferrocene addition
+#[derive(Diagnostic)]
+#[diag("validation manifest `{$path}` is out of date for crate `{$krate}`")]
+#[note("the manifest was written for crate hash `{$expected}`, but `{$krate}` has hash {$found}")]
+#[help("review the changes to `{$krate}`, then update its `hash` in the manifest")]
+pub(crate) struct StaleValidationManifest<'a> {
+    pub path: &'a Path,
+    pub krate: &'a str,
+    pub expected: String,
+    pub found: String,
+}
+
+// Ferrocene addition
+#[derive(Diagnostic)]
+#[diag("validation manifest `{$path}` lists `{$item}` as both validated and unvalidated")]
+pub(crate) struct ConflictingValidationManifestEntry<'a> {
+    pub path: &'a Path,
+    pub item: &'a str,
+}
+
+// Ferrocene addition
+#[derive(Diagnostic)]
+#[diag("validation manifest `{$path}` lists `{$item}`, which is not an item in crate `{$krate}`")]
+#[help("items are named by their full path, as printed by the `ferrocene::unvalidated` lint")]
+pub(crate) struct UnknownValidationManifestEntry<'a> {
+    pub path: &'a Path,
+    pub krate: &'a str,
+    pub item: &'a str,
+}
+
+// Ferrocene addition
+#[derive(Diagnostic)]
+#[diag("failed to read the stack sizes from `{$path}`: {$error}")]
+#[note("the functions it defines are reported as having unknown frame sizes")]
+pub(crate) struct CouldntReadStackSizes<'a> {
+    pub path: &'a Path,
+    pub error: String,
+}
+
+// Ferrocene addition
+#[derive(Diagnostic)]
+#[diag("failed to write the stack usage report to `{$path}`: {$error}")]
+pub(crate) struct CouldntWriteStackUsageReport<'a> {
+    pub path: &'a Path,
+    pub error: String,
+}
@@ -32,6 +32,7 @@
 use rustc_middle::middle::debugger_visualizer::DebuggerVisualizerFile;
 use rustc_middle::middle::dependency_format::Dependencies;
 use rustc_middle::middle::exported_symbols::SymbolExportKind;
+use rustc_middle::mono::StackUsageCallGraph;
 use rustc_middle::ty::TyCtxt;
 use rustc_middle::util::Providers;
 use rustc_serialize::opaque::{FileEncoder, MemDecoder};
@@ -55,6 +56,7 @@
 pub mod size_of_val;
 pub mod target_features;
 pub mod traits;
+mod validation_manifest;
 
 pub struct ModuleCodegen<M> {
     /// The name of the module. When the crate may be saved between
@@ -304,6 +306,8 @@ pub struct CrateInfo {
     pub symbol_rename_suffix: String,
     pub each_linked_rlib_file_for_lto: Vec<PathBuf>,
     pub exported_symbols_for_lto: Vec<String>,
+    /// Ferrocene addition: only present with `-Z stack-usage-report`.
+    pub stack_usage_call_graph: Option<StackUsageCallGraph>,
 }
 
 /// Target-specific options that get set in `cfg(...)`.
@@ -343,6 +347,7 @@ pub fn provide(providers: &mut Providers) {
     crate::base::provide(&mut providers.queries);
     crate::target_features::provide(&mut providers.queries);
     crate::codegen_attrs::provide(&mut providers.queries);
+    crate::validation_manifest::provide(&mut providers.queries);
     providers.queries.global_backend_features = |_tcx: TyCtxt<'_>, ()| vec![];
 }
 
@@ -0,0 +1,145 @@
+//! Ferrocene addition: loading the file passed to `-Z ferrocene-validation-manifest`.
+//!
+//! The manifest lists items in upstream crates that should be treated as validated, or as
+//! unvalidated, regardless of their `#[ferrocene::prevalidated]` attributes. This lets third-party
+//! crates be validated without patching them:
+//! ```json
+//! {
+//!   "heapless": {
+//!     "hash": "8c1a5e0f2b7d4c9e1f3a6b8d0e2c4a6f",
+//!     "validated": ["heapless::vec::Vec::<T, N>::push"],
+//!     "unvalidated": ["heapless::vec::Vec::<T, N>::extend_from_slice"]
+//!   }
+//! }
+//! ```
+//!
+//! Items are named by their full path, as printed by the `ferrocene::unvalidated` lint. Paths that
+//! don't name any item in the crate are an error.
+//!
+//! `hash` is the crate hash of the crate the manifest was reviewed against. If the crate has
+//! changed since then, the manifest is stale and we error, showing the new hash.
+//!
+//! Crates that are not dependencies of the current crate are ignored, so that a single manifest
+//! can be passed to every crate in a build.
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def_id::{CrateNum, DefId, DefIndex};
+use rustc_metadata::creader::CStore;
+use rustc_middle::middle::codegen_fn_attrs::ferrocene::ValidationManifest;
+use rustc_middle::query::Providers;
+use rustc_middle::ty::TyCtxt;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_span::Symbol;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::diagnostics;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestCrate {
+    hash: String,
+    #[serde(default)]
+    validated: Vec<String>,
+    #[serde(default)]
+    unvalidated: Vec<String>,
+}
+
+fn ferrocene_validation_manifest(tcx: TyCtxt<'_>, (): ()) -> ValidationManifest {
+    let mut manifest = ValidationManifest::default();
+    let Some(path) = &tcx.sess.opts.unstable_opts.ferrocene_validation_manifest else {
+        return manifest;
+    };
+    // Make sure cargo rebuilds us when the manifest changes.
+    tcx.sess.file_depinfo.borrow_mut().insert(Symbol::intern(&path.to_string_lossy()));
+
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            tcx.dcx().emit_err(diagnostics::CouldntReadValidationManifest {
+                path,
+                error: err.to_string(),
+            });
+            return manifest;
+        }
+    };
+    // Sorted, so that errors are emitted in a stable order.
+    let crates: BTreeMap<String, ManifestCrate> = match serde_json::from_str(&contents) {
+        Ok(crates) => crates,
+        Err(err) => {
+            tcx.dcx().emit_err(diagnostics::MalformedValidationManifest {
+                path,
+                error: err.to_string(),
+            });
+            return manifest;
+        }
+    };
+
+    for (name, entry) in crates {
+        let symbol = Symbol::intern(&name);
+        let candidates: Vec<CrateNum> =
+            tcx.crates(()).iter().copied().filter(|&cnum| tcx.crate_name(cnum) == symbol).collect();
+        if candidates.is_empty() {
+            debug!("ignoring manifest entry for `{name}`, which is not a dependency");
+            continue;
+        }
+
+        // There may be several versions of the same crate in the graph. Only one of them can
+        // match the hash.
+        let Some(cnum) =
+            candidates.iter().copied().find(|&cnum| tcx.crate_hash(cnum).to_hex() == entry.hash)
+        else {
+            let found = candidates.iter().map(|&cnum| format!("`{}`", tcx.crate_hash(cnum)));
+            tcx.dcx().emit_err(diagnostics::StaleValidationManifest {
+                path,
+                krate: &name,
+                expected: entry.hash,
+                found: found.collect::<Vec<_>>().join(", "),
+            });
+            continue;
+        };
+
+        let mut items = FxHashMap::default();
+        let validated = entry.validated.iter().map(|item| (item, true));
+        let unvalidated = entry.unvalidated.iter().map(|item| (item, false));
+        for (item, status) in validated.chain(unvalidated) {
+            if items.get(item).is_some_and(|&previous| previous != status) {
+                tcx.dcx().emit_err(diagnostics::ConflictingValidationManifestEntry { path, item });
+            }
+            items.insert(item.clone(), status);
+        }
+
+        // An entry that names no item in the crate would silently do nothing, e.g. if it has a
+        // typo in it. Reject those.
+        let mut unknown: FxHashSet<&str> = items.keys().map(String::as_str).collect();
+        // The number of items is covered by the crate hash we checked above.
+        let num_def_ids = CStore::from_tcx(tcx).num_def_ids_untracked(cnum);
+        for index in 0..num_def_ids {
+            if unknown.is_empty() {
+                break;
+            }
+            let def_id = DefId { krate: cnum, index: DefIndex::from_usize(index) };
+            unknown.remove(with_no_trimmed_paths!(tcx.def_path_str(def_id)).as_str());
+        }
+        for item in entry.validated.iter().chain(&entry.unvalidated) {
+            if unknown.remove(item.as_str()) {
+                tcx.dcx().emit_err(diagnostics::UnknownValidationManifestEntry {
+                    path,
+                    krate: &name,
+                    item,
+                });
+            }
+        }
+
+        manifest.crates.insert(cnum, items);
+    }
+
+    manifest
+}
+
+pub(crate) fn provide(providers: &mut Providers) {
+    *providers = Providers { ferrocene_validation_manifest, ..*providers };
+}
@@ -628,7 +628,10 @@ fn print_crate_info(
 
     // NativeStaticLibs and LinkArgs are special - printed during linking
     // (empty iterator returns true)
-    if sess.opts.prints.iter().all(|p| p.kind == NativeStaticLibs || p.kind == LinkArgs) {
+    // Ferrocene addition: so is FerroceneValidationStats, printed by the post-mono lint pass.
+    if sess.opts.prints.iter().all(|p| {
+        p.kind == NativeStaticLibs || p.kind == LinkArgs || p.kind == FerroceneValidationStats
+    }) {
         return Compilation::Continue;
     }
 
@@ -821,6 +824,9 @@ fn print_crate_info(
             // Any output here interferes with Cargo's parsing of other printed output
             NativeStaticLibs => {}
             LinkArgs => {}
+            // Ferrocene addition: printed after analysis or codegen, by
+            // `rustc_interface::passes`.
+            FerroceneValidationStats => {}
             SplitDebuginfo => {
                 use rustc_target::spec::SplitDebuginfo::{Off, Packed, Unpacked};
 
@@ -25,6 +25,7 @@
 use rustc_hir::definitions::Definitions;
 use rustc_hir::{Attribute, find_attr};
 use rustc_incremental::setup_dep_graph;
+use rustc_lint::ferrocene::PostMonoOutputs;
 use rustc_lint::{BufferedEarlyLint, EarlyCheckNode, LintStore, unerased_lint_store};
 use rustc_metadata::EncodedMetadata;
 use rustc_metadata::creader::CStore;
@@ -36,7 +37,9 @@
 use rustc_passes::{abi_test, input_stats, layout_test};
 use rustc_resolve::{Resolver, ResolverOutputs};
 use rustc_session::Session;
-use rustc_session::config::{CrateType, Input, OutFileName, OutputFilenames, OutputType};
+use rustc_session::config::{
+    CrateType, Input, OutFileName, OutputFilenames, OutputType, PrintKind,
+};
 use rustc_session::cstore::Untracked;
 use rustc_session::diagnostics::feature_err;
 use rustc_session::output::{filename_for_input, invalid_output_for_target};
@@ -981,8 +984,11 @@ pub fn create_and_enter_global_ctxt<T, F: for<'tcx> FnOnce(TyCtxt<'tcx>) -> T>(
         // also checking if it's validated.
         // We can't do this directly in `rustc_monomorphize::collect_and_partition_mono_items`
         // because at that point we don't yet have access to rustc_lint.
+        // Only diagnostics are emitted here: they are replayed when this query is loaded from
+        // the incremental cache, while other side effects would not be. Those are produced by
+        // `ferrocene_export_validated_roots` instead.
         let roots = rustc_monomorphize::collect_validated_roots(tcx);
-        rustc_lint::ferrocene::lint_validated_roots(tcx, roots);
+        rustc_lint::ferrocene::lint_validated_roots(tcx, roots, PostMonoOutputs::Diagnostics);
         items
     };
 
@@ -1280,6 +1286,42 @@ fn analysis(tcx: TyCtxt<'_>, (): ()) {
             });
         });
     }
+
+    // Ferrocene addition: the post-mono `ferrocene::unvalidated` pass normally runs as part of
+    // `collect_and_partition_mono_items`, which is only called when generating code. With
+    // `-Z ferrocene-post-mono-check`, also run it for `cargo check`, so that instantiations of
+    // generic functions are checked without a full build. This only collects the validated roots;
+    // it does not collect or partition any other mono items.
+    // `--print=ferrocene-validation-stats` and `-Z deny-reachable-panics` are reported by this
+    // pass, so it also needs to run then.
+    if (tcx.sess.opts.unstable_opts.ferrocene_post_mono_check
+        || tcx.sess.opts.unstable_opts.deny_reachable_panics
+        || tcx.sess.opts.prints.iter().any(|p| p.kind == PrintKind::FerroceneValidationStats))
+        && !tcx.sess.opts.output_types.should_codegen()
+    {
+        sess.time("ferrocene_post_mono_check", || {
+            let roots = rustc_monomorphize::collect_validated_roots(tcx);
+            rustc_lint::ferrocene::lint_validated_roots(tcx, roots, PostMonoOutputs::All);
+        });
+    }
+}
+
+/// Ferrocene addition: write `-Z ferrocene-validated-graph` and print
+/// `--print=ferrocene-validation-stats`.
+///
+/// This re-walks the validated roots without emitting any lints. It can't happen inside
+/// `collect_and_partition_mono_items`, because that query may be loaded from the incremental cache
+/// without running, which would leave a stale graph behind and print nothing.
+fn ferrocene_export_validated_roots(tcx: TyCtxt<'_>) {
+    if tcx.sess.opts.unstable_opts.ferrocene_validated_graph.is_none()
+        && !tcx.sess.opts.prints.iter().any(|p| p.kind == PrintKind::FerroceneValidationStats)
+    {
+        return;
+    }
+    tcx.sess.time("ferrocene_export_validated_roots", || {
+        let roots = rustc_monomorphize::collect_validated_roots(tcx);
+        rustc_lint::ferrocene::lint_validated_roots(tcx, roots, PostMonoOutputs::Exports);
+    });
 }
 
 /// Runs the codegen backend, after which the AST and analysis can
@@ -1334,6 +1376,11 @@ pub(crate) fn start_codegen<'tcx>(
         tcx.sess.code_stats.print_type_sizes();
     }
 
+    // Ferrocene addition: without codegen, this is done by the `ferrocene_post_mono_check` pass.
+    if !tcx.sess.opts.unstable_opts.no_codegen && tcx.sess.opts.output_types.should_codegen() {
+        ferrocene_export_validated_roots(tcx);
+    }
+
     let crate_info = CrateInfo::new(tcx, codegen_backend.target_cpu(tcx.sess));
 
     (codegen, crate_info, metadata)
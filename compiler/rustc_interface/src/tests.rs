@@ -12,12 +12,13 @@
 use rustc_session::config::{
     AnnotateMoves, AutoDiff, BranchProtection, CFGuard, Cfg, CodegenRetagOptions, CoverageLevel,
     CoverageOptions, DebugInfo, DumpMonoStatsFormat, ErrorOutputType, ExternEntry, ExternLocation,
-    Externs, FmtDebug, FunctionReturn, IncrementalStateAssertion, InliningThreshold, Input,
-    InstrumentCoverage, InstrumentMcount, InstrumentXRay, LinkSelfContained, LinkerPluginLto,
-    LocationDetail, LtoCli, MirIncludeSpans, NextSolverConfig, Offload, Options, OutFileName,
-    OutputType, OutputTypes, PAuthKey, PacRet, Passes, PatchableFunctionEntry, Polonius,
-    ProcMacroExecutionStrategy, Strip, SwitchWithOptPath, SymbolManglingVersion, WasiExecModel,
-    build_configuration, build_session_options, rustc_optgroups,
+    Externs, FerroceneValidatedRoots, FmtDebug, FunctionReturn, IncrementalStateAssertion,
+    InliningThreshold, Input, InstrumentCoverage, InstrumentMcount, InstrumentXRay,
+    LinkSelfContained, LinkerPluginLto, LocationDetail, LtoCli, MirIncludeSpans, NextSolverConfig,
+    Offload, Options, OutFileName, OutputType, OutputTypes, PAuthKey, PacRet, Passes,
+    PatchableFunctionEntry, Polonius, ProcMacroExecutionStrategy, Strip, SwitchWithOptPath,
+    SymbolManglingVersion, WasiExecModel, build_configuration, build_session_options,
+    rustc_optgroups,
 };
 use rustc_session::lint::Level;
 use rustc_session::search_paths::SearchPath;
@@ -812,6 +813,7 @@ macro_rules! tracked {
     tracked!(debug_info_type_line_numbers, true);
     tracked!(debuginfo_for_profiling, true);
     tracked!(default_visibility, Some(rustc_target::spec::SymbolVisibility::Hidden));
+    tracked!(deny_reachable_panics, true);
     tracked!(dep_info_omit_d_target, true);
     tracked!(direct_access_external_data, Some(true));
     tracked!(dual_proc_macros, true);
@@ -819,6 +821,13 @@ macro_rules! tracked {
     tracked!(embed_metadata, false);
     tracked!(embed_source, true);
     tracked!(export_executable_symbols, true);
+    tracked!(ferrocene_fn_ptr_escape_analysis, true);
+    tracked!(ferrocene_post_mono_check, true);
+    tracked!(
+        ferrocene_validated_roots,
+        FerroceneValidatedRoots { exported: true, extern_c: false }
+    );
+    tracked!(ferrocene_validation_manifest, Some(PathBuf::from("manifest.json")));
     tracked!(fewer_names, Some(true));
     tracked!(fixed_x18, true);
     tracked!(flatten_format_args, false);
@@ -855,6 +864,7 @@ macro_rules! tracked {
     tracked!(no_generate_arange_section, true);
     tracked!(no_link, true);
     tracked!(no_profiler_runtime, true);
+    tracked!(no_std_test_harness, true);
     tracked!(no_trait_vptr, true);
     tracked!(no_unique_section_names, true);
     tracked!(offload, vec![Offload::Device]);
@@ -892,6 +902,7 @@ macro_rules! tracked {
     tracked!(split_lto_unit, Some(true));
     tracked!(src_hash_algorithm, Some(SourceFileHashAlgorithm::Sha1));
     tracked!(stack_protector, StackProtector::All);
+    tracked!(stack_usage_report, Some(PathBuf::from("stack-usage.json")));
     tracked!(staticlib_hide_internal_symbols, true);
     tracked!(staticlib_rename_internal_symbols, true);
     tracked!(teach, true);
@@ -920,6 +931,9 @@ macro_rules! tracked_no_crate_hash {
             assert_non_crate_hash_different(&reference, &opts);
         };
     }
+    // Ferrocene addition: the graph is written by the post-mono lint query, which must run again
+    // when the path changes.
+    tracked_no_crate_hash!(ferrocene_validated_graph, Some(PathBuf::from("validated-graph.json")));
     tracked_no_crate_hash!(no_codegen, true);
     tracked_no_crate_hash!(verbose_internals, true);
 }
@@ -1,14 +1,29 @@
 //! ## Recommended reading
 //! - [Errors and lints](https://rustc-dev-guide.rust-lang.org/diagnostics.html)
 
-use rustc_errors::{Diag, MultiSpan};
+use std::path::Path;
+
+use rustc_errors::{Applicability, Diag, MultiSpan, listify, pluralize};
 use rustc_hir::def_id::DefId;
 use rustc_hir::{HirId, LangItem};
+use rustc_macros::Diagnostic;
+use rustc_middle::middle::codegen_fn_attrs::ferrocene::{
+    ValidatedRoot, item_is_validated, validated_root,
+};
 use rustc_span::{STDLIB_STABLE_CRATES, Span};
 use tracing::debug;
 
 use crate::ferrocene::post_mono::InstantiationSite;
-use crate::ferrocene::{LintState, UNVALIDATED, UnvalidatedImplCause, Use, UseKind};
+use crate::ferrocene::{
+    LintState, UNVALIDATED, UnvalidatedImplCause, UnvalidatedReason, Use, UseKind,
+};
+
+#[derive(Diagnostic)]
+#[diag("failed to write the validated call graph to `{$path}`: {$error}")]
+pub(super) struct CouldntWriteValidatedGraph<'a> {
+    pub path: &'a Path,
+    pub error: String,
+}
 
 /// Diagnostics.
 impl<'tcx> LintState<'tcx> {
@@ -19,26 +34,79 @@ fn func_span(&self, def_id: DefId) -> Span {
         }
     }
 
-    pub(super) fn lint_use(&mut self, lint_node: HirId, use_: Use<'tcx>) {
+    /// If the item being linted is only checked as an entry point, without being validated, why.
+    fn entry_point(&self) -> Option<ValidatedRoot> {
+        let owner = self.item.to_def_id();
+        if item_is_validated(self.tcx, owner).validated() {
+            return None;
+        }
+        validated_root(self.tcx, owner)
+    }
+
+    pub(super) fn lint_use(
+        &mut self,
+        lint_node: HirId,
+        use_: Use<'tcx>,
+        reason: UnvalidatedReason,
+    ) {
         let Self { tcx, item: owner, .. } = *self;
         let (callee, receiver_span) = (use_.def_id(), use_.span);
 
         debug!("linting node {lint_node:?}");
+        let entry_point = self.entry_point();
 
         tcx.emit_node_span_lint(UNVALIDATED, lint_node, receiver_span, rustc_errors::DiagDecorator(|diag| {
             let callee_descr = tcx.def_descr(callee);
-            let owner_descr = tcx.def_descr(owner.into());
-            diag.primary_message(format!(
-                "validated {owner_descr} {} an unvalidated {callee_descr}",
-                use_.present_tense()
-            ));
-
+            let owner_descr = match entry_point {
+                None => format!("validated {}", tcx.def_descr(owner.into())),
+                Some(_) => format!("{} checked as an entry point", tcx.def_descr(owner.into())),
+            };
             // Need to do this lazily or `with_no_trimmed_paths` will panic :/
             let name = match use_.opt_instance() {
                 None => tcx.def_path_str(callee),
                 Some(instance) => tcx.def_path_str_with_args(callee, instance.args),
             };
-            diag.span_label(self.func_span(callee), format!("`{name}` is unvalidated"));
+
+            match reason {
+                UnvalidatedReason::Unvalidated => {
+                    let assoc_fns = match use_.kind {
+                        UseKind::TraitObjectCast(UnvalidatedImplCause::AssocFns(fns), _) => fns,
+                        _ => &[callee],
+                    };
+                    diag.primary_message(format!(
+                        "{owner_descr} {} {} unvalidated {callee_descr}{}",
+                        use_.present_tense(),
+                        if assoc_fns.len() == 1 { "an".to_owned() } else { assoc_fns.len().to_string() },
+                        pluralize!(assoc_fns.len()),
+                    ));
+                    diag.span_label(self.func_span(callee), format!("`{name}` is unvalidated"));
+                    for &assoc_fn in &assoc_fns[1..] {
+                        diag.span_label(
+                            self.func_span(assoc_fn),
+                            format!("`{}` is unvalidated", tcx.def_path_str(assoc_fn)),
+                        );
+                    }
+                }
+                UnvalidatedReason::InsufficientLevel(required) => {
+                    let owner_descr = tcx.def_descr(owner.into());
+                    let checked = if entry_point.is_some() { "checked" } else { "validated" };
+                    diag.primary_message(format!(
+                        "{owner_descr} {checked} for {required} {} an insufficiently validated {callee_descr}",
+                        use_.present_tense()
+                    ));
+                    diag.span_label(
+                        self.func_span(callee),
+                        format!("`{name}` is not validated for {required}"),
+                    );
+                    diag.note(format!(
+                        "`{}` is {} for {required}, so everything it uses must be validated for {} at {} or above",
+                        tcx.def_path_str(owner),
+                        if entry_point.is_some() { "an entry point checked by Ferrocene" } else { "validated" },
+                        required.standard(),
+                        required.level(),
+                    ));
+                }
+            }
 
             if let UseKind::ContainsFnPtr(_, ty) = use_.kind {
                 diag.note(format!("`{name}` contains a function pointer that might be called at runtime"));
@@ -59,18 +127,32 @@ pub(super) fn lint_use(&mut self, lint_node: HirId, use_: Use<'tcx>) {
                 self.shown_item = true;
                 let mut validated_span = MultiSpan::from_span(self.func_span(owner.into()));
                 if let Some(annotation) = self.annotation {
-                    validated_span.push_span_label(annotation, "marked as validated here");
+                    let label = match entry_point {
+                        None => "marked as validated here",
+                        Some(_) => "marked as an entry point here",
+                    };
+                    validated_span.push_span_label(annotation, label);
                 }
 
                 self.decorate_cast(use_, diag);
                 self.decorate_instantiation(use_, diag, Some(&mut validated_span));
 
+                let owner_status = match entry_point {
+                    None => "is validated",
+                    Some(_) => "is an entry point checked by Ferrocene",
+                };
                 diag.span_note(
                     validated_span,
-                    format!("`{}` is validated", tcx.def_path_str(owner)),
+                    format!("`{}` {owner_status}", tcx.def_path_str(owner)),
                 );
                 if self.annotation.is_none() {
-                    diag.note("main functions are assumed to be validated");
+                    match entry_point {
+                        Some(root) => diag.note(format!(
+                            "{} are checked as entry points because of `-Z ferrocene-validated-roots`",
+                            root.descr()
+                        )),
+                        None => diag.note("main functions are assumed to be validated"),
+                    };
                 }
             } else {
                 self.decorate_cast(use_, diag);
@@ -87,11 +169,14 @@ fn decorate_cast(&self, use_: Use<'tcx>, diag: &mut Diag<'_, ()>) {
         } else if let UseKind::TraitObjectCast(cause, ty) = use_.kind {
             diag.note(format!("once `{ty}` is cast to a dynamic trait object, Ferrocene can no longer tell whether it is validated"));
             match cause {
-                UnvalidatedImplCause::AssocFn(assoc_fn) => {
+                UnvalidatedImplCause::AssocFns(assoc_fns) => {
+                    let names =
+                        listify(assoc_fns, |&assoc_fn| format!("`{}`", tcx.def_path_str(assoc_fn)))
+                            .unwrap();
                     diag.note(format!(
-                        "as a precaution, it must assume you will eventually call `{}`",
-                        tcx.def_path_str(assoc_fn)
+                        "as a precaution, it must assume you will eventually call {names}"
                     ));
+                    self.suggest_validating(assoc_fns, diag);
                 }
                 UnvalidatedImplCause::UnresolvedGenericImpl(..) => {
                     unreachable!("all generics should be resolved by post-mono")
@@ -100,6 +185,30 @@ fn decorate_cast(&self, use_: Use<'tcx>, diag: &mut Diag<'_, ()>) {
         }
     }
 
+    /// Suggest marking each of `fns` that is defined in the current crate as validated.
+    fn suggest_validating(&self, fns: &[DefId], diag: &mut Diag<'_, ()>) {
+        let tcx = self.tcx;
+        let source_map = tcx.sess.source_map();
+        let suggestion: Vec<_> = fns
+            .iter()
+            .filter(|assoc_fn| assoc_fn.is_local())
+            .map(|&assoc_fn| {
+                let span = tcx.def_span(assoc_fn).shrink_to_lo();
+                let indent = source_map.indentation_before(span).unwrap_or_default();
+                (span, format!("#[ferrocene::prevalidated]\n{indent}"))
+            })
+            .collect();
+        if suggestion.is_empty() {
+            return;
+        }
+        let msg = if suggestion.len() == 1 {
+            "if this function has been verified, mark it as validated"
+        } else {
+            "if these functions have been verified, mark them as validated"
+        };
+        diag.multipart_suggestion_verbose(msg, suggestion, Applicability::MaybeIncorrect);
+    }
+
     fn decorate_instantiation(
         &self,
         use_: Use<'tcx>,
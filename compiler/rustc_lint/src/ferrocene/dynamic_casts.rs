@@ -75,7 +75,7 @@ pub(super) fn check_fn_ptr_coercion(
     ///    `impl Display for String`.
     /// 4. For each method in the impl, check whether it's validated. For example, we would check
     ///    `<String as Diplay>::fmt`, see that it's unvalidated, and return its `DefId` in the
-    ///    `UseKind`.
+    ///    `UseKind`, along with every other unvalidated method from any of the impls.
     #[instrument(skip(self, try_instantiate, span), ret)]
     pub(super) fn check_dyn_trait_coercion(
         &self,
@@ -100,6 +100,7 @@ pub(super) fn check_dyn_trait_coercion(
         }
 
         let bound_traits = self.dyn_trait_refs(coerce_src, coerce_dst);
+        let mut unvalidated_fns = vec![];
         // NOTE: this only checks functions directly on the `trait_ref`.
         // Supertraits are already handled in `dyn_trait_refs` as a separate trait.
         for trait_ref in bound_traits {
@@ -133,12 +134,12 @@ pub(super) fn check_dyn_trait_coercion(
                     args: _,
                     nested: _,
                 }) => {
-                    // This function in the impl needs to be marked with `prevalidated`.
-                    if let Some(impl_fn) = self.find_unvalidated_impl_fn(impl_def_id) {
-                        return Some(UseKind::TraitObjectCast(
-                            UnvalidatedImplCause::AssocFn(impl_fn),
-                            coerce_src,
-                        ));
+                    // These functions in the impl need to be marked with `prevalidated`.
+                    for impl_fn in self.find_unvalidated_impl_fns(impl_def_id) {
+                        // Supertraits may be reachable through more than one path.
+                        if !unvalidated_fns.contains(&impl_fn) {
+                            unvalidated_fns.push(impl_fn);
+                        }
                     }
                 }
                 // builtin impls are always ok
@@ -162,7 +163,12 @@ pub(super) fn check_dyn_trait_coercion(
                 }
             }
         }
-        None
+
+        if unvalidated_fns.is_empty() {
+            return None;
+        }
+        let unvalidated_fns = tcx.arena.alloc_from_iter(unvalidated_fns);
+        Some(UseKind::TraitObjectCast(UnvalidatedImplCause::AssocFns(unvalidated_fns), coerce_src))
     }
 
     /// Given a call to a function-like type, return the instantiated function definition,
@@ -354,13 +360,13 @@ fn custom_coerce_unsize_info(
         }
     }
 
-    /// Given an `impl`, find the first associated function that isn't validated.
-    ///
-    /// FIXME: list all unvalidated functions, not just the first.
-    fn find_unvalidated_impl_fn(&self, impl_block: DefId) -> Option<DefId> {
+    /// Given an `impl`, find all associated functions that aren't validated, in the order they
+    /// are declared in the trait.
+    fn find_unvalidated_impl_fns(&self, impl_block: DefId) -> Vec<DefId> {
         let tcx = self.tcx;
 
         let trait_to_impl_map = tcx.impl_item_implementor_ids(impl_block);
+        let mut unvalidated = vec![];
         for trait_item in tcx.associated_item_def_ids(tcx.impl_trait_id(impl_block)) {
             debug!("considering {trait_item:?}");
             if !tcx.def_kind(*trait_item).is_fn_like() {
@@ -377,10 +383,9 @@ fn find_unvalidated_impl_fn(&self, impl_block: DefId) -> Option<DefId> {
             }
 
             debug!("found unvalidated method {impl_fn:?}");
-            // This function in the impl needs to be marked with `prevalidated`.
-            return Some(impl_fn);
+            unvalidated.push(impl_fn);
         }
-        return None;
+        unvalidated
     }
 
     fn find_trait_impl(
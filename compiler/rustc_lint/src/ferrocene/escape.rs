@@ -0,0 +1,257 @@
+//! An opt-in escape analysis for function pointer casts, enabled by
+//! `-Z ferrocene-fn-ptr-escape-analysis`.
+//!
+//! By default, we lint every cast of an unvalidated function to a function pointer; see "function
+//! pointers" in the [module docs](super). That is simple, but it also lints pointers that are
+//! provably harmless. For example, a validated function may build a local dispatch table with
+//! an unvalidated default handler, then overwrite that entry before anything is called:
+//! ```rust
+//! # #![feature(register_tool)] #![register_tool(ferrocene)]
+//! # fn unvalidated() {} #[ferrocene::prevalidated] fn validated() {}
+//! #[ferrocene::prevalidated]
+//! fn dispatch(i: usize) {
+//!     let mut table: [fn(); 2] = [unvalidated; 2];
+//!     table = [validated; 2];
+//!     table[i]();
+//! }
+//! ```
+//!
+//! With this analysis enabled, the post-mono pass instead follows the pointer created by each cast
+//! through the locals of the current body, using a forward dataflow analysis. The cast is only
+//! linted if, at some point, the pointer may be:
+//! - called, or
+//! - moved somewhere we can't follow it: returned, passed to another function, written through a
+//!   reference, stored in a local that has been borrowed, cast to another type, or dropped as
+//!   part of a value whose drop glue may call it.
+//!
+//! Copying the pointer between locals (including into and out of arrays, tuples, and structs) is
+//! followed, and overwriting a whole local forgets it. Comparing the pointer is fine, since the
+//! result can't be called.
+//!
+//! The analysis is intra-procedural and conservative: anything it does not understand is treated
+//! as an escape.
+
+use rustc_index::bit_set::DenseBitSet;
+use rustc_middle::mir::visit::{PlaceContext, Visitor};
+use rustc_middle::mir::{
+    self, Body, Local, Location, Operand, RETURN_PLACE, Rvalue, Statement, StatementKind,
+    Terminator, TerminatorEdges, TerminatorKind,
+};
+use rustc_middle::ty::TyCtxt;
+use rustc_mir_dataflow::Analysis;
+use rustc_mir_dataflow::impls::borrowed_locals;
+use tracing::debug;
+
+/// Returns whether the function pointer created by the cast at `cast` may be called, or may escape
+/// `body`. If not, the cast does not need to be linted.
+pub(super) fn fn_ptr_may_be_used<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    cast: Location,
+) -> bool {
+    let borrowed = borrowed_locals(body);
+    let mut cursor = MaybeHoldsFnPtr { cast }
+        .iterate_to_fixpoint(tcx, body, Some("ferrocene_fn_ptr_escape"))
+        .into_results_cursor(body);
+
+    for (block, data) in mir::traversal::reachable(body) {
+        for (statement_index, statement) in data.statements.iter().enumerate() {
+            let location = Location { block, statement_index };
+            cursor.seek_before_primary_effect(location);
+            if statement_escapes(statement, location == cast, cursor.get(), &borrowed) {
+                debug!("function pointer escapes at {location:?}: {statement:?}");
+                return true;
+            }
+        }
+
+        let location = body.terminator_loc(block);
+        cursor.seek_before_primary_effect(location);
+        if terminator_uses(data.terminator(), cursor.get()) {
+            debug!("function pointer is used at {location:?}: {:?}", data.terminator().kind);
+            return true;
+        }
+    }
+
+    false
+}
+
+/// A forward dataflow analysis that tracks which locals may hold the function pointer created by
+/// the cast at `cast`. The pointer may be stored in a field or element of the local.
+struct MaybeHoldsFnPtr {
+    cast: Location,
+}
+
+impl<'tcx> Analysis<'tcx> for MaybeHoldsFnPtr {
+    type Domain = DenseBitSet<Local>;
+    const NAME: &'static str = "maybe_holds_fn_ptr";
+
+    fn bottom_value(&self, body: &Body<'tcx>) -> Self::Domain {
+        // bottom = doesn't hold the pointer
+        DenseBitSet::new_empty(body.local_decls.len())
+    }
+
+    fn initialize_start_block(&self, _: &Body<'tcx>, _: &mut Self::Domain) {
+        // The pointer hasn't been created yet on function entry.
+    }
+
+    fn apply_primary_statement_effect(
+        &self,
+        state: &mut Self::Domain,
+        statement: &Statement<'tcx>,
+        location: Location,
+    ) {
+        match &statement.kind {
+            StatementKind::Assign(assign) => {
+                let (place, rvalue) = &**assign;
+                let holds = location == self.cast
+                    || match rvalue_flow(rvalue, state) {
+                        Flow::None => false,
+                        // The pointer may have been copied, or we lost track of it. In the second
+                        // case, `fn_ptr_may_be_used` will give up anyway.
+                        Flow::Copy | Flow::Escape => true,
+                    };
+                if holds {
+                    state.insert(place.local);
+                } else if place.as_local().is_some() {
+                    // The whole local was overwritten with something else.
+                    state.remove(place.local);
+                }
+            }
+            StatementKind::StorageDead(local) => {
+                state.remove(*local);
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_primary_terminator_effect<'mir>(
+        &self,
+        _state: &mut Self::Domain,
+        terminator: &'mir Terminator<'tcx>,
+        _location: Location,
+    ) -> TerminatorEdges<'mir, 'tcx> {
+        // A call can't return our pointer unless we passed it in, which we treat as an escape.
+        terminator.edges()
+    }
+}
+
+/// Where a value read by an [`Rvalue`] ends up.
+#[derive(Copy, Clone, Debug)]
+enum Flow {
+    /// The rvalue does not read the pointer, or the result can't hold it.
+    None,
+    /// The pointer may be copied into the destination of the assignment.
+    Copy,
+    /// The pointer may end up somewhere we can't follow.
+    Escape,
+}
+
+fn rvalue_flow<'tcx>(rvalue: &Rvalue<'tcx>, state: &DenseBitSet<Local>) -> Flow {
+    let holds = |operand: &Operand<'tcx>| match operand {
+        Operand::Copy(place) | Operand::Move(place) => state.contains(place.local),
+        _ => false,
+    };
+    let any = |found: bool| if found { Flow::Copy } else { Flow::None };
+
+    match rvalue {
+        Rvalue::Use(operand, _)
+        | Rvalue::Repeat(operand, _)
+        | Rvalue::WrapUnsafeBinder(operand, _) => any(holds(operand)),
+        Rvalue::Aggregate(_, operands) => any(operands.iter().any(holds)),
+        Rvalue::CopyForDeref(place) => any(state.contains(place.local)),
+        // None of these can produce a function pointer.
+        Rvalue::BinaryOp(..) | Rvalue::UnaryOp(..) | Rvalue::Discriminant(..) => Flow::None,
+        // Taking a reference, or casting to another type (e.g. a `usize` in a vector table).
+        Rvalue::Ref(..)
+        | Rvalue::RawPtr(..)
+        | Rvalue::Reborrow(..)
+        | Rvalue::Cast(..)
+        | Rvalue::ThreadLocalRef(..) => {
+            if mentions_any(|v| v.visit_rvalue(rvalue, Location::START), state) {
+                Flow::Escape
+            } else {
+                Flow::None
+            }
+        }
+    }
+}
+
+fn statement_escapes<'tcx>(
+    statement: &Statement<'tcx>,
+    is_cast: bool,
+    state: &DenseBitSet<Local>,
+    borrowed: &DenseBitSet<Local>,
+) -> bool {
+    match &statement.kind {
+        StatementKind::Assign(assign) => {
+            let (place, rvalue) = &**assign;
+            let flow = if is_cast { Flow::Copy } else { rvalue_flow(rvalue, state) };
+            match flow {
+                Flow::None => false,
+                Flow::Escape => true,
+                // We can only follow the pointer if it's stored directly in a local that nothing
+                // else can see.
+                Flow::Copy => {
+                    place.is_indirect()
+                        || place.local == RETURN_PLACE
+                        || borrowed.contains(place.local)
+                }
+            }
+        }
+        // e.g. `copy_nonoverlapping`.
+        StatementKind::Intrinsic(_) => {
+            mentions_any(|v| v.visit_statement(statement, Location::START), state)
+        }
+        _ => false,
+    }
+}
+
+/// Returns whether `terminator` may call the pointer or let it escape.
+fn terminator_uses<'tcx>(terminator: &Terminator<'tcx>, state: &DenseBitSet<Local>) -> bool {
+    match &terminator.kind {
+        // Calling the pointer means we have to lint; so does passing it to anything else.
+        // NOTE: this ignores the destination, since it's written to, not read from.
+        TerminatorKind::Call { func, args, .. } | TerminatorKind::TailCall { func, args, .. } => {
+            std::iter::once(func)
+                .chain(args.iter().map(|arg| &arg.node))
+                .any(|operand| mentions_any(|v| v.visit_operand(operand, Location::START), state))
+        }
+        // Function pointers don't need to be dropped, but a value holding one may have a `Drop`
+        // impl which calls it. We don't follow the drop glue, so treat this as an escape.
+        TerminatorKind::Drop { place, .. } => state.contains(place.local),
+        // None of these can read a pointer.
+        TerminatorKind::Goto { .. }
+        | TerminatorKind::SwitchInt { .. }
+        | TerminatorKind::Assert { .. }
+        | TerminatorKind::FalseEdge { .. }
+        | TerminatorKind::FalseUnwind { .. }
+        | TerminatorKind::UnwindResume
+        | TerminatorKind::UnwindTerminate(_)
+        | TerminatorKind::Unreachable => false,
+        // We already checked for writes to the return place.
+        TerminatorKind::Return => false,
+        TerminatorKind::InlineAsm { .. }
+        | TerminatorKind::Yield { .. }
+        | TerminatorKind::CoroutineDrop => {
+            mentions_any(|v| v.visit_terminator(terminator, Location::START), state)
+        }
+    }
+}
+
+/// Returns whether any local visited by `visit` is in `state`.
+fn mentions_any(visit: impl FnOnce(&mut MentionsLocal<'_>), state: &DenseBitSet<Local>) -> bool {
+    let mut visitor = MentionsLocal { state, found: false };
+    visit(&mut visitor);
+    visitor.found
+}
+
+struct MentionsLocal<'a> {
+    state: &'a DenseBitSet<Local>,
+    found: bool,
+}
+
+impl<'tcx> Visitor<'tcx> for MentionsLocal<'_> {
+    fn visit_local(&mut self, local: Local, _context: PlaceContext, _location: Location) {
+        self.found |= self.state.contains(local);
+    }
+}
@@ -0,0 +1,123 @@
+//! A machine-readable export of the call graph walked by the [post-mono pass](super::post_mono).
+//! This is enabled by `-Z ferrocene-validated-graph=<path>`.
+//!
+//! The graph is written by the driver after codegen rather than from inside a query, so that it is
+//! written on every build, even when the lints themselves are replayed from the incremental cache.
+//!
+//! The output is a single JSON object:
+//! ```json
+//! {
+//!   "roots": ["validated_fn"],
+//!   "edges": [
+//!     {
+//!       "caller": "validated_fn",
+//!       "callee": "<Unvalidated as std::clone::Clone>::clone",
+//!       "kind": "call",
+//!       "span": "src/lib.rs:10:5: 10:14",
+//!       "validated": false
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Each function body is only walked once, even if it is reachable from more than one root, so
+//! each edge is only listed once. To find all functions reachable from a given root, follow the
+//! edges transitively starting from that root.
+//!
+//! Functions in other crates whose MIR is not available are leaves of the graph; they were
+//! walked when their own crate was compiled.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write as _};
+use std::path::Path;
+
+use rustc_middle::middle::codegen_fn_attrs::ferrocene::item_is_validated;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{Instance, TyCtxt};
+use tracing::debug;
+
+use crate::ferrocene::diagnostics::CouldntWriteValidatedGraph;
+use crate::ferrocene::{UnvalidatedImplCause, Use, UseKind};
+
+#[derive(serde::Serialize)]
+struct GraphEdge {
+    caller: String,
+    callee: String,
+    kind: &'static str,
+    span: String,
+    validated: bool,
+}
+
+#[derive(Default, serde::Serialize)]
+pub(super) struct ValidatedGraph {
+    roots: Vec<String>,
+    edges: Vec<GraphEdge>,
+}
+
+impl ValidatedGraph {
+    /// Returns `None` if `-Z ferrocene-validated-graph` was not passed.
+    pub(super) fn new(tcx: TyCtxt<'_>) -> Option<Self> {
+        tcx.sess.opts.unstable_opts.ferrocene_validated_graph.as_ref().map(|_| Self::default())
+    }
+
+    pub(super) fn record_root(&mut self, instance: Instance<'_>) {
+        self.roots.push(with_no_trimmed_paths!(instance.to_string()));
+    }
+
+    pub(super) fn record_edge<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        caller: Instance<'tcx>,
+        use_: Use<'tcx>,
+    ) {
+        // A trait object cast may make several unvalidated functions reachable at once.
+        // Record an edge for each.
+        let callees = match use_.kind {
+            UseKind::TraitObjectCast(UnvalidatedImplCause::AssocFns(fns), _) => fns,
+            _ => &[use_.def_id()],
+        };
+        for &callee_id in callees {
+            let callee = match use_.opt_instance() {
+                Some(instance) => with_no_trimmed_paths!(instance.to_string()),
+                None => with_no_trimmed_paths!(tcx.def_path_str(callee_id)),
+            };
+            let edge = GraphEdge {
+                caller: with_no_trimmed_paths!(caller.to_string()),
+                callee,
+                kind: use_.kind.graph_kind(),
+                span: tcx.sess.source_map().span_to_diagnostic_string(use_.span),
+                validated: item_is_validated(tcx, callee_id).validated(),
+            };
+            debug!("recording edge {} -> {}", edge.caller, edge.callee);
+            self.edges.push(edge);
+        }
+    }
+
+    /// Write the graph to the path given by `-Z ferrocene-validated-graph`.
+    pub(super) fn write(&self, tcx: TyCtxt<'_>) {
+        let Some(path) = &tcx.sess.opts.unstable_opts.ferrocene_validated_graph else {
+            return;
+        };
+        if let Err(err) = self.write_to(path) {
+            tcx.dcx().emit_fatal(CouldntWriteValidatedGraph { path, error: err.to_string() });
+        }
+    }
+
+    fn write_to(&self, path: &Path) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut file, self)?;
+        writeln!(file)?;
+        file.flush()
+    }
+}
+
+impl<'tcx> UseKind<'tcx> {
+    fn graph_kind(self) -> &'static str {
+        match self {
+            UseKind::Called(..) => "call",
+            UseKind::FnPtrCast(..) => "fn-ptr-cast",
+            UseKind::TraitObjectCast(..) => "trait-object-cast",
+            UseKind::ContainsFnPtr(..) => "contains-fn-ptr",
+        }
+    }
+}
@@ -47,9 +47,10 @@
 //! We have no idea whether some validated code is going to call `option.map(returns_ptr())`.
 //! So we need to lint at the cast site instead.
 //!
-//! It might be possible to do fancy dataflow analysis to only disallow this if the pointer
-//! "escapes" the current function, but that's complicated, and always checking at the cast site is
-//! simple.
+//! By default, we always check at the cast site, which is simple. With
+//! `-Z ferrocene-fn-ptr-escape-analysis`, the post-mono pass instead runs a dataflow analysis and
+//! only lints if the pointer may be called or "escape" the current function; see [`escape`] for
+//! details. Since this needs MIR, the THIR pass leaves function pointer casts alone in that mode.
 //!
 //! ### const blocks
 //!
@@ -168,20 +169,40 @@
     report_in_external_macro: true
 }
 
+declare_tool_lint! {
+    /// The `ferrocene::reachable_panic` lint detects operations in verified code that may panic,
+    /// such as indexing, arithmetic overflow checks, `unwrap`, or slicing.
+    ///
+    /// This lint is a Ferrocene addition, and does not exist in upstream rustc.
+    ///
+    /// This lint is deny-by-default, but is only checked with `-Z deny-reachable-panics`.
+    /// Panics that are justified can be allowed where they happen. See [`panics`] for details.
+    pub ferrocene::REACHABLE_PANIC,
+    Deny,
+    "a verified function may panic",
+    report_in_external_macro: true
+}
+
 // NOTE: LintUnvalidated is public.
-declare_lint_pass!(LintUnvalidated => [UNVALIDATED]);
+declare_lint_pass!(LintUnvalidated => [UNVALIDATED, REACHABLE_PANIC]);
 
-pub use post_mono::lint_validated_roots;
+pub use post_mono::{PostMonoOutputs, lint_validated_roots};
 
 mod diagnostics;
 mod dynamic_casts;
+mod escape;
+mod graph;
+mod panics;
 mod post_mono;
+mod stats;
 mod thir;
 
 use rustc_data_structures::fx::FxHashSet;
 use rustc_hir::def::DefKind;
 use rustc_hir::{HirId, Item};
-use rustc_middle::middle::codegen_fn_attrs::ferrocene::{ValidatedStatus, item_is_validated};
+use rustc_middle::middle::codegen_fn_attrs::ferrocene::{
+    QualificationLevel, Validated, ValidatedStatus, item_is_validated, item_levels, validated_root,
+};
 use rustc_middle::span_bug;
 use rustc_middle::ty::{Instance, Ty, TyCtxt};
 use rustc_session::{declare_lint_pass, declare_tool_lint};
@@ -221,17 +242,25 @@ struct LintState<'tcx> {
     item: LocalDefId,
     /// For diagnostics; used to point to the `#[ferrocene::prevalidated]` attribute.
     annotation: Option<Span>,
+    /// The qualification levels `item` was validated at. Every item it uses must meet them.
+    levels: Validated,
     /// For diagnostics; see [`lint_use`](LintState::lint_use).
     shown_item: bool,
     /// For deduplication; see [`check_use`](LintState::check_use).
     shown_lints: FxHashSet<DefId>,
+    /// Whether [`check_use`](LintState::check_use) reports anything. This is `false` when the
+    /// post-mono pass is only re-run to produce its exports.
+    emit_lints: bool,
 }
 
 impl<'tcx> LintState<'tcx> {
     /// Check whether `item` needs to be linted at all. If so, return a new `LintState`.
     fn new(tcx: TyCtxt<'tcx>, item: LocalDefId) -> Option<Self> {
-        let ValidatedStatus::Validated { annotation } = item_is_validated(tcx, item.into()) else {
-            return None;
+        let annotation = match item_is_validated(tcx, item.into()) {
+            ValidatedStatus::Validated { annotation } => annotation,
+            // Entry points are checked like validated functions, without being validated.
+            ValidatedStatus::Unvalidated => validated_root(tcx, item.into())?.annotation(),
+            ValidatedStatus::WorkaroundDelegationBugs => return None,
         };
 
         if tcx.hir_node_by_def_id(item).associated_body().is_none() {
@@ -253,24 +282,39 @@ fn new(tcx: TyCtxt<'tcx>, item: LocalDefId) -> Option<Self> {
         }
 
         debug!("check {item:?}");
+        let levels = item_levels(tcx, item.into());
         Some(LintState {
             tcx,
             item,
             annotation,
+            levels,
             shown_item: false,
             shown_lints: FxHashSet::default(),
+            emit_lints: true,
         })
     }
 
     /// Check whether an item use needs to be linted. If so, lint it.
     fn check_use(&mut self, lint_node: HirId, use_: Use<'tcx>) {
+        if !self.emit_lints {
+            return;
+        }
         let tcx = self.tcx;
         let callee = use_.def_id();
 
-        if matches!(item_is_validated(tcx, callee), ValidatedStatus::Validated { .. }) {
-            debug!("no need to lint call to validated {callee:?}");
-            return;
-        }
+        let reason = match item_is_validated(tcx, callee) {
+            ValidatedStatus::Validated { .. } => {
+                let callee_levels = item_levels(tcx, callee);
+                let Some(required) = callee_levels.missing_level(&self.levels) else {
+                    debug!("no need to lint call to validated {callee:?}");
+                    return;
+                };
+                UnvalidatedReason::InsufficientLevel(required)
+            }
+            ValidatedStatus::Unvalidated | ValidatedStatus::WorkaroundDelegationBugs => {
+                UnvalidatedReason::Unvalidated
+            }
+        };
 
         // We have conditional logic below that -Z deduplicate-diagnostics doesn't know about.
         // Deduplicate lints manually.
@@ -279,10 +323,19 @@ fn check_use(&mut self, lint_node: HirId, use_: Use<'tcx>) {
             return;
         }
 
-        self.lint_use(lint_node, use_);
+        self.lint_use(lint_node, use_, reason);
     }
 }
 
+/// Why a [`Use`] was linted.
+#[derive(Copy, Clone, Debug)]
+enum UnvalidatedReason {
+    /// The item is not validated at all.
+    Unvalidated,
+    /// The item is validated, but not at a level required by the item using it.
+    InsufficientLevel(QualificationLevel),
+}
+
 #[derive(Debug)]
 enum InstantiateResult<'tcx> {
     /// Compilation is going to fail anyway. No need to do anything fancy.
@@ -312,10 +365,9 @@ struct Use<'tcx> {
 
 #[derive(Copy, Clone, Debug)]
 enum UnvalidatedImplCause<'tcx> {
-    /// An associated function from the source type's impl of one of the traits we were casting to.
-    ///
-    /// FIXME(diagnostics): this should have all unvalidated items in the impl, not just the first.
-    AssocFn(DefId),
+    /// Every unvalidated associated function from the source type's impls of the traits we were
+    /// casting to. Never empty.
+    AssocFns(&'tcx [DefId]),
     /// Only occurs pre-mono.
     UnresolvedGenericImpl(rustc_middle::ty::PolyTraitRef<'tcx>),
 }
@@ -335,7 +387,8 @@ fn def_id(self) -> DefId {
         match self.kind {
             UseKind::Called(instance) | UseKind::FnPtrCast(instance) => instance.def_id(),
             UseKind::ContainsFnPtr(id, _) => id,
-            UseKind::TraitObjectCast(UnvalidatedImplCause::AssocFn(id), _) => id,
+            // We only need one function for deduplication; diagnostics show all of them.
+            UseKind::TraitObjectCast(UnvalidatedImplCause::AssocFns(ids), _) => ids[0],
             UseKind::TraitObjectCast(UnvalidatedImplCause::UnresolvedGenericImpl(trait_ref), _) => {
                 trait_ref.def_id()
             }
@@ -0,0 +1,436 @@
+//! Panics reachable from validated code, reported by `-Z deny-reachable-panics` as the
+//! [`ferrocene::reachable_panic`](REACHABLE_PANIC) lint.
+//!
+//! While the [post-mono pass](super::post_mono) walks the call graph, we record every call and
+//! every runtime check ([`TerminatorKind::Assert`]) we see. Once all roots have been walked, we
+//! report each place in the current crate that may panic, either because of a runtime check in
+//! its own body, or because it calls a function in another crate that may panic. A function
+//! "may panic" if it can reach `panic_fmt` or anything in `core::panicking`, or a function in
+//! another crate whose MIR isn't available. We can't see what the latter do, so unless they are
+//! known not to panic (see [`is_panic_free`]) or were already checked in their own crate (see
+//! [`is_checked_upstream`]), we report calls to them as "may panic (body unavailable)".
+//!
+//! Panics in functions defined in the current crate are reported where they happen, not at every
+//! call site. Each report names the operation the panic comes from, and one path from a validated
+//! root to the panic:
+//! ```text
+//! error: reachable panic: `unwrap` or `expect` on a `None` or `Err` value
+//!   --> src/lib.rs:4:5
+//!    |
+//! LL |     x.unwrap()
+//!    |     ^^^^^^^^^^ this `unwrap` or `expect` may fail
+//!    |
+//!    = note: reachable through `validated_fn` -> `get` -> `core::option::Option::<T>::unwrap`
+//!            -> `core::option::unwrap_failed` -> `core::panicking::panic`
+//! ```
+//!
+//! Panics that are justified can be allowed with `#[allow(ferrocene::reachable_panic)]` on the
+//! operation or any item around it, like any other lint.
+
+use std::collections::VecDeque;
+use std::collections::hash_map::Entry;
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexSet};
+use rustc_hir::{HirId, LangItem};
+use rustc_middle::middle::codegen_fn_attrs::ferrocene::item_is_validated;
+use rustc_middle::mir::{self, AssertKind, Body, SourceScope};
+use rustc_middle::ty::{Instance, TyCtxt};
+use rustc_span::def_id::DefId;
+use rustc_span::{Span, sym};
+use tracing::debug;
+
+use crate::ferrocene::REACHABLE_PANIC;
+
+// for intra-doc links
+#[rustfmt::skip]
+#[allow(unused_imports)]
+use rustc_middle::mir::TerminatorKind;
+
+/// The operation a panic originates from.
+#[derive(Copy, Clone, Debug)]
+enum PanicCause {
+    Indexing,
+    Overflow,
+    DivisionByZero,
+    Unwrap,
+    SliceBounds,
+    /// A direct call to a panicking function, e.g. through `panic!` or `assert!`.
+    Explicit,
+    /// A call to a function that may panic, for none of the reasons above.
+    Call,
+    /// A call that reaches a function in another crate whose body we can't see.
+    Opaque,
+    /// Any other assertion inserted by the compiler, e.g. for misaligned pointer dereferences.
+    RuntimeCheck,
+}
+
+impl PanicCause {
+    fn from_assert(msg: &mir::AssertMessage<'_>) -> Self {
+        match msg {
+            AssertKind::BoundsCheck { .. } => PanicCause::Indexing,
+            AssertKind::Overflow(..) | AssertKind::OverflowNeg(_) => PanicCause::Overflow,
+            AssertKind::DivisionByZero(_) | AssertKind::RemainderByZero(_) => {
+                PanicCause::DivisionByZero
+            }
+            AssertKind::ResumedAfterReturn(_)
+            | AssertKind::ResumedAfterPanic(_)
+            | AssertKind::ResumedAfterDrop(_)
+            | AssertKind::MisalignedPointerDereference { .. }
+            | AssertKind::NullPointerDereference
+            | AssertKind::NullReferenceConstructed
+            | AssertKind::InvalidEnumConstruction(_) => PanicCause::RuntimeCheck,
+        }
+    }
+
+    /// The library functions that are only called when a specific operation fails.
+    fn from_failure_fn(tcx: TyCtxt<'_>, def_id: DefId) -> Option<Self> {
+        match tcx.get_diagnostic_name(def_id)? {
+            sym::option_unwrap_failed | sym::option_expect_failed | sym::result_unwrap_failed => {
+                Some(PanicCause::Unwrap)
+            }
+            sym::slice_index_fail | sym::str_slice_error_fail => Some(PanicCause::SliceBounds),
+            _ => None,
+        }
+    }
+
+    fn descr(self) -> &'static str {
+        match self {
+            PanicCause::Indexing => "index out of bounds",
+            PanicCause::Overflow => "arithmetic overflow",
+            PanicCause::DivisionByZero => "division by zero",
+            PanicCause::Unwrap => "`unwrap` or `expect` on a `None` or `Err` value",
+            PanicCause::SliceBounds => "slice range out of bounds",
+            PanicCause::Explicit => "explicit panic",
+            PanicCause::Call => "call to a function that may panic",
+            PanicCause::Opaque => "call to a function that may panic (body unavailable)",
+            PanicCause::RuntimeCheck => "failed runtime check",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PanicCause::Indexing => "this index may be out of bounds",
+            PanicCause::Overflow => "this arithmetic operation may overflow",
+            PanicCause::DivisionByZero => "this may divide by zero",
+            PanicCause::Unwrap => "this `unwrap` or `expect` may fail",
+            PanicCause::SliceBounds => "this slice range may be out of bounds",
+            PanicCause::Explicit => "this panics",
+            PanicCause::Call => "this may panic",
+            PanicCause::Opaque => "this may panic (body unavailable)",
+            PanicCause::RuntimeCheck => "this runtime check may fail",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+enum SiteKind<'tcx> {
+    Assert(PanicCause),
+    Call(Instance<'tcx>),
+}
+
+/// A call or assertion in the body of `caller`.
+#[derive(Copy, Clone, Debug)]
+struct Site<'tcx> {
+    caller: Instance<'tcx>,
+    kind: SiteKind<'tcx>,
+    span: Span,
+    lint_node: HirId,
+}
+
+/// Why a function in another crate may panic. Following [`Witness::Calls`] always ends in one of
+/// the other variants.
+#[derive(Copy, Clone, Debug)]
+enum Witness<'tcx> {
+    /// The function always panics.
+    Panics,
+    /// One of the assertions in its body may fail.
+    Assert(PanicCause),
+    /// The function is only called when this operation fails, see
+    /// [`from_failure_fn`](PanicCause::from_failure_fn).
+    Fails(PanicCause),
+    /// It calls this function, which may panic.
+    Calls(Instance<'tcx>),
+    /// We don't have the MIR of the function, so we have to assume it may panic.
+    Opaque,
+}
+
+#[derive(Default)]
+pub(super) struct ReachablePanics<'tcx> {
+    roots: Vec<Instance<'tcx>>,
+    /// Every call and assertion we walked past, in the order we saw them.
+    sites: Vec<Site<'tcx>>,
+    /// Functions in other crates we couldn't walk because their MIR isn't available.
+    opaque: FxIndexSet<Instance<'tcx>>,
+}
+
+impl<'tcx> ReachablePanics<'tcx> {
+    /// Returns `None` if `-Z deny-reachable-panics` was not passed.
+    pub(super) fn new(tcx: TyCtxt<'tcx>) -> Option<Self> {
+        tcx.sess.opts.unstable_opts.deny_reachable_panics.then(Self::default)
+    }
+
+    pub(super) fn record_root(&mut self, instance: Instance<'tcx>) {
+        self.roots.push(instance);
+    }
+
+    pub(super) fn record_call(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        caller: Instance<'tcx>,
+        mut callee: Instance<'tcx>,
+        span: Span,
+        lint_node: HirId,
+    ) {
+        if tcx.intrinsic(callee.def_id()).is_some() {
+            // The post-mono pass walks the fallback body instead; see `visit_instance`.
+            callee = Instance::new_raw(callee.def_id(), callee.args);
+        }
+        self.sites.push(Site { caller, kind: SiteKind::Call(callee), span, lint_node });
+    }
+
+    /// Record that the body of `instance` is not available, so we can't know whether it panics.
+    pub(super) fn record_opaque(&mut self, tcx: TyCtxt<'tcx>, instance: Instance<'tcx>) {
+        let def_id = instance.def_id();
+        if !is_panic_fn(tcx, def_id)
+            && !is_panic_free(tcx, def_id)
+            && !is_checked_upstream(tcx, def_id)
+        {
+            self.opaque.insert(instance);
+        }
+    }
+
+    pub(super) fn record_assert(
+        &mut self,
+        caller: Instance<'tcx>,
+        msg: &mir::AssertMessage<'tcx>,
+        span: Span,
+        lint_node: HirId,
+    ) {
+        let kind = SiteKind::Assert(PanicCause::from_assert(msg));
+        self.sites.push(Site { caller, kind, span, lint_node });
+    }
+
+    pub(super) fn report(self, tcx: TyCtxt<'tcx>) {
+        let is_local = |instance: Instance<'tcx>| instance.def_id().is_local();
+        let panics = |instance: Instance<'tcx>| is_panic_fn(tcx, instance.def_id());
+
+        // Find out which functions in other crates may panic. We only look at paths through other
+        // crates: functions in the current crate report their own panics.
+        let mut callers: FxHashMap<_, Vec<_>> = FxHashMap::default();
+        let mut panic_sources = vec![];
+        for site in &self.sites {
+            if let SiteKind::Call(callee) = site.kind
+                && panics(callee)
+            {
+                panic_sources.push((callee, Witness::Panics));
+            }
+            if is_local(site.caller) || panics(site.caller) {
+                continue;
+            }
+            match site.kind {
+                SiteKind::Assert(cause) => {
+                    panic_sources.push((site.caller, Witness::Assert(cause)))
+                }
+                SiteKind::Call(callee) => callers.entry(callee).or_default().push(site.caller),
+            }
+        }
+        panic_sources.extend(self.opaque.iter().map(|&instance| (instance, Witness::Opaque)));
+        let panic_paths = shortest_paths(&callers, panic_sources);
+        // The shortest path to a panic may not be the most useful one: indexing a slice with a
+        // range also has a precondition check that panics. Prefer paths through a function that
+        // tells us what went wrong.
+        let failure_sources = self.sites.iter().filter_map(|site| match site.kind {
+            SiteKind::Call(callee) if panic_paths.contains_key(&callee) => {
+                Some((callee, Witness::Fails(PanicCause::from_failure_fn(tcx, callee.def_id())?)))
+            }
+            _ => None,
+        });
+        let failure_paths = shortest_paths(&callers, failure_sources.collect());
+
+        // Find out how each function was reached from a root.
+        let mut callees: FxHashMap<_, Vec<_>> = FxHashMap::default();
+        for site in &self.sites {
+            if let SiteKind::Call(callee) = site.kind {
+                callees.entry(site.caller).or_default().push(callee);
+            }
+        }
+        let mut reached_from = FxHashMap::default();
+        let mut queue = VecDeque::new();
+        for &root in &self.roots {
+            reached_from.insert(root, None);
+            queue.push_back(root);
+        }
+        while let Some(caller) = queue.pop_front() {
+            if panics(caller) {
+                continue;
+            }
+            for &callee in callees.get(&caller).into_iter().flatten() {
+                if let Entry::Vacant(entry) = reached_from.entry(callee) {
+                    entry.insert(Some(caller));
+                    queue.push_back(callee);
+                }
+            }
+        }
+
+        let mut reported = FxHashSet::default();
+        let mut reports = vec![];
+        for site in &self.sites {
+            if !is_local(site.caller) || panics(site.caller) {
+                continue;
+            }
+
+            let mut path = vec![site.caller];
+            while let Some(&Some(caller)) = reached_from.get(path.last().unwrap()) {
+                path.push(caller);
+            }
+            path.reverse();
+
+            let cause = match site.kind {
+                SiteKind::Assert(cause) => cause,
+                SiteKind::Call(callee) if panics(callee) => {
+                    path.push(callee);
+                    PanicCause::Explicit
+                }
+                SiteKind::Call(callee) if failure_paths.contains_key(&callee) => {
+                    path.push(callee);
+                    let (failure_fn, Witness::Fails(cause)) =
+                        follow(&failure_paths, callee, &mut path)
+                    else {
+                        unreachable!("failure paths always end in a failure function")
+                    };
+                    follow(&panic_paths, failure_fn, &mut path);
+                    cause
+                }
+                SiteKind::Call(callee) if panic_paths.contains_key(&callee) => {
+                    path.push(callee);
+                    match follow(&panic_paths, callee, &mut path) {
+                        (_, Witness::Assert(cause)) => cause,
+                        (_, Witness::Opaque) => PanicCause::Opaque,
+                        _ => PanicCause::Call,
+                    }
+                }
+                // Either the callee can't panic, or it is in the current crate and reports its
+                // own panics.
+                SiteKind::Call(_) => continue,
+            };
+
+            // Generic functions may be instantiated more than once. Only report each panic once.
+            if !reported.insert((site.span, site.lint_node)) {
+                continue;
+            }
+            debug!("reachable panic at {:?}: {cause:?} through {path:?}", site.span);
+            reports.push((site, cause, path));
+        }
+
+        // Roots are walked in an arbitrary (but stable) order. Report in source order instead.
+        reports.sort_by_key(|(site, ..)| site.span);
+        for (site, cause, path) in reports {
+            tcx.emit_node_span_lint(
+                REACHABLE_PANIC,
+                site.lint_node,
+                site.span,
+                rustc_errors::DiagDecorator(|diag| {
+                    diag.primary_message(format!("reachable panic: {}", cause.descr()));
+                    diag.span_label(site.span, cause.label());
+                    // Need to do this lazily or `with_no_trimmed_paths` will panic :/
+                    let path = path
+                        .iter()
+                        .map(|instance| format!("`{}`", tcx.def_path_str(instance.def_id())))
+                        .collect::<Vec<_>>()
+                        .join(" -> ");
+                    diag.note(format!("reachable through {path}"));
+                    diag.help_once(
+                        "if this panic is justified, allow `ferrocene::reachable_panic` here \
+                        and give a `reason`",
+                    );
+                }),
+            );
+        }
+    }
+}
+
+/// Walk backwards from `sources` along `callers`, breadth-first, to find the shortest path from
+/// each function to one of `sources`.
+fn shortest_paths<'tcx>(
+    callers: &FxHashMap<Instance<'tcx>, Vec<Instance<'tcx>>>,
+    sources: Vec<(Instance<'tcx>, Witness<'tcx>)>,
+) -> FxHashMap<Instance<'tcx>, Witness<'tcx>> {
+    let mut paths = FxHashMap::default();
+    let mut queue = VecDeque::new();
+    for (source, witness) in sources {
+        if let Entry::Vacant(entry) = paths.entry(source) {
+            entry.insert(witness);
+            queue.push_back(source);
+        }
+    }
+    while let Some(callee) = queue.pop_front() {
+        for &caller in callers.get(&callee).into_iter().flatten() {
+            if let Entry::Vacant(entry) = paths.entry(caller) {
+                entry.insert(Witness::Calls(callee));
+                queue.push_back(caller);
+            }
+        }
+    }
+    paths
+}
+
+/// Follow `paths` starting from `start`, pushing every function on the way to `path`. Returns the
+/// last function and why it may panic.
+fn follow<'tcx>(
+    paths: &FxHashMap<Instance<'tcx>, Witness<'tcx>>,
+    start: Instance<'tcx>,
+    path: &mut Vec<Instance<'tcx>>,
+) -> (Instance<'tcx>, Witness<'tcx>) {
+    let mut current = start;
+    loop {
+        match paths[&current] {
+            Witness::Calls(next) => {
+                path.push(next);
+                current = next;
+            }
+            witness => return (current, witness),
+        }
+    }
+}
+
+/// Whether calling `def_id` always panics: it is `panic_fmt`, `begin_panic`, or anything in
+/// `core::panicking`.
+fn is_panic_fn(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    if tcx.is_lang_item(def_id, LangItem::PanicFmt)
+        || tcx.is_lang_item(def_id, LangItem::BeginPanic)
+    {
+        return true;
+    }
+    tcx.crate_name(def_id.krate) == sym::core
+        && tcx.def_path(def_id).data.first().and_then(|data| data.data.get_opt_name())
+            == Some(sym::panicking)
+}
+
+/// Whether `def_id` is known not to panic, even though we can't see its body: foreign functions
+/// are not Rust code, and can only reach a panic by calling back into Rust code, which is checked
+/// where its address is taken.
+fn is_panic_free(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    tcx.is_foreign_item(def_id)
+}
+
+/// Whether `def_id` was already checked for reachable panics in the crate that defines it:
+/// validated functions are roots there, so any panic they can reach was reported (or justified)
+/// when that crate was compiled. Reporting them again at every call site would only bury the calls
+/// into unvalidated code, which nobody has looked at.
+fn is_checked_upstream(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    item_is_validated(tcx, def_id).validated()
+}
+
+/// If `span` was inlined into `body`, return the span of the outermost inlined call instead, which
+/// is the one the user wrote.
+pub(super) fn callsite_span(body: &Body<'_>, scope: SourceScope, mut span: Span) -> Span {
+    let mut scope = Some(scope);
+    while let Some(current) = scope {
+        let data = &body.source_scopes[current];
+        if let Some((_, callsite)) = data.inlined {
+            span = callsite;
+        }
+        scope = data.parent_scope;
+    }
+    span
+}
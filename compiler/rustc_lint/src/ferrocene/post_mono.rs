@@ -15,6 +15,19 @@
 //! 3. The functions that instantiated it (recursively, back to the mono root), which we call the
 //!    [`InstantiationSite`].
 //!
+//! With `-Z ferrocene-fn-ptr-escape-analysis`, casts to function pointers are only linted if the
+//! pointer may be called or escape the current body; see [`escape`](super::escape).
+//!
+//! With `-Z ferrocene-validated-graph`, every edge we traverse is also recorded in a
+//! [`ValidatedGraph`] and written out as JSON once all roots have been walked.
+//!
+//! With `--print=ferrocene-validation-stats`, every function we reach is recorded in
+//! [`ValidationStats`], to tell apart unvalidated functions that are used from ones that are not.
+//!
+//! With `-Z deny-reachable-panics`, every call and assertion we see is recorded in
+//! [`ReachablePanics`], and each panic reachable from a validated root is reported once all roots
+//! have been walked.
+//!
 //! ## Recommended reading
 //! - [MIR Debugging](https://rustc-dev-guide.rust-lang.org/mir/debugging.html)
 
@@ -36,7 +49,10 @@
 use rustc_span::Span;
 use tracing::{debug, info, trace};
 
-use crate::ferrocene::{InstantiateResult, LintState, UnvalidatedImplCause, Use, UseKind};
+use crate::ferrocene::graph::ValidatedGraph;
+use crate::ferrocene::panics::{self, ReachablePanics};
+use crate::ferrocene::stats::ValidationStats;
+use crate::ferrocene::{InstantiateResult, LintState, UnvalidatedImplCause, Use, UseKind, escape};
 
 struct LintPostMono<'a, 'tcx> {
     /// The function we are currently traversing.
@@ -48,6 +64,12 @@ struct LintPostMono<'a, 'tcx> {
     /// This needs to store Instances, not DefIds, because different instantiations may call
     /// different concrete functions, and we want to make sure we lint all of them.
     visited: &'a mut FxHashSet<Instance<'tcx>>,
+    /// Only present with `-Z ferrocene-validated-graph`.
+    graph: &'a mut Option<ValidatedGraph>,
+    /// Only present with `--print=ferrocene-validation-stats`.
+    stats: &'a mut Option<ValidationStats>,
+    /// Only present with `-Z deny-reachable-panics`.
+    panics: &'a mut Option<ReachablePanics<'tcx>>,
     /// A list of all items we are going to traverse.
     /// This is needed to avoid non-determinism in diagnostics; we don't want `from_instantiation`
     /// to vary based on iteration order.
@@ -70,17 +92,48 @@ pub(super) struct InstantiationSite<'tcx> {
     pub(super) drop_fn: Option<DefId>,
 }
 
+/// What [`lint_validated_roots`] produces while walking the call graph.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PostMonoOutputs {
+    /// Only diagnostics. These are replayed when a query is served from the incremental cache,
+    /// so they can be emitted from inside `collect_and_partition_mono_items`.
+    Diagnostics,
+    /// Only side effects: writing `-Z ferrocene-validated-graph` and printing
+    /// `--print=ferrocene-validation-stats`. The query system can't replay these, so they have to be produced outside of any cached query.
+    Exports,
+    /// Both diagnostics and side effects.
+    All,
+}
+
+impl PostMonoOutputs {
+    fn diagnostics(self) -> bool {
+        matches!(self, PostMonoOutputs::Diagnostics | PostMonoOutputs::All)
+    }
+
+    fn exports(self) -> bool {
+        matches!(self, PostMonoOutputs::Exports | PostMonoOutputs::All)
+    }
+}
+
 /// Lint all used items recursively, starting from validated roots.
 /// Validated roots are calculated in `rustc_monomorphize::collector::ferrocene`, see there for
 /// details.
 ///
 /// We can't depend on anything in rustc_monomorphize here because we're too early in [rustc's
 /// dependency graph](https://rustc-dev-guide.rust-lang.org/compiler-src.html#big-picture). Instead
-/// we call this function in a query override in `rustc_interface`.
-pub fn lint_validated_roots<'tcx>(tcx: TyCtxt<'tcx>, roots: UnordSet<MonoItem<'tcx>>) {
+/// we call this function in a query override in `rustc_interface`, and again from the driver for
+/// the [`PostMonoOutputs::Exports`].
+pub fn lint_validated_roots<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    roots: UnordSet<MonoItem<'tcx>>,
+    outputs: PostMonoOutputs,
+) {
     trace!("all roots: {roots:?}");
 
     let mut visited = FxHashSet::default();
+    let mut graph = if outputs.exports() { ValidatedGraph::new(tcx) } else { None };
+    let mut stats = if outputs.exports() { ValidationStats::new(tcx) } else { None };
+    let mut panics = if outputs.diagnostics() { ReachablePanics::new(tcx) } else { None };
 
     // We need to sort these for query stability.
     let roots = tcx.with_stable_hashing_context(move |mut hcx| roots.into_sorted(&mut hcx, true));
@@ -117,9 +170,35 @@ pub fn lint_validated_roots<'tcx>(tcx: TyCtxt<'tcx>, roots: UnordSet<MonoItem<'t
         debug!("linting root: {instance:?}");
         let def_id = instance.def_id().expect_local();
         if let Some(mut linter) = LintState::new(tcx, def_id) {
-            LintPostMono::visit_instance(&mut linter, &mut visited, &roots, instance, None);
+            linter.emit_lints = outputs.diagnostics();
+            if let Some(graph) = &mut graph {
+                graph.record_root(instance);
+            }
+            if let Some(panics) = &mut panics {
+                panics.record_root(instance);
+            }
+            LintPostMono::visit_instance(
+                &mut linter,
+                &mut visited,
+                &mut graph,
+                &mut stats,
+                &mut panics,
+                &roots,
+                instance,
+                None,
+            );
         }
     }
+
+    if let Some(graph) = graph {
+        graph.write(tcx);
+    }
+    if let Some(stats) = stats {
+        stats.print(tcx);
+    }
+    if let Some(panics) = panics {
+        panics.report(tcx);
+    }
 }
 
 impl<'a, 'tcx> mir::visit::Visitor<'tcx> for LintPostMono<'a, 'tcx> {
@@ -129,11 +208,29 @@ fn visit_terminator(&mut self, terminator: &Terminator<'tcx>, location: Location
             let use_ = self.use_(UseKind::Called(callee_instance), terminator.source_info.span);
             self.on_edge(use_, terminator.source_info.scope, pre_mono_call);
         }
+        if let TerminatorKind::Assert { cond, expected, msg, .. } = &terminator.kind
+            && self.panics.is_some()
+            // Assertions that always hold are removed during codegen.
+            && cond.constant().and_then(|c| c.const_.try_to_bool()) != Some(*expected)
+        {
+            let scope = terminator.source_info.scope;
+            let lint_node = self.lint_node(scope);
+            let span = panics::callsite_span(self.body, scope, terminator.source_info.span);
+            self.panics.as_mut().unwrap().record_assert(self.instance, msg, span, lint_node);
+        }
         self.super_terminator(terminator, location);
     }
 
     fn visit_rvalue(&mut self, rval: &Rvalue<'tcx>, location: Location) {
         let Some((call_span, use_kind)) = self.find_dynamic_cast(rval) else { return };
+        let tcx = self.linter.tcx;
+        if let UseKind::FnPtrCast(_) = use_kind
+            && tcx.sess.opts.unstable_opts.ferrocene_fn_ptr_escape_analysis
+            && !escape::fn_ptr_may_be_used(tcx, self.body, location)
+        {
+            debug!("function pointer cast at {location:?} is never called and never escapes");
+            return;
+        }
         let source_info = self.body.source_info(location);
         let use_ = self.use_(use_kind, call_span);
         self.on_edge(use_, source_info.scope, use_.def_id());
@@ -193,12 +290,12 @@ fn use_(&self, kind: UseKind<'tcx>, span: Span) -> Use<'tcx> {
         Use { kind, span, from_instantiation: self.from_instantiation }
     }
 
-    fn lint(&mut self, use_: Use<'tcx>, scope: SourceScope) -> HirId {
+    fn lint_node(&self, scope: SourceScope) -> HirId {
         // Try to update the lint node if possible, but use the lint node of the caller if the
         // callee is cross-crate.
         // FIXME: we have enough info here to show a backtrace of how the function was instantiated,
         // maybe pass that in so we can show it?
-        let lint_node = match scope.lint_root(&self.body.source_scopes) {
+        match scope.lint_root(&self.body.source_scopes) {
             Some(node) => node,
             None => match self.from_instantiation.as_ref() {
                 // This is a bit odd - we use the HIR id of the caller function,
@@ -209,7 +306,18 @@ fn lint(&mut self, use_: Use<'tcx>, scope: SourceScope) -> HirId {
                 // We don't have anywhere to point to, so just point to the crate root.
                 None => CRATE_HIR_ID,
             },
-        };
+        }
+    }
+
+    fn lint(&mut self, use_: Use<'tcx>, scope: SourceScope) -> HirId {
+        let lint_node = self.lint_node(scope);
+
+        if let Some(graph) = self.graph {
+            graph.record_edge(self.linter.tcx, self.instance, use_);
+        }
+        if let Some(stats) = self.stats {
+            stats.record_use(use_);
+        }
 
         // Lint this use.
         self.linter.check_use(lint_node, use_);
@@ -246,13 +354,18 @@ fn on_edge(&mut self, use_: Use<'tcx>, scope: SourceScope, pre_mono_callee: DefI
             // `main`.
             //
             // Therefore we don't have an instance and can't check its body.
-            UseKind::TraitObjectCast(UnvalidatedImplCause::AssocFn(_), _) => return,
+            UseKind::TraitObjectCast(UnvalidatedImplCause::AssocFns(_), _) => return,
             // In any other case we should have fully monomorphized the function.
             _ => use_.opt_instance().unwrap_or_else(|| {
                 span_bug!(use_.span, "called expect_instance on a THIR-only lint kind")
             }),
         };
 
+        if let Some(panics) = self.panics {
+            let span = panics::callsite_span(self.body, scope, use_.span);
+            panics.record_call(self.linter.tcx, self.instance, callee_instance, span, lint_node);
+        }
+
         // Keep the call span for diagnostics.
         let site = if Some(self.instance.def_id()) == self.linter.tcx.lang_items().drop_glue_fn() {
             // We want to show a better span; drop_in_place is never interesting since the body is
@@ -278,6 +391,9 @@ fn on_edge(&mut self, use_: Use<'tcx>, scope: SourceScope, pre_mono_callee: DefI
         LintPostMono::visit_instance(
             self.linter,
             self.visited,
+            self.graph,
+            self.stats,
+            self.panics,
             self.roots,
             callee_instance,
             Some(site),
@@ -287,6 +403,9 @@ fn on_edge(&mut self, use_: Use<'tcx>, scope: SourceScope, pre_mono_callee: DefI
     fn visit_instance(
         linter: &'a mut LintState<'tcx>,
         visited: &mut FxHashSet<Instance<'tcx>>,
+        graph: &mut Option<ValidatedGraph>,
+        stats: &mut Option<ValidationStats>,
+        panics: &mut Option<ReachablePanics<'tcx>>,
         roots: &'a [MonoItem<'tcx>],
         mut instance: Instance<'tcx>,
         from_instantiation: Option<InstantiationSite<'tcx>>,
@@ -318,6 +437,12 @@ fn visit_instance(
             // MIR between crates.
             // We must have checked the item when it was compiled, so just ignore it.
             info!("no MIR for {owner:?}");
+            // We can't tell whether it panics though, so assume it might.
+            if let Some(panics) = panics
+                && let InstanceKind::Item(_) = instance.def
+            {
+                panics.record_opaque(tcx, instance);
+            }
             return;
         }
 
@@ -339,7 +464,17 @@ fn visit_instance(
 
         let body = tcx.instance_mir(instance.def);
         trace!(body = ?body, "visiting body");
-        let mut this = LintPostMono { linter, visited, roots, instance, body, from_instantiation };
+        let mut this = LintPostMono {
+            linter,
+            visited,
+            graph,
+            stats,
+            panics,
+            roots,
+            instance,
+            body,
+            from_instantiation,
+        };
         for (bb, data) in mir::traversal::preorder(body) {
             this.visit_basic_block_data(bb, data);
         }
@@ -0,0 +1,141 @@
+//! Per-module counts of validated and unvalidated functions in the current crate, printed by
+//! `--print=ferrocene-validation-stats`. This is meant for tracking progress towards validating a
+//! crate, not as evidence of compliance; for that, use the lint itself.
+//!
+//! Each function defined in the current crate is counted exactly once, as one of:
+//! - `prevalidated`: the function is validated, either by `#[ferrocene::prevalidated]` or by a
+//!   validation manifest.
+//! - `entrypoint`: the function is an unvalidated entry point, such as `#[ferrocene::entrypoint]`.
+//!   Everything it uses is checked, but it is not validated itself.
+//! - `reachable`: the function is unvalidated, but the [post-mono pass](super::post_mono) reached
+//!   it from a validated root. These are the functions the lint reports.
+//! - `unreachable`: the function is unvalidated, and is not used by any validated function.
+//!
+//! The output is a table, sorted by module:
+//! ```text
+//! module       prevalidated  entrypoint  reachable  unreachable
+//! my_crate                2           1          1            0
+//! my_crate::io            0           0          0            4
+//! total                   2           1          1            4
+//! ```
+//!
+//! Closures are not counted separately. Requesting this output runs the post-mono pass even when
+//! not generating code, as if `-Z ferrocene-post-mono-check` had been passed. The table is printed
+//! by the driver rather than from inside a query, so that it is also printed when the lints are
+//! replayed from the incremental cache.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_middle::middle::codegen_fn_attrs::ferrocene::{item_is_validated, validated_root};
+use rustc_middle::ty::TyCtxt;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_session::config::PrintKind;
+
+use crate::ferrocene::{UnvalidatedImplCause, Use, UseKind};
+
+#[derive(Copy, Clone, Default)]
+struct ModuleStats {
+    prevalidated: usize,
+    entrypoint: usize,
+    reachable: usize,
+    unreachable: usize,
+}
+
+impl ModuleStats {
+    fn add(&mut self, other: ModuleStats) {
+        self.prevalidated += other.prevalidated;
+        self.entrypoint += other.entrypoint;
+        self.reachable += other.reachable;
+        self.unreachable += other.unreachable;
+    }
+}
+
+#[derive(Default)]
+pub(super) struct ValidationStats {
+    /// Every function used by a function we walked. The validated roots themselves are not
+    /// included, but are counted as validated anyway.
+    reached: FxHashSet<DefId>,
+}
+
+impl ValidationStats {
+    /// Returns `None` if `--print=ferrocene-validation-stats` was not passed.
+    pub(super) fn new(tcx: TyCtxt<'_>) -> Option<Self> {
+        tcx.sess
+            .opts
+            .prints
+            .iter()
+            .any(|req| req.kind == PrintKind::FerroceneValidationStats)
+            .then(Self::default)
+    }
+
+    pub(super) fn record_use(&mut self, use_: Use<'_>) {
+        match use_.kind {
+            // A trait object cast may make several unvalidated functions reachable at once.
+            UseKind::TraitObjectCast(UnvalidatedImplCause::AssocFns(fns), _) => {
+                self.reached.extend(fns.iter().copied())
+            }
+            _ => {
+                self.reached.insert(use_.def_id());
+            }
+        }
+    }
+
+    /// Print the table to the output given by `--print=ferrocene-validation-stats`.
+    pub(super) fn print(&self, tcx: TyCtxt<'_>) {
+        let crate_name = tcx.crate_name(LOCAL_CRATE);
+        let mut modules = BTreeMap::<String, ModuleStats>::new();
+
+        for def_id in tcx.hir_body_owners() {
+            if !matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn) {
+                continue;
+            }
+            let module = tcx.parent_module_from_def_id(def_id).to_def_id();
+            let path = with_no_trimmed_paths!(tcx.def_path_str(module));
+            let module = if path.is_empty() {
+                crate_name.to_string()
+            } else {
+                format!("{crate_name}::{path}")
+            };
+
+            let stats = modules.entry(module).or_default();
+            if item_is_validated(tcx, def_id.to_def_id()).validated() {
+                stats.prevalidated += 1;
+            } else if validated_root(tcx, def_id.to_def_id()).is_some() {
+                stats.entrypoint += 1;
+            } else if self.reached.contains(&def_id.to_def_id()) {
+                stats.reachable += 1;
+            } else {
+                stats.unreachable += 1;
+            }
+        }
+
+        let mut total = ModuleStats::default();
+        for stats in modules.values() {
+            total.add(*stats);
+        }
+
+        let width =
+            modules.keys().map(|module| module.len()).max().unwrap_or(0).max("module".len());
+        let mut out = String::new();
+        writeln!(out, "{:width$}  prevalidated  entrypoint  reachable  unreachable", "module")
+            .unwrap();
+        for (module, stats) in modules.iter().chain([(&"total".to_string(), &total)]) {
+            writeln!(
+                out,
+                "{module:width$}  {:>12}  {:>10}  {:>9}  {:>11}",
+                stats.prevalidated, stats.entrypoint, stats.reachable, stats.unreachable,
+            )
+            .unwrap();
+        }
+
+        for req in &tcx.sess.opts.prints {
+            if req.kind == PrintKind::FerroceneValidationStats {
+                req.out.overwrite(&out, tcx.sess);
+            }
+        }
+    }
+}
@@ -127,6 +127,10 @@ fn find_unvalidated_use(&mut self, expr: &thir::Expr<'tcx>) -> Option<Use<'tcx>>
                 source,
                 ..
             } => {
+                if tcx.sess.opts.unstable_opts.ferrocene_fn_ptr_escape_analysis {
+                    // Escape analysis needs MIR; leave this to the post-mono pass.
+                    return None;
+                }
                 let source_ty = self.thir[source].ty;
                 let fn_ptr_trait = tcx.require_lang_item(LangItem::FnPtrTrait, expr.span);
                 let trait_ref = Binder::dummy(ExistentialTraitRef::new_from_args(
@@ -1113,8 +1113,11 @@ fn should_encode_mir(
         DefKind::SyntheticCoroutineBody => (false, true),
         // Full-fledged functions + closures
         DefKind::AssocFn | DefKind::Fn | DefKind::Closure => {
+            // Ferrocene addition: `-Z ferrocene-post-mono-check` walks the MIR of generic
+            // functions from upstream crates, even if those crates were only checked.
             let opt = tcx.sess.opts.unstable_opts.always_encode_mir
-                || (tcx.sess.opts.output_types.should_codegen()
+                || ((tcx.sess.opts.output_types.should_codegen()
+                    || tcx.sess.opts.unstable_opts.ferrocene_post_mono_check)
                     && reachable_set.contains(&def_id)
                     && (tcx.generics_of(def_id).requires_monomorphization(tcx)
                         || tcx.cross_crate_inlinable(def_id)));
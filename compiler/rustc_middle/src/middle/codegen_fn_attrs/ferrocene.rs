@@ -1,23 +1,259 @@
+use std::fmt;
+
+use rustc_abi::ExternAbi;
+use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def::DefKind;
-use rustc_hir::def_id::DefId;
+use rustc_hir::def_id::{CrateNum, DefId};
 use rustc_hir::{
-    ForeignItem, ForeignItemKind, Item, ItemKind, Node, TraitFn, TraitItem, TraitItemKind,
+    self as hir, ForeignItem, ForeignItemKind, Item, ItemKind, Node, TraitFn, TraitItem,
+    TraitItemKind, find_attr,
 };
 use rustc_macros::{StableHash, TyDecodable, TyEncodable};
+use rustc_middle::ty::print::with_no_trimmed_paths;
 use rustc_middle::ty::{self, TyCtxt};
 use rustc_span::{Span, Symbol, sym};
 use tracing::info;
 
-#[derive(Clone, TyEncodable, TyDecodable, StableHash, Debug)]
+#[derive(Clone, Default, TyEncodable, TyDecodable, StableHash, Debug)]
 pub struct Validated {
-    // prevalidated: bool,
-    // FIXME: exported_constraint, validated, standards, qualification levels, entrypoint
+    /// The levels given by `#[ferrocene::prevalidated(standard = "...", level = "...")]`.
+    ///
+    /// This is empty for a bare `#[ferrocene::prevalidated]`, and for items that are implicitly
+    /// validated, such as `main`. Those place no restrictions on their callers or callees.
+    pub levels: Vec<QualificationLevel>,
+    // FIXME: exported_constraint, entrypoint
+}
+
+impl Validated {
+    /// Return the first level in `required` that `self` does not meet, if any.
+    ///
+    /// An item meets a level if it was validated against the same standard, at that level or
+    /// a more stringent one.
+    pub fn missing_level(&self, required: &Validated) -> Option<QualificationLevel> {
+        if self.levels.is_empty() {
+            return None;
+        }
+        required.levels.iter().copied().find(|req| {
+            !self.levels.iter().any(|have| have.standard == req.standard && have.level >= req.level)
+        })
+    }
+}
+
+/// The safety standards understood by `#[ferrocene::prevalidated]`, along with their
+/// levels, ordered from least to most stringent.
+pub const SAFETY_STANDARDS: &[(&str, &[&str])] = &[
+    ("ISO 26262", &["QM", "ASIL-A", "ASIL-B", "ASIL-C", "ASIL-D"]),
+    ("IEC 61508", &["SIL 1", "SIL 2", "SIL 3", "SIL 4"]),
+    ("IEC 62304", &["Class A", "Class B", "Class C"]),
+    ("DO-178C", &["DAL E", "DAL D", "DAL C", "DAL B", "DAL A"]),
+];
+
+/// A level of a safety standard, such as ISO 26262 ASIL-D.
+#[derive(Copy, Clone, PartialEq, Eq, TyEncodable, TyDecodable, StableHash, Debug)]
+pub struct QualificationLevel {
+    /// Index into [`SAFETY_STANDARDS`].
+    standard: u8,
+    /// Index into the standard's levels. Higher is more stringent.
+    level: u8,
+}
+
+impl QualificationLevel {
+    pub fn standard(self) -> &'static str {
+        SAFETY_STANDARDS[self.standard as usize].0
+    }
+
+    pub fn level(self) -> &'static str {
+        SAFETY_STANDARDS[self.standard as usize].1[self.level as usize]
+    }
+}
+
+impl fmt::Display for QualificationLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.standard(), self.level())
+    }
+}
+
+/// Why the arguments to a `#[ferrocene::prevalidated]` attribute could not be parsed.
+pub enum ValidatedAttrError {
+    /// The arguments were not of the form `standard = "...", level = "..."`.
+    Malformed {
+        span: Span,
+    },
+    UnknownStandard {
+        span: Span,
+        standard: Symbol,
+    },
+    UnknownLevel {
+        span: Span,
+        standard: &'static str,
+        level: Symbol,
+    },
 }
 
 const VALIDATED_ATTR: &[Symbol; 2] = &[sym::ferrocene, sym::prevalidated];
+const ENTRYPOINT_ATTR: &[Symbol; 2] = &[sym::ferrocene, sym::entrypoint];
+
+/// Parse the arguments of a single `#[ferrocene::prevalidated]` attribute.
+/// Returns `None` for an attribute without arguments.
+fn parse_validated_attr(
+    attr: &hir::Attribute,
+) -> Result<Option<QualificationLevel>, ValidatedAttrError> {
+    let Some(items) = attr.meta_item_list() else {
+        if attr.value_str().is_some() {
+            return Err(ValidatedAttrError::Malformed { span: attr.span() });
+        }
+        return Ok(None);
+    };
+
+    let (mut standard, mut level) = (None, None);
+    for item in &items {
+        let slot = match item.name() {
+            Some(sym::standard) => &mut standard,
+            Some(sym::level) => &mut level,
+            _ => return Err(ValidatedAttrError::Malformed { span: item.span() }),
+        };
+        match item.value_str() {
+            Some(value) if slot.is_none() => *slot = Some((value, item.span())),
+            _ => return Err(ValidatedAttrError::Malformed { span: item.span() }),
+        }
+    }
+    let (Some((standard, standard_span)), Some((level, level_span))) = (standard, level) else {
+        return Err(ValidatedAttrError::Malformed { span: attr.span() });
+    };
+
+    let Some(standard_idx) =
+        SAFETY_STANDARDS.iter().position(|(name, _)| *name == standard.as_str())
+    else {
+        return Err(ValidatedAttrError::UnknownStandard { span: standard_span, standard });
+    };
+    let (standard_name, levels) = SAFETY_STANDARDS[standard_idx];
+    let Some(level_idx) = levels.iter().position(|name| *name == level.as_str()) else {
+        return Err(ValidatedAttrError::UnknownLevel {
+            span: level_span,
+            standard: standard_name,
+            level,
+        });
+    };
+
+    Ok(Some(QualificationLevel { standard: standard_idx as u8, level: level_idx as u8 }))
+}
+
+/// Collect the qualification levels from every `#[ferrocene::prevalidated]` attribute on
+/// `def_id`. Malformed attributes are passed to `on_error` and otherwise ignored.
+///
+/// Like [`item_is_validated`], closures inherit the levels of the function they are defined in.
+/// Only the item's own attributes are considered; items that are implicitly validated (such as
+/// `main` or builtin derives) have no levels.
+pub fn validated_levels(
+    tcx: TyCtxt<'_>,
+    def_id: DefId,
+    mut on_error: impl FnMut(ValidatedAttrError),
+) -> Validated {
+    let owner = tcx.typeck_root_def_id(def_id);
+    let mut validated = Validated::default();
+    for attr in tcx.get_attrs_by_path(owner, VALIDATED_ATTR) {
+        match parse_validated_attr(attr) {
+            Ok(Some(level)) => validated.levels.push(level),
+            Ok(None) => {}
+            Err(err) => on_error(err),
+        }
+    }
+    validated
+}
+
+/// The qualification levels of `def_id`, as recorded in its [`codegen_fn_attrs`], which are
+/// encoded in the metadata of upstream crates. Items without codegen attributes have no levels.
+///
+/// [`codegen_fn_attrs`]: crate::middle::codegen_fn_attrs::CodegenFnAttrs::validated
+pub fn item_levels(tcx: TyCtxt<'_>, def_id: DefId) -> Validated {
+    if !tcx.def_kind(def_id).has_codegen_attrs() {
+        return Validated::default();
+    }
+    tcx.codegen_fn_attrs(def_id).validated.clone().unwrap_or_default()
+}
+
+/// Overrides for the validation status of items in upstream crates, loaded from the file passed to
+/// `-Z ferrocene-validation-manifest`. This allows validating crates without patching them.
+#[derive(Debug, Default)]
+pub struct ValidationManifest {
+    /// For each upstream crate in the manifest, maps the path of an item (as printed by
+    /// `def_path_str` without trimming) to whether it is validated.
+    pub crates: FxHashMap<CrateNum, FxHashMap<String, bool>>,
+}
+
+impl ValidationManifest {
+    fn status(&self, tcx: TyCtxt<'_>, def_id: DefId) -> Option<bool> {
+        let items = self.crates.get(&def_id.krate)?;
+        items.get(&with_no_trimmed_paths!(tcx.def_path_str(def_id))).copied()
+    }
+}
+
+/// Why a function is a validated entry point. Everything an entry point uses is checked like for a
+/// validated function, but the entry point itself is not validated: calling it from validated code
+/// is linted like calling any other unvalidated function.
+#[derive(Copy, Clone, Debug)]
+pub enum ValidatedRoot {
+    /// `#[ferrocene::entrypoint]`, e.g. a function called from outside Rust.
+    Entrypoint { annotation: Span },
+    /// `#[no_mangle]` or `#[export_name]` with `-Z ferrocene-validated-roots=exported`, e.g. an
+    /// interrupt handler in a vector table.
+    Exported,
+    /// `extern "C"` with `-Z ferrocene-validated-roots=extern-c`, e.g. a function called from C.
+    ExternC,
+}
+
+impl ValidatedRoot {
+    pub fn descr(self) -> &'static str {
+        match self {
+            ValidatedRoot::Entrypoint { .. } => "`#[ferrocene::entrypoint]` functions",
+            ValidatedRoot::Exported => "exported functions",
+            ValidatedRoot::ExternC => "`extern \"C\"` functions",
+        }
+    }
+
+    /// The `#[ferrocene::entrypoint]` attribute, if any.
+    pub fn annotation(self) -> Option<Span> {
+        match self {
+            ValidatedRoot::Entrypoint { annotation } => Some(annotation),
+            ValidatedRoot::Exported | ValidatedRoot::ExternC => None,
+        }
+    }
+}
+
+/// Returns whether `def_id` is a validated entry point, because of `#[ferrocene::entrypoint]` or
+/// `-Z ferrocene-validated-roots`. This is separate from [`item_is_validated`]: being an entry point
+/// only starts the checks, it doesn't make the function validated.
+///
+/// Only functions in the current crate are considered. Upstream crates were checked with their own
+/// flags when they were compiled; downstream crates calling them need an annotation instead.
+pub fn validated_root(tcx: TyCtxt<'_>, def_id: DefId) -> Option<ValidatedRoot> {
+    let roots = tcx.sess.opts.unstable_opts.ferrocene_validated_roots;
+    let local = def_id.as_local()?;
+    if !matches!(tcx.def_kind(local), DefKind::Fn | DefKind::AssocFn) {
+        return None;
+    }
+
+    // NOTE: we can't use `codegen_fn_attrs` here, since it calls `item_is_validated`.
+    if let Some(attr) = tcx.get_attrs_by_path(def_id, ENTRYPOINT_ATTR).next() {
+        Some(ValidatedRoot::Entrypoint { annotation: attr.span() })
+    } else if roots.exported
+        && (find_attr!(tcx, local, NoMangle(..)) || find_attr!(tcx, local, ExportName { .. }))
+    {
+        Some(ValidatedRoot::Exported)
+    } else if roots.extern_c && matches!(tcx.fn_sig(local).skip_binder().abi(), ExternAbi::C { .. })
+    {
+        Some(ValidatedRoot::ExternC)
+    } else {
+        None
+    }
+}
 
 pub enum ValidatedStatus {
-    /// `annotation` is None IFF this is the `main` entrypoint.
+    /// `annotation` is the `#[ferrocene::prevalidated]` attribute, or the
+    /// `#[ferrocene::entrypoint]` attribute for closures defined in an entry point.
+    /// It is None IFF this is the `main` entrypoint, a closure defined in a root given by
+    /// `-Z ferrocene-validated-roots`, a synthetic item, or an item that is validated by
+    /// `-Z ferrocene-validation-manifest`.
     Validated {
         annotation: Option<Span>,
     },
@@ -54,6 +290,15 @@ pub fn item_is_validated(tcx: TyCtxt<'_>, def_id: DefId) -> ValidatedStatus {
         return ValidatedStatus::Validated { annotation: None };
     }
 
+    // The manifest takes precedence over any attributes in upstream crates.
+    if !owner.is_local() && tcx.sess.opts.unstable_opts.ferrocene_validation_manifest.is_some() {
+        match tcx.ferrocene_validation_manifest(()).status(tcx, owner) {
+            Some(true) => return ValidatedStatus::Validated { annotation: None },
+            Some(false) => return ValidatedStatus::Unvalidated,
+            None => {}
+        }
+    }
+
     // Skip intrinsics, extern functions, and associated functions with no default.
     // We only have to do this for local DefIds; nothing makes it to post-mono without a body unless
     // it's an intrinsic.
@@ -128,6 +373,12 @@ pub fn item_is_validated(tcx: TyCtxt<'_>, def_id: DefId) -> ValidatedStatus {
         ValidatedStatus::WorkaroundDelegationBugs
     } else if main_is_validated {
         ValidatedStatus::Validated { annotation: None }
+    } else if owner != def_id
+        && let Some(root) = validated_root(tcx, owner)
+    {
+        // Entry points are not validated, but closures defined in one are part of its body, which
+        // is checked like a validated function.
+        ValidatedStatus::Validated { annotation: root.annotation() }
     } else {
         ValidatedStatus::Unvalidated
     }
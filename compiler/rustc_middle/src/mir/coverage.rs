@@ -50,6 +50,23 @@ pub struct CounterId {}
     pub struct ExpressionId {}
 }
 
+rustc_index::newtype_index! {
+    /// ID of an MC/DC condition, local to its decision. Values ascend from 0.
+    ///
+    /// LLVM represents condition IDs as `int16_t`, which bounds the number of
+    /// conditions in a decision. (Ferrocene addition)
+    #[stable_hash]
+    #[encodable]
+    #[orderable]
+    #[max = 0x7FFF]
+    #[debug_format = "ConditionId({})"]
+    pub struct ConditionId {}
+}
+
+impl ConditionId {
+    pub const START: Self = Self::from_usize(0);
+}
+
 /// Enum that can hold a constant zero value, the ID of an physical coverage
 /// counter, or the ID of a coverage-counter expression.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -90,6 +107,21 @@ pub enum CoverageKind {
     /// During codegen, this might be lowered to `llvm.instrprof.increment` or
     /// to a no-op, depending on the outcome of counter-creation.
     VirtualCounter { bcb: BasicCoverageBlock },
+
+    /// Marks the point in MIR control flow where an MC/DC condition was
+    /// evaluated, adding `index` to the test vector index of the enclosing
+    /// decision.
+    ///
+    /// This is eventually lowered to updates of a stack-allocated condition
+    /// bitmap in LLVM IR. (Ferrocene addition)
+    CondBitmapUpdate { index: u32, decision_depth: u16 },
+
+    /// Marks the point in MIR control flow where an MC/DC decision was fully
+    /// evaluated, recording its test vector in the global bitmap.
+    ///
+    /// This is eventually lowered to `llvm.instrprof.mcdc.tvbitmap.update` in
+    /// LLVM IR. (Ferrocene addition)
+    TestVectorBitmapUpdate { bitmap_idx: u32, decision_depth: u16 },
 }
 
 impl Debug for CoverageKind {
@@ -99,6 +131,12 @@ fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
             SpanMarker => write!(fmt, "SpanMarker"),
             BlockMarker { id } => write!(fmt, "BlockMarker({:?})", id.index()),
             VirtualCounter { bcb } => write!(fmt, "VirtualCounter({bcb:?})"),
+            CondBitmapUpdate { index, decision_depth } => {
+                write!(fmt, "CondBitmapUpdate(index={index:?}, depth={decision_depth:?})")
+            }
+            TestVectorBitmapUpdate { bitmap_idx, decision_depth } => {
+                write!(fmt, "TestVectorUpdate({bitmap_idx:?}, depth={decision_depth:?})")
+            }
         }
     }
 }
@@ -135,6 +173,16 @@ pub enum MappingKind {
     Code { bcb: BasicCoverageBlock },
     /// Associates a branch region with separate counters for true and false.
     Branch { true_bcb: BasicCoverageBlock, false_bcb: BasicCoverageBlock },
+    /// Associates a branch region of an MC/DC condition with separate counters
+    /// for true and false, and its position in the decision. (Ferrocene addition)
+    MCDCBranch {
+        true_bcb: BasicCoverageBlock,
+        false_bcb: BasicCoverageBlock,
+        mcdc_params: ConditionInfo,
+    },
+    /// Associates a decision region with its test vector bitmap and number of
+    /// conditions. (Ferrocene addition)
+    MCDCDecision(DecisionInfo),
 }
 
 #[derive(Clone, Debug)]
@@ -158,6 +206,13 @@ pub struct FunctionCoverageInfo {
     pub priority_list: Vec<BasicCoverageBlock>,
 
     pub mappings: Vec<Mapping>,
+
+    /// Number of bits of the global test vector bitmap used by the MC/DC
+    /// decisions of this function. (Ferrocene addition)
+    pub mcdc_bitmap_bits: usize,
+    /// The depth of the deepest nested decision, plus 1, which is the number
+    /// of condition bitmaps to allocate. (Ferrocene addition)
+    pub mcdc_num_condition_bitmaps: usize,
 }
 
 /// Coverage information for a function, recorded during MIR building and
@@ -174,6 +229,8 @@ pub struct CoverageInfoHi {
     /// data structures without having to scan the entire body first.
     pub num_block_markers: usize,
     pub branch_spans: Vec<BranchSpan>,
+    /// MC/DC decisions, and the conditions they are made of. (Ferrocene addition)
+    pub mcdc_spans: Vec<(MCDCDecisionSpan, Vec<MCDCBranchSpan>)>,
 }
 
 #[derive(Clone, Debug)]
@@ -184,6 +241,44 @@ pub struct BranchSpan {
     pub false_marker: BlockMarkerId,
 }
 
+/// Position of an MC/DC condition in its decision: the condition evaluated
+/// next when it is true or false, or `None` if the decision is then decided.
+#[derive(Copy, Clone, Debug)]
+#[derive(TyEncodable, TyDecodable, Hash, StableHash)]
+pub struct ConditionInfo {
+    pub condition_id: ConditionId,
+    pub true_next_id: Option<ConditionId>,
+    pub false_next_id: Option<ConditionId>,
+}
+
+#[derive(Clone, Debug)]
+#[derive(TyEncodable, TyDecodable, Hash, StableHash)]
+pub struct MCDCBranchSpan {
+    pub span: Span,
+    pub condition_info: ConditionInfo,
+    pub true_marker: BlockMarkerId,
+    pub false_marker: BlockMarkerId,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[derive(TyEncodable, TyDecodable, Hash, StableHash)]
+pub struct DecisionInfo {
+    /// Index of the first bit of the decision in the function's test vector bitmap.
+    pub bitmap_idx: u32,
+    pub num_conditions: u16,
+}
+
+#[derive(Clone, Debug)]
+#[derive(TyEncodable, TyDecodable, Hash, StableHash)]
+pub struct MCDCDecisionSpan {
+    pub span: Span,
+    /// Markers of the blocks reached once the outcome of the decision is known.
+    pub end_markers: Vec<BlockMarkerId>,
+    /// How many decisions this one is nested in.
+    pub decision_depth: u16,
+    pub num_conditions: usize,
+}
+
 /// Contains information needed during codegen, obtained by inspecting the
 /// function's MIR after MIR optimizations.
 ///
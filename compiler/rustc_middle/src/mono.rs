@@ -13,7 +13,7 @@
 use rustc_hir::ItemId;
 use rustc_hir::attrs::{InlineAttr, Linkage};
 use rustc_hir::def_id::{CrateNum, DefId, DefIdSet, LOCAL_CRATE};
-use rustc_macros::{StableHash, TyDecodable, TyEncodable};
+use rustc_macros::{Decodable, Encodable, StableHash, TyDecodable, TyEncodable};
 use rustc_session::config::OptLevel;
 use rustc_span::{Span, Symbol};
 use rustc_target::spec::SymbolVisibility;
@@ -342,6 +342,27 @@ pub struct MonoItemPartitions<'tcx> {
     pub all_mono_items: &'tcx DefIdSet,
 }
 
+/// Ferrocene addition: the call graph of the functions codegened in the current crate, keyed by
+/// symbol name. This is combined with the frame sizes emitted by the backend to compute the
+/// worst-case stack usage reported by `-Z stack-usage-report`.
+#[derive(Clone, Debug, Default, Encodable, Decodable)]
+pub struct StackUsageCallGraph {
+    /// The mono roots, i.e. the functions the stack usage is reported for.
+    pub roots: Vec<String>,
+    pub functions: FxIndexMap<String, StackUsageCallees>,
+}
+
+/// Ferrocene addition: see [`StackUsageCallGraph`].
+#[derive(Clone, Debug, Default, Encodable, Decodable)]
+pub struct StackUsageCallees {
+    /// The functions called directly, including drop glue and the panic functions called by
+    /// assertions.
+    pub direct: Vec<String>,
+    /// Whether the function calls a function pointer or a trait object method, or uses a function
+    /// in inline assembly. The callee of such calls is not known.
+    pub indirect: bool,
+}
+
 #[derive(Debug, StableHash)]
 pub struct CodegenUnit<'tcx> {
     /// A name for this CGU. Incremental compilation requires that
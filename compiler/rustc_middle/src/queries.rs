@@ -81,6 +81,7 @@
 use crate::infer::canonical::{self, Canonical};
 use crate::lint::LintExpectation;
 use crate::metadata::ModChild;
+use crate::middle::codegen_fn_attrs::ferrocene::ValidationManifest;
 use crate::middle::codegen_fn_attrs::{CodegenFnAttrs, SanitizerFnAttrs};
 use crate::middle::dead_code::DeadCodeLivenessSummary;
 use crate::middle::debugger_visualizer::DebuggerVisualizerFile;
@@ -96,6 +97,7 @@
 };
 use crate::mono::{
     CodegenUnit, CollectionMode, MonoItem, MonoItemPartitions, NormalizationErrorInMono,
+    StackUsageCallGraph,
 };
 use crate::query::describe_as_module;
 use crate::query::plumbing::{define_callbacks, maybe_into_query_key};
@@ -1534,6 +1536,16 @@
         feedable
     }
 
+    /// Ferrocene addition: the overrides passed with `-Z ferrocene-validation-manifest`, if any.
+    /// See [`item_is_validated`](crate::middle::codegen_fn_attrs::ferrocene::item_is_validated).
+    query ferrocene_validation_manifest(_: ()) -> &'tcx ValidationManifest {
+        arena_cache
+        // Reads a file outside of the query system.
+        eval_always
+        no_hash
+        desc { "loading the Ferrocene validation manifest" }
+    }
+
     query asm_target_features(def_id: DefId) -> &'tcx FxIndexSet<Symbol> {
         desc { "computing target features for inline asm of `{}`", tcx.def_path_str(def_id) }
     }
@@ -2452,6 +2464,15 @@
         desc { "collect_and_partition_mono_items" }
     }
 
+    /// Ferrocene addition: the call graph of the functions codegened in this crate, used by
+    /// `-Z stack-usage-report`.
+    query stack_usage_call_graph(_: ()) -> &'tcx StackUsageCallGraph {
+        arena_cache
+        eval_always
+        no_hash
+        desc { "computing the call graph for the stack usage report" }
+    }
+
     query is_codegened_item(def_id: DefId) -> bool {
         desc { "determining whether `{}` needs codegen", tcx.def_path_str(def_id) }
     }
@@ -204,14 +204,19 @@ fn ast_block_stmts(
                         let else_block_span = this.thir[*else_block].span;
                         let (matching, failure) =
                             this.in_if_then_scope(last_remainder_scope, else_block_span, |this| {
-                                this.lower_let_expr(
+                                // Ferrocene addition: the pattern of `let-else` is not part of
+                                // any enclosing MC/DC decision.
+                                this.mcdc_increment_depth_if_enabled();
+                                let matching = this.lower_let_expr(
                                     block,
                                     *initializer,
                                     pattern,
                                     None,
                                     initializer_span,
                                     DeclareLetBindings::No,
-                                )
+                                );
+                                this.mcdc_decrement_depth_if_enabled();
+                                matching
                             });
                         matching.and(failure)
                     });
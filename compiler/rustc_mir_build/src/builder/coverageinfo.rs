@@ -2,14 +2,20 @@
 use std::collections::hash_map::Entry;
 
 use rustc_data_structures::fx::FxHashMap;
-use rustc_middle::mir::coverage::{BlockMarkerId, BranchSpan, CoverageInfoHi, CoverageKind};
+use rustc_middle::mir::coverage::{
+    BlockMarkerId, BranchSpan, CoverageInfoHi, CoverageKind, MCDCBranchSpan,
+};
 use rustc_middle::mir::{self, BasicBlock, SourceInfo, UnOp};
 use rustc_middle::thir::{ExprId, ExprKind, Pat, Thir};
 use rustc_middle::ty::TyCtxt;
 use rustc_span::def_id::LocalDefId;
 
+use crate::builder::coverageinfo::mcdc::MCDCInfoBuilder;
 use crate::builder::{Builder, CFG};
 
+// Ferrocene addition
+mod mcdc;
+
 /// Collects coverage-related information during MIR building, to eventually be
 /// turned into a function's [`CoverageInfoHi`] when MIR building is complete.
 pub(crate) struct CoverageInfoBuilder {
@@ -20,6 +26,8 @@ pub(crate) struct CoverageInfoBuilder {
 
     /// Present if branch coverage is enabled.
     branch_info: Option<BranchInfo>,
+    /// Present if MC/DC coverage is enabled. (Ferrocene addition)
+    mcdc_info: Option<MCDCInfoBuilder>,
 }
 
 #[derive(Default)]
@@ -78,6 +86,7 @@ pub(crate) fn new_if_enabled(tcx: TyCtxt<'_>, def_id: LocalDefId) -> Option<Self
             nots: FxHashMap::default(),
             markers: BlockMarkerGen::default(),
             branch_info: tcx.sess.instrument_coverage_branch().then(BranchInfo::default),
+            mcdc_info: tcx.sess.instrument_coverage_mcdc().then(MCDCInfoBuilder::default),
         })
     }
 
@@ -129,11 +138,26 @@ fn visit_with_not_info(&mut self, thir: &Thir<'_>, expr_id: ExprId, not_info: No
 
     fn register_two_way_branch<'tcx>(
         &mut self,
+        tcx: TyCtxt<'tcx>,
         cfg: &mut CFG<'tcx>,
         source_info: SourceInfo,
         true_block: BasicBlock,
         false_block: BasicBlock,
     ) {
+        // Separate path for handling branches when MC/DC is enabled.
+        if let Some(mcdc_info) = self.mcdc_info.as_mut() {
+            let inject_block_marker =
+                |source_info, block| self.markers.inject_block_marker(cfg, source_info, block);
+            mcdc_info.visit_evaluated_condition(
+                tcx,
+                source_info,
+                true_block,
+                false_block,
+                inject_block_marker,
+            );
+            return;
+        }
+
         // Bail out if branch coverage is not enabled.
         let Some(branch_info) = self.branch_info.as_mut() else { return };
 
@@ -148,32 +172,54 @@ fn register_two_way_branch<'tcx>(
     }
 
     pub(crate) fn into_done(self) -> Box<CoverageInfoHi> {
-        let Self { nots: _, markers: BlockMarkerGen { num_block_markers }, branch_info } = self;
+        let Self { nots: _, markers: BlockMarkerGen { num_block_markers }, branch_info, mcdc_info } =
+            self;
 
-        let branch_spans =
+        let mut branch_spans =
             branch_info.map(|branch_info| branch_info.branch_spans).unwrap_or_default();
 
+        let (degraded_spans, mcdc_spans) =
+            mcdc_info.map(MCDCInfoBuilder::into_done).unwrap_or_default();
+        // Conditions that are not part of an MC/DC decision get branch coverage.
+        branch_spans.extend(degraded_spans.into_iter().map(degraded_branch_span));
+
         // For simplicity, always return an info struct (without Option), even
         // if there's nothing interesting in it.
-        Box::new(CoverageInfoHi { num_block_markers, branch_spans })
+        Box::new(CoverageInfoHi { num_block_markers, branch_spans, mcdc_spans })
     }
 
     pub(crate) fn as_done(&self) -> Box<CoverageInfoHi> {
-        let &Self { nots: _, markers: BlockMarkerGen { num_block_markers }, ref branch_info } =
-            self;
-
-        let branch_spans = branch_info
+        let &Self {
+            nots: _,
+            markers: BlockMarkerGen { num_block_markers },
+            ref branch_info,
+            ref mcdc_info,
+        } = self;
+
+        let mut branch_spans = branch_info
             .as_ref()
             .map(|branch_info| branch_info.branch_spans.as_slice())
             .unwrap_or_default()
             .to_owned();
 
+        let mut mcdc_spans = vec![];
+        if let Some(mcdc_info) = mcdc_info {
+            branch_spans
+                .extend(mcdc_info.degraded_spans().iter().cloned().map(degraded_branch_span));
+            mcdc_spans = mcdc_info.mcdc_spans().to_owned();
+        }
+
         // For simplicity, always return an info struct (without Option), even
         // if there's nothing interesting in it.
-        Box::new(CoverageInfoHi { num_block_markers, branch_spans })
+        Box::new(CoverageInfoHi { num_block_markers, branch_spans, mcdc_spans })
     }
 }
 
+fn degraded_branch_span(span: MCDCBranchSpan) -> BranchSpan {
+    let MCDCBranchSpan { span, condition_info: _, true_marker, false_marker } = span;
+    BranchSpan { span, true_marker, false_marker }
+}
+
 impl<'tcx> Builder<'_, 'tcx> {
     /// If condition coverage is enabled, inject extra blocks and marker statements
     /// that will let us track the value of the condition in `place`.
@@ -223,7 +269,13 @@ pub(crate) fn visit_coverage_standalone_condition(
             mir::TerminatorKind::if_(mir::Operand::Copy(place), true_block, false_block),
         );
 
-        coverage_info.register_two_way_branch(&mut self.cfg, source_info, true_block, false_block);
+        coverage_info.register_two_way_branch(
+            self.tcx,
+            &mut self.cfg,
+            source_info,
+            true_block,
+            false_block,
+        );
 
         let join_block = self.cfg.start_new_block();
         self.cfg.goto(true_block, source_info, join_block);
@@ -254,7 +306,13 @@ pub(crate) fn visit_coverage_branch_condition(
 
         let source_info = SourceInfo { span: self.thir[expr_id].span, scope: self.source_scope };
 
-        coverage_info.register_two_way_branch(&mut self.cfg, source_info, then_block, else_block);
+        coverage_info.register_two_way_branch(
+            self.tcx,
+            &mut self.cfg,
+            source_info,
+            then_block,
+            else_block,
+        );
     }
 
     /// If branch coverage is enabled, inject marker statements into `true_block`
@@ -271,6 +329,12 @@ pub(crate) fn visit_coverage_conditional_let(
         let Some(coverage_info) = self.coverage_info.as_mut() else { return };
 
         let source_info = SourceInfo { span: pattern.span, scope: self.source_scope };
-        coverage_info.register_two_way_branch(&mut self.cfg, source_info, true_block, false_block);
+        coverage_info.register_two_way_branch(
+            self.tcx,
+            &mut self.cfg,
+            source_info,
+            true_block,
+            false_block,
+        );
     }
 }
@@ -0,0 +1,361 @@
+//! MC/DC instrumentation (Ferrocene addition).
+//!
+//! While lowering a decision (such as an `if` condition or a match guard), its
+//! conditions are given IDs and "next" IDs, describing the tree of possible
+//! evaluations that LLVM uses to compute test vectors.
+
+use std::collections::VecDeque;
+
+use rustc_data_structures::fx::FxHashSet;
+use rustc_middle::bug;
+use rustc_middle::mir::coverage::{
+    BlockMarkerId, ConditionId, ConditionInfo, MCDCBranchSpan, MCDCDecisionSpan,
+};
+use rustc_middle::mir::{BasicBlock, SourceInfo};
+use rustc_middle::thir::{ExprId, ExprKind, LogicalOp};
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Span;
+
+use crate::builder::Builder;
+use crate::diagnostics::MCDCExceedsConditionLimit;
+
+/// LLVM uses `i16` to represent condition IDs, so `i16::MAX` is the hard limit
+/// for the number of conditions in a decision.
+const MAX_CONDITIONS_IN_DECISION: usize = i16::MAX as usize;
+
+#[derive(Default)]
+struct MCDCDecisionCtx {
+    /// Conditions that still have to be evaluated, in evaluation order from the
+    /// back, used to construct the condition evaluation tree.
+    decision_stack: VecDeque<ConditionInfo>,
+    processing_decision: Option<MCDCDecisionSpan>,
+    conditions: Vec<MCDCBranchSpan>,
+}
+
+#[derive(Default)]
+struct MCDCState {
+    /// One context per decision being lowered. Decisions can be nested, for
+    /// example when an `if` condition contains a closure call with `&&` in its
+    /// arguments, in which case the inner decision gets its own context.
+    decision_ctx_stack: Vec<MCDCDecisionCtx>,
+    /// Right-hand sides of lazy boolean operators lowered as values, which
+    /// continue the decision of their parent operator instead of starting one.
+    value_rhs_operands: FxHashSet<ExprId>,
+}
+
+impl MCDCState {
+    /// Decision depth is given as a u16 to reduce the size of the `CoverageKind`,
+    /// as it is very unlikely that the depth ever reaches 2^16.
+    fn decision_depth(&self) -> u16 {
+        match u16::try_from(self.decision_ctx_stack.len())
+            .expect("decision depth did not fit in u16, this is likely an instrumentation error")
+            .checked_sub(1)
+        {
+            Some(depth) => depth,
+            None => bug!("unexpected empty decision stack"),
+        }
+    }
+
+    // Conditions are assigned IDs as the expression tree is visited in pre-order.
+    // A composite sub-expression gives its ID to its LHS, and generates a new one
+    // for its RHS.
+    //
+    // Example: "x = (A && B) || (C && D) || (E && F)"
+    //
+    //      Visit Depth1:
+    //              (A && B) || (C && D) || (E && F)
+    //              ^-------LHS--------^    ^-RHS--^
+    //                      ID=0              ID=1
+    //
+    //      Visit LHS-Depth2:
+    //              (A && B) || (C && D)
+    //              ^-LHS--^    ^-RHS--^
+    //                ID=0        ID=2
+    //
+    //      Visit LHS-Depth3:
+    //               (A && B)
+    //               LHS   RHS
+    //               ID=0  ID=3
+    //
+    //      Visit RHS-Depth3:
+    //                         (C && D)
+    //                         LHS   RHS
+    //                         ID=2  ID=4
+    //
+    //      Visit RHS-Depth2:              (E && F)
+    //                                     LHS   RHS
+    //                                     ID=1  ID=5
+    //
+    //      Visit Depth1:
+    //              (A && B)  || (C && D)  || (E && F)
+    //              ID=0  ID=3   ID=2  ID=4   ID=1  ID=5
+    //
+    // A "next" ID of `None` means that the outcome of the decision is known.
+    //
+    // As the expression tree is visited in pre-order, the condition info of a
+    // parent is always assigned before its children are visited:
+    // - If the op is AND, the "false_next" of LHS and RHS is the parent's
+    //   "false_next". The "true_next" of the LHS is the RHS, and the "true_next"
+    //   of the RHS is the parent's "true_next".
+    // - If the op is OR, the "true_next" of LHS and RHS is the parent's
+    //   "true_next". The "false_next" of the LHS is the RHS, and the "false_next"
+    //   of the RHS is the parent's "false_next".
+    fn record_conditions(&mut self, op: LogicalOp, span: Span) {
+        let decision_depth = self.decision_depth();
+        let Some(decision_ctx) = self.decision_ctx_stack.last_mut() else {
+            bug!("unexpected empty decision_ctx_stack")
+        };
+        let decision = match decision_ctx.processing_decision.as_mut() {
+            Some(decision) => {
+                decision.span = decision.span.to(span);
+                decision
+            }
+            None => decision_ctx.processing_decision.insert(MCDCDecisionSpan {
+                span,
+                num_conditions: 0,
+                end_markers: vec![],
+                decision_depth,
+            }),
+        };
+
+        let parent_condition = decision_ctx.decision_stack.pop_back().unwrap_or_else(|| {
+            assert_eq!(
+                decision.num_conditions, 0,
+                "decision stack must be empty only for empty decision"
+            );
+            decision.num_conditions += 1;
+            ConditionInfo {
+                condition_id: ConditionId::START,
+                true_next_id: None,
+                false_next_id: None,
+            }
+        });
+        let lhs_id = parent_condition.condition_id;
+
+        // Decisions exceeding the limit are degraded to branch coverage once
+        // complete, so saturate the ID rather than overflowing it.
+        let rhs_condition_id =
+            ConditionId::from_usize(decision.num_conditions.min(MAX_CONDITIONS_IN_DECISION));
+        decision.num_conditions += 1;
+        let (lhs, rhs) = match op {
+            LogicalOp::And => {
+                let lhs = ConditionInfo {
+                    condition_id: lhs_id,
+                    true_next_id: Some(rhs_condition_id),
+                    false_next_id: parent_condition.false_next_id,
+                };
+                let rhs = ConditionInfo {
+                    condition_id: rhs_condition_id,
+                    true_next_id: parent_condition.true_next_id,
+                    false_next_id: parent_condition.false_next_id,
+                };
+                (lhs, rhs)
+            }
+            LogicalOp::Or => {
+                let lhs = ConditionInfo {
+                    condition_id: lhs_id,
+                    true_next_id: parent_condition.true_next_id,
+                    false_next_id: Some(rhs_condition_id),
+                };
+                let rhs = ConditionInfo {
+                    condition_id: rhs_condition_id,
+                    true_next_id: parent_condition.true_next_id,
+                    false_next_id: parent_condition.false_next_id,
+                };
+                (lhs, rhs)
+            }
+        };
+        // The expression tree is visited in pre-order, so place the LHS on top.
+        decision_ctx.decision_stack.push_back(rhs);
+        decision_ctx.decision_stack.push_back(lhs);
+    }
+
+    /// Records an evaluated condition in the current decision, returning the
+    /// decision and its conditions if it was the last one. Conditions that are
+    /// not part of a decision made of several conditions are pushed to
+    /// `degraded_branches` instead, as they only need branch coverage.
+    fn try_finish_decision(
+        &mut self,
+        span: Span,
+        true_marker: BlockMarkerId,
+        false_marker: BlockMarkerId,
+        degraded_branches: &mut Vec<MCDCBranchSpan>,
+    ) -> Option<(MCDCDecisionSpan, Vec<MCDCBranchSpan>)> {
+        let Some(decision_ctx) = self.decision_ctx_stack.last_mut() else {
+            // Conditions outside of any decision, such as the pattern of `let-else`.
+            degraded_branches.push(degraded_branch(span, true_marker, false_marker));
+            return None;
+        };
+        let Some(condition_info) = decision_ctx.decision_stack.pop_back() else {
+            // Decisions made of a single condition are covered by branch coverage.
+            degraded_branches.push(degraded_branch(span, true_marker, false_marker));
+            return None;
+        };
+        let Some(decision) = decision_ctx.processing_decision.as_mut() else {
+            bug!("processing decision should have been created before any conditions are taken");
+        };
+        if condition_info.true_next_id.is_none() {
+            decision.end_markers.push(true_marker);
+        }
+        if condition_info.false_next_id.is_none() {
+            decision.end_markers.push(false_marker);
+        }
+        decision_ctx.conditions.push(MCDCBranchSpan {
+            span,
+            condition_info,
+            true_marker,
+            false_marker,
+        });
+
+        if decision_ctx.decision_stack.is_empty() {
+            let conditions = std::mem::take(&mut decision_ctx.conditions);
+            decision_ctx.processing_decision.take().map(|decision| (decision, conditions))
+        } else {
+            None
+        }
+    }
+}
+
+fn degraded_branch(
+    span: Span,
+    true_marker: BlockMarkerId,
+    false_marker: BlockMarkerId,
+) -> MCDCBranchSpan {
+    MCDCBranchSpan {
+        span,
+        condition_info: ConditionInfo {
+            condition_id: ConditionId::START,
+            true_next_id: None,
+            false_next_id: None,
+        },
+        true_marker,
+        false_marker,
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct MCDCInfoBuilder {
+    degraded_spans: Vec<MCDCBranchSpan>,
+    mcdc_spans: Vec<(MCDCDecisionSpan, Vec<MCDCBranchSpan>)>,
+    state: MCDCState,
+}
+
+impl MCDCInfoBuilder {
+    pub(crate) fn visit_evaluated_condition(
+        &mut self,
+        tcx: TyCtxt<'_>,
+        source_info: SourceInfo,
+        true_block: BasicBlock,
+        false_block: BasicBlock,
+        mut inject_block_marker: impl FnMut(SourceInfo, BasicBlock) -> BlockMarkerId,
+    ) {
+        let true_marker = inject_block_marker(source_info, true_block);
+        let false_marker = inject_block_marker(source_info, false_block);
+
+        // `try_finish_decision` returns the decision once all of its conditions
+        // were instrumented, i.e. when the decision is "complete".
+        if let Some((decision, conditions)) = self.state.try_finish_decision(
+            source_info.span,
+            true_marker,
+            false_marker,
+            &mut self.degraded_spans,
+        ) {
+            let num_conditions = conditions.len();
+            assert_eq!(
+                num_conditions, decision.num_conditions,
+                "final number of conditions is not correct"
+            );
+            match num_conditions {
+                0 => unreachable!("decision with no condition is not expected"),
+                1..=MAX_CONDITIONS_IN_DECISION => {
+                    self.mcdc_spans.push((decision, conditions));
+                }
+                _ => {
+                    self.degraded_spans.extend(conditions);
+
+                    tcx.dcx().emit_warn(MCDCExceedsConditionLimit {
+                        span: decision.span,
+                        num_conditions,
+                        max_conditions: MAX_CONDITIONS_IN_DECISION,
+                    });
+                }
+            }
+        }
+    }
+
+    pub(crate) fn degraded_spans(&self) -> &[MCDCBranchSpan] {
+        &self.degraded_spans
+    }
+
+    pub(crate) fn mcdc_spans(&self) -> &[(MCDCDecisionSpan, Vec<MCDCBranchSpan>)] {
+        &self.mcdc_spans
+    }
+
+    pub(crate) fn into_done(
+        self,
+    ) -> (Vec<MCDCBranchSpan>, Vec<(MCDCDecisionSpan, Vec<MCDCBranchSpan>)>) {
+        (self.degraded_spans, self.mcdc_spans)
+    }
+}
+
+impl Builder<'_, '_> {
+    /// If MC/DC is enabled, records the conditions of a `&&` or `||` in the
+    /// decision being lowered.
+    pub(crate) fn visit_coverage_branch_operation(&mut self, logical_op: LogicalOp, span: Span) {
+        if let Some(coverage_info) = self.coverage_info.as_mut()
+            && let Some(mcdc_info) = coverage_info.mcdc_info.as_mut()
+        {
+            mcdc_info.state.record_conditions(logical_op, span);
+        }
+    }
+
+    /// If MC/DC is enabled, starts a new decision context, for the lowering of
+    /// a condition that can't be part of any enclosing decision.
+    pub(crate) fn mcdc_increment_depth_if_enabled(&mut self) {
+        if let Some(coverage_info) = self.coverage_info.as_mut()
+            && let Some(mcdc_info) = coverage_info.mcdc_info.as_mut()
+        {
+            mcdc_info.state.decision_ctx_stack.push(MCDCDecisionCtx::default());
+        };
+    }
+
+    /// Ends the decision context started by [`Self::mcdc_increment_depth_if_enabled`].
+    pub(crate) fn mcdc_decrement_depth_if_enabled(&mut self) {
+        if let Some(coverage_info) = self.coverage_info.as_mut()
+            && let Some(mcdc_info) = coverage_info.mcdc_info.as_mut()
+            && mcdc_info.state.decision_ctx_stack.pop().is_none()
+        {
+            bug!("unexpected empty decision stack");
+        };
+    }
+
+    /// If MC/DC is enabled, prepares the lowering of a `&&` or `||` as a value,
+    /// returning true if it starts a new decision context, which must then be
+    /// ended with [`Self::mcdc_decrement_depth_if_enabled`] once the operator
+    /// is fully lowered.
+    ///
+    /// The right-hand side of such an operator continues the decision of its
+    /// parent when it is itself a lazy boolean operator, for example in
+    /// `let x = a && (b || c);`.
+    pub(crate) fn mcdc_enter_value_logical_op(&mut self, expr_id: ExprId, rhs: ExprId) -> bool {
+        let Some(coverage_info) = self.coverage_info.as_mut() else { return false };
+        let Some(mcdc_info) = coverage_info.mcdc_info.as_mut() else { return false };
+
+        let is_continuation = mcdc_info.state.value_rhs_operands.remove(&expr_id);
+        if !is_continuation {
+            mcdc_info.state.decision_ctx_stack.push(MCDCDecisionCtx::default());
+        }
+
+        let mut rhs = rhs;
+        while let ExprKind::Use { source: inner } | ExprKind::Scope { value: inner, .. } =
+            self.thir[rhs].kind
+        {
+            rhs = inner;
+        }
+        if let ExprKind::LogicalOp { .. } = self.thir[rhs].kind {
+            mcdc_info.state.value_rhs_operands.insert(rhs);
+        }
+
+        !is_continuation
+    }
+}
@@ -87,6 +87,8 @@ pub(crate) fn expr_into_dest(
                         // Lower the condition, and have it branch into `then` and `else` blocks.
                         let (then_block, else_block) =
                             this.in_if_then_scope(condition_scope, then_span, |this| {
+                                // Ferrocene addition: the condition is an MC/DC decision.
+                                this.mcdc_increment_depth_if_enabled();
                                 let then_blk = this
                                     .then_else_break(
                                         block,
@@ -96,6 +98,7 @@ pub(crate) fn expr_into_dest(
                                         DeclareLetBindings::Yes, // Declare `let` bindings normally
                                     )
                                     .into_block();
+                                this.mcdc_decrement_depth_if_enabled();
 
                                 // Lower the `then` arm into its block.
                                 this.expr_into_dest(destination, then_blk, then)
@@ -160,6 +163,11 @@ pub(crate) fn expr_into_dest(
                 let condition_scope = this.local_scope();
                 let source_info = this.source_info(expr.span);
 
+                // Ferrocene addition: record the MC/DC conditions of the operator,
+                // which is a decision on its own unless it continues its parent's.
+                let new_mcdc_decision = this.mcdc_enter_value_logical_op(expr_id, rhs);
+                this.visit_coverage_branch_operation(op, expr.span);
+
                 // We first evaluate the left-hand side of the predicate ...
                 let (then_block, else_block) =
                     this.in_if_then_scope(condition_scope, expr.span, |this| {
@@ -198,6 +206,9 @@ pub(crate) fn expr_into_dest(
                 // Instrument the lowered RHS's value for condition coverage.
                 // (Does nothing if condition coverage is not enabled.)
                 this.visit_coverage_standalone_condition(rhs, destination, &mut rhs_block);
+                if new_mcdc_decision {
+                    this.mcdc_decrement_depth_if_enabled();
+                }
 
                 let target = this.cfg.start_new_block();
                 this.cfg.goto(rhs_block, source_info, target);
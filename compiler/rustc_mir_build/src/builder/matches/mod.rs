@@ -116,12 +116,14 @@ fn then_else_break_inner(
 
         match expr.kind {
             ExprKind::LogicalOp { op: LogicalOp::And, lhs, rhs } => {
+                this.visit_coverage_branch_operation(LogicalOp::And, expr_span);
                 let lhs_then_block = this.then_else_break_inner(block, lhs, args).into_block();
                 let rhs_then_block =
                     this.then_else_break_inner(lhs_then_block, rhs, args).into_block();
                 rhs_then_block.unit()
             }
             ExprKind::LogicalOp { op: LogicalOp::Or, lhs, rhs } => {
+                this.visit_coverage_branch_operation(LogicalOp::Or, expr_span);
                 let local_scope = this.local_scope();
                 let (lhs_success_block, failure_block) =
                     this.in_if_then_scope(local_scope, expr_span, |this| {
@@ -2450,13 +2452,17 @@ fn bind_and_guard_matched_candidate(
             let (post_guard_block, otherwise_post_guard_block) =
                 self.in_if_then_scope(match_scope, guard_span, |this| {
                     guard_span = this.thir[guard].span;
-                    this.then_else_break(
+                    // Ferrocene addition: the guard is an MC/DC decision.
+                    this.mcdc_increment_depth_if_enabled();
+                    let guard_blocks = this.then_else_break(
                         block,
                         guard,
                         None, // Use `self.local_scope()` as the temp scope
                         this.source_info(arm.span),
                         DeclareLetBindings::No, // For guards, `let` bindings are declared separately
-                    )
+                    );
+                    this.mcdc_decrement_depth_if_enabled();
+                    guard_blocks
                 });
 
             // If this isn't the final sub-branch being lowered, we need to unschedule drops of
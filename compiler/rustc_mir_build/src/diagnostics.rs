@@ -1438,3 +1438,15 @@ pub(crate) struct ConstContinueUnknownJumpTarget {
     #[primary_span]
     pub span: Span,
 }
+
+// Ferrocene addition
+#[derive(Diagnostic)]
+#[diag(
+    "number of conditions in decision ({$num_conditions}) exceeds limit ({$max_conditions}), so MC/DC analysis will not count this expression"
+)]
+pub(crate) struct MCDCExceedsConditionLimit {
+    #[primary_span]
+    pub(crate) span: Span,
+    pub(crate) num_conditions: usize,
+    pub(crate) max_conditions: usize,
+}
@@ -1,7 +1,9 @@
 use itertools::Itertools;
 use rustc_data_structures::fx::{FxIndexMap, FxIndexSet, IndexEntry};
 use rustc_middle::mir;
-use rustc_middle::mir::coverage::{BasicCoverageBlock, BranchSpan};
+use rustc_middle::mir::coverage::{
+    BasicCoverageBlock, BranchSpan, MCDCBranchSpan, MCDCDecisionSpan,
+};
 use rustc_span::{ExpnKind, Span, SyntaxContext};
 
 use crate::coverage::from_mir;
@@ -61,6 +63,9 @@ pub(crate) struct ExpnNode {
 
     /// Branch spans (recorded during MIR building) belonging to this expansion.
     pub(crate) branch_spans: Vec<BranchSpan>,
+    /// MC/DC decisions and their conditions (recorded during MIR building)
+    /// belonging to this expansion. (Ferrocene addition)
+    pub(crate) mcdc_spans: Vec<(MCDCDecisionSpan, Vec<MCDCBranchSpan>)>,
 
     /// Hole spans belonging to this expansion, to be carved out from the
     /// code spans during span refinement.
@@ -90,6 +95,7 @@ fn for_context(context: SyntaxContext) -> Self {
             minmax_bcbs: None,
 
             branch_spans: vec![],
+            mcdc_spans: vec![],
 
             hole_spans: vec![],
         }
@@ -179,6 +185,12 @@ pub(crate) fn build_expn_tree(
                 node.branch_spans.push(BranchSpan::clone(branch_span));
             }
         }
+        // Ferrocene addition
+        for (decision, conditions) in &coverage_info_hi.mcdc_spans {
+            if let Some(node) = nodes.get_mut(&decision.span.ctxt()) {
+                node.mcdc_spans.push((decision.clone(), conditions.clone()));
+            }
+        }
     }
 
     Ok(ExpnTree { nodes })
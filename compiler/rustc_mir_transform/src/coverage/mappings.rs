@@ -1,10 +1,14 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use rustc_data_structures::fx::FxIndexMap;
 use rustc_index::IndexVec;
 use rustc_middle::mir::coverage::{
-    BlockMarkerId, BranchSpan, CoverageInfoHi, CoverageKind, Mapping, MappingKind,
+    BasicCoverageBlock, BlockMarkerId, BranchSpan, ConditionId, ConditionInfo, CoverageInfoHi,
+    CoverageKind, DecisionInfo, MCDCBranchSpan, MCDCDecisionSpan, Mapping, MappingKind,
 };
 use rustc_middle::mir::{self, BasicBlock, StatementKind};
 use rustc_middle::ty::TyCtxt;
-use rustc_span::ExpnKind;
+use rustc_span::{ExpnKind, Span};
 
 use crate::coverage::expansion::{self, ExpnTree};
 use crate::coverage::graph::CoverageGraph;
@@ -18,9 +22,41 @@ pub(crate) enum MappingsError {
     TreeSortFailure,
 }
 
+/// LLVM uses `i32` to index the test vector bitmap of a function. (Ferrocene addition)
+const MCDC_MAX_BITMAP_SIZE: usize = i32::MAX as usize;
+
+/// A condition of an MC/DC decision, resolved to BCBs. (Ferrocene addition)
+#[derive(Debug)]
+pub(crate) struct MCDCBranch {
+    pub(crate) span: Span,
+    pub(crate) true_bcb: BasicCoverageBlock,
+    pub(crate) false_bcb: BasicCoverageBlock,
+    pub(crate) condition_info: ConditionInfo,
+    /// Offset added to the test vector index if this condition is true.
+    pub(crate) true_index: usize,
+    /// Offset added to the test vector index if this condition is false.
+    pub(crate) false_index: usize,
+}
+
+/// An MC/DC decision, resolved to BCBs. (Ferrocene addition)
+#[derive(Debug)]
+pub(crate) struct MCDCDecision {
+    pub(crate) span: Span,
+    /// BCBs reached once the outcome of the decision is known.
+    pub(crate) end_bcbs: BTreeSet<BasicCoverageBlock>,
+    pub(crate) bitmap_idx: usize,
+    pub(crate) num_test_vectors: usize,
+    pub(crate) decision_depth: u16,
+}
+
 #[derive(Default)]
 pub(crate) struct ExtractedMappings {
     pub(crate) mappings: Vec<Mapping>,
+    /// Ferrocene addition: MC/DC decisions with their conditions, and the
+    /// bitmaps needed to track their test vectors.
+    pub(crate) mcdc_mappings: Vec<(MCDCDecision, Vec<MCDCBranch>)>,
+    pub(crate) mcdc_bitmap_bits: usize,
+    pub(crate) mcdc_num_condition_bitmaps: usize,
 }
 
 /// Extracts coverage-relevant spans from MIR, and uses them to create
@@ -40,11 +76,31 @@ pub(crate) fn extract_mappings_from_mir<'tcx>(
 
     extract_branch_mappings(mir_body, hir_info, graph, &expn_tree, &mut mappings);
 
+    let mut mcdc_mappings = vec![];
+    let mut mcdc_bitmap_bits = 0;
+    extract_mcdc_mappings(
+        mir_body,
+        hir_info,
+        graph,
+        &expn_tree,
+        &mut mappings,
+        &mut mcdc_mappings,
+        &mut mcdc_bitmap_bits,
+    );
+
     if mappings.is_empty() {
         tracing::debug!("no mappings were extracted");
         return Err(MappingsError::NoMappings);
     }
-    Ok(ExtractedMappings { mappings })
+
+    // One condition bitmap is needed per level of nested decisions.
+    let mcdc_num_condition_bitmaps = mcdc_mappings
+        .iter()
+        .map(|&(MCDCDecision { decision_depth, .. }, _)| usize::from(decision_depth) + 1)
+        .max()
+        .unwrap_or_default();
+
+    Ok(ExtractedMappings { mappings, mcdc_mappings, mcdc_bitmap_bits, mcdc_num_condition_bitmaps })
 }
 
 fn resolve_block_markers(
@@ -96,3 +152,173 @@ fn extract_branch_mappings(
         },
     ));
 }
+
+// Ferrocene addition
+fn extract_mcdc_mappings(
+    mir_body: &mir::Body<'_>,
+    hir_info: &ExtractedHirInfo,
+    graph: &CoverageGraph,
+    expn_tree: &ExpnTree,
+    mappings: &mut Vec<Mapping>,
+    mcdc_mappings: &mut Vec<(MCDCDecision, Vec<MCDCBranch>)>,
+    mcdc_bitmap_bits: &mut usize,
+) {
+    let Some(coverage_info_hi) = mir_body.coverage_info_hi.as_deref() else { return };
+    let block_markers = resolve_block_markers(coverage_info_hi, mir_body);
+
+    // As with branch spans, ignore decisions introduced by expansion.
+    let Some(node) = expn_tree.get(hir_info.body_span.ctxt()) else { return };
+    if node.expn_kind != ExpnKind::Root {
+        return;
+    }
+
+    let bcb_from_marker = |marker: BlockMarkerId| graph.bcb_from_bb(block_markers[marker]?);
+
+    let resolve_branch =
+        |decision_span: Span, branch_span: &MCDCBranchSpan| -> Option<MCDCBranch> {
+            let &MCDCBranchSpan { span, condition_info, true_marker, false_marker } = branch_span;
+            // Conditions expanded from a macro, such as `matches!(..) && b`, are
+            // mapped to the macro call in the decision.
+            let span = span.find_ancestor_inside_same_ctxt(decision_span)?;
+            let true_bcb = bcb_from_marker(true_marker)?;
+            let false_bcb = bcb_from_marker(false_marker)?;
+            Some(MCDCBranch {
+                span,
+                true_bcb,
+                false_bcb,
+                condition_info,
+                true_index: 0,
+                false_index: 0,
+            })
+        };
+
+    let to_branch_mapping = |branch: &MCDCBranch| Mapping {
+        span: branch.span,
+        kind: MappingKind::Branch { true_bcb: branch.true_bcb, false_bcb: branch.false_bcb },
+    };
+
+    for (decision_span, branch_spans) in &node.mcdc_spans {
+        let MCDCDecisionSpan { span, ref end_markers, decision_depth, num_conditions: _ } =
+            *decision_span;
+
+        let mut branches = branch_spans
+            .iter()
+            .filter_map(|branch_span| resolve_branch(span, branch_span))
+            .collect::<Vec<_>>();
+        let end_bcbs = end_markers
+            .iter()
+            .map(|&marker| bcb_from_marker(marker))
+            .collect::<Option<BTreeSet<_>>>();
+
+        // If some of the conditions or outcomes were removed during MIR building
+        // (for example because they are statically known), the decision can't
+        // be instrumented, but the remaining conditions still get branch coverage.
+        let Some(end_bcbs) = end_bcbs.filter(|_| branches.len() == branch_spans.len()) else {
+            mappings.extend(branches.iter().map(to_branch_mapping));
+            continue;
+        };
+
+        let num_test_vectors = calc_test_vectors_index(&mut branches);
+        let bitmap_idx = *mcdc_bitmap_bits;
+        match bitmap_idx.checked_add(num_test_vectors) {
+            Some(bitmap_end) if bitmap_end <= MCDC_MAX_BITMAP_SIZE => {
+                *mcdc_bitmap_bits = bitmap_end;
+            }
+            // Too many test vectors to track, so fall back to branch coverage.
+            _ => {
+                mappings.extend(branches.iter().map(to_branch_mapping));
+                continue;
+            }
+        }
+
+        let num_conditions = u16::try_from(branches.len())
+            .expect("MIR building should have limited the number of conditions");
+        mappings.push(Mapping {
+            span,
+            kind: MappingKind::MCDCDecision(DecisionInfo {
+                bitmap_idx: bitmap_idx as u32,
+                num_conditions,
+            }),
+        });
+        mappings.extend(branches.iter().map(|branch| Mapping {
+            span: branch.span,
+            kind: MappingKind::MCDCBranch {
+                true_bcb: branch.true_bcb,
+                false_bcb: branch.false_bcb,
+                mcdc_params: branch.condition_info,
+            },
+        }));
+
+        mcdc_mappings.push((
+            MCDCDecision { span, end_bcbs, bitmap_idx, num_test_vectors, decision_depth },
+            branches,
+        ));
+    }
+}
+
+/// Assigns to each condition the offsets added to the test vector index when it
+/// is true or false, and returns the number of test vectors of the decision.
+///
+/// This must produce the same indices as `TVIdxBuilder` in LLVM's coverage
+/// mapping reader, as `llvm-cov` uses them to decode the bitmap: conditions are
+/// visited breadth-first, false successors before true ones, and the outcomes
+/// are then sorted by descending number of paths reaching them.
+fn calc_test_vectors_index(conditions: &mut Vec<MCDCBranch>) -> usize {
+    let mut indegree_stats = IndexVec::<ConditionId, usize>::from_elem_n(0, conditions.len());
+    // `num_paths` is `width` described at the comment of `TVIdxBuilder` in llvm-project.
+    let mut num_paths_stats = IndexVec::<ConditionId, usize>::from_elem_n(0, conditions.len());
+    let mut next_conditions = conditions
+        .iter_mut()
+        .map(|branch| {
+            let ConditionInfo { condition_id, true_next_id, false_next_id } = branch.condition_info;
+            [true_next_id, false_next_id]
+                .into_iter()
+                .flatten()
+                .for_each(|next_id| indegree_stats[next_id] += 1);
+            (condition_id, branch)
+        })
+        .collect::<FxIndexMap<_, _>>();
+
+    let mut queue = VecDeque::from_iter(next_conditions.swap_remove(&ConditionId::START));
+    num_paths_stats[ConditionId::START] = 1;
+    let mut decision_end_nodes = Vec::new();
+    while let Some(branch) = queue.pop_front() {
+        let ConditionInfo { condition_id, true_next_id, false_next_id } = branch.condition_info;
+        let (false_index, true_index) = (&mut branch.false_index, &mut branch.true_index);
+        let this_paths_count = num_paths_stats[condition_id];
+        // Visit the false successor first, in the same order as llvm-cov.
+        for (next, index) in [(false_next_id, false_index), (true_next_id, true_index)] {
+            if let Some(next_id) = next {
+                let next_paths_count = &mut num_paths_stats[next_id];
+                *index = *next_paths_count;
+                *next_paths_count = next_paths_count.saturating_add(this_paths_count);
+                let next_indegree = &mut indegree_stats[next_id];
+                *next_indegree -= 1;
+                if *next_indegree == 0 {
+                    queue.push_back(next_conditions.swap_remove(&next_id).expect(
+                        "conditions with non-zero indegree before must be in next_conditions",
+                    ));
+                }
+            } else {
+                decision_end_nodes.push((this_paths_count, condition_id, index));
+            }
+        }
+    }
+    assert!(next_conditions.is_empty(), "the decision tree has untouched nodes");
+
+    // LLVM expects the outcomes sorted by descending number of paths (keeping
+    // the visiting order for ties), which keeps the bitmap of decisions such as
+    // `a && b && c && ...` small.
+    let mut cur_idx = 0usize;
+    decision_end_nodes.sort_by_key(|&(num_paths, _, _)| std::cmp::Reverse(num_paths));
+    for (num_paths, condition_id, index) in decision_end_nodes {
+        assert_eq!(
+            num_paths, num_paths_stats[condition_id],
+            "end nodes should not be updated since they were visited"
+        );
+        assert_eq!(*index, 0, "end nodes must not be assigned twice");
+        *index = cur_idx;
+        cur_idx = cur_idx.saturating_add(num_paths);
+    }
+    cur_idx
+}
@@ -6,7 +6,7 @@
 use crate::PassPolicy;
 use crate::coverage::counters::BcbCountersData;
 use crate::coverage::graph::CoverageGraph;
-use crate::coverage::mappings::ExtractedMappings;
+use crate::coverage::mappings::{ExtractedMappings, MCDCBranch, MCDCDecision};
 
 mod counters;
 mod expansion;
@@ -69,7 +69,7 @@ fn instrument_function_for_coverage<'tcx>(tcx: TyCtxt<'tcx>, mir_body: &mut mir:
 
     ////////////////////////////////////////////////////
     // Extract coverage spans and other mapping info from MIR.
-    let ExtractedMappings { mappings } =
+    let extracted_mappings =
         match mappings::extract_mappings_from_mir(tcx, mir_body, &hir_info, &graph) {
             Ok(m) => m,
             Err(error) => {
@@ -77,6 +77,8 @@ fn instrument_function_for_coverage<'tcx>(tcx: TyCtxt<'tcx>, mir_body: &mut mir:
                 return;
             }
         };
+    let ExtractedMappings { mappings, mcdc_mappings, mcdc_bitmap_bits, mcdc_num_condition_bitmaps } =
+        extracted_mappings;
 
     // Use the coverage graph to prepare intermediate data that will eventually
     // be used to assign physical counters and counter expressions to points in
@@ -86,6 +88,7 @@ fn instrument_function_for_coverage<'tcx>(tcx: TyCtxt<'tcx>, mir_body: &mut mir:
 
     // Inject coverage statements into MIR.
     inject_coverage_statements(mir_body, &graph);
+    inject_mcdc_statements(mir_body, &graph, &mcdc_mappings);
 
     mir_body.function_coverage_info = Some(Box::new(FunctionCoverageInfo {
         function_source_hash: hir_info.function_source_hash,
@@ -94,6 +97,9 @@ fn instrument_function_for_coverage<'tcx>(tcx: TyCtxt<'tcx>, mir_body: &mut mir:
         priority_list,
 
         mappings,
+
+        mcdc_bitmap_bits,
+        mcdc_num_condition_bitmaps,
     }));
 }
 
@@ -105,6 +111,46 @@ fn inject_coverage_statements<'tcx>(mir_body: &mut mir::Body<'tcx>, graph: &Cove
     }
 }
 
+/// For each MC/DC decision, inject statements that update its condition bitmap
+/// when a condition is evaluated, and that record the resulting test vector once
+/// the outcome of the decision is known. (Ferrocene addition)
+fn inject_mcdc_statements<'tcx>(
+    mir_body: &mut mir::Body<'tcx>,
+    graph: &CoverageGraph,
+    mcdc_mappings: &[(MCDCDecision, Vec<MCDCBranch>)],
+) {
+    for (decision, conditions) in mcdc_mappings {
+        // Inject the test vector update first, because `inject_statement` always
+        // inserts at the start of the block, and the condition bitmap must be
+        // updated before it is recorded.
+        for &end in &decision.end_bcbs {
+            let end_bb = graph[end].leader_bb();
+            inject_statement(
+                mir_body,
+                CoverageKind::TestVectorBitmapUpdate {
+                    bitmap_idx: decision.bitmap_idx as u32,
+                    decision_depth: decision.decision_depth,
+                },
+                end_bb,
+            );
+        }
+
+        for &MCDCBranch { true_bcb, false_bcb, true_index, false_index, .. } in conditions {
+            for (index, bcb) in [(false_index, false_bcb), (true_index, true_bcb)] {
+                let bb = graph[bcb].leader_bb();
+                inject_statement(
+                    mir_body,
+                    CoverageKind::CondBitmapUpdate {
+                        index: index as u32,
+                        decision_depth: decision.decision_depth,
+                    },
+                    bb,
+                );
+            }
+        }
+    }
+}
+
 fn inject_statement(mir_body: &mut mir::Body<'_>, counter_kind: CoverageKind, bb: BasicBlock) {
     debug!("  injecting statement {counter_kind:?} for {bb:?}");
     let data = &mut mir_body[bb];
@@ -830,6 +830,12 @@ fn check_codegen_attributes<'tcx, I: Inliner<'tcx>>(
         return Err("incompatible sanitizer set");
     }
 
+    // Ferrocene addition: the condition bitmaps of MC/DC are allocated per
+    // instance, so inlined MC/DC statements would update the caller's bitmaps.
+    if tcx.sess.instrument_coverage_mcdc() {
+        return Err("incompatible with MC/DC coverage");
+    }
+
     // Two functions are compatible if the callee has no attribute (meaning
     // that it's codegen agnostic), or sets an attribute that is identical
     // to this function's attribute.
@@ -207,6 +207,8 @@
 
 // Ferrocene addition
 pub(crate) mod ferrocene;
+// Ferrocene addition
+pub(crate) mod stack_usage;
 
 use std::cell::OnceCell;
 use std::ops::ControlFlow;
@@ -221,7 +223,7 @@
 use rustc_hir::def_id::{DefId, DefIdMap, LocalDefId};
 use rustc_hir::lang_items::LangItem;
 use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
-use rustc_middle::middle::codegen_fn_attrs::ferrocene::Validated;
+use rustc_middle::middle::codegen_fn_attrs::ferrocene::{Validated, validated_root};
 use rustc_middle::mir::interpret::{AllocId, ErrorHandled, GlobalAlloc, Scalar};
 use rustc_middle::mir::visit::Visitor as MirVisitor;
 use rustc_middle::mir::{self, Body, Location, MentionedItem, traversal};
@@ -1709,7 +1711,9 @@ fn is_root(&self, def_id: LocalDefId) -> bool {
                 // This catches errors sooner when `cargo build`-ing a library.
                 MonoItemCollectionStrategy::Validated => {
                     // Explicit match is intentional, please update this if you add new fields.
-                    matches!(self.tcx.codegen_fn_attrs(def_id).validated, Some(Validated {}))
+                    matches!(self.tcx.codegen_fn_attrs(def_id).validated, Some(Validated { levels: _ }))
+                        // Entry points are not validated, but the checks start from them.
+                        || validated_root(self.tcx, def_id.to_def_id()).is_some()
                 }
             }
     }
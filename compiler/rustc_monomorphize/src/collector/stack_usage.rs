@@ -0,0 +1,203 @@
+//! Ferrocene addition: the call graph used by `-Z stack-usage-report`.
+//!
+//! The graph is keyed by symbol name, so that the backend can combine it with the frame sizes it
+//! reads from the object files once codegen is done. Only functions codegened in this crate have
+//! their callees listed: other functions are leaves of the graph.
+//!
+//! A function's callees are found by walking its MIR the same way the collector does, except that
+//! we keep track of *how* each function is used. Only direct calls are edges of the graph, while
+//! calls through function pointers and vtables are recorded as indirect calls, as their callee
+//! can't be known statically.
+
+use rustc_data_structures::fx::FxIndexSet;
+use rustc_hir::lang_items::LangItem;
+use rustc_middle::mir::visit::Visitor as MirVisitor;
+use rustc_middle::mir::{self, Body, Location, traversal};
+use rustc_middle::mono::{MonoItem, StackUsageCallGraph, StackUsageCallees};
+use rustc_middle::ty::layout::ValidityRequirement;
+use rustc_middle::ty::{self, Instance, InstanceKind, ShimKind, Ty, TyCtxt, TypeFoldable};
+use rustc_span::Span;
+use tracing::debug;
+
+use crate::collector::{MonoItemCollectionStrategy, collect_roots};
+
+pub(crate) fn stack_usage_call_graph(tcx: TyCtxt<'_>, (): ()) -> StackUsageCallGraph {
+    let mut graph = StackUsageCallGraph::default();
+
+    let strategy = if tcx.sess.link_dead_code() {
+        MonoItemCollectionStrategy::Eager
+    } else {
+        MonoItemCollectionStrategy::Lazy
+    };
+    for root in collect_roots(tcx, strategy) {
+        if let MonoItem::Fn(instance) = root {
+            graph.roots.push(tcx.symbol_name(instance).name.to_owned());
+        }
+    }
+    graph.roots.sort();
+    graph.roots.dedup();
+
+    for cgu in tcx.collect_and_partition_mono_items(()).codegen_units {
+        for &item in cgu.items().keys() {
+            let MonoItem::Fn(instance) = item else { continue };
+            let symbol = tcx.symbol_name(instance).name;
+            if graph.functions.contains_key(symbol) {
+                // Items may be instantiated in more than one codegen unit.
+                continue;
+            }
+            let callees = callees_of_instance(tcx, instance);
+            debug!("callees of {symbol}: {callees:?}");
+            graph.functions.insert(symbol.to_owned(), callees);
+        }
+    }
+
+    graph
+}
+
+fn callees_of_instance<'tcx>(tcx: TyCtxt<'tcx>, instance: Instance<'tcx>) -> StackUsageCallees {
+    let body = tcx.instance_mir(instance.def);
+    let mut collector =
+        CallCollector { tcx, body, instance, direct: FxIndexSet::default(), indirect: false };
+    // Blocks that are unreachable after monomorphization are not codegened.
+    for (bb, data) in traversal::mono_reachable(body, tcx, instance) {
+        collector.visit_basic_block_data(bb, data);
+    }
+
+    let CallCollector { direct, indirect, .. } = collector;
+    StackUsageCallees {
+        direct: direct.into_iter().map(|callee| tcx.symbol_name(callee).name.to_owned()).collect(),
+        indirect,
+    }
+}
+
+struct CallCollector<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    instance: Instance<'tcx>,
+    direct: FxIndexSet<Instance<'tcx>>,
+    indirect: bool,
+}
+
+impl<'a, 'tcx> CallCollector<'a, 'tcx> {
+    fn monomorphize<T>(&self, value: T) -> T
+    where
+        T: TypeFoldable<TyCtxt<'tcx>>,
+    {
+        self.instance.instantiate_mir_and_normalize_erasing_regions(
+            self.tcx,
+            ty::TypingEnv::fully_monomorphized(),
+            ty::EarlyBinder::bind(self.tcx, value),
+        )
+    }
+
+    fn push_lang_item(&mut self, lang_item: LangItem, source: Span) {
+        let instance = Instance::mono(self.tcx, self.tcx.require_lang_item(lang_item, source));
+        self.direct.insert(instance);
+    }
+
+    /// Records a call to a value of type `callee_ty`, see `visit_fn_use` in the collector.
+    fn push_call(&mut self, callee_ty: Ty<'tcx>, source: Span) {
+        let ty::FnDef(def_id, args) = *callee_ty.kind() else {
+            // Calls through function pointers.
+            self.indirect = true;
+            return;
+        };
+        let instance = Instance::expect_resolve(
+            self.tcx,
+            ty::TypingEnv::fully_monomorphized(),
+            def_id,
+            args.no_bound_vars().unwrap(),
+            source,
+        );
+        self.push_instance(instance, source);
+    }
+
+    /// See `visit_instance_use` in the collector.
+    fn push_instance(&mut self, instance: Instance<'tcx>, source: Span) {
+        let tcx = self.tcx;
+        if let Some(intrinsic) = tcx.intrinsic(instance.def_id()) {
+            if ValidityRequirement::from_intrinsic(intrinsic.name).is_some() {
+                // These are lowered in codegen to nothing or a call to `panic_nounwind`.
+                self.push_lang_item(LangItem::PanicNounwind, source);
+            } else if !intrinsic.must_be_overridden
+                && (tcx.sess.opts.unstable_opts.force_intrinsic_fallback
+                    || !tcx.sess.replaced_intrinsics.contains(&intrinsic.name))
+            {
+                // The fallback body is called like any other function.
+                self.direct.insert(Instance::new_raw(instance.def_id(), instance.args));
+            }
+            // Otherwise the intrinsic is lowered by the backend, most often without a call.
+            return;
+        }
+
+        match instance.def {
+            InstanceKind::Virtual(..) => self.indirect = true,
+            InstanceKind::Shim(ShimKind::DropGlue(_, None)) => {
+                // The type doesn't need to be dropped, nothing is called.
+            }
+            _ => {
+                self.direct.insert(instance);
+            }
+        }
+    }
+}
+
+impl<'a, 'tcx> MirVisitor<'tcx> for CallCollector<'a, 'tcx> {
+    fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, _location: Location) {
+        let source = terminator.source_info.span;
+
+        match terminator.kind {
+            mir::TerminatorKind::Call { ref func, .. }
+            | mir::TerminatorKind::TailCall { ref func, .. } => {
+                let callee_ty = self.monomorphize(func.ty(self.body, self.tcx));
+                self.push_call(callee_ty, source);
+            }
+            mir::TerminatorKind::Drop { ref place, .. } => {
+                let ty = self.monomorphize(place.ty(self.body, self.tcx).ty);
+                self.push_instance(Instance::resolve_drop_glue(self.tcx, ty), source);
+            }
+            mir::TerminatorKind::InlineAsm { ref operands, .. } => {
+                // Functions referred to by inline assembly may be called by it, but we don't know
+                // how: treat them like function pointers.
+                if operands.iter().any(|op| matches!(op, mir::InlineAsmOperand::SymFn { .. })) {
+                    self.indirect = true;
+                }
+            }
+            mir::TerminatorKind::Assert { ref msg, .. } => {
+                let lang_item = match &**msg {
+                    mir::AssertKind::BoundsCheck { .. } => LangItem::PanicBoundsCheck,
+                    mir::AssertKind::MisalignedPointerDereference { .. } => {
+                        LangItem::PanicMisalignedPointerDereference
+                    }
+                    mir::AssertKind::NullPointerDereference => {
+                        LangItem::PanicNullPointerDereference
+                    }
+                    mir::AssertKind::NullReferenceConstructed => {
+                        LangItem::PanicNullReferenceConstructed
+                    }
+                    mir::AssertKind::InvalidEnumConstruction(_) => {
+                        LangItem::PanicInvalidEnumConstruction
+                    }
+                    _ => msg.panic_function(),
+                };
+                self.push_lang_item(lang_item, source);
+            }
+            mir::TerminatorKind::UnwindTerminate(reason) => {
+                self.push_lang_item(reason.lang_item(), source);
+            }
+            mir::TerminatorKind::Goto { .. }
+            | mir::TerminatorKind::SwitchInt { .. }
+            | mir::TerminatorKind::UnwindResume
+            | mir::TerminatorKind::Return
+            | mir::TerminatorKind::Unreachable
+            | mir::TerminatorKind::CoroutineDrop
+            | mir::TerminatorKind::Yield { .. }
+            | mir::TerminatorKind::FalseEdge { .. }
+            | mir::TerminatorKind::FalseUnwind { .. } => {}
+        }
+
+        if let Some(mir::UnwindAction::Terminate(reason)) = terminator.unwind() {
+            self.push_lang_item(reason.lang_item(), source);
+        }
+    }
+}
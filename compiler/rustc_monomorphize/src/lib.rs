@@ -52,4 +52,6 @@ fn custom_coerce_unsize_info<'tcx>(
 pub fn provide(providers: &mut Providers) {
     partitioning::provide(providers);
     mono_checks::provide(&mut providers.queries);
+    // Ferrocene addition
+    providers.queries.stack_usage_call_graph = collector::stack_usage::stack_usage_call_graph;
 }
@@ -167,6 +167,17 @@ pub struct CoverageOptions {
     pub discard_all_spans_in_codegen: bool,
 }
 
+/// Kinds of functions that `-Z ferrocene-validated-roots` treats as validated entry points, in the
+/// same way as `main`. This is for firmware, which is entered through interrupt handlers and
+/// functions called from C rather than through `main`. (Ferrocene addition)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct FerroceneValidatedRoots {
+    /// `exported`: functions with `#[no_mangle]` or `#[export_name]`.
+    pub exported: bool,
+    /// `extern-c`: functions defined with the `extern "C"` ABI.
+    pub extern_c: bool,
+}
+
 /// Controls whether branch coverage is enabled.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
 pub enum CoverageLevel {
@@ -187,10 +198,12 @@ pub enum CoverageLevel {
     /// //           ^ last operand
     /// ```
     ///
-    /// This level is mainly intended to be a stepping-stone towards full MC/DC
-    /// instrumentation, so it might be removed in the future when MC/DC is
-    /// sufficiently complete, or if it is making MC/DC changes difficult.
+    /// This level is a stepping-stone towards full MC/DC instrumentation, and
+    /// is included in it.
     Condition,
+    /// Instrument for MC/DC. Mostly a superset of condition coverage, but
+    /// might differ in some corner cases. (Ferrocene addition)
+    Mcdc,
 }
 
 // The different settings that the `-Z offload` flag can have.
@@ -3313,12 +3326,13 @@ pub(crate) mod dep_tracking {
 
     use super::{
         AnnotateMoves, AutoDiff, BranchProtection, CFGuard, CFProtection, CodegenRetagOptions,
-        CoverageOptions, CrateType, DebugInfo, DebugInfoCompression, ErrorOutputType, FmtDebug,
-        FunctionReturn, InliningThreshold, InstrumentCoverage, InstrumentMcount, InstrumentXRay,
-        LinkerPluginLto, LocationDetail, LtoCli, MirStripDebugInfo, NextSolverConfig, Offload,
-        OptLevel, OutFileName, OutputType, OutputTypes, PatchableFunctionEntry, PointerAuthOption,
-        Polonius, ResolveDocLinks, SourceFileHashAlgorithm, SplitDwarfKind, SwitchWithOptPath,
-        SymbolManglingVersion, WasiExecModel,
+        CoverageOptions, CrateType, DebugInfo, DebugInfoCompression, ErrorOutputType,
+        FerroceneValidatedRoots, FmtDebug, FunctionReturn, InliningThreshold, InstrumentCoverage,
+        InstrumentMcount, InstrumentXRay, LinkerPluginLto, LocationDetail, LtoCli,
+        MirStripDebugInfo, NextSolverConfig, Offload, OptLevel, OutFileName, OutputType,
+        OutputTypes, PatchableFunctionEntry, PointerAuthOption, Polonius, ResolveDocLinks,
+        SourceFileHashAlgorithm, SplitDwarfKind, SwitchWithOptPath, SymbolManglingVersion,
+        WasiExecModel,
     };
     use crate::lint;
     use crate::utils::NativeLib;
@@ -3426,6 +3440,7 @@ fn hash(
         CodegenRetagOptions,
         RustcVersion,
         PointerAuthOption,
+        FerroceneValidatedRoots,
     );
 
     impl<T1, T2> DepTrackingHash for (T1, T2)
@@ -32,6 +32,8 @@ pub enum PrintKind {
     CrateName,
     CrateRootLintLevels,
     DeploymentTarget,
+    // Ferrocene addition
+    FerroceneValidationStats,
     FileNames,
     HostTuple,
     LinkArgs,
@@ -66,6 +68,7 @@ fn name(self) -> &'static str {
             CrateName => "crate-name",
             CrateRootLintLevels => "crate-root-lint-levels",
             DeploymentTarget => "deployment-target",
+            FerroceneValidationStats => "ferrocene-validation-stats",
             FileNames => "file-names",
             HostTuple => "host-tuple",
             LinkArgs => "link-args",
@@ -115,6 +118,7 @@ fn is_stable(self) -> bool {
             BackendHasZstd => false,     // (perma-unstable, for use by compiletest)
             CheckCfg => false,
             CrateRootLintLevels => false,
+            FerroceneValidationStats => false,
             SupportedCrateTypes => false,
             TargetSpecJson => false,
             TargetSpecJsonSchema => false,
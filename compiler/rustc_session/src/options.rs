@@ -847,7 +847,9 @@ mod desc {
     pub(crate) const parse_linker_flavor: &str = ::rustc_target::spec::LinkerFlavorCli::one_of();
     pub(crate) const parse_dump_mono_stats: &str = "`markdown` (default) or `json`";
     pub(crate) const parse_instrument_coverage: &str = parse_bool;
-    pub(crate) const parse_coverage_options: &str = "`block` | `branch` | `condition`";
+    pub(crate) const parse_coverage_options: &str = "`block` | `branch` | `condition` | `mcdc`";
+    pub(crate) const parse_ferrocene_validated_roots: &str =
+        "a comma-separated list of `exported`, `extern-c`";
     pub(crate) const parse_codegen_retag_options: &str =
         "either no value or a comma-separated list of settings: `no-precise-im`, `no-precise-pin`";
     pub(crate) const parse_instrument_mcount: &str =
@@ -1639,6 +1641,8 @@ pub(crate) fn parse_coverage_options(slot: &mut CoverageOptions, v: Option<&str>
                 "block" => slot.level = CoverageLevel::Block,
                 "branch" => slot.level = CoverageLevel::Branch,
                 "condition" => slot.level = CoverageLevel::Condition,
+                // Ferrocene addition
+                "mcdc" => slot.level = CoverageLevel::Mcdc,
                 "discard-all-spans-in-codegen" => slot.discard_all_spans_in_codegen = true,
                 _ => return false,
             }
@@ -1646,6 +1650,22 @@ pub(crate) fn parse_coverage_options(slot: &mut CoverageOptions, v: Option<&str>
         true
     }
 
+    pub(crate) fn parse_ferrocene_validated_roots(
+        slot: &mut FerroceneValidatedRoots,
+        v: Option<&str>,
+    ) -> bool {
+        let Some(v) = v else { return false };
+
+        for kind in v.split(',') {
+            match kind {
+                "exported" => slot.exported = true,
+                "extern-c" => slot.extern_c = true,
+                _ => return false,
+            }
+        }
+        true
+    }
+
     pub(crate) fn parse_instrument_mcount(slot: &mut InstrumentMcount, v: Option<&str>) -> bool {
         let mut use_mcount = false;
         if parse_bool(&mut use_mcount, v) {
@@ -2406,6 +2426,9 @@ pub(crate) fn parse_rust_version(slot: &mut Option<RustcVersion>, v: Option<&str
         "overrides the `default_visibility` setting of the target"),
     deny_partial_mitigations: () = ((), parse_deny_partial_mitigations, [UNTRACKED],
         "Deny mitigations not enabled for all dependency crates (comma separated list)"),
+    deny_reachable_panics: bool = (false, parse_bool, [TRACKED],
+        "report every panic reachable from a validated root after monomorphization, as the \
+        `ferrocene::reachable_panic` lint (Ferrocene addition)"),
     dep_info_omit_d_target: bool = (false, parse_bool, [TRACKED],
         "in dep-info output, omit targets for tracking dependencies of the dep-info files \
         themselves (default: no)"),
@@ -2471,6 +2494,23 @@ pub(crate) fn parse_rust_version(slot: &mut Option<RustcVersion>, v: Option<&str
         "rely on user specified linker commands to find clangrt"),
     extra_const_ub_checks: bool = (false, parse_bool, [TRACKED],
         "turns on more checks to detect const UB, which can be slow (default: no)"),
+    ferrocene_fn_ptr_escape_analysis: bool = (false, parse_bool, [TRACKED],
+        "only lint casts of unvalidated functions to function pointers if the pointer may be \
+        called or escape the current function (Ferrocene addition)"),
+    ferrocene_post_mono_check: bool = (false, parse_bool, [TRACKED],
+        "run the post-monomorphization `ferrocene::unvalidated` lint pass even when not generating \
+        code, e.g. under `cargo check` (Ferrocene addition)"),
+    ferrocene_validated_graph: Option<PathBuf> = (None, parse_opt_pathbuf, [TRACKED_NO_CRATE_HASH],
+        "write the call graph reachable from validated roots to a JSON file at this path \
+        (Ferrocene addition)"),
+    ferrocene_validated_roots: FerroceneValidatedRoots = (FerroceneValidatedRoots::default(),
+        parse_ferrocene_validated_roots, [TRACKED],
+        "treat these kinds of functions as validated entry points, like `main`: `exported` \
+        (`#[no_mangle]` or `#[export_name]`), `extern-c` (`extern \"C\"` functions) \
+        (Ferrocene addition)"),
+    ferrocene_validation_manifest: Option<PathBuf> = (None, parse_opt_pathbuf, [TRACKED],
+        "read the validation status of items in upstream crates from this JSON file, \
+        instead of from their `#[ferrocene::prevalidated]` attributes (Ferrocene addition)"),
     #[rustc_lint_opt_deny_field_access("use `Session::fewer_names` instead of this field")]
     fewer_names: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "reduce memory use by retaining fewer names within compilation artifacts (LLVM-IR) \
@@ -2656,6 +2696,9 @@ pub(crate) fn parse_rust_version(slot: &mut Option<RustcVersion>, v: Option<&str
         "use `--jobs-backend=1` instead"),
     no_profiler_runtime: bool = (false, parse_no_value, [TRACKED],
         "prevent automatic injection of the profiler_builtins crate"),
+    no_std_test_harness: bool = (false, parse_bool, [TRACKED],
+        "build `--test` crates with the `no_std` test harness of `core::ferrocene_test::harness` \
+        rather than with libtest (Ferrocene addition)"),
     no_steal_thir: bool = (false, parse_bool, [UNTRACKED],
         "don't steal the THIR when we're done with it; useful for rustc drivers (default: no)"),
     no_trait_vptr: bool = (false, parse_no_value, [TRACKED],
@@ -2842,6 +2885,10 @@ pub(crate) fn parse_rust_version(slot: &mut Option<RustcVersion>, v: Option<&str
     #[rustc_lint_opt_deny_field_access("use `Session::stack_protector` instead of this field")]
     stack_protector: StackProtector = (StackProtector::None, parse_stack_protector, [TRACKED] { MITIGATION: StackProtector },
         "control stack smash protection strategy (`rustc --print stack-protector-strategies` for details)"),
+    stack_usage_report: Option<PathBuf> = (None, parse_opt_pathbuf, [TRACKED],
+        "write the worst-case stack usage of each mono root to a JSON file at this path, \
+        combining the frame sizes emitted by the backend with the call graph (implies \
+        `-Z emit-stack-sizes`) (Ferrocene addition)"),
     staticlib_allow_rdylib_deps: bool = (false, parse_bool, [TRACKED],
         "allow staticlibs to have rust dylib dependencies"),
     staticlib_hide_internal_symbols: bool = (false, parse_bool, [TRACKED],
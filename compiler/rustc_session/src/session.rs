@@ -582,6 +582,12 @@ pub fn instrument_coverage_condition(&self) -> bool {
             && self.opts.unstable_opts.coverage_options.level >= CoverageLevel::Condition
     }
 
+    // Ferrocene addition
+    pub fn instrument_coverage_mcdc(&self) -> bool {
+        self.instrument_coverage()
+            && self.opts.unstable_opts.coverage_options.level >= CoverageLevel::Mcdc
+    }
+
     /// Provides direct access to the `CoverageOptions` struct, so that
     /// individual flags for debugging/testing coverage instrumetation don't
     /// need separate accessors.
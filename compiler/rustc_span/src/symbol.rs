@@ -891,6 +891,7 @@
         enable,
         end,
         entry_nops,
+        entrypoint,
         env,
         env_CFG_RELEASE: env!("CFG_RELEASE"),
         eq,
@@ -959,6 +960,7 @@
         fence,
         ferris: "🦀",
         ferrocene,
+        ferrocene_test,
         fetch_update,
         ffi_const,
         ffi_pure,
@@ -1188,6 +1190,7 @@
         len,
         let_chains,
         let_else,
+        level,
         lhs,
         lib,
         libc,
@@ -1489,6 +1492,8 @@
         optin_builtin_traits,
         option,
         option_env,
+        option_expect_failed,
+        option_unwrap_failed,
         options,
         or,
         or_patterns,
@@ -1716,6 +1721,7 @@
         residual,
         result,
         result_ffi_guarantees,
+        result_unwrap_failed,
         return_address,
         return_position_impl_trait_in_trait,
         return_type_notation,
@@ -2009,6 +2015,7 @@
         slice_from_raw_parts,
         slice_from_raw_parts_mut,
         slice_get_unchecked,
+        slice_index_fail,
         slice_len_fn,
         slice_patterns,
         slicing_syntax,
@@ -2034,6 +2041,7 @@
         sse4a_target_feature,
         stable,
         staged_api,
+        standard,
         start,
         state,
         static_align,
@@ -2059,6 +2067,7 @@
         str_inherent_from_utf8_mut,
         str_inherent_from_utf8_unchecked,
         str_inherent_from_utf8_unchecked_mut,
+        str_slice_error_fail,
         strict_provenance_lints,
         string_deref_patterns,
         stringify,
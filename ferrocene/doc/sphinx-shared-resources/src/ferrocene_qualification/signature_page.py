@@ -27,7 +27,7 @@ class SignatureStatus:
             raise RuntimeError("invalid value to ferrocene_signature")
 
         try:
-            self.context["config"] = tomli.loads(self.load_file("config.toml"))
+            self.context["config"] = tomli.loads(self.load_file("config.toml", copy=True))
             self.private_files = tomli.loads(self.load_file("signature.toml"))["files"]
             self.load_private_file("pinned.toml", copy=True)
 
@@ -38,7 +38,7 @@ class SignatureStatus:
                         self.load_private_file(f"{role}.cosign-bundle", copy=True)
                     )
                     time = datetime.datetime.utcfromtimestamp(
-                        bundle["rekorBundle"]["Payload"]["integratedTime"]
+                        integrated_time(bundle)
                     ).strftime("%Y-%m-%d %H:%M:%S UTC")
                     present = True
                 except FileNotFoundError:
@@ -140,8 +140,18 @@ def build_finished(app, exception):
             f.write("signatures\n")
 
 
+def integrated_time(bundle):
+    # Sigstore bundles (cosign's --new-bundle-format) store the transparency log entries in
+    # their verification material, while legacy cosign bundles store a single Rekor bundle.
+    if "mediaType" in bundle:
+        entry = bundle["verificationMaterial"]["tlogEntries"][0]
+        return int(entry["integratedTime"])
+    return bundle["rekorBundle"]["Payload"]["integratedTime"]
+
+
 def setup(app):
     app.connect("doctree-read", doctree_read)
     app.connect("html-collect-pages", html_collect_pages)
     app.connect("html-page-context", html_page_context)
     app.connect("build-finished", build_finished)
+
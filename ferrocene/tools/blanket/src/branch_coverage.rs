@@ -0,0 +1,345 @@
+//! Branch and MC/DC coverage, for safety standards that require more than line coverage (e.g.
+//! ISO 26262 ASIL-D, DO-178C DAL A).
+//!
+//! The regions are only emitted when the instrumented code was built with
+//! `-Zcoverage-options=branch` (branches) or `-Zcoverage-options=condition` (branches and MC/DC
+//! decisions). `llvm_profparser`'s report only keeps line hits, so the branch regions are read from
+//! the output of `llvm-cov export -format=text`, run on the same profiles and objects. The
+//! `-path-equivalence` given to `llvm-cov` must match the one given to blanket, otherwise no
+//! branch will be found.
+//!
+//! Branches and decisions are identified by their whole region, not just where they start: the
+//! condition `a` and the decision `a && b` start at the same position, as do the two conditions of
+//! a `matches!` expansion.
+//!
+//! Each branch and decision is then classified against the function of the symbol report that
+//! contains it, like lines are:
+//! - A branch is `Covered` if both outcomes were taken, `TrueOnly`/`FalseOnly` if only one was,
+//!   and `Untested` if neither was.
+//! - A decision is covered if every one of its conditions was shown to independently affect the
+//!   outcome.
+//! - A branch or decision that is not covered, and starts on a line inside a
+//!   `#[ferrocene::annotation]`, is `Annotated` instead.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::{Annotation, annotate};
+
+/// `CounterMappingRegion::BranchRegion` in LLVM.
+const BRANCH_REGION: u32 = 4;
+/// `CounterMappingRegion::MCDCBranchRegion` in LLVM.
+const MCDC_BRANCH_REGION: u32 = 6;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) enum BranchCoverageStatus {
+    Covered,
+    TrueOnly,
+    FalseOnly,
+    Untested,
+    Annotated,
+}
+
+impl BranchCoverageStatus {
+    pub(crate) fn to_css_class(&self) -> &str {
+        match self {
+            BranchCoverageStatus::Covered => "branch-covered",
+            BranchCoverageStatus::TrueOnly | BranchCoverageStatus::FalseOnly => "branch-partial",
+            BranchCoverageStatus::Untested => "branch-untested",
+            BranchCoverageStatus::Annotated => "branch-annotated",
+        }
+    }
+
+    pub(crate) fn to_human(&self) -> &str {
+        match self {
+            BranchCoverageStatus::Covered => "Both outcomes taken",
+            BranchCoverageStatus::TrueOnly => "Only the true outcome taken",
+            BranchCoverageStatus::FalseOnly => "Only the false outcome taken",
+            BranchCoverageStatus::Untested => "No outcome taken",
+            BranchCoverageStatus::Annotated => "Annotated",
+        }
+    }
+
+    /// The number of outcomes taken, out of two.
+    pub(crate) fn outcomes_taken(&self) -> usize {
+        match self {
+            BranchCoverageStatus::Covered => 2,
+            BranchCoverageStatus::TrueOnly | BranchCoverageStatus::FalseOnly => 1,
+            BranchCoverageStatus::Untested | BranchCoverageStatus::Annotated => 0,
+        }
+    }
+}
+
+/// `(start line, start column, end line, end column)` of a region.
+pub(crate) type Region = (usize, usize, usize, usize);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct BranchCoverage {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    #[serde(default)]
+    pub(crate) end_line: usize,
+    #[serde(default)]
+    pub(crate) end_column: usize,
+    pub(crate) status: BranchCoverageStatus,
+}
+
+impl BranchCoverage {
+    pub(crate) fn region(&self) -> Region {
+        (self.line, self.column, self.end_line, self.end_column)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct DecisionCoverage {
+    pub(crate) start_line: usize,
+    pub(crate) end_line: usize,
+    pub(crate) column: usize,
+    #[serde(default)]
+    pub(crate) end_column: usize,
+    /// Whether each condition of the decision was shown to independently affect the outcome.
+    pub(crate) conditions: Vec<bool>,
+    pub(crate) annotated: bool,
+}
+
+impl DecisionCoverage {
+    pub(crate) fn covered_conditions(&self) -> usize {
+        self.conditions.iter().filter(|covered| **covered).count()
+    }
+
+    pub(crate) fn is_covered(&self) -> bool {
+        self.conditions.iter().all(|covered| *covered)
+    }
+}
+
+#[derive(Default)]
+struct Branch {
+    true_count: u64,
+    false_count: u64,
+}
+
+#[derive(Default)]
+struct FileBranches {
+    /// Instantiations of the same generic function are merged.
+    branches: BTreeMap<Region, Branch>,
+    /// Whether each condition of the decision was covered by any instantiation.
+    decisions: BTreeMap<Region, Vec<bool>>,
+}
+
+/// The branch regions of every file in an `llvm-cov export`.
+#[derive(Default)]
+pub(crate) struct BranchReport {
+    files: BTreeMap<PathBuf, FileBranches>,
+}
+
+#[derive(Deserialize)]
+struct Export {
+    data: Vec<ExportData>,
+}
+
+#[derive(Deserialize)]
+struct ExportData {
+    files: Vec<ExportFile>,
+}
+
+type ExportBranch = (usize, usize, usize, usize, u64, u64, u32, u32, u32);
+
+#[derive(Deserialize)]
+struct ExportFile {
+    filename: PathBuf,
+    /// `[line_start, col_start, line_end, col_end, true_count, false_count, file_id,
+    /// expanded_file_id, kind]`
+    #[serde(default)]
+    branches: Vec<ExportBranch>,
+    /// `[line_start, col_start, line_end, col_end, ..., [condition covered, ...]]`. The fields in
+    /// between changed across LLVM versions, so they are not used.
+    #[serde(default)]
+    mcdc_records: Vec<Vec<serde_json::Value>>,
+}
+
+impl BranchReport {
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path).context(format!("failed to open {}", path.display()))?;
+        let export = serde_json::from_reader(file)
+            .context(format!("failed to parse llvm-cov export from {}", path.display()))?;
+        Self::from_export(export).context(format!("invalid llvm-cov export {}", path.display()))
+    }
+
+    fn from_export(export: Export) -> Result<Self> {
+        let mut report = BranchReport::default();
+        for file in export.data.into_iter().flat_map(|data| data.files) {
+            // Same as the coverage report, so that `get_coverage` can look files up by the
+            // canonicalized path of the symbol report.
+            let filename = file.filename.canonicalize().unwrap_or(file.filename);
+            let entry = report.files.entry(filename).or_default();
+
+            for (line, column, end_line, end_column, true_count, false_count, _, _, kind) in
+                file.branches
+            {
+                if kind != BRANCH_REGION && kind != MCDC_BRANCH_REGION {
+                    continue;
+                }
+                let branch =
+                    entry.branches.entry((line, column, end_line, end_column)).or_default();
+                branch.true_count += true_count;
+                branch.false_count += false_count;
+            }
+
+            for record in file.mcdc_records {
+                let (region, conditions) = parse_mcdc_record(&record)?;
+                let decision =
+                    entry.decisions.entry(region).or_insert_with(|| vec![false; conditions.len()]);
+                // A condition is covered if any instantiation covered it.
+                for (merged, covered) in decision.iter_mut().zip(conditions) {
+                    *merged |= covered;
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Classify the branches and decisions starting within `lines` of `file`, marking the
+    /// annotations that exempt any of them as used.
+    pub(crate) fn classify(
+        &self,
+        file: &Path,
+        lines: std::ops::RangeInclusive<usize>,
+        mut annotations: Option<&mut Vec<Annotation>>,
+    ) -> (Vec<BranchCoverage>, Vec<DecisionCoverage>) {
+        let Some(file) = self.files.get(file) else { return (vec![], vec![]) };
+        let branches = file
+            .branches
+            .iter()
+            .filter(|((line, ..), _)| lines.contains(line))
+            .map(|(&(line, column, end_line, end_column), branch)| {
+                let status = match (branch.true_count > 0, branch.false_count > 0) {
+                    (true, true) => BranchCoverageStatus::Covered,
+                    (true, false) => BranchCoverageStatus::TrueOnly,
+                    (false, true) => BranchCoverageStatus::FalseOnly,
+                    (false, false) => BranchCoverageStatus::Untested,
+                };
+                let status = if status != BranchCoverageStatus::Covered
+                    && annotate(annotations.as_deref_mut(), line)
+                {
+                    BranchCoverageStatus::Annotated
+                } else {
+                    status
+                };
+                BranchCoverage { line, column, end_line, end_column, status }
+            })
+            .collect();
+
+        let decisions = file
+            .decisions
+            .iter()
+            .filter(|((line, ..), _)| lines.contains(line))
+            .map(|(&(start_line, column, end_line, end_column), conditions)| {
+                let mut coverage = DecisionCoverage {
+                    start_line,
+                    end_line,
+                    column,
+                    end_column,
+                    conditions: conditions.clone(),
+                    annotated: false,
+                };
+                coverage.annotated =
+                    !coverage.is_covered() && annotate(annotations.as_deref_mut(), start_line);
+                coverage
+            })
+            .collect();
+
+        (branches, decisions)
+    }
+}
+
+fn parse_mcdc_record(record: &[serde_json::Value]) -> Result<(Region, Vec<bool>)> {
+    let number = |i: usize| {
+        record
+            .get(i)
+            .and_then(|value| value.as_u64())
+            .map(|n| n as usize)
+            .context("missing field in MC/DC record")
+    };
+    let Some(conditions) = record.last().and_then(|value| value.as_array()) else {
+        bail!("missing conditions in MC/DC record");
+    };
+    let conditions = conditions
+        .iter()
+        .map(|covered| covered.as_bool().context("condition is not a boolean"))
+        .collect::<Result<_>>()?;
+    Ok(((number(0)?, number(1)?, number(2)?, number(3)?), conditions))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn report(file: serde_json::Value) -> BranchReport {
+        let export = serde_json::from_value(json!({ "data": [{ "files": [file] }] })).unwrap();
+        BranchReport::from_export(export).unwrap()
+    }
+
+    #[test]
+    fn test_regions_sharing_a_start() {
+        let report = report(json!({
+            "filename": "/ferrocene/library/core/src/lib.rs",
+            "branches": [
+                // Two conditions starting at the same position, e.g. `a` and `a && b` in
+                // `(a && b) || c`.
+                [3, 8, 3, 9, 1, 0, 0, 0, BRANCH_REGION],
+                [3, 8, 3, 14, 0, 1, 0, 0, BRANCH_REGION],
+                // Another instantiation of the same function.
+                [3, 8, 3, 9, 0, 2, 0, 0, BRANCH_REGION],
+                // Not a branch.
+                [4, 1, 4, 2, 1, 1, 0, 0, 0],
+                [5, 4, 5, 5, 0, 0, 0, 0, MCDC_BRANCH_REGION],
+            ],
+            "mcdc_records": [
+                [3, 8, 3, 14, 2, 0, 0, [true, false]],
+                [3, 8, 3, 19, 3, 0, 0, [false, false, true]],
+                [3, 8, 3, 14, 2, 0, 0, [false, true]],
+            ],
+        }));
+
+        let file = Path::new("/ferrocene/library/core/src/lib.rs");
+        let mut annotations = vec![Annotation { start: 5, end: 5, unused: true }];
+        let (branches, decisions) = report.classify(file, 1..=5, Some(&mut annotations));
+
+        let branches =
+            branches.iter().map(|branch| (branch.region(), &branch.status)).collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ((3, 8, 3, 9), &BranchCoverageStatus::Covered),
+                ((3, 8, 3, 14), &BranchCoverageStatus::FalseOnly),
+                ((5, 4, 5, 5), &BranchCoverageStatus::Annotated),
+            ],
+            branches
+        );
+        assert!(!annotations[0].unused);
+
+        let decisions = decisions
+            .iter()
+            .map(|decision| (decision.end_column, decision.conditions.as_slice()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(14, &[true, true][..]), (19, &[false, false, true][..])], decisions);
+
+        let (branches, decisions) = report.classify(file, 4..=4, None);
+        assert!(branches.is_empty() && decisions.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_mcdc_record() {
+        let export = serde_json::from_value(json!({ "data": [{ "files": [{
+            "filename": "lib.rs",
+            "mcdc_records": [[3, 8, 3]],
+        }] }] }))
+        .unwrap();
+        assert!(BranchReport::from_export(export).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+//! A [Cobertura](https://cobertura.github.io/cobertura/) XML report, for CI dashboards that don't
+//! understand our HTML report (e.g. GitLab merge request coverage, Jenkins).
+//!
+//! Each source file is a `<class>`, grouped into a `<package>` per directory, and each function in
+//! the symbol report is a `<method>`. Cobertura has no notion of annotated or ignored lines, so we
+//! keep the distinction as extra attributes, which consumers ignore:
+//! - Annotated lines have `hits="0"` like any other untested line, and `ferrocene-annotated="true"`.
+//! - Ignored lines are not instrumented, so they don't count towards the line rate. They are only
+//!   listed inside their `<method>`, with `ferrocene-ignored="true"`.
+//! - Each `<method>` has `ferrocene-status` and `ferrocene-annotated` attributes, using the same
+//!   status names as the HTML report.
+//!
+//! As in the HTML report, annotated lines count as untested in the line rates. Hit counts are 1 for
+//! tested lines and 0 otherwise; the report only records whether a line was tested.
+//!
+//! Branches (with `--branch-export`) count towards the branch rate of their `<method>` and of every
+//! level above it. Each line of a `<class>` with branches has `branch="true"` and a
+//! `condition-coverage` counting both outcomes of each branch. Annotated branches count as untested
+//! in the branch rates.
+//!
+//! A rate with nothing to count, such as the branch rate of a method without branches, is always
+//! `1`, at every level.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::branch_coverage::BranchCoverage;
+use crate::{FileCoverage, FunctionCoverage, LineCoverageStatus};
+
+pub(crate) fn generate(coverage: &[FunctionCoverage], sources: &Path) -> String {
+    let files = crate::group_by_file(coverage);
+    let mut packages = BTreeMap::<String, Vec<(&Path, &FileCoverage<'_>)>>::new();
+    for (filename, file) in &files {
+        let package = filename.parent().map(|dir| dir.display().to_string()).unwrap_or_default();
+        packages.entry(package).or_default().push((*filename, file));
+    }
+
+    let (total_valid, total_covered) = files.values().fold((0, 0), |(valid, covered), file| {
+        let (v, c) = line_counts(file.lines.values().copied());
+        (valid + v, covered + c)
+    });
+    let (branches_valid, branches_covered) =
+        branch_counts(files.values().flat_map(|file| file.branches.values().copied()));
+
+    let mut out = String::new();
+    writeln!(out, r#"<?xml version="1.0" ?>"#).unwrap();
+    writeln!(
+        out,
+        r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<coverage line-rate="{}" branch-rate="{}" lines-covered="{total_covered}" lines-valid="{total_valid}" branches-covered="{branches_covered}" branches-valid="{branches_valid}" complexity="0" version="blanket" timestamp="0">"#,
+        rate(total_valid, total_covered),
+        rate(branches_valid, branches_covered),
+    )
+    .unwrap();
+    writeln!(out, "  <sources>").unwrap();
+    writeln!(out, "    <source>{}</source>", escape(&sources.display().to_string())).unwrap();
+    writeln!(out, "  </sources>").unwrap();
+    writeln!(out, "  <packages>").unwrap();
+    for (package, files) in &packages {
+        let (valid, covered) =
+            line_counts(files.iter().flat_map(|(_, file)| file.lines.values().copied()));
+        let (branches_valid, branches_covered) =
+            branch_counts(files.iter().flat_map(|(_, file)| file.branches.values().copied()));
+        writeln!(
+            out,
+            r#"    <package name="{}" line-rate="{}" branch-rate="{}" complexity="0">"#,
+            escape(package),
+            rate(valid, covered),
+            rate(branches_valid, branches_covered),
+        )
+        .unwrap();
+        writeln!(out, "      <classes>").unwrap();
+        for (filename, file) in files {
+            write_class(&mut out, filename, file);
+        }
+        writeln!(out, "      </classes>").unwrap();
+        writeln!(out, "    </package>").unwrap();
+    }
+    writeln!(out, "  </packages>").unwrap();
+    writeln!(out, "</coverage>").unwrap();
+    out
+}
+
+fn write_class(out: &mut String, filename: &Path, file: &FileCoverage<'_>) {
+    let filename = escape(&filename.display().to_string());
+    let (valid, covered) = line_counts(file.lines.values().copied());
+    let (branches_valid, branches_covered) = branch_counts(file.branches.values().copied());
+    writeln!(
+        out,
+        r#"        <class name="{filename}" filename="{filename}" line-rate="{}" branch-rate="{}" complexity="0">"#,
+        rate(valid, covered),
+        rate(branches_valid, branches_covered),
+    )
+    .unwrap();
+
+    writeln!(out, "          <methods>").unwrap();
+    for function in &file.functions {
+        let (valid, covered) = line_counts(function.lines.lines.iter().map(|(_, status)| status));
+        let (branches_valid, branches_covered) = branch_counts(function.branches.iter());
+        writeln!(
+            out,
+            r#"            <method name="{}" signature="" line-rate="{}" branch-rate="{}" complexity="0" ferrocene-status="{}" ferrocene-annotated="{}">"#,
+            escape(&function.source_name),
+            rate(valid, covered),
+            rate(branches_valid, branches_covered),
+            function.status.to_css_class(),
+            function.annotated.as_str(),
+        )
+        .unwrap();
+        writeln!(out, "              <lines>").unwrap();
+        for (line, status) in &function.lines.lines {
+            write_line(out, "                ", *line, status, true, None);
+        }
+        writeln!(out, "              </lines>").unwrap();
+        writeln!(out, "            </method>").unwrap();
+    }
+    writeln!(out, "          </methods>").unwrap();
+
+    writeln!(out, "          <lines>").unwrap();
+    for (line, status) in &file.lines {
+        let branches = branch_counts(
+            file.branches
+                .range((*line, 0, 0, 0)..=(*line, usize::MAX, usize::MAX, usize::MAX))
+                .map(|(_, branch)| *branch),
+        );
+        let branches = (branches.0 > 0).then_some(branches);
+        write_line(out, "            ", *line, status, false, branches);
+    }
+    writeln!(out, "          </lines>").unwrap();
+    writeln!(out, "        </class>").unwrap();
+}
+
+fn write_line(
+    out: &mut String,
+    indent: &str,
+    line: usize,
+    status: &LineCoverageStatus,
+    include_ignored: bool,
+    branches: Option<(usize, usize)>,
+) {
+    let (hits, extra) = match status {
+        LineCoverageStatus::Tested => (1, ""),
+        LineCoverageStatus::Untested => (0, ""),
+        LineCoverageStatus::Annotated => (0, r#" ferrocene-annotated="true""#),
+        LineCoverageStatus::Ignored if include_ignored => (0, r#" ferrocene-ignored="true""#),
+        LineCoverageStatus::Ignored => return,
+    };
+    let branch = match branches {
+        Some((valid, covered)) => format!(
+            r#"branch="true" condition-coverage="{}% ({covered}/{valid})""#,
+            covered * 100 / valid
+        ),
+        None => r#"branch="false""#.to_string(),
+    };
+    writeln!(out, r#"{indent}<line number="{line}" hits="{hits}" {branch}{extra}/>"#).unwrap();
+}
+
+/// Returns the number of instrumented lines, and how many of them were tested.
+fn line_counts<'a>(lines: impl Iterator<Item = &'a LineCoverageStatus>) -> (usize, usize) {
+    lines.fold((0, 0), |(valid, covered), status| match status {
+        LineCoverageStatus::Tested => (valid + 1, covered + 1),
+        LineCoverageStatus::Untested | LineCoverageStatus::Annotated => (valid + 1, covered),
+        LineCoverageStatus::Ignored => (valid, covered),
+    })
+}
+
+/// Returns the number of branch outcomes, and how many of them were taken.
+fn branch_counts<'a>(branches: impl Iterator<Item = &'a BranchCoverage>) -> (usize, usize) {
+    branches.fold((0, 0), |(valid, covered), branch| {
+        (valid + 2, covered + branch.status.outcomes_taken())
+    })
+}
+
+/// The fraction of `valid` that was `covered`, or `1` if there is nothing to cover.
+fn rate(valid: usize, covered: usize) -> String {
+    if valid == 0 { "1".to_string() } else { format!("{:.4}", covered as f64 / valid as f64) }
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample_coverage;
+
+    #[test]
+    fn test_generate() {
+        let expected = r#"<?xml version="1.0" ?>
+<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">
+<coverage line-rate="0.5000" branch-rate="0.5000" lines-covered="3" lines-valid="6" branches-covered="3" branches-valid="6" complexity="0" version="blanket" timestamp="0">
+  <sources>
+    <source>/ferrocene</source>
+  </sources>
+  <packages>
+    <package name="library/core/src" line-rate="0.5000" branch-rate="0.5000" complexity="0">
+      <classes>
+        <class name="library/core/src/a.rs" filename="library/core/src/a.rs" line-rate="0.6000" branch-rate="0.5000" complexity="0">
+          <methods>
+            <method name="core::a::tested" signature="" line-rate="0.6667" branch-rate="0.7500" complexity="0" ferrocene-status="partially-tested" ferrocene-annotated="not-annotated">
+              <lines>
+                <line number="1" hits="1" branch="false"/>
+                <line number="2" hits="1" branch="false"/>
+                <line number="3" hits="0" branch="false" ferrocene-ignored="true"/>
+                <line number="4" hits="0" branch="false"/>
+              </lines>
+            </method>
+            <method name="core::a::annotated" signature="" line-rate="0.5000" branch-rate="0.0000" complexity="0" ferrocene-status="partially-tested" ferrocene-annotated="fully-annotated">
+              <lines>
+                <line number="6" hits="1" branch="false"/>
+                <line number="7" hits="0" branch="false" ferrocene-annotated="true"/>
+              </lines>
+            </method>
+          </methods>
+          <lines>
+            <line number="1" hits="1" branch="false"/>
+            <line number="2" hits="1" branch="true" condition-coverage="75% (3/4)"/>
+            <line number="4" hits="0" branch="false"/>
+            <line number="6" hits="1" branch="false"/>
+            <line number="7" hits="0" branch="true" condition-coverage="0% (0/2)" ferrocene-annotated="true"/>
+          </lines>
+        </class>
+        <class name="library/core/src/b.rs" filename="library/core/src/b.rs" line-rate="0.0000" branch-rate="1" complexity="0">
+          <methods>
+            <method name="&lt;T as core::b::Foo&gt;::untested" signature="" line-rate="0.0000" branch-rate="1" complexity="0" ferrocene-status="fully-untested" ferrocene-annotated="not-annotated">
+              <lines>
+                <line number="1" hits="0" branch="false"/>
+              </lines>
+            </method>
+          </methods>
+          <lines>
+            <line number="1" hits="0" branch="false"/>
+          </lines>
+        </class>
+      </classes>
+    </package>
+  </packages>
+</coverage>
+"#;
+        assert_eq!(expected, generate(&sample_coverage(), Path::new("/ferrocene")));
+    }
+}
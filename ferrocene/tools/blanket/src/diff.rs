@@ -0,0 +1,518 @@
+//! `blanket diff`: compare the coverage of two runs, e.g. before and after upgrading Ferrocene, so
+//! that every regression can be justified.
+//!
+//! Each side is either a set of profiles and objects (like `blanket show`), or the output of a
+//! previous `blanket show --json-out`. Functions are matched by file and name; functions sharing
+//! both (e.g. the same method in two impl blocks) are matched in source order. Since the source of
+//! a function may change between runs, its lines are matched with a line diff of the source of
+//! each side, ignoring indentation. Lines only present on one side are listed as added or removed.
+//!
+//! A regression is a line that was `Tested` and is now `Untested`, or was removed. Lines that are
+//! now `Annotated` are listed, but are not regressions, since the annotation already justifies
+//! them.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result, bail};
+use clap::Parser;
+use llvm_profparser::PathRemapping;
+
+use crate::{FunctionCoverage, LineCoverageStatus, load_coverage};
+
+#[derive(Clone, Debug, Eq, PartialEq, Parser)]
+pub struct DiffCommand {
+    /// Coverage before the change, as written by `blanket show --json-out`
+    #[arg(long, conflicts_with_all = ["old_instr_profile", "old_objects", "old_symbol_report"])]
+    old_json: Option<PathBuf>,
+    /// Profile data before the change
+    #[arg(long = "old-instr-profile")]
+    old_instr_profile: Vec<PathBuf>,
+    /// Coverage executable or object file before the change
+    #[arg(long = "old-object")]
+    old_objects: Vec<PathBuf>,
+    /// Symbol report before the change
+    #[arg(long = "old-report")]
+    old_symbol_report: Option<PathBuf>,
+
+    /// Coverage after the change, as written by `blanket show --json-out`
+    #[arg(long, conflicts_with_all = ["new_instr_profile", "new_objects", "new_symbol_report"])]
+    new_json: Option<PathBuf>,
+    /// Profile data after the change
+    #[arg(long = "new-instr-profile")]
+    new_instr_profile: Vec<PathBuf>,
+    /// Coverage executable or object file after the change
+    #[arg(long = "new-object")]
+    new_objects: Vec<PathBuf>,
+    /// Symbol report after the change
+    #[arg(long = "new-report")]
+    new_symbol_report: Option<PathBuf>,
+
+    /// Pair of paths for a remapping to allow loading files after move. Comma separated in the
+    /// order `source,dest`
+    #[arg(long = "path-equivalence")]
+    path_remapping: Option<PathRemapping>,
+    /// Required when either side is given as profiles. Also the default for `--old-src` and
+    /// `--new-src`
+    #[arg(long = "ferrocene-src", short = 's')]
+    ferrocene: Option<PathBuf>,
+    /// Source tree the old coverage was collected from, to match lines that moved
+    #[arg(long = "old-src")]
+    old_src: Option<PathBuf>,
+    /// Source tree the new coverage was collected from, to match lines that moved
+    #[arg(long = "new-src")]
+    new_src: Option<PathBuf>,
+    /// Exit with an error if any tested line became untested or was removed
+    #[arg(long)]
+    deny_regressions: bool,
+}
+
+impl DiffCommand {
+    pub(crate) fn run(&self) -> Result<()> {
+        let old = self.load(
+            "old",
+            &self.old_json,
+            &self.old_instr_profile,
+            &self.old_objects,
+            &self.old_symbol_report,
+        )?;
+        let new = self.load(
+            "new",
+            &self.new_json,
+            &self.new_instr_profile,
+            &self.new_objects,
+            &self.new_symbol_report,
+        )?;
+        let mut old_sources = Sources::new(self.source_root("old", &self.old_src)?);
+        let mut new_sources = Sources::new(self.source_root("new", &self.new_src)?);
+
+        let mut changed = 0;
+        let mut regressions = 0;
+        for (old_fn, new_fn) in match_functions(&old, &new) {
+            let (old_fn, new_fn) = match (old_fn, new_fn) {
+                (Some(old_fn), Some(new_fn)) => (old_fn, new_fn),
+                (Some(old_fn), None) => {
+                    println!("removed: {} ({})", describe(old_fn), tested_summary(old_fn));
+                    changed += 1;
+                    continue;
+                }
+                (None, Some(new_fn)) => {
+                    println!("added: {} ({})", describe(new_fn), tested_summary(new_fn));
+                    changed += 1;
+                    continue;
+                }
+                (None, None) => unreachable!(),
+            };
+            let diff = diff_function(
+                old_fn,
+                old_sources.lines(&old_fn.relative_path)?,
+                new_fn,
+                new_sources.lines(&new_fn.relative_path)?,
+            );
+            if diff.is_empty() {
+                continue;
+            }
+
+            changed += 1;
+            println!(
+                "changed: {} ({} -> {})",
+                describe(new_fn),
+                tested_summary(old_fn),
+                tested_summary(new_fn)
+            );
+            let path = new_fn.relative_path.display();
+            for change in diff {
+                regressions += usize::from(change.is_regression());
+                match change {
+                    LineChange::Added { line, status } => {
+                        println!("  {path}:{line}: added ({status})");
+                    }
+                    LineChange::Removed { line, status } => {
+                        println!(
+                            "  {}{path}: removed line {line} (was {status})",
+                            if change.is_regression() { "REGRESSION " } else { "" },
+                        );
+                    }
+                    LineChange::Changed { old_line, new_line, old_status, new_status } => {
+                        println!(
+                            "  {}{path}:{new_line} (was line {old_line}): {old_status} -> \
+                            {new_status}",
+                            if change.is_regression() { "REGRESSION " } else { "" },
+                        );
+                    }
+                }
+            }
+        }
+
+        println!(
+            "---\n\
+            Functions changed: {changed}\n\
+            Lines regressed (Tested -> Untested or removed): {regressions}\n\
+            ---"
+        );
+
+        if self.deny_regressions && regressions > 0 {
+            bail!("{regressions} tested line(s) became untested or were removed");
+        }
+        Ok(())
+    }
+
+    fn source_root(&self, side: &str, src: &Option<PathBuf>) -> Result<PathBuf> {
+        match src.as_ref().or(self.ferrocene.as_ref()) {
+            Some(src) => Ok(src.clone()),
+            None => bail!("either --{side}-src or --ferrocene-src must be given"),
+        }
+    }
+
+    fn load(
+        &self,
+        side: &str,
+        json: &Option<PathBuf>,
+        instr_profile: &[PathBuf],
+        objects: &[PathBuf],
+        symbol_report: &Option<PathBuf>,
+    ) -> Result<Vec<FunctionCoverage>> {
+        if let Some(json) = json {
+            let file = File::open(json).context(format!("failed to open {}", json.display()))?;
+            return serde_json::from_reader(file)
+                .context(format!("failed to parse coverage from {}", json.display()));
+        }
+
+        let Some(symbol_report) = symbol_report else {
+            bail!("either --{side}-json or --{side}-report must be given");
+        };
+        let Some(ferrocene) = &self.ferrocene else {
+            bail!("--ferrocene-src is required when using --{side}-report");
+        };
+        if instr_profile.is_empty() {
+            bail!("--{side}-instr-profile is required when using --{side}-report");
+        }
+        load_coverage(
+            instr_profile,
+            objects,
+            self.path_remapping.as_ref(),
+            symbol_report,
+            ferrocene,
+            None,
+        )
+    }
+}
+
+type FunctionPair<'a> = (Option<&'a FunctionCoverage>, Option<&'a FunctionCoverage>);
+
+/// Pair each old function with its new version, by file and name. Functions sharing both are
+/// paired in source order, and any left over on one side are added or removed.
+fn match_functions<'a>(
+    old: &'a [FunctionCoverage],
+    new: &'a [FunctionCoverage],
+) -> Vec<FunctionPair<'a>> {
+    fn group(functions: &[FunctionCoverage]) -> BTreeMap<(&Path, &str), Vec<&FunctionCoverage>> {
+        let mut groups = BTreeMap::<_, Vec<_>>::new();
+        for function in functions {
+            let key = (function.relative_path.as_path(), function.source_name.as_str());
+            groups.entry(key).or_default().push(function);
+        }
+        for functions in groups.values_mut() {
+            functions.sort_by_key(|function| first_line(function));
+        }
+        groups
+    }
+
+    let mut old = group(old);
+    let mut new = group(new);
+    let keys = old.keys().chain(new.keys()).copied().collect::<BTreeSet<_>>();
+    let mut pairs = Vec::new();
+    for key in keys {
+        let mut old = old.remove(&key).unwrap_or_default().into_iter();
+        let mut new = new.remove(&key).unwrap_or_default().into_iter();
+        loop {
+            match (old.next(), new.next()) {
+                (None, None) => break,
+                pair => pairs.push(pair),
+            }
+        }
+    }
+    pairs
+}
+
+#[derive(Debug, PartialEq)]
+enum LineChange<'a> {
+    Added {
+        line: usize,
+        status: &'a LineCoverageStatus,
+    },
+    Removed {
+        line: usize,
+        status: &'a LineCoverageStatus,
+    },
+    Changed {
+        old_line: usize,
+        new_line: usize,
+        old_status: &'a LineCoverageStatus,
+        new_status: &'a LineCoverageStatus,
+    },
+}
+
+impl LineChange<'_> {
+    fn is_regression(&self) -> bool {
+        matches!(
+            self,
+            LineChange::Changed {
+                old_status: LineCoverageStatus::Tested,
+                new_status: LineCoverageStatus::Untested,
+                ..
+            } | LineChange::Removed { status: LineCoverageStatus::Tested, .. }
+        )
+    }
+}
+
+/// The source files of one side of the diff, read on demand.
+struct Sources {
+    root: PathBuf,
+    files: BTreeMap<PathBuf, Vec<String>>,
+}
+
+impl Sources {
+    fn new(root: PathBuf) -> Self {
+        Sources { root, files: BTreeMap::new() }
+    }
+
+    fn lines(&mut self, relative_path: &Path) -> Result<&[String]> {
+        if !self.files.contains_key(relative_path) {
+            let path = self.root.join(relative_path);
+            let contents = fs::read_to_string(&path)
+                .context(format!("failed to read source file {}", path.display()))?;
+            let lines = contents.lines().map(str::to_owned).collect();
+            self.files.insert(relative_path.to_owned(), lines);
+        }
+        Ok(&self.files[relative_path])
+    }
+}
+
+/// Compare the lines of two versions of a function. `old_source` and `new_source` are the lines of
+/// the file each version is in.
+///
+/// Lines are paired with a longest common subsequence of the source lines spanned by the function,
+/// so that inserting or deleting a line doesn't shift every line after it. Lines that are paired
+/// but only instrumented on one side are listed as added or removed.
+fn diff_function<'a>(
+    old: &'a FunctionCoverage,
+    old_source: &[String],
+    new: &'a FunctionCoverage,
+    new_source: &[String],
+) -> Vec<LineChange<'a>> {
+    fn statuses(function: &FunctionCoverage) -> BTreeMap<usize, &LineCoverageStatus> {
+        function.lines.lines.iter().map(|(line, status)| (*line, status)).collect()
+    }
+    fn text(function: &FunctionCoverage, source: &[String]) -> Vec<(usize, String)> {
+        let lines = function.lines.lines.iter().map(|(line, _)| *line);
+        let (Some(first), Some(last)) = (lines.clone().min(), lines.max()) else {
+            return Vec::new();
+        };
+        let text = |line: usize| {
+            line.checked_sub(1).and_then(|index| source.get(index)).map_or("", |text| text.trim())
+        };
+        (first..=last).map(|line| (line, text(line).to_owned())).collect()
+    }
+
+    let (old_statuses, new_statuses) = (statuses(old), statuses(new));
+    let (old_text, new_text) = (text(old, old_source), text(new, new_source));
+
+    let mut changes = Vec::new();
+    let removed = |changes: &mut Vec<_>, line| {
+        if let Some(&status) = old_statuses.get(&line) {
+            changes.push(LineChange::Removed { line, status });
+        }
+    };
+    let added = |changes: &mut Vec<_>, line| {
+        if let Some(&status) = new_statuses.get(&line) {
+            changes.push(LineChange::Added { line, status });
+        }
+    };
+
+    let (mut old_next, mut new_next) = (0, 0);
+    let pairs = common_lines(&old_text, &new_text);
+    for (old_index, new_index) in pairs.into_iter().chain([(old_text.len(), new_text.len())]) {
+        for (line, _) in &old_text[old_next..old_index] {
+            removed(&mut changes, *line);
+        }
+        for (line, _) in &new_text[new_next..new_index] {
+            added(&mut changes, *line);
+        }
+        let (Some((old_line, _)), Some((new_line, _))) =
+            (old_text.get(old_index), new_text.get(new_index))
+        else {
+            break;
+        };
+        match (old_statuses.get(old_line), new_statuses.get(new_line)) {
+            (Some(&old_status), Some(&new_status)) if old_status != new_status => {
+                changes.push(LineChange::Changed {
+                    old_line: *old_line,
+                    new_line: *new_line,
+                    old_status,
+                    new_status,
+                });
+            }
+            (Some(_), None) => removed(&mut changes, *old_line),
+            (None, Some(_)) => added(&mut changes, *new_line),
+            _ => {}
+        }
+        (old_next, new_next) = (old_index + 1, new_index + 1);
+    }
+    changes
+}
+
+/// The indices of the lines in a longest common subsequence of `old` and `new`, compared by text.
+fn common_lines(old: &[(usize, String)], new: &[(usize, String)]) -> Vec<(usize, usize)> {
+    // `lengths[i][j]` is the length of the longest common subsequence of `old[i..]` and `new[j..]`.
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i].1 == new[j].1 {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut pairs = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old[i].1 == new[j].1 {
+            pairs.push((i, j));
+            (i, j) = (i + 1, j + 1);
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+fn first_line(function: &FunctionCoverage) -> Option<usize> {
+    function.lines.lines.iter().map(|(line, _)| *line).min()
+}
+
+fn describe(function: &FunctionCoverage) -> String {
+    match first_line(function) {
+        Some(line) => {
+            format!("{} ({}:{line})", function.source_name, function.relative_path.display())
+        }
+        None => format!("{} ({})", function.source_name, function.relative_path.display()),
+    }
+}
+
+fn tested_summary(function: &FunctionCoverage) -> String {
+    let lines = &function.lines.lines;
+    let tested = lines.iter().filter(|(_, status)| *status == LineCoverageStatus::Tested).count();
+    let instrumented =
+        lines.iter().filter(|(_, status)| *status != LineCoverageStatus::Ignored).count();
+    format!("{tested}/{instrumented} lines tested")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LineCoverage;
+    use crate::LineCoverageStatus::*;
+
+    fn function(name: &str, lines: Vec<(usize, LineCoverageStatus)>) -> FunctionCoverage {
+        FunctionCoverage::new(
+            name.into(),
+            "library/core/src/a.rs".into(),
+            LineCoverage { lines },
+            vec![],
+            vec![],
+        )
+    }
+
+    /// A source file with `lines` starting at line `first`.
+    fn source(first: usize, lines: &[&str]) -> Vec<String> {
+        let padding = std::iter::repeat_n("", first - 1);
+        padding.chain(lines.iter().copied()).map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_diff_function() {
+        let old_source = source(10, &["fn f() {", "    a();", "    b();", "    c();", "}"]);
+        let old = function("f", vec![(10, Tested), (11, Tested), (12, Untested), (13, Tested)]);
+        // Moved down by 5 lines and reindented, with a line inserted in the middle and the last
+        // line removed.
+        let new_source = source(15, &["fn f() {", "  new();", "  a();", "  b();", "}"]);
+        let new =
+            function("f", vec![(15, Tested), (16, Untested), (17, Untested), (18, Annotated)]);
+        let diff = diff_function(&old, &old_source, &new, &new_source);
+        assert_eq!(
+            vec![
+                LineChange::Added { line: 16, status: &Untested },
+                LineChange::Changed {
+                    old_line: 11,
+                    new_line: 17,
+                    old_status: &Tested,
+                    new_status: &Untested,
+                },
+                LineChange::Changed {
+                    old_line: 12,
+                    new_line: 18,
+                    old_status: &Untested,
+                    new_status: &Annotated,
+                },
+                LineChange::Removed { line: 13, status: &Tested },
+            ],
+            diff
+        );
+        let regressions: Vec<_> = diff.iter().map(LineChange::is_regression).collect();
+        assert_eq!(vec![false, true, false, true], regressions);
+
+        let grown_source =
+            source(10, &["fn f() {", "    a();", "    b();", "    c();", "    d();"]);
+        let grown = function(
+            "f",
+            vec![(10, Tested), (11, Tested), (12, Untested), (13, Tested), (14, Untested)],
+        );
+        assert_eq!(
+            vec![LineChange::Added { line: 14, status: &Untested }],
+            diff_function(&old, &old_source, &grown, &grown_source)
+        );
+        assert!(diff_function(&old, &old_source, &old, &old_source).is_empty());
+    }
+
+    #[test]
+    fn test_diff_function_repeated_lines() {
+        // Identical lines are paired in order, rather than all with the first match.
+        let old_source = source(1, &["x();", "x();", "x();"]);
+        let old = function("f", vec![(1, Tested), (2, Tested), (3, Untested)]);
+        let new_source = source(1, &["x();", "y();", "x();", "x();"]);
+        let new = function("f", vec![(1, Tested), (2, Tested), (3, Tested), (4, Untested)]);
+        assert_eq!(
+            vec![LineChange::Added { line: 2, status: &Tested }],
+            diff_function(&old, &old_source, &new, &new_source)
+        );
+    }
+
+    #[test]
+    fn test_match_functions() {
+        let old = vec![
+            function("<Foo as Clone>::clone", vec![(1, Tested)]),
+            function("<Foo as Clone>::clone", vec![(5, Untested)]),
+            function("removed", vec![(9, Tested)]),
+        ];
+        let new = vec![
+            function("added", vec![(1, Tested)]),
+            // Same name twice in the same file: matched in source order, not collapsed.
+            function("<Foo as Clone>::clone", vec![(8, Tested)]),
+            function("<Foo as Clone>::clone", vec![(3, Tested)]),
+        ];
+        let pairs = match_functions(&old, &new)
+            .into_iter()
+            .map(|(old, new)| (old.and_then(first_line), new.and_then(first_line)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![(Some(1), Some(3)), (Some(5), Some(8)), (None, Some(1)), (Some(9), None)],
+            pairs
+        );
+    }
+}
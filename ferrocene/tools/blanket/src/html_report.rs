@@ -3,6 +3,7 @@
 
 use maud::{DOCTYPE, PreEscaped};
 
+use crate::branch_coverage::DecisionCoverage;
 use crate::{Annotated, FunctionCoverage, FunctionCoverageStatus, LineCoverageStatus};
 
 const CSS: &str = include_str!("../assets/html_report.css");
@@ -197,6 +198,10 @@ fn generate_function(
         _ => (),
     }
 
+    let branch_outcomes = function.branches.len() * 2;
+    let branch_outcomes_taken: usize =
+        function.branches.iter().map(|branch| branch.status.outcomes_taken()).sum();
+
     let filename = function.relative_path.display();
     let html = maud::html!(
         details class=(class_set.into_iter().collect::<Vec<_>>().join(" ")) data-status=(function_css_class) {
@@ -209,22 +214,25 @@ fn generate_function(
                 div {
                     "File: " (filename)
                 }
+                @if !function.branches.is_empty() {
+                    div {
+                        (format!("Branch outcomes taken: {branch_outcomes_taken}/{branch_outcomes}"))
+                    }
+                }
                 code {
                     pre {
                         @for (linenum, line, status) in lines {
-                            @match status {
-                                LineCoverageStatus::Tested => span class="line line-tested" data-filename=(filename) data-linenum=(linenum) {
-                                    (line) "\n"
-                                },
-                                LineCoverageStatus::Untested => span class="line line-untested" data-filename=(filename) data-linenum=(linenum) {
-                                    (line) "\n"
-                                },
-                                LineCoverageStatus::Annotated => span class="line line-annotated" data-filename=(filename) data-linenum=(linenum) {
-                                    (line) "\n"
-                                },
-                                LineCoverageStatus::Ignored => span class="line line-ignored" data-filename=(filename) data-linenum=(linenum) {
-                                    (line) "\n"
-                                },
+                            span class=(format!("line {}", line_css_class(status))) data-filename=(filename) data-linenum=(linenum) {
+                                (line)
+                                @for branch in function.branches.iter().filter(|branch| branch.line == *linenum) {
+                                    " " span class=(format!("branch {}", branch.status.to_css_class())) title=(format!("Branch at column {}: {}", branch.column, branch.status.to_human())) {}
+                                }
+                                @for decision in function.decisions.iter().filter(|decision| decision.start_line == *linenum) {
+                                    " " span class=(decision_css_class(decision)) title=(format!("Decision at column {}: {}/{} conditions independently covered", decision.column, decision.covered_conditions(), decision.conditions.len())) {
+                                        (format!("MC/DC {}/{}", decision.covered_conditions(), decision.conditions.len()))
+                                    }
+                                }
+                                "\n"
                             }
                         }
                     }
@@ -234,3 +242,22 @@ fn generate_function(
     );
     Ok(html)
 }
+
+fn line_css_class(status: &LineCoverageStatus) -> &'static str {
+    match status {
+        LineCoverageStatus::Tested => "line-tested",
+        LineCoverageStatus::Untested => "line-untested",
+        LineCoverageStatus::Annotated => "line-annotated",
+        LineCoverageStatus::Ignored => "line-ignored",
+    }
+}
+
+fn decision_css_class(decision: &DecisionCoverage) -> &'static str {
+    if decision.is_covered() {
+        "decision decision-covered"
+    } else if decision.annotated {
+        "decision decision-annotated"
+    } else {
+        "decision decision-uncovered"
+    }
+}
@@ -0,0 +1,152 @@
+//! An [LCOV tracefile](https://manpages.debian.org/unstable/lcov/geninfo.1.en.html#TRACEFILE_FORMAT)
+//! for CI dashboards that don't understand our HTML report.
+//!
+//! LCOV has no notion of annotated or ignored lines, so we keep the distinction as extra records:
+//! - Ignored lines are not instrumented, so they have no `DA` record, but each has an
+//!   `IGNORED:<line>` record.
+//! - Annotated lines have `DA:<line>,0` like any other untested line, followed by an
+//!   `ANNOTATED:<line>` record.
+//! - Each function has a `FNSTATUS:<status>,<annotated>,<name>` record, using the same status
+//!   names as the HTML report.
+//! - Branches (with `--branch-export`) have a `BRDA` record per outcome. Branches starting on the
+//!   same line are numbered as blocks `0`, `1`, ... in source order. Annotated branches are followed
+//!   by a `BRANNOTATED:<line>,<block>` record.
+//!
+//! Consumers that don't know about these records ignore them. Hit counts are 1 for tested lines and
+//! branch outcomes and 0 otherwise; the report only records whether they were tested, not how
+//! often.
+
+use std::fmt::Write as _;
+
+use crate::branch_coverage::BranchCoverageStatus;
+use crate::{FunctionCoverage, LineCoverageStatus};
+
+pub(crate) fn generate(coverage: &[FunctionCoverage]) -> String {
+    let mut out = String::new();
+    for (filename, file) in crate::group_by_file(coverage) {
+        writeln!(out, "TN:").unwrap();
+        writeln!(out, "SF:{}", filename.display()).unwrap();
+
+        let mut functions_hit = 0;
+        for function in &file.functions {
+            let start = function.lines.lines.first().map_or(0, |(line, _)| *line);
+            let hit = function.is_hit();
+            functions_hit += usize::from(hit);
+            writeln!(out, "FN:{start},{}", function.source_name).unwrap();
+            writeln!(out, "FNDA:{},{}", usize::from(hit), function.source_name).unwrap();
+            writeln!(
+                out,
+                "FNSTATUS:{},{},{}",
+                function.status.to_css_class(),
+                function.annotated.as_str(),
+                function.source_name
+            )
+            .unwrap();
+        }
+        writeln!(out, "FNF:{}", file.functions.len()).unwrap();
+        writeln!(out, "FNH:{functions_hit}").unwrap();
+
+        let (mut branches_found, mut branches_hit) = (0, 0);
+        let mut block = 0;
+        let mut previous_line = None;
+        for ((line, ..), branch) in &file.branches {
+            block = if previous_line == Some(line) { block + 1 } else { 0 };
+            previous_line = Some(line);
+
+            let (taken_true, taken_false) = match branch.status {
+                BranchCoverageStatus::Covered => (1, 1),
+                BranchCoverageStatus::TrueOnly => (1, 0),
+                BranchCoverageStatus::FalseOnly => (0, 1),
+                BranchCoverageStatus::Untested | BranchCoverageStatus::Annotated => (0, 0),
+            };
+            writeln!(out, "BRDA:{line},{block},0,{taken_true}").unwrap();
+            writeln!(out, "BRDA:{line},{block},1,{taken_false}").unwrap();
+            if branch.status == BranchCoverageStatus::Annotated {
+                writeln!(out, "BRANNOTATED:{line},{block}").unwrap();
+            }
+            branches_found += 2;
+            branches_hit += taken_true + taken_false;
+        }
+        if branches_found > 0 {
+            writeln!(out, "BRF:{branches_found}").unwrap();
+            writeln!(out, "BRH:{branches_hit}").unwrap();
+        }
+
+        let (mut found, mut hit) = (0, 0);
+        for (line, status) in &file.lines {
+            match status {
+                LineCoverageStatus::Tested => {
+                    writeln!(out, "DA:{line},1").unwrap();
+                    hit += 1;
+                }
+                LineCoverageStatus::Untested => writeln!(out, "DA:{line},0").unwrap(),
+                LineCoverageStatus::Annotated => {
+                    writeln!(out, "DA:{line},0").unwrap();
+                    writeln!(out, "ANNOTATED:{line}").unwrap();
+                }
+                LineCoverageStatus::Ignored => {
+                    writeln!(out, "IGNORED:{line}").unwrap();
+                    continue;
+                }
+            }
+            found += 1;
+        }
+        writeln!(out, "LF:{found}").unwrap();
+        writeln!(out, "LH:{hit}").unwrap();
+        writeln!(out, "end_of_record").unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample_coverage;
+
+    #[test]
+    fn test_generate() {
+        let expected = "\
+TN:
+SF:library/core/src/a.rs
+FN:1,core::a::tested
+FNDA:1,core::a::tested
+FNSTATUS:partially-tested,not-annotated,core::a::tested
+FN:6,core::a::annotated
+FNDA:1,core::a::annotated
+FNSTATUS:partially-tested,fully-annotated,core::a::annotated
+FNF:2
+FNH:2
+BRDA:2,0,0,1
+BRDA:2,0,1,0
+BRDA:2,1,0,1
+BRDA:2,1,1,1
+BRDA:7,0,0,0
+BRDA:7,0,1,0
+BRANNOTATED:7,0
+BRF:6
+BRH:3
+DA:1,1
+DA:2,1
+IGNORED:3
+DA:4,0
+DA:6,1
+DA:7,0
+ANNOTATED:7
+LF:5
+LH:3
+end_of_record
+TN:
+SF:library/core/src/b.rs
+FN:1,<T as core::b::Foo>::untested
+FNDA:0,<T as core::b::Foo>::untested
+FNSTATUS:fully-untested,not-annotated,<T as core::b::Foo>::untested
+FNF:1
+FNH:0
+DA:1,0
+LF:1
+LH:0
+end_of_record
+";
+        assert_eq!(expected, generate(&sample_coverage()));
+    }
+}
@@ -1,17 +1,27 @@
 // Derived from https://github.com/xd009642/llvm-profparser/blob/f12a20d33b371f62a3b63f3a19d2320c25aa48b9/src/bin/cov.rs
 
+use std::collections::BTreeMap;
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[allow(unused_imports)]
 use anyhow::{Context as _, Result};
 use clap::{Parser, Subcommand};
 use llvm_profparser::*;
 use maud::Render;
+use serde::{Deserialize, Serialize};
 use tracing_subscriber::filter::filter_fn;
 use tracing_subscriber::{Layer, Registry};
 
+use crate::branch_coverage::{
+    BranchCoverage, BranchCoverageStatus, BranchReport, DecisionCoverage, Region,
+};
+
+mod branch_coverage;
+mod cobertura_report;
+mod diff;
 mod html_report;
+mod lcov_report;
 mod rustc_driver;
 
 #[derive(Clone, Debug, Eq, PartialEq, Parser)]
@@ -26,6 +36,11 @@ pub enum Command {
         #[command(flatten)]
         show: ShowCommand,
     },
+    /// Compare two coverage runs, e.g. before and after upgrading Ferrocene.
+    Diff {
+        #[command(flatten)]
+        diff: diff::DiffCommand,
+    },
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Parser)]
@@ -48,15 +63,28 @@ pub struct ShowCommand {
     symbol_report: PathBuf,
     #[arg(long = "ferrocene-src", short = 's')]
     ferrocene: PathBuf,
+    /// Branch and MC/DC regions, as written by `llvm-cov export -format=text` for the same profiles
+    /// and objects
+    #[arg(long)]
+    branch_export: Option<PathBuf>,
     /// Turn on debug logging
     #[arg(long)]
     debug: bool,
     /// Produce a HTML report
     #[arg(long)]
     html_out: Option<PathBuf>,
+    /// Produce an LCOV tracefile
+    #[arg(long)]
+    lcov_out: Option<PathBuf>,
+    /// Produce a Cobertura XML report
+    #[arg(long)]
+    cobertura_out: Option<PathBuf>,
+    /// Save the coverage of each function as JSON, for use with `blanket diff`
+    #[arg(long)]
+    json_out: Option<PathBuf>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 enum LineCoverageStatus {
     Tested,
     Untested,
@@ -75,12 +103,12 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 struct LineCoverage {
     lines: Vec<(usize, LineCoverageStatus)>,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 enum FunctionCoverageStatus {
     FullyTested,
     PartiallyTested,
@@ -139,18 +167,33 @@ fn to_human(&self) -> &str {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 enum Annotated {
     Fully,
     Partially,
     Not,
 }
 
-#[derive(Debug)]
+impl Annotated {
+    fn as_str(&self) -> &str {
+        match self {
+            Annotated::Fully => "fully-annotated",
+            Annotated::Partially => "partially-annotated",
+            Annotated::Not => "not-annotated",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 struct FunctionCoverage {
     source_name: String,
     relative_path: PathBuf,
     lines: LineCoverage,
+    /// Empty unless blanket was given `--branch-export`.
+    #[serde(default)]
+    branches: Vec<BranchCoverage>,
+    #[serde(default)]
+    decisions: Vec<DecisionCoverage>,
     status: FunctionCoverageStatus,
     annotated: Annotated,
 }
@@ -160,11 +203,63 @@ fn new(
         source_name: String,
         filename: PathBuf,
         lines: LineCoverage,
-        annotated: Annotated,
+        branches: Vec<BranchCoverage>,
+        decisions: Vec<DecisionCoverage>,
     ) -> Self {
         let status = FunctionCoverageStatus::new(&lines);
-        Self { source_name, relative_path: filename, lines, status, annotated }
+        let annotated = get_annotation_status(&lines, &branches, &decisions);
+        Self { source_name, relative_path: filename, lines, branches, decisions, status, annotated }
+    }
+
+    /// Whether any line of the function was tested.
+    fn is_hit(&self) -> bool {
+        self.lines.lines.iter().any(|(_, status)| *status == LineCoverageStatus::Tested)
+    }
+}
+
+/// The functions in a single source file, for reports that are organized by file.
+struct FileCoverage<'a> {
+    functions: Vec<&'a FunctionCoverage>,
+    /// The status of each line covered by at least one function.
+    lines: BTreeMap<usize, &'a LineCoverageStatus>,
+    /// Each branch, by region.
+    branches: BTreeMap<Region, &'a BranchCoverage>,
+}
+
+/// Group functions by file. If functions overlap (e.g. a closure inside a function), a line or
+/// branch is tested if any function tested it.
+fn group_by_file(coverage: &[FunctionCoverage]) -> BTreeMap<&Path, FileCoverage<'_>> {
+    fn rank(status: &LineCoverageStatus) -> u8 {
+        match status {
+            LineCoverageStatus::Ignored => 0,
+            LineCoverageStatus::Annotated => 1,
+            LineCoverageStatus::Untested => 2,
+            LineCoverageStatus::Tested => 3,
+        }
     }
+
+    let mut files = BTreeMap::<&Path, FileCoverage<'_>>::new();
+    for function in coverage {
+        let file = files.entry(function.relative_path.as_path()).or_insert_with(|| FileCoverage {
+            functions: vec![],
+            lines: BTreeMap::new(),
+            branches: BTreeMap::new(),
+        });
+        file.functions.push(function);
+        for (line, status) in &function.lines.lines {
+            let merged = file.lines.entry(*line).or_insert(status);
+            if rank(status) > rank(merged) {
+                *merged = status;
+            }
+        }
+        for branch in &function.branches {
+            let merged = file.branches.entry(branch.region()).or_insert(branch);
+            if branch.status.outcomes_taken() > merged.status.outcomes_taken() {
+                *merged = branch;
+            }
+        }
+    }
+    files
 }
 
 struct Span {
@@ -180,51 +275,73 @@ struct Annotation {
     unused: bool,
 }
 
-fn get_annotation_status(
-    annotations: Option<&mut Vec<Annotation>>,
-    lines: &mut Vec<(usize, LineCoverageStatus)>,
-) -> Annotated {
-    annotations.map_or(Annotated::Not, |annotations| {
-        let mut untested_count = 0;
-        let mut annotated_count = 0;
-
-        for (line, status) in lines {
-            if *status != LineCoverageStatus::Untested {
-                continue;
-            }
-            untested_count += 1;
-
-            for Annotation { start, end, unused } in annotations.iter_mut() {
-                if (*start..=*end).contains(line) {
-                    *unused = false;
-                    *status = LineCoverageStatus::Annotated;
-                    annotated_count += 1;
-
-                    break;
-                }
-            }
+/// Whether `line` is inside one of `annotations`, marking that annotation as used.
+fn annotate(annotations: Option<&mut Vec<Annotation>>, line: usize) -> bool {
+    let Some(annotations) = annotations else { return false };
+    for Annotation { start, end, unused } in annotations.iter_mut() {
+        if (*start..=*end).contains(&line) {
+            *unused = false;
+            return true;
         }
+    }
+    false
+}
 
-        if annotated_count == 0 {
-            // If no lines are annotated, the function is not annotated.
-            Annotated::Not
-        } else if annotated_count < untested_count {
-            // If there are less annotated lines than untested lines, the function is partially
-            // annotated.
-            Annotated::Partially
-        } else {
-            // Otherwise the function is fully annotated
-            Annotated::Fully
+/// Mark the untested lines inside an annotation as `Annotated`.
+fn annotate_lines(
+    mut annotations: Option<&mut Vec<Annotation>>,
+    lines: &mut Vec<(usize, LineCoverageStatus)>,
+) {
+    for (line, status) in lines {
+        if *status == LineCoverageStatus::Untested && annotate(annotations.as_deref_mut(), *line) {
+            *status = LineCoverageStatus::Annotated;
         }
-    })
+    }
+}
+
+fn get_annotation_status(
+    lines: &LineCoverage,
+    branches: &[BranchCoverage],
+    decisions: &[DecisionCoverage],
+) -> Annotated {
+    // Lines, branches and decisions that would need an annotation, and those that have one.
+    let untested_count = lines
+        .lines
+        .iter()
+        .filter(|(_, status)| {
+            matches!(status, LineCoverageStatus::Untested | LineCoverageStatus::Annotated)
+        })
+        .count()
+        + branches.iter().filter(|branch| branch.status != BranchCoverageStatus::Covered).count()
+        + decisions.iter().filter(|decision| !decision.is_covered()).count();
+    let annotated_count = lines
+        .lines
+        .iter()
+        .filter(|(_, status)| *status == LineCoverageStatus::Annotated)
+        .count()
+        + branches.iter().filter(|branch| branch.status == BranchCoverageStatus::Annotated).count()
+        + decisions.iter().filter(|decision| decision.annotated).count();
+
+    if annotated_count == 0 {
+        // If nothing is annotated, the function is not annotated.
+        Annotated::Not
+    } else if annotated_count < untested_count {
+        // If there are less annotated lines than untested lines, the function is partially
+        // annotated.
+        Annotated::Partially
+    } else {
+        // Otherwise the function is fully annotated
+        Annotated::Fully
+    }
 }
 
 fn get_coverage(
     report: &CoverageReport,
+    branch_report: Option<&BranchReport>,
     span: Span,
     ferrocene: &std::path::Path,
     source_name: String,
-    annotations: Option<&mut Vec<Annotation>>,
+    mut annotations: Option<&mut Vec<Annotation>>,
 ) -> Result<FunctionCoverage> {
     let Span { filename, start_line, end_line } = span;
     let absolute_path = if filename.is_relative() {
@@ -238,6 +355,12 @@ fn get_coverage(
 
     let source_lines = start_line..=end_line;
     let source_name = source_name;
+    let classify_branches = |annotations| match branch_report {
+        Some(branch_report) => {
+            branch_report.classify(&absolute_path, source_lines.clone(), annotations)
+        }
+        None => (vec![], vec![]),
+    };
 
     // we didn't get any hits from the tool, so we don't know which lines shouldn't be
     // considered. report them all as considered and missing coverage.
@@ -251,14 +374,15 @@ fn get_coverage(
         );
 
         // All lines require annotations as we didn't get any hits from the tool.
-        let annotated = get_annotation_status(annotations, &mut no_coverage.lines);
+        annotate_lines(annotations.as_deref_mut(), &mut no_coverage.lines);
+        let (branches, decisions) = classify_branches(annotations);
 
-        return Ok(FunctionCoverage::new(source_name, filename, no_coverage, annotated));
+        return Ok(FunctionCoverage::new(source_name, filename, no_coverage, branches, decisions));
     };
 
     let mut covered = vec![];
 
-    for line in source_lines {
+    for line in source_lines.clone() {
         // one more thing to do: within a function, some lines will always be uncovered (e.g. }
         // closing braces). so we do have to trust the coverage tool to report those accurately.
         let status = match func_coverage.hits_for_line(line) {
@@ -269,9 +393,11 @@ fn get_coverage(
         covered.push((line, status));
     }
 
-    let annotated = get_annotation_status(annotations, &mut covered);
+    annotate_lines(annotations.as_deref_mut(), &mut covered);
+    let (branches, decisions) = classify_branches(annotations);
 
-    Ok(FunctionCoverage::new(source_name, filename, LineCoverage { lines: covered }, annotated))
+    let lines = LineCoverage { lines: covered };
+    Ok(FunctionCoverage::new(source_name, filename, lines, branches, decisions))
 }
 
 impl ShowCommand {
@@ -279,22 +405,14 @@ fn run(&self) -> Result<()> {
         if self.debug {
             let _ = enable_debug_logging();
         }
-        let instr_prof = if self.instr_profile.len() == 1 {
-            parse(&self.instr_profile[0])?
-        } else if self.instr_profile.len() > 1 {
-            merge_profiles(&self.instr_profile)?
-        } else {
-            panic!("Must provide an instrumentation profile");
-        };
-        let mapping = CoverageMapping::new(&self.objects, &instr_prof, false)?;
-        let mut report = mapping.generate_report()?;
-        if let Some(remapping) = self.path_remapping.as_ref() {
-            report.apply_remapping(remapping);
-        }
-
-        let mut coverage = rustc_driver::coverage(self, &report)?;
-        coverage.sort_by(|f1, f2| f1.source_name.cmp(&f2.source_name));
-        let coverage = coverage;
+        let coverage = load_coverage(
+            &self.instr_profile,
+            &self.objects,
+            self.path_remapping.as_ref(),
+            &self.symbol_report,
+            &self.ferrocene,
+            self.branch_export.as_deref(),
+        )?;
 
         let total = coverage.len();
         let mut count_fully_tested = 0;
@@ -321,23 +439,87 @@ fn run(&self) -> Result<()> {
             Total: {total}\n\
             ---"
         );
+        if self.branch_export.is_some() {
+            let branches = coverage.iter().flat_map(|function| &function.branches);
+            let (taken, outcomes) = branches.fold((0, 0), |(taken, outcomes), branch| {
+                (taken + branch.status.outcomes_taken(), outcomes + 2)
+            });
+            let decisions = coverage.iter().flat_map(|function| &function.decisions);
+            let (covered, conditions) =
+                decisions.fold((0, 0), |(covered, conditions), decision| {
+                    (
+                        covered + decision.covered_conditions(),
+                        conditions + decision.conditions.len(),
+                    )
+                });
+            println!(
+                "Branch outcomes taken: {taken}/{outcomes}\n\
+                MC/DC conditions covered: {covered}/{conditions}\n\
+                ---"
+            );
+        }
 
         if let Some(ref html_out) = self.html_out {
-            let out = html_out.display();
             let html = html_report::generate(&coverage, &self.ferrocene)
                 .context("failed to generate HTML report")?;
-            let parent = html_out.parent().unwrap();
-            std::fs::create_dir_all(parent)
-                .context(format!("Failed to create {}", parent.display()))?;
-            std::fs::write(html_out, html.render().into_string())
-                .context(format!("failed to write HTML report to {out}"))?;
-            println!("Generated coverage report at {out}");
+            write_report(html_out, "HTML report", &html.render().into_string())?;
+        }
+        if let Some(ref lcov_out) = self.lcov_out {
+            write_report(lcov_out, "LCOV tracefile", &lcov_report::generate(&coverage))?;
+        }
+        if let Some(ref cobertura_out) = self.cobertura_out {
+            let xml = cobertura_report::generate(&coverage, &self.ferrocene);
+            write_report(cobertura_out, "Cobertura report", &xml)?;
+        }
+        if let Some(ref json_out) = self.json_out {
+            let json = serde_json::to_string(&coverage).context("failed to serialize coverage")?;
+            write_report(json_out, "JSON coverage", &json)?;
         }
 
         Ok(())
     }
 }
 
+/// Compute the coverage of every function in the symbol report, from LLVM profiles. The functions
+/// are sorted by name.
+fn load_coverage(
+    instr_profile: &[PathBuf],
+    objects: &[PathBuf],
+    path_remapping: Option<&PathRemapping>,
+    symbol_report: &Path,
+    ferrocene: &Path,
+    branch_export: Option<&Path>,
+) -> Result<Vec<FunctionCoverage>> {
+    let instr_prof = if instr_profile.len() == 1 {
+        parse(&instr_profile[0])?
+    } else if instr_profile.len() > 1 {
+        merge_profiles(instr_profile)?
+    } else {
+        panic!("Must provide an instrumentation profile");
+    };
+    let mapping = CoverageMapping::new(objects, &instr_prof, false)?;
+    let mut report = mapping.generate_report()?;
+    if let Some(remapping) = path_remapping {
+        report.apply_remapping(remapping);
+    }
+
+    let branch_report = branch_export.map(BranchReport::load).transpose()?;
+
+    let mut coverage =
+        rustc_driver::coverage(symbol_report, ferrocene, &report, branch_report.as_ref())?;
+    coverage.sort_by(|f1, f2| f1.source_name.cmp(&f2.source_name));
+    Ok(coverage)
+}
+
+fn write_report(path: &Path, kind: &str, contents: &str) -> Result<()> {
+    let out = path.display();
+    let parent = path.parent().unwrap();
+    std::fs::create_dir_all(parent).context(format!("Failed to create {}", parent.display()))?;
+    std::fs::write(path, contents).context(format!("failed to write {kind} to {out}"))?;
+    println!("Generated {kind} at {out}");
+    Ok(())
+}
+
 fn enable_debug_logging() -> anyhow::Result<()> {
     let fmt = tracing_subscriber::fmt::Layer::default();
     let subscriber = fmt
@@ -351,5 +533,78 @@ fn main() -> Result<()> {
     let opts = Opts::parse();
     match opts.cmd {
         Command::Show { show } => show.run(),
+        Command::Diff { diff } => diff.run(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two files: the first with tested, untested, annotated and ignored lines and branches, the
+    /// second with a single untested function whose name needs escaping in XML.
+    pub(crate) fn sample_coverage() -> Vec<FunctionCoverage> {
+        use LineCoverageStatus::*;
+
+        let a = PathBuf::from("library/core/src/a.rs");
+        let b = PathBuf::from("library/core/src/b.rs");
+        let branch = |line, column, end_column, status| BranchCoverage {
+            line,
+            column,
+            end_line: line,
+            end_column,
+            status,
+        };
+        vec![
+            FunctionCoverage::new(
+                "core::a::tested".into(),
+                a.clone(),
+                LineCoverage { lines: vec![(1, Tested), (2, Tested), (3, Ignored), (4, Untested)] },
+                vec![
+                    branch(2, 8, 9, BranchCoverageStatus::TrueOnly),
+                    branch(2, 8, 14, BranchCoverageStatus::Covered),
+                ],
+                vec![],
+            ),
+            FunctionCoverage::new(
+                "core::a::annotated".into(),
+                a,
+                LineCoverage { lines: vec![(6, Tested), (7, Annotated)] },
+                vec![branch(7, 12, 20, BranchCoverageStatus::Annotated)],
+                vec![],
+            ),
+            FunctionCoverage::new(
+                "<T as core::b::Foo>::untested".into(),
+                b,
+                LineCoverage { lines: vec![(1, Untested)] },
+                vec![],
+                vec![],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_function_status() {
+        let coverage = sample_coverage();
+        assert_eq!(FunctionCoverageStatus::PartiallyTested, coverage[0].status);
+        assert_eq!(Annotated::Not, coverage[0].annotated);
+        assert_eq!(FunctionCoverageStatus::PartiallyTested, coverage[1].status);
+        assert_eq!(Annotated::Fully, coverage[1].annotated);
+        assert_eq!(FunctionCoverageStatus::FullyUntested, coverage[2].status);
+    }
+
+    #[test]
+    fn test_group_by_file() {
+        let coverage = sample_coverage();
+        let files = group_by_file(&coverage);
+        assert_eq!(2, files.len());
+        let a = &files[Path::new("library/core/src/a.rs")];
+        assert_eq!(2, a.functions.len());
+        assert_eq!(vec![1, 2, 3, 4, 6, 7], a.lines.keys().copied().collect::<Vec<_>>());
+        // Both branches starting at 2:8 are kept.
+        assert_eq!(
+            vec![(2, 8, 2, 9), (2, 8, 2, 14), (7, 12, 7, 20)],
+            a.branches.keys().copied().collect::<Vec<_>>()
+        );
     }
 }
@@ -1,16 +1,23 @@
 use std::collections::BTreeMap;
 use std::fs::File;
+use std::path::Path;
 
 use anyhow::{Context as _, Result, bail};
 use build_helper::symbol_report::{Function, SymbolReport};
 use llvm_profparser::CoverageReport;
 
-use crate::{Annotation, FunctionCoverage, ShowCommand, Span};
+use crate::branch_coverage::BranchReport;
+use crate::{Annotation, FunctionCoverage, Span};
 
-pub fn coverage(cmd: &ShowCommand, report: &CoverageReport) -> Result<Vec<FunctionCoverage>> {
+pub fn coverage(
+    symbol_report: &Path,
+    ferrocene: &Path,
+    report: &CoverageReport,
+    branch_report: Option<&BranchReport>,
+) -> Result<Vec<FunctionCoverage>> {
     let SymbolReport { symbols, annotations }: SymbolReport = serde_json::from_reader(
-        File::open(&cmd.symbol_report)
-            .context(format!("failed to open symbol file {}", cmd.symbol_report.display()))?,
+        File::open(symbol_report)
+            .context(format!("failed to open symbol file {}", symbol_report.display()))?,
     )?;
     let mut coverage = vec![];
 
@@ -26,13 +33,17 @@ pub fn coverage(cmd: &ShowCommand, report: &CoverageReport) -> Result<Vec<Functi
             )
         })
         .collect::<BTreeMap<_, _>>();
-    for Function { qualified_name, filename, start_line, end_line } in symbols {
+    // Only the certified subset, and the entry points checked with it, need to be covered.
+    for Function { qualified_name, filename, start_line, end_line, .. } in
+        symbols.into_iter().filter(|f| f.status.is_checked())
+    {
         let annotations = annotations.get_mut(&filename);
         let span = Span { filename: filename.into(), start_line, end_line };
         coverage.push(super::get_coverage(
             report,
+            branch_report,
             span,
-            &cmd.ferrocene,
+            ferrocene,
             qualified_name,
             annotations,
         )?);
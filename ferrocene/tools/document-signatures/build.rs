@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Embed the roles signing the qualification documents, and the identity they sign with, from
+//! the `signature/config.toml` of the documents in the repository. `verify-document` trusts them
+//! instead of the configuration shipped alongside the signatures, which could be altered.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+fn main() {
+    let docs = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../doc");
+    let mut documents = std::fs::read_dir(&docs)
+        .and_then(|entries| entries.map(|entry| Ok(entry?.path())).collect::<Result<Vec<_>, _>>())
+        .unwrap_or_else(|err| panic!("failed to list {}: {err}", docs.display()));
+    documents.sort();
+
+    let mut roles = BTreeMap::new();
+    for document in documents {
+        let config = document.join("signature").join("config.toml");
+        if !config.is_file() {
+            continue;
+        }
+        println!("cargo:rerun-if-changed={}", config.display());
+
+        let contents = std::fs::read_to_string(&config)
+            .unwrap_or_else(|err| panic!("failed to read {}: {err}", config.display()));
+        let parsed: toml::Value = toml::from_str(&contents)
+            .unwrap_or_else(|err| panic!("failed to parse {}: {err}", config.display()));
+        let Some(document_roles) = parsed.get("roles").and_then(|roles| roles.as_table()) else {
+            panic!("missing roles in {}", config.display());
+        };
+
+        for (name, role) in document_roles {
+            let Some(email) = role.get("email").and_then(|email| email.as_str()) else {
+                panic!("missing email of role {name} in {}", config.display());
+            };
+            match roles.insert(name.clone(), email.to_string()) {
+                Some(other) if other != email => {
+                    panic!("role {name} is assigned to both {other} and {email}")
+                }
+                _ => {}
+            }
+        }
+    }
+    if roles.is_empty() {
+        panic!("no signature/config.toml found in {}", docs.display());
+    }
+
+    let mut generated = String::from("&[\n");
+    for (name, email) in &roles {
+        generated.push_str(&format!("    ({name:?}, {email:?}),\n"));
+    }
+    generated.push_str("]\n");
+
+    let out = Path::new(&std::env::var("OUT_DIR").unwrap()).join("trusted_roles.rs");
+    std::fs::write(out, generated).expect("failed to write the trusted roles");
+}
@@ -5,6 +5,7 @@
 use std::path::Path;
 
 use anyhow::Error;
+use document_signatures::Identity;
 
 static IDPS: &[IdP] = &[IdP {
     display_name: "Microsoft",
@@ -49,6 +50,10 @@ pub(crate) fn idp(&self) -> Result<&'static IdP, Error> {
         }
         anyhow::bail!("unsupported email domain: {domain}");
     }
+
+    pub(crate) fn identity(&self) -> Result<Identity<'_>, Error> {
+        Ok(Identity { email: &self.email, oidc_issuer: self.idp()?.url })
+    }
 }
 
 pub(crate) struct IdP {
@@ -1,43 +1,115 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
+//! Parsing of the signature bundles emitted by cosign, either in the legacy format of
+//! `cosign sign-blob --bundle` or in the Sigstore bundle format of `--new-bundle-format`. Only the
+//! latter contains the inclusion proof of the transparency log.
+
 use std::io::{BufReader, Cursor};
 use std::path::Path;
 
-use anyhow::Error;
+use anyhow::{Context, Error};
 use asn1_rs::oid;
 use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use x509_parser::certificate::X509Certificate;
 use x509_parser::extensions::{GeneralName, ParsedExtension};
 use x509_parser::pem::Pem;
 
-pub(crate) struct RawCosignBundle {
+pub struct RawCosignBundle {
     pem: Pem,
+    pub(crate) signature: Vec<u8>,
+    pub(crate) rekor_bundle: Option<RekorBundle>,
+    pub(crate) inclusion_proof: Option<InclusionProof>,
 }
 
 impl RawCosignBundle {
-    pub(crate) fn load(path: &Path) -> Result<Self, Error> {
-        let contents = std::fs::read(path)?;
-        let bundle: OnDiskBundle = serde_json::from_slice(&contents)?;
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        Self::from_slice(&std::fs::read(path)?)
+    }
+
+    pub fn from_slice(contents: &[u8]) -> Result<Self, Error> {
+        let media_type: MediaType = serde_json::from_slice(contents)?;
+        match media_type.media_type {
+            Some(media_type) if media_type.starts_with(SIGSTORE_BUNDLE_MEDIA_TYPE) => {
+                Self::from_sigstore_bundle(serde_json::from_slice(contents)?)
+            }
+            Some(media_type) => anyhow::bail!("unsupported bundle media type: {media_type}"),
+            None => Self::from_legacy_bundle(serde_json::from_slice(contents)?),
+        }
+    }
 
-        let raw_pem = BufReader::new(Cursor::new(
-            base64::engine::general_purpose::STANDARD.decode(&bundle.cert)?,
-        ));
+    fn from_legacy_bundle(bundle: OnDiskBundle) -> Result<Self, Error> {
+        let raw_pem = BufReader::new(Cursor::new(BASE64.decode(&bundle.cert)?));
         let pem = Pem::read(raw_pem)?.0;
-        Ok(Self { pem })
+        let signature = BASE64.decode(&bundle.base64_signature)?;
+        Ok(Self { pem, signature, rekor_bundle: bundle.rekor_bundle, inclusion_proof: None })
+    }
+
+    fn from_sigstore_bundle(bundle: SigstoreBundle) -> Result<Self, Error> {
+        let material = bundle.verification_material;
+        // Version 0.3 of the format contains only the signing certificate, while previous
+        // versions contain the whole chain starting from the signing certificate.
+        let certificate = match (material.certificate, material.x509_certificate_chain) {
+            (Some(certificate), _) => certificate,
+            (None, Some(chain)) => chain.certificates.into_iter().next().context("empty chain")?,
+            (None, None) => anyhow::bail!("the bundle doesn't contain the signing certificate"),
+        };
+        let pem =
+            Pem { label: "CERTIFICATE".into(), contents: BASE64.decode(certificate.raw_bytes)? };
+        let signature = BASE64.decode(&bundle.message_signature.signature)?;
+
+        let Some(entry) = material.tlog_entries.into_iter().next() else {
+            return Ok(Self { pem, signature, rekor_bundle: None, inclusion_proof: None });
+        };
+        // The signed entry timestamp signs the same payload as in the legacy format.
+        let rekor_bundle = match entry.inclusion_promise {
+            Some(promise) => Some(RekorBundle {
+                signed_entry_timestamp: promise.signed_entry_timestamp,
+                payload: RekorPayload {
+                    body: entry.canonicalized_body,
+                    integrated_time: entry.integrated_time.parse()?,
+                    log_id: hex::encode(BASE64.decode(&entry.log_id.key_id)?),
+                    log_index: entry.log_index.parse()?,
+                },
+            }),
+            None => None,
+        };
+        let inclusion_proof = match entry.inclusion_proof {
+            Some(proof) => Some(InclusionProof {
+                log_index: proof.log_index.parse()?,
+                tree_size: proof.tree_size.parse()?,
+                root_hash: BASE64.decode(&proof.root_hash)?,
+                hashes: proof.hashes.iter().map(|h| BASE64.decode(h)).collect::<Result<_, _>>()?,
+                checkpoint: proof.checkpoint.envelope,
+            }),
+            None => None,
+        };
+        Ok(Self { pem, signature, rekor_bundle, inclusion_proof })
     }
 
-    pub(crate) fn parse(&self) -> Result<CosignBundle<'_>, Error> {
+    pub fn parse(&self) -> Result<CosignBundle<'_>, Error> {
         Ok(CosignBundle { certificate: self.pem.parse_x509()? })
     }
+
+    /// Whether the bundle contains the inclusion proof of the transparency log, which is required
+    /// to verify it offline.
+    pub fn has_inclusion_proof(&self) -> bool {
+        self.inclusion_proof.is_some()
+    }
+
+    /// DER encoding of the signing certificate.
+    pub(crate) fn certificate_der(&self) -> &[u8] {
+        &self.pem.contents
+    }
 }
 
-pub(crate) struct CosignBundle<'raw> {
-    certificate: X509Certificate<'raw>,
+pub struct CosignBundle<'raw> {
+    pub(crate) certificate: X509Certificate<'raw>,
 }
 
 impl CosignBundle<'_> {
-    pub(crate) fn email(&self) -> Result<String, Error> {
+    pub fn email(&self) -> Result<String, Error> {
         let ext = self
             .certificate
             .get_extension_unique(&oid!(2.5.29.17))?
@@ -53,16 +125,126 @@ pub(crate) fn email(&self) -> Result<String, Error> {
         Ok(email.to_string())
     }
 
-    pub(crate) fn idp(&self) -> Result<String, Error> {
+    pub fn idp(&self) -> Result<String, Error> {
         let ext = self
             .certificate
             .get_extension_unique(&oid!(1.3.6.1.4.1.57264.1.1))?
             .ok_or_else(|| anyhow::anyhow!("missing fulcio idp"))?;
-        Ok(std::str::from_utf8(&ext.value)?.to_string())
+        Ok(std::str::from_utf8(ext.value)?.to_string())
     }
 }
 
+const SIGSTORE_BUNDLE_MEDIA_TYPE: &str = "application/vnd.dev.sigstore.bundle";
+
 #[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MediaType {
+    media_type: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub(crate) struct OnDiskBundle {
+    base64_signature: String,
     cert: String,
+    rekor_bundle: Option<RekorBundle>,
+}
+
+/// Proof that the signature was uploaded to the Rekor transparency log.
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct RekorBundle {
+    pub(crate) signed_entry_timestamp: String,
+    pub(crate) payload: RekorPayload,
+}
+
+/// Rekor log entry, signed by the log in the signed entry timestamp. The fields are in the order
+/// of their canonical JSON serialization, which is what the timestamp signs.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RekorPayload {
+    pub(crate) body: String,
+    pub(crate) integrated_time: i64,
+    #[serde(rename = "logID")]
+    pub(crate) log_id: String,
+    pub(crate) log_index: i64,
+}
+
+/// Proof that the entry is included in the transparency log, as of a checkpoint signed by it.
+pub(crate) struct InclusionProof {
+    pub(crate) log_index: u64,
+    pub(crate) tree_size: u64,
+    pub(crate) root_hash: Vec<u8>,
+    pub(crate) hashes: Vec<Vec<u8>>,
+    pub(crate) checkpoint: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SigstoreBundle {
+    verification_material: VerificationMaterial,
+    message_signature: MessageSignature,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VerificationMaterial {
+    certificate: Option<RawBytes>,
+    x509_certificate_chain: Option<CertificateChain>,
+    #[serde(default)]
+    tlog_entries: Vec<TlogEntry>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawBytes {
+    raw_bytes: String,
+}
+
+#[derive(serde::Deserialize)]
+struct CertificateChain {
+    certificates: Vec<RawBytes>,
+}
+
+#[derive(serde::Deserialize)]
+struct MessageSignature {
+    signature: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TlogEntry {
+    log_index: String,
+    log_id: LogId,
+    integrated_time: String,
+    inclusion_promise: Option<InclusionPromise>,
+    inclusion_proof: Option<RawInclusionProof>,
+    canonicalized_body: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LogId {
+    key_id: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InclusionPromise {
+    signed_entry_timestamp: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawInclusionProof {
+    log_index: String,
+    root_hash: String,
+    tree_size: String,
+    hashes: Vec<String>,
+    checkpoint: Checkpoint,
+}
+
+#[derive(serde::Deserialize)]
+struct Checkpoint {
+    envelope: String,
 }
@@ -0,0 +1,360 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Verification of cosign bundles without cosign and without network access, equivalent to:
+//!
+//! ```text
+//! cosign verify-blob $blob --bundle $bundle \
+//!     --certificate-identity $email --certificate-oidc-issuer $idp
+//! ```
+//!
+//! The checks performed are:
+//!
+//! * The signing certificate was issued by Fulcio, whose root is embedded in the binary, and was
+//!   valid when the signature was uploaded to the Rekor transparency log.
+//! * The signing certificate was issued to the expected email, verified by the expected identity
+//!   provider.
+//! * The signature of the blob was made with the key of the signing certificate.
+//! * Rekor, whose public key is embedded in the binary, signed a promise to include the signature
+//!   (and its certificate) in the transparency log at the recorded time.
+//! * The signature is included in the transparency log, according to the inclusion proof and the
+//!   checkpoint signed by Rekor. Bundles without an inclusion proof are rejected.
+
+use anyhow::{Context, Error, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ring::signature::{ECDSA_P256_SHA256_ASN1, UnparsedPublicKey};
+use sha2::{Digest, Sha256};
+use x509_parser::certificate::X509Certificate;
+use x509_parser::pem::{Pem, parse_x509_pem};
+use x509_parser::prelude::FromDer;
+use x509_parser::time::ASN1Time;
+use x509_parser::x509::SubjectPublicKeyInfo;
+
+use crate::cosign_bundle::{RawCosignBundle, RekorBundle};
+use crate::transparency_log::verify_inclusion_proof;
+use crate::trusted_root::TrustedRoot;
+
+/// Identity the blob must have been signed by.
+#[derive(Debug, Clone, Copy)]
+pub struct Identity<'a> {
+    /// Email address the signing certificate was issued to.
+    pub email: &'a str,
+    /// URL of the identity provider that verified the email address.
+    pub oidc_issuer: &'a str,
+}
+
+pub fn verify_blob(
+    bundle: &RawCosignBundle,
+    blob: &[u8],
+    identity: &Identity<'_>,
+    root: &TrustedRoot,
+) -> Result<(), Error> {
+    let rekor_bundle = bundle
+        .rekor_bundle
+        .as_ref()
+        .ok_or_else(|| anyhow!("the bundle doesn't contain the rekor transparency log entry"))?;
+    let inclusion_proof = bundle.inclusion_proof.as_ref().ok_or_else(|| {
+        anyhow!("the bundle doesn't contain the inclusion proof of the transparency log")
+    })?;
+
+    let rekor_pem = load_pem(root.rekor_public_key)?;
+    let (_, rekor_key) = SubjectPublicKeyInfo::from_der(&rekor_pem.contents)?;
+    let rekor_key = &rekor_key.subject_public_key.data;
+    let log_id = Sha256::digest(&rekor_pem.contents);
+
+    let integrated_time = verify_rekor_bundle(rekor_bundle, &log_id, rekor_key)?;
+    let body = BASE64.decode(&rekor_bundle.payload.body)?;
+    verify_inclusion_proof(inclusion_proof, &body, &log_id, rekor_key)?;
+
+    let parsed = bundle.parse()?;
+    verify_certificate(&parsed.certificate, integrated_time, root)?;
+
+    let email = parsed.email()?;
+    if email != identity.email {
+        bail!("signed by {email} rather than {}", identity.email);
+    }
+    let idp = parsed.idp()?;
+    if idp != identity.oidc_issuer {
+        bail!("identity verified by {idp} rather than {}", identity.oidc_issuer);
+    }
+
+    let signing_key = &parsed.certificate.public_key().subject_public_key.data;
+    UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, signing_key)
+        .verify(blob, &bundle.signature)
+        .map_err(|_| anyhow!("the signature doesn't match the signed file"))?;
+
+    verify_rekor_entry(&body, bundle, blob)?;
+
+    Ok(())
+}
+
+/// Verify the chain of the signing certificate up to the embedded Fulcio root, at the time the
+/// signature was recorded in the transparency log. Fulcio certificates are only valid for a few
+/// minutes, so the signature must have been made within that window.
+fn verify_certificate(
+    certificate: &X509Certificate<'_>,
+    time: ASN1Time,
+    trusted_root: &TrustedRoot,
+) -> Result<(), Error> {
+    let root_pem = load_pem(trusted_root.fulcio_root)?;
+    let root = root_pem.parse_x509()?;
+    let intermediate_pem = load_pem(trusted_root.fulcio_intermediate)?;
+    let intermediate = intermediate_pem.parse_x509()?;
+
+    intermediate
+        .verify_signature(Some(root.public_key()))
+        .context("the embedded fulcio intermediate certificate is not signed by the root")?;
+    certificate
+        .verify_signature(Some(intermediate.public_key()))
+        .context("the signing certificate was not issued by fulcio")?;
+
+    for (name, cert) in [("root", &root), ("intermediate", &intermediate), ("signing", certificate)]
+    {
+        if !cert.validity().is_valid_at(time) {
+            bail!("the {name} certificate was not valid when the signature was made");
+        }
+    }
+
+    let code_signing = certificate.extended_key_usage()?.is_some_and(|eku| eku.value.code_signing);
+    if !code_signing {
+        bail!("the signing certificate is not valid for code signing");
+    }
+
+    Ok(())
+}
+
+/// Verify the signed entry timestamp of the bundle, returning the time the entry was recorded.
+fn verify_rekor_bundle(
+    rekor_bundle: &RekorBundle,
+    log_id: &[u8],
+    rekor_key: &[u8],
+) -> Result<ASN1Time, Error> {
+    let payload = &rekor_bundle.payload;
+    if payload.log_id != hex::encode(log_id) {
+        bail!("the signature was recorded in an unknown transparency log ({})", payload.log_id);
+    }
+
+    // The timestamp signs the canonical JSON serialization of the payload, with sorted keys.
+    let canonical = serde_json::to_vec(payload)?;
+    let timestamp = BASE64.decode(&rekor_bundle.signed_entry_timestamp)?;
+    UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, rekor_key)
+        .verify(&canonical, &timestamp)
+        .map_err(|_| anyhow!("invalid signed entry timestamp of the transparency log"))?;
+
+    Ok(ASN1Time::from_timestamp(payload.integrated_time)?)
+}
+
+/// Verify that the transparency log entry (signed by Rekor) is about this signature.
+fn verify_rekor_entry(body: &[u8], bundle: &RawCosignBundle, blob: &[u8]) -> Result<(), Error> {
+    let entry: RekorEntry =
+        serde_json::from_slice(body).context("failed to parse the transparency log entry")?;
+    if entry.kind != "hashedrekord" {
+        bail!("unsupported kind of transparency log entry: {}", entry.kind);
+    }
+
+    let hash = &entry.spec.data.hash;
+    if hash.algorithm != "sha256" || hash.value != hex::encode(Sha256::digest(blob)) {
+        bail!("the transparency log entry is about a different file");
+    }
+
+    let signature = &entry.spec.signature;
+    if BASE64.decode(&signature.content)? != bundle.signature {
+        bail!("the transparency log entry is about a different signature");
+    }
+    let certificate = BASE64.decode(&signature.public_key.content)?;
+    let (_, certificate) = parse_x509_pem(&certificate)?;
+    if certificate.contents != bundle.certificate_der() {
+        bail!("the transparency log entry is about a different signing certificate");
+    }
+
+    Ok(())
+}
+
+fn load_pem(contents: &str) -> Result<Pem, Error> {
+    Ok(parse_x509_pem(contents.trim().as_bytes())?.1)
+}
+
+#[derive(serde::Deserialize)]
+struct RekorEntry {
+    kind: String,
+    spec: RekorEntrySpec,
+}
+
+#[derive(serde::Deserialize)]
+struct RekorEntrySpec {
+    data: RekorEntryData,
+    signature: RekorEntrySignature,
+}
+
+#[derive(serde::Deserialize)]
+struct RekorEntryData {
+    hash: RekorEntryHash,
+}
+
+#[derive(serde::Deserialize)]
+struct RekorEntryHash {
+    algorithm: String,
+    value: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RekorEntrySignature {
+    content: String,
+    public_key: RekorEntryPublicKey,
+}
+
+#[derive(serde::Deserialize)]
+struct RekorEntryPublicKey {
+    content: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::trusted_root::SIGSTORE;
+
+    // Generated by test_data/generate.py, signed by a test instance of Fulcio and Rekor.
+    const TEST_ROOT: TrustedRoot = TrustedRoot {
+        fulcio_root: include_str!("../test_data/fulcio-root.pem"),
+        fulcio_intermediate: include_str!("../test_data/fulcio-intermediate.pem"),
+        rekor_public_key: include_str!("../test_data/rekor.pub"),
+    };
+    const BLOB: &[u8] = include_bytes!("../test_data/blob.txt");
+    const BUNDLE: &str = include_str!("../test_data/bundle.json");
+    const LEGACY_BUNDLE: &str = include_str!("../test_data/legacy-bundle.json");
+
+    const IDENTITY: Identity<'static> = Identity {
+        email: "signer@ferrous-systems.com",
+        oidc_issuer: "https://login.microsoftonline.com",
+    };
+
+    fn verify(bundle: &str, identity: &Identity<'_>, root: &TrustedRoot) -> Result<(), Error> {
+        verify_blob(&RawCosignBundle::from_slice(bundle.as_bytes())?, BLOB, identity, root)
+    }
+
+    /// Verify the bundle after applying `tamper` to it, returning the error message.
+    fn verify_tampered(tamper: impl FnOnce(&mut Value)) -> String {
+        let mut bundle: Value = serde_json::from_str(BUNDLE).unwrap();
+        tamper(&mut bundle);
+        match verify(&bundle.to_string(), &IDENTITY, &TEST_ROOT) {
+            Ok(()) => panic!("the tampered bundle was verified"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    fn tlog_entry(bundle: &mut Value) -> &mut Value {
+        &mut bundle["verificationMaterial"]["tlogEntries"][0]
+    }
+
+    /// Flip the last bit of a base64 field, keeping it valid base64.
+    fn flip(value: &mut Value) {
+        let mut decoded = BASE64.decode(value.as_str().unwrap()).unwrap();
+        *decoded.last_mut().unwrap() ^= 1;
+        *value = Value::String(BASE64.encode(decoded));
+    }
+
+    #[test]
+    fn test_valid_bundle() {
+        verify(BUNDLE, &IDENTITY, &TEST_ROOT).unwrap();
+    }
+
+    #[test]
+    fn test_tampered_blob() {
+        let bundle = RawCosignBundle::from_slice(BUNDLE.as_bytes()).unwrap();
+        let err = verify_blob(&bundle, b"tampered", &IDENTITY, &TEST_ROOT).unwrap_err();
+        assert_eq!("the signature doesn't match the signed file", err.to_string());
+    }
+
+    #[test]
+    fn test_tampered_signature() {
+        let err = verify_tampered(|bundle| flip(&mut bundle["messageSignature"]["signature"]));
+        assert_eq!("the signature doesn't match the signed file", err);
+    }
+
+    #[test]
+    fn test_tampered_signed_entry_timestamp() {
+        let err = verify_tampered(|bundle| {
+            flip(&mut tlog_entry(bundle)["inclusionPromise"]["signedEntryTimestamp"])
+        });
+        assert_eq!("invalid signed entry timestamp of the transparency log", err);
+
+        let err = verify_tampered(|bundle| tlog_entry(bundle)["integratedTime"] = "1".into());
+        assert_eq!("invalid signed entry timestamp of the transparency log", err);
+    }
+
+    #[test]
+    fn test_wrong_identity() {
+        let identity = Identity { email: "someone-else@ferrous-systems.com", ..IDENTITY };
+        let err = verify(BUNDLE, &identity, &TEST_ROOT).unwrap_err();
+        assert_eq!(
+            "signed by signer@ferrous-systems.com rather than someone-else@ferrous-systems.com",
+            err.to_string()
+        );
+
+        let identity = Identity { oidc_issuer: "https://accounts.google.com", ..IDENTITY };
+        let err = verify(BUNDLE, &identity, &TEST_ROOT).unwrap_err();
+        assert_eq!(
+            "identity verified by https://login.microsoftonline.com rather than \
+             https://accounts.google.com",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_bad_certificate_chain() {
+        let root = TrustedRoot { rekor_public_key: TEST_ROOT.rekor_public_key, ..SIGSTORE };
+        let err = verify(BUNDLE, &IDENTITY, &root).unwrap_err();
+        assert_eq!("the signing certificate was not issued by fulcio", err.to_string());
+    }
+
+    #[test]
+    fn test_unknown_transparency_log() {
+        let err = verify(BUNDLE, &IDENTITY, &SIGSTORE).unwrap_err();
+        assert!(err.to_string().starts_with("the signature was recorded in an unknown"));
+    }
+
+    #[test]
+    fn test_tampered_inclusion_proof() {
+        let err =
+            verify_tampered(|bundle| flip(&mut tlog_entry(bundle)["inclusionProof"]["hashes"][0]));
+        assert_eq!("the inclusion proof doesn't match the root hash of the transparency log", err);
+
+        let err =
+            verify_tampered(|bundle| tlog_entry(bundle)["inclusionProof"]["logIndex"] = "3".into());
+        assert_eq!("the inclusion proof doesn't match the root hash of the transparency log", err);
+
+        let err = verify_tampered(|bundle| {
+            tlog_entry(bundle)["inclusionProof"]["hashes"].as_array_mut().unwrap().pop();
+        });
+        assert_eq!("the inclusion proof is shorter than the audit path", err);
+    }
+
+    #[test]
+    fn test_tampered_checkpoint() {
+        let err = verify_tampered(|bundle| {
+            let checkpoint = &mut tlog_entry(bundle)["inclusionProof"]["checkpoint"]["envelope"];
+            *checkpoint =
+                checkpoint.as_str().unwrap().replacen("rekor.test - 1", "other", 1).into();
+        });
+        assert_eq!("invalid signature of the transparency log checkpoint", err);
+    }
+
+    #[test]
+    fn test_missing_inclusion_proof() {
+        let err = verify_tampered(|bundle| {
+            tlog_entry(bundle).as_object_mut().unwrap().remove("inclusionProof");
+        });
+        assert_eq!("the bundle doesn't contain the inclusion proof of the transparency log", err);
+
+        // Legacy bundles never contain the inclusion proof.
+        let err = verify(LEGACY_BUNDLE, &IDENTITY, &TEST_ROOT).unwrap_err();
+        assert_eq!(
+            "the bundle doesn't contain the inclusion proof of the transparency log",
+            err.to_string()
+        );
+    }
+}
@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Verification of cosign signature bundles without cosign and without network access, shared
+//! by the `document-signatures` tool and by `ferrocene-self-test`.
+
+mod cosign_bundle;
+mod cosign_verify;
+mod transparency_log;
+mod trusted_root;
+
+pub use crate::cosign_bundle::{CosignBundle, RawCosignBundle};
+pub use crate::cosign_verify::{Identity, verify_blob};
+pub use crate::trusted_root::{SIGSTORE, TrustedRoot};
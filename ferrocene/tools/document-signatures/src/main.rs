@@ -2,10 +2,10 @@
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
 mod config;
-mod cosign_bundle;
 mod pinned;
 mod sign;
 mod signature_files;
+mod tarball;
 mod verify;
 
 use std::env::VarError;
@@ -33,6 +33,10 @@ enum CliCommand {
         source_dir: PathBuf,
         output_dir: PathBuf,
     },
+    /// Verify the signatures of a built document offline, without any external tool.
+    VerifyDocument {
+        document_dir: PathBuf,
+    },
 }
 
 // \u{2d} replaces "-" to avoid REUSE mistakenly detecting these lines as a license.
@@ -44,14 +48,16 @@ enum CliCommand {
 
 fn main() -> Result<(), Error> {
     let cli = Cli::parse();
-    let env = Env::load()?;
 
     match cli.cmd {
         CliCommand::Sign { source_dir, output_dir, force } => {
-            sign::sign(&source_dir, &output_dir, force, &env)?;
+            sign::sign(&source_dir, &output_dir, force, &Env::load()?)?;
         }
         CliCommand::Verify { source_dir, output_dir } => {
-            verify::verify(&source_dir, &output_dir, &env)?;
+            verify::verify(&source_dir, &output_dir, &Env::load()?)?;
+        }
+        CliCommand::VerifyDocument { document_dir } => {
+            verify::verify_document(&document_dir)?;
         }
     }
 
@@ -9,9 +9,10 @@
 use sha2::{Digest, Sha256};
 use tempfile::NamedTempFile;
 
-use crate::{Env, TOML_HEADER_COMMENTS};
+use crate::{Env, TOML_HEADER_COMMENTS, tarball};
 
-const TAR_REPRODUCIBILITY_FLAGS: &[&str] = &[
+// Keep in sync with the emulation of `tar` in the tarball module.
+pub(crate) const TAR_REPRODUCIBILITY_FLAGS: &[&str] = &[
     // Use a consistent ordering for the files in the archive.
     "--sort=name",
     // Use a pinned date for the modification time, otherwise touching a file would change the hash
@@ -44,10 +45,7 @@ pub(crate) struct Pinned {
 
 impl Pinned {
     pub(crate) fn generate(env: &Env, output_dir: &Path) -> Result<(Self, NamedTempFile), Error> {
-        let document_id = std::fs::read_to_string(output_dir.join("document-id.txt"))
-            .context("failed to read document-id.txt from the output directory")?
-            .trim()
-            .to_string();
+        let document_id = read_document_id(output_dir)?;
 
         let mut saved_tarfile = NamedTempFile::new()?;
 
@@ -73,6 +71,15 @@ pub(crate) fn generate(env: &Env, output_dir: &Path) -> Result<(Self, NamedTempF
         Ok((Self { document_id, tarball_sha256 }, saved_tarfile))
     }
 
+    /// Compute the same data as [`Pinned::generate`] without invoking `tar`, for when the tarball
+    /// itself is not needed.
+    pub(crate) fn compute(output_dir: &Path) -> Result<Self, Error> {
+        Ok(Self {
+            document_id: read_document_id(output_dir)?,
+            tarball_sha256: tarball::sha256(output_dir)?,
+        })
+    }
+
     pub(crate) fn toml_comments(&self) -> Result<String, Error> {
         let mut comments = String::new();
         comments.push_str(TOML_HEADER_COMMENTS);
@@ -104,3 +111,10 @@ fn generate_random_data(&self, into: &mut String) -> Result<(), Error> {
         Ok(())
     }
 }
+
+fn read_document_id(output_dir: &Path) -> Result<String, Error> {
+    Ok(std::fs::read_to_string(output_dir.join("document-id.txt"))
+        .context("failed to read document-id.txt from the output directory")?
+        .trim()
+        .to_string())
+}
@@ -5,11 +5,11 @@
 use std::process::Command;
 
 use anyhow::{Context, Error};
+use document_signatures::RawCosignBundle;
 use tempfile::NamedTempFile;
 
 use crate::Env;
 use crate::config::Config;
-use crate::cosign_bundle::RawCosignBundle;
 use crate::pinned::Pinned;
 use crate::signature_files::SignatureFiles;
 
@@ -96,6 +96,9 @@ fn generate_and_load_cosign_bundle(
         .arg(pinned_temp.path())
         .arg("--bundle")
         .arg(bundle_temp.path())
+        // Unlike the legacy format, the Sigstore bundle format includes the inclusion proof of
+        // the transparency log, required to verify the signature offline.
+        .arg("--new-bundle-format")
         .arg("--yes")
         .status()?;
     if !status.success() {
@@ -0,0 +1,230 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Hash of the tarball `tar` produces with [`TAR_REPRODUCIBILITY_FLAGS`], computed without
+//! invoking `tar`, to verify signatures on systems without GNU tar.
+//!
+//! The archive is reproduced byte by byte, in GNU format: a 512 bytes header per entry, followed
+//! by the contents padded to 512 bytes, names longer than 100 bytes stored in a preceding
+//! `././@LongLink` entry, and the archive padded to a multiple of 20 records.
+//!
+//! [`TAR_REPRODUCIBILITY_FLAGS`]: crate::pinned::TAR_REPRODUCIBILITY_FLAGS
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Error, bail};
+use sha2::{Digest, Sha256};
+
+/// `--mtime=2020-01-01T00:00:00Z`
+const MTIME: u64 = 1577836800;
+/// `--exclude=./signature`
+const EXCLUDED: &str = "signature";
+
+const BLOCK: usize = 512;
+const RECORD: usize = 20 * BLOCK;
+
+pub(crate) fn sha256(dir: &Path) -> Result<String, Error> {
+    let mut writer = Writer { hasher: Sha256::new(), len: 0 };
+    writer.entry("./", dir)?;
+    writer.finish();
+    Ok(hex::encode(writer.hasher.finalize()))
+}
+
+struct Writer {
+    hasher: Sha256,
+    len: usize,
+}
+
+impl Writer {
+    fn entry(&mut self, name: &str, path: &Path) -> Result<(), Error> {
+        let metadata = std::fs::symlink_metadata(path)
+            .with_context(|| format!("failed to read the metadata of {}", path.display()))?;
+        let mode = normalize_mode(&metadata);
+
+        if metadata.is_symlink() {
+            let target = std::fs::read_link(path)?;
+            let Some(target) = target.to_str() else {
+                bail!("symlink target of {} is not utf-8", path.display());
+            };
+            self.header(name, mode, 0, b'2', target)?;
+        } else if metadata.is_dir() {
+            self.header(name, mode, 0, b'5', "")?;
+
+            // `--sort=name` sorts the entries of each directory by their bytes.
+            let mut children = Vec::new();
+            for entry in std::fs::read_dir(path)? {
+                let entry = entry?;
+                let Ok(file_name) = entry.file_name().into_string() else {
+                    bail!("file name of {} is not utf-8", entry.path().display());
+                };
+                children.push((file_name, entry.path()));
+            }
+            children.sort();
+
+            for (file_name, child) in children {
+                if name == "./" && file_name == EXCLUDED {
+                    continue;
+                }
+                let child_name = format!("{name}{file_name}");
+                if child.symlink_metadata()?.is_dir() {
+                    self.entry(&format!("{child_name}/"), &child)?;
+                } else {
+                    self.entry(&child_name, &child)?;
+                }
+            }
+        } else {
+            self.header(name, mode, metadata.len(), b'0', "")?;
+            let mut file = File::open(path)?;
+            let copied = std::io::copy(&mut file, &mut self.hasher)?;
+            self.len += copied as usize;
+            self.pad();
+        }
+        Ok(())
+    }
+
+    fn header(
+        &mut self,
+        name: &str,
+        mode: u32,
+        size: u64,
+        kind: u8,
+        link: &str,
+    ) -> Result<(), Error> {
+        if link.len() > 100 {
+            bail!("symlink target {link} is too long");
+        }
+        if name.len() > 100 {
+            let mut long_name = name.as_bytes().to_vec();
+            long_name.push(0);
+            self.raw_header("././@LongLink", 0o644, long_name.len() as u64, 0, b'L', "");
+            self.write(&long_name);
+            self.pad();
+        }
+        self.raw_header(name, mode, size, MTIME, kind, link);
+        Ok(())
+    }
+
+    fn raw_header(&mut self, name: &str, mode: u32, size: u64, mtime: u64, kind: u8, link: &str) {
+        let mut header = [0u8; BLOCK];
+        let mut field = |offset: usize, value: &[u8]| {
+            let len = value.len().min(header.len() - offset);
+            header[offset..offset + len].copy_from_slice(&value[..len]);
+        };
+
+        field(0, &name.as_bytes()[..name.len().min(100)]);
+        field(100, format!("{mode:07o}\0").as_bytes());
+        // `--owner=0 --group=0 --numeric-owner`
+        field(108, b"0000000\0");
+        field(116, b"0000000\0");
+        field(124, format!("{size:011o}\0").as_bytes());
+        field(136, format!("{mtime:011o}\0").as_bytes());
+        field(148, b"        ");
+        field(156, &[kind]);
+        field(157, link.as_bytes());
+        field(257, b"ustar  \0");
+
+        let checksum: u32 = header.iter().map(|&byte| u32::from(byte)).sum();
+        header[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+        self.write(&header);
+    }
+
+    fn write(&mut self, data: &[u8]) {
+        self.hasher.write_all(data).expect("hashing can't fail");
+        self.len += data.len();
+    }
+
+    fn pad(&mut self) {
+        let remainder = self.len % BLOCK;
+        if remainder != 0 {
+            self.write(&[0; BLOCK][..BLOCK - remainder]);
+        }
+    }
+
+    fn finish(&mut self) {
+        // The end of the archive is marked by two empty blocks, and the last record is padded.
+        self.write(&[0; 2 * BLOCK]);
+        let remainder = self.len % RECORD;
+        if remainder != 0 {
+            self.write(&vec![0; RECORD - remainder]);
+        }
+    }
+}
+
+/// `--mode=u+rw,go+r,go-w`
+#[cfg(unix)]
+fn normalize_mode(metadata: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    (metadata.permissions().mode() & 0o7777 | 0o644) & !0o022
+}
+
+/// `--mode=u+rw,go+r,go-w`, with the permissions Windows doesn't have inferred like `tar` does.
+#[cfg(not(unix))]
+fn normalize_mode(metadata: &std::fs::Metadata) -> u32 {
+    if metadata.is_dir() { 0o755 } else { 0o644 }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::{PermissionsExt, symlink};
+    use std::process::Command;
+
+    use tempfile::{NamedTempFile, TempDir};
+
+    use super::*;
+    use crate::pinned::{Pinned, TAR_REPRODUCIBILITY_FLAGS};
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    /// Hash the tarball `tar` itself produces, like `Pinned::generate` does.
+    fn tar_sha256(dir: &Path) -> String {
+        let tarball = NamedTempFile::new().unwrap();
+        let status = Command::new(crate::find_tar_binary().unwrap())
+            .args(TAR_REPRODUCIBILITY_FLAGS)
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "."])
+            .arg("-f")
+            .arg(tarball.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        hex::encode(Sha256::digest(std::fs::read(tarball.path()).unwrap()))
+    }
+
+    #[test]
+    fn test_compute_matches_tar() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(root, "document-id.txt", "TEST\n");
+        write(root, "index.html", "<html></html>");
+        write(root, "_static/empty.css", "");
+        write(root, &format!("_static/{}.js", "a".repeat(120)), "long name");
+        write(root, "_static/nested/signature/kept.txt", "only the top-level one is excluded");
+        write(root, "signature/pinned.toml", "excluded");
+        write(root, "script.sh", "#!/bin/sh\n");
+        std::fs::set_permissions(root.join("script.sh"), std::fs::Permissions::from_mode(0o700))
+            .unwrap();
+        std::fs::set_permissions(root.join("index.html"), std::fs::Permissions::from_mode(0o600))
+            .unwrap();
+        symlink("index.html", root.join("link.html")).unwrap();
+
+        let expected = tar_sha256(root);
+        let pinned = Pinned::compute(root).unwrap();
+        assert_eq!("TEST", pinned.document_id);
+        assert_eq!(expected, pinned.tarball_sha256);
+
+        // The signature directory must not affect the hash.
+        write(root, "signature/pinned.toml", "changed");
+        assert_eq!(expected, Pinned::compute(root).unwrap().tarball_sha256);
+
+        write(root, "_static/nested/signature/kept.txt", "changed");
+        assert_ne!(expected, Pinned::compute(root).unwrap().tarball_sha256);
+        assert_eq!(tar_sha256(root), Pinned::compute(root).unwrap().tarball_sha256);
+    }
+}
@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Verification of the inclusion of an entry in the Rekor transparency log, without network
+//! access. Rekor is a Merkle tree as defined by RFC 6962: the inclusion proof is the audit path
+//! from the entry to the root of the tree, and the root is attested by a checkpoint signed by the
+//! log in the signed note format.
+
+use anyhow::{Error, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ring::signature::{ECDSA_P256_SHA256_ASN1, UnparsedPublicKey};
+use sha2::{Digest, Sha256};
+
+use crate::cosign_bundle::InclusionProof;
+
+/// Prefix of the signature lines of a signed note.
+const SIGNATURE_PREFIX: &str = "\u{2014} ";
+
+/// Verify that `body` is included in the transparency log identified by `log_id`, whose public
+/// key is `rekor_key`.
+pub(crate) fn verify_inclusion_proof(
+    proof: &InclusionProof,
+    body: &[u8],
+    log_id: &[u8],
+    rekor_key: &[u8],
+) -> Result<(), Error> {
+    let leaf = hash_leaf(body);
+    let root = root_from_audit_path(proof.log_index, proof.tree_size, leaf, &proof.hashes)?;
+    if root[..] != proof.root_hash[..] {
+        bail!("the inclusion proof doesn't match the root hash of the transparency log");
+    }
+
+    let (tree_size, root_hash) = verify_checkpoint(&proof.checkpoint, log_id, rekor_key)?;
+    if tree_size != proof.tree_size || root_hash != proof.root_hash {
+        bail!("the inclusion proof is not about the tree of the signed checkpoint");
+    }
+
+    Ok(())
+}
+
+/// Compute the root of the tree from the audit path of a leaf, following RFC 9162 (2.1.3.2).
+fn root_from_audit_path(
+    index: u64,
+    tree_size: u64,
+    leaf: [u8; 32],
+    hashes: &[Vec<u8>],
+) -> Result<[u8; 32], Error> {
+    if index >= tree_size {
+        bail!("the entry index {index} is outside of the tree of size {tree_size}");
+    }
+
+    let mut fnode = index;
+    let mut snode = tree_size - 1;
+    let mut root = leaf;
+    for hash in hashes {
+        if snode == 0 {
+            bail!("the inclusion proof is longer than the audit path");
+        }
+        if fnode & 1 == 1 || fnode == snode {
+            root = hash_children(hash, &root);
+            while fnode & 1 == 0 && fnode != 0 {
+                fnode >>= 1;
+                snode >>= 1;
+            }
+        } else {
+            root = hash_children(&root, hash);
+        }
+        fnode >>= 1;
+        snode >>= 1;
+    }
+    if snode != 0 {
+        bail!("the inclusion proof is shorter than the audit path");
+    }
+
+    Ok(root)
+}
+
+/// Verify the signature of a checkpoint, returning the size and root hash of the tree it attests.
+///
+/// The checkpoint is a signed note: the origin of the log, the tree size and the base64 root hash
+/// on separate lines, followed by an empty line and the signatures. Each signature line contains
+/// the name of the signer and the base64 of the first four bytes of the log ID followed by the
+/// signature of the text before the empty line.
+fn verify_checkpoint(
+    checkpoint: &str,
+    log_id: &[u8],
+    rekor_key: &[u8],
+) -> Result<(u64, Vec<u8>), Error> {
+    let invalid = || anyhow!("the checkpoint of the transparency log is malformed");
+
+    let (text, signatures) = checkpoint.split_once("\n\n").ok_or_else(invalid)?;
+    let text = format!("{text}\n");
+
+    let mut verified = false;
+    for line in signatures.lines() {
+        let (_name, signature) = line
+            .strip_prefix(SIGNATURE_PREFIX)
+            .and_then(|line| line.rsplit_once(' '))
+            .ok_or_else(invalid)?;
+        let signature = BASE64.decode(signature)?;
+        let Some((key_hint, signature)) = signature.split_at_checked(4) else {
+            return Err(invalid());
+        };
+        if key_hint != &log_id[..4] {
+            continue;
+        }
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, rekor_key)
+            .verify(text.as_bytes(), signature)
+            .map_err(|_| anyhow!("invalid signature of the transparency log checkpoint"))?;
+        verified = true;
+    }
+    if !verified {
+        bail!("the checkpoint is not signed by the transparency log");
+    }
+
+    let mut lines = text.lines();
+    let _origin = lines.next().ok_or_else(invalid)?;
+    let tree_size = lines.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+    let root_hash = BASE64.decode(lines.next().ok_or_else(invalid)?)?;
+    Ok((tree_size, root_hash))
+}
+
+fn hash_leaf(data: &[u8]) -> [u8; 32] {
+    Sha256::new().chain_update([0]).chain_update(data).finalize().into()
+}
+
+fn hash_children(left: &[u8], right: &[u8]) -> [u8; 32] {
+    Sha256::new().chain_update([1]).chain_update(left).chain_update(right).finalize().into()
+}
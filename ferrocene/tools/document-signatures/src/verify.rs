@@ -1,58 +1,47 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
-use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use anyhow::{Context, Error};
+use document_signatures::{RawCosignBundle, SIGSTORE, verify_blob};
 
 use crate::Env;
-use crate::config::Config;
+use crate::config::{Config, Role};
 use crate::pinned::Pinned;
 use crate::signature_files::SignatureFiles;
 
-pub(crate) fn verify(source_dir: &Path, output_dir: &Path, env: &Env) -> Result<(), Error> {
-    let signature_files = SignatureFiles::load(source_dir, env)?;
-
-    let pinned_toml = if let Some(mut file) = signature_files.on_disk_as_tempfile("pinned.toml")? {
-        let mut contents = Vec::new();
-        file.read_to_end(&mut contents)?;
+/// Roles that must sign every qualification document, and the identity they must sign with, as
+/// configured in the `signature/config.toml` of the documents when this tool was built. See
+/// `build.rs` for how they are embedded.
+const TRUSTED_ROLES: &[(&str, &str)] = include!(concat!(env!("OUT_DIR"), "/trusted_roles.rs"));
 
-        let existing: Pinned = toml::from_slice(&contents)?;
-        let (expected, _) = Pinned::generate(env, output_dir)?;
-
-        if existing != expected {
-            eprintln!("Signature incorrect: {}", output_dir.display());
-            if existing.document_id != expected.document_id {
-                eprintln!("existing document id: {}", existing.document_id);
-                eprintln!("expected document id: {}", expected.document_id);
-            }
-            if existing.tarball_sha256 != expected.tarball_sha256 {
-                eprintln!("existing tarball sha256: {}", existing.tarball_sha256);
-                eprintln!("expected tarball sha256: {}", expected.tarball_sha256);
-            }
-
-            // Print a diff that explains why this broke.
-            let diff_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("diff.py");
-            Command::new(diff_path).arg("local").arg(output_dir.file_name().unwrap()).status()?;
+fn trusted_roles() -> impl Iterator<Item = (&'static str, Role)> {
+    TRUSTED_ROLES.iter().map(|&(name, email)| (name, Role { email: email.into() }))
+}
 
-            anyhow::bail!("pinned documentation file outdated");
-        } else {
-            eprintln!("Signature correct: {}", output_dir.display());
-        }
+pub(crate) fn verify(source_dir: &Path, output_dir: &Path, env: &Env) -> Result<(), Error> {
+    let signature_files = SignatureFiles::load(source_dir, env)?;
 
-        file
-    } else {
+    let Some(pinned_toml) = signature_files.read("pinned.toml")? else {
         // The document was not signed.
         eprintln!("WARNING: {} is not signed", output_dir.display());
         return Ok(());
     };
 
+    if !verify_pinned(&pinned_toml, output_dir)? {
+        // Print a diff that explains why this broke.
+        let diff_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("diff.py");
+        Command::new(diff_path).arg("local").arg(output_dir.file_name().unwrap()).status()?;
+
+        anyhow::bail!("pinned documentation file outdated");
+    }
+
     let config = Config::load(source_dir)?;
     for (role_name, role) in config.roles.iter() {
         let maybe_bundle = signature_files
-            .on_disk_as_tempfile(&format!("{role_name}.cosign-bundle"))
+            .read(&format!("{role_name}.cosign-bundle"))
             .with_context(|| format!("failed to read signature for role {role_name}"))?;
 
         let bundle = match maybe_bundle {
@@ -67,19 +56,106 @@ pub(crate) fn verify(source_dir: &Path, output_dir: &Path, env: &Env) -> Result<
             None => anyhow::bail!("missing signature file for role {role_name}"),
         };
 
-        eprintln!("checking role {role_name}");
-        let status = Command::new(&env.cosign_binary)
-            .arg("verify-blob")
-            .arg(pinned_toml.path())
-            .arg("--bundle")
-            .arg(bundle.path())
-            .args(["--certificate-identity", &role.email])
-            .args(["--certificate-oidc-issuer", role.idp()?.url])
-            .status()?;
-        if !status.success() {
-            anyhow::bail!("failed to verify signature for role {role_name} (exited with {status})");
+        let bundle = parse_bundle(role_name, &bundle)?;
+        if bundle.has_inclusion_proof() {
+            verify_role(role_name, role, &bundle, &pinned_toml)?;
+        } else {
+            // Bundles in the legacy format don't contain the inclusion proof, which cosign fetches
+            // from the transparency log instead.
+            verify_role_with_cosign(role_name, role, &signature_files, env)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Verify the signatures of a built document, using only the files shipped alongside it in the
+/// `signature` directory and the identities embedded in this tool. This doesn't require network
+/// access, nor any external tool.
+pub(crate) fn verify_document(document_dir: &Path) -> Result<(), Error> {
+    let signature_dir = document_dir.join("signature");
+    let read = |name: &str| {
+        std::fs::read(signature_dir.join(name))
+            .with_context(|| format!("failed to read {name} from {}", signature_dir.display()))
+    };
+
+    if !signature_dir.join("pinned.toml").exists() {
+        anyhow::bail!("{} is not signed", document_dir.display());
+    }
+    let pinned_toml = read("pinned.toml")?;
+    if !verify_pinned(&pinned_toml, document_dir)? {
+        anyhow::bail!("the contents of the document changed after it was signed");
+    }
+
+    for (role_name, role) in trusted_roles() {
+        let bundle = read(&format!("{role_name}.cosign-bundle"))
+            .with_context(|| format!("missing signature for role {role_name}"))?;
+        verify_role(role_name, &role, &parse_bundle(role_name, &bundle)?, &pinned_toml)?;
+    }
+
+    eprintln!("All signatures of {} are valid", document_dir.display());
+    Ok(())
+}
+
+/// Compare the signed `pinned.toml` with the one computed from the document, returning whether
+/// they match.
+fn verify_pinned(pinned_toml: &[u8], output_dir: &Path) -> Result<bool, Error> {
+    let existing: Pinned = toml::from_slice(pinned_toml)?;
+    let expected = Pinned::compute(output_dir)?;
+
+    if existing != expected {
+        eprintln!("Signature incorrect: {}", output_dir.display());
+        if existing.document_id != expected.document_id {
+            eprintln!("existing document id: {}", existing.document_id);
+            eprintln!("expected document id: {}", expected.document_id);
+        }
+        if existing.tarball_sha256 != expected.tarball_sha256 {
+            eprintln!("existing tarball sha256: {}", existing.tarball_sha256);
+            eprintln!("expected tarball sha256: {}", expected.tarball_sha256);
         }
+        Ok(false)
+    } else {
+        eprintln!("Signature correct: {}", output_dir.display());
+        Ok(true)
     }
+}
 
+fn parse_bundle(role_name: &str, bundle: &[u8]) -> Result<RawCosignBundle, Error> {
+    RawCosignBundle::from_slice(bundle)
+        .with_context(|| format!("failed to parse signature for role {role_name}"))
+}
+
+fn verify_role(
+    role_name: &str,
+    role: &Role,
+    bundle: &RawCosignBundle,
+    pinned_toml: &[u8],
+) -> Result<(), Error> {
+    eprintln!("checking role {role_name}");
+    verify_blob(bundle, pinned_toml, &role.identity()?, &SIGSTORE)
+        .with_context(|| format!("failed to verify signature for role {role_name}"))
+}
+
+fn verify_role_with_cosign(
+    role_name: &str,
+    role: &Role,
+    signature_files: &SignatureFiles<'_>,
+    env: &Env,
+) -> Result<(), Error> {
+    eprintln!("checking role {role_name} with cosign");
+    let pinned_toml = signature_files.on_disk_as_tempfile("pinned.toml")?.unwrap();
+    let bundle =
+        signature_files.on_disk_as_tempfile(&format!("{role_name}.cosign-bundle"))?.unwrap();
+    let status = Command::new(&env.cosign_binary)
+        .arg("verify-blob")
+        .arg(pinned_toml.path())
+        .arg("--bundle")
+        .arg(bundle.path())
+        .args(["--certificate-identity", &role.email])
+        .args(["--certificate-oidc-issuer", role.idp()?.url])
+        .status()?;
+    if !status.success() {
+        anyhow::bail!("failed to verify signature for role {role_name} (exited with {status})");
+    }
     Ok(())
 }
@@ -0,0 +1,241 @@
+#!/usr/bin/env -S uv run
+# SPDX-License-Identifier: MIT OR Apache-2.0
+# SPDX-FileCopyrightText: The Ferrocene Developers
+
+# This script generates the signature bundles used by the tests of the offline verifier. They are
+# signed by a private test instance of Fulcio and Rekor, whose trust anchors are written alongside
+# the bundles, since the real instances can't be used to sign arbitrary test data.
+#
+# The generated files are committed: only run this script again when the fixtures need to change.
+
+# /// script
+# dependencies = ["cryptography"]
+# ///
+
+from pathlib import Path
+import base64
+import datetime
+import hashlib
+import json
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec
+from cryptography.x509.oid import ExtendedKeyUsageOID, NameOID
+
+OUT = Path(__file__).parent
+EMAIL = "signer@ferrous-systems.com"
+IDP = "https://login.microsoftonline.com"
+ORIGIN = "rekor.test - 1"
+INTEGRATED_TIME = 1760000000
+BLOB = b"document-id = \"TEST\"\ntarball-sha256 = \"0000\"\n"
+CHECKSUMS = (
+    hashlib.sha256(b"hello world").hexdigest() + "  lib/librustc_driver.so\n"
+).encode()
+# The entry is in the middle of the tree, to exercise both sides of the audit path.
+LEAVES = 7
+LEAF_INDEX = 4
+
+
+def b64(data):
+    return base64.b64encode(data).decode()
+
+
+def der(key):
+    return key.public_key().public_bytes(
+        serialization.Encoding.DER, serialization.PublicFormat.SubjectPublicKeyInfo
+    )
+
+
+def name(common_name):
+    return x509.Name(
+        [
+            x509.NameAttribute(NameOID.ORGANIZATION_NAME, "ferrocene.test"),
+            x509.NameAttribute(NameOID.COMMON_NAME, common_name),
+        ]
+    )
+
+
+def ca(subject, key, issuer, issuer_key):
+    return (
+        x509.CertificateBuilder()
+        .subject_name(name(subject))
+        .issuer_name(name(issuer))
+        .public_key(key.public_key())
+        .serial_number(x509.random_serial_number())
+        .not_valid_before(datetime.datetime(2020, 1, 1, tzinfo=datetime.timezone.utc))
+        .not_valid_after(datetime.datetime(2040, 1, 1, tzinfo=datetime.timezone.utc))
+        .add_extension(x509.BasicConstraints(ca=True, path_length=None), critical=True)
+        .sign(issuer_key, hashes.SHA256())
+    )
+
+
+def leaf_hash(data):
+    return hashlib.sha256(b"\x00" + data).digest()
+
+
+def node_hash(left, right):
+    return hashlib.sha256(b"\x01" + left + right).digest()
+
+
+def split(n):
+    k = 1
+    while k * 2 < n:
+        k *= 2
+    return k
+
+
+# Merkle tree hash and audit path, as defined by RFC 6962.
+def tree_hash(leaves):
+    if len(leaves) == 1:
+        return leaf_hash(leaves[0])
+    k = split(len(leaves))
+    return node_hash(tree_hash(leaves[:k]), tree_hash(leaves[k:]))
+
+
+def audit_path(index, leaves):
+    if len(leaves) == 1:
+        return []
+    k = split(len(leaves))
+    if index < k:
+        return audit_path(index, leaves[:k]) + [tree_hash(leaves[k:])]
+    return audit_path(index - k, leaves[k:]) + [tree_hash(leaves[:k])]
+
+
+def sign(key, data):
+    return key.sign(data, ec.ECDSA(hashes.SHA256()))
+
+
+root_key = ec.generate_private_key(ec.SECP256R1())
+intermediate_key = ec.generate_private_key(ec.SECP256R1())
+signing_key = ec.generate_private_key(ec.SECP256R1())
+rekor_key = ec.generate_private_key(ec.SECP256R1())
+
+root = ca("fulcio", root_key, "fulcio", root_key)
+intermediate = ca("fulcio-intermediate", intermediate_key, "fulcio", root_key)
+signing_time = datetime.datetime.fromtimestamp(INTEGRATED_TIME, tz=datetime.timezone.utc)
+certificate = (
+    x509.CertificateBuilder()
+    .subject_name(x509.Name([]))
+    .issuer_name(name("fulcio-intermediate"))
+    .public_key(signing_key.public_key())
+    .serial_number(x509.random_serial_number())
+    .not_valid_before(signing_time - datetime.timedelta(minutes=1))
+    .not_valid_after(signing_time + datetime.timedelta(minutes=10))
+    .add_extension(x509.SubjectAlternativeName([x509.RFC822Name(EMAIL)]), critical=True)
+    .add_extension(x509.ExtendedKeyUsage([ExtendedKeyUsageOID.CODE_SIGNING]), critical=False)
+    .add_extension(
+        x509.UnrecognizedExtension(x509.ObjectIdentifier("1.3.6.1.4.1.57264.1.1"), IDP.encode()),
+        critical=False,
+    )
+    .sign(intermediate_key, hashes.SHA256())
+)
+certificate_pem = certificate.public_bytes(serialization.Encoding.PEM)
+
+
+def sign_blob(blob):
+    """Sign the blob and record it in the transparency log, returning the Sigstore bundle and the
+    legacy bundle."""
+    signature = sign(signing_key, blob)
+    body = json.dumps(
+        {
+            "apiVersion": "0.0.1",
+            "kind": "hashedrekord",
+            "spec": {
+                "data": {
+                    "hash": {"algorithm": "sha256", "value": hashlib.sha256(blob).hexdigest()}
+                },
+                "signature": {
+                    "content": b64(signature),
+                    "publicKey": {"content": b64(certificate_pem)},
+                },
+            },
+        },
+        separators=(",", ":"),
+    ).encode()
+
+    log_id = hashlib.sha256(der(rekor_key)).digest()
+    log_index = 1234
+    payload = {
+        "body": b64(body),
+        "integratedTime": INTEGRATED_TIME,
+        "logID": log_id.hex(),
+        "logIndex": log_index,
+    }
+    signed_entry_timestamp = sign(
+        rekor_key, json.dumps(payload, sort_keys=True, separators=(",", ":")).encode()
+    )
+
+    leaves = [f"other entry {i}".encode() for i in range(LEAVES)]
+    leaves[LEAF_INDEX] = body
+    root_hash = tree_hash(leaves)
+    checkpoint = f"{ORIGIN}\n{LEAVES}\n{b64(root_hash)}\n"
+    checkpoint_signature = log_id[:4] + sign(rekor_key, checkpoint.encode())
+    checkpoint += f"\n— rekor.test {b64(checkpoint_signature)}\n"
+
+    bundle = {
+        "mediaType": "application/vnd.dev.sigstore.bundle.v0.3+json",
+        "verificationMaterial": {
+            "certificate": {
+                "rawBytes": b64(certificate.public_bytes(serialization.Encoding.DER))
+            },
+            "tlogEntries": [
+                {
+                    "logIndex": str(log_index),
+                    "logId": {"keyId": b64(log_id)},
+                    "kindVersion": {"kind": "hashedrekord", "version": "0.0.1"},
+                    "integratedTime": str(INTEGRATED_TIME),
+                    "inclusionPromise": {"signedEntryTimestamp": b64(signed_entry_timestamp)},
+                    "inclusionProof": {
+                        "logIndex": str(LEAF_INDEX),
+                        "rootHash": b64(root_hash),
+                        "treeSize": str(LEAVES),
+                        "hashes": [b64(h) for h in audit_path(LEAF_INDEX, leaves)],
+                        "checkpoint": {"envelope": checkpoint},
+                    },
+                    "canonicalizedBody": b64(body),
+                }
+            ],
+        },
+        "messageSignature": {
+            "messageDigest": {
+                "algorithm": "SHA2_256",
+                "digest": b64(hashlib.sha256(blob).digest()),
+            },
+            "signature": b64(signature),
+        },
+    }
+
+    # Bundle in the format emitted by `cosign sign-blob --bundle`, without an inclusion proof.
+    legacy_bundle = {
+        "base64Signature": b64(signature),
+        "cert": b64(certificate_pem),
+        "rekorBundle": {
+            "SignedEntryTimestamp": b64(signed_entry_timestamp),
+            "Payload": payload,
+        },
+    }
+
+    return bundle, legacy_bundle
+
+
+bundle, legacy_bundle = sign_blob(BLOB)
+# Signed checksums, used by the tests of ferrocene-self-test.
+checksums_bundle, _ = sign_blob(CHECKSUMS)
+
+(OUT / "blob.txt").write_bytes(BLOB)
+(OUT / "bundle.json").write_text(json.dumps(bundle, indent=2) + "\n")
+(OUT / "legacy-bundle.json").write_text(json.dumps(legacy_bundle, indent=2) + "\n")
+(OUT / "checksums.sha256").write_bytes(CHECKSUMS)
+(OUT / "checksums.sha256.cosign-bundle").write_text(
+    json.dumps(checksums_bundle, indent=2) + "\n"
+)
+(OUT / "fulcio-root.pem").write_bytes(root.public_bytes(serialization.Encoding.PEM))
+(OUT / "fulcio-intermediate.pem").write_bytes(
+    intermediate.public_bytes(serialization.Encoding.PEM)
+)
+(OUT / "rekor.pub").write_bytes(
+    rekor_key.public_key().public_bytes(
+        serialization.Encoding.PEM, serialization.PublicFormat.SubjectPublicKeyInfo
+    )
+)
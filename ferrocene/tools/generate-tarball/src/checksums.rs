@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Checksums of every file in the package, verified by `ferrocene-self-test` once installed.
+//!
+//! The checksums are stored in the `sha256sum` format, one `{hex sha256}  {path}` line per file
+//! with `/` as the path separator, and can optionally be signed with cosign.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Error, anyhow};
+use walkdir::WalkDir;
+
+use crate::signatures::hash_file;
+
+/// Directory, relative to the package root, containing the checksums of every component.
+pub(crate) const CHECKSUMS_DIR: &str = "share/ferrocene/checksums";
+
+pub(crate) fn write_checksums(
+    component: &str,
+    package_dir: &Path,
+    cosign_binary: Option<&Path>,
+) -> Result<(), Error> {
+    let mut checksums = String::new();
+    for entry in WalkDir::new(package_dir).sort_by_file_name() {
+        let entry = entry?;
+        if !entry.path().is_file() {
+            continue;
+        }
+        let relative_path = entry.path().strip_prefix(package_dir)?;
+        // Checksums of other components (and their signatures) can't be known in advance.
+        if relative_path.starts_with(CHECKSUMS_DIR) {
+            continue;
+        }
+
+        let mut normalized = Vec::new();
+        for part in relative_path.components() {
+            normalized.push(
+                part.as_os_str()
+                    .to_str()
+                    .ok_or_else(|| anyhow!("path {relative_path:?} is not utf-8"))?,
+            );
+        }
+
+        for byte in hash_file(entry.path())? {
+            write!(checksums, "{byte:02x}")?;
+        }
+        writeln!(checksums, "  {}", normalized.join("/"))?;
+    }
+
+    let dest_dir = package_dir.join(CHECKSUMS_DIR);
+    std::fs::create_dir_all(&dest_dir)?;
+    let dest = dest_dir.join(format!("{component}.sha256"));
+    std::fs::write(&dest, checksums)?;
+
+    if let Some(cosign_binary) = cosign_binary {
+        let bundle = dest_dir.join(format!("{component}.sha256.cosign-bundle"));
+        let status = Command::new(cosign_binary)
+            .arg("sign-blob")
+            .arg(&dest)
+            .arg("--bundle")
+            .arg(&bundle)
+            // ferrocene-self-test verifies the signature offline, which requires the inclusion
+            // proof of the transparency log only present in the Sigstore bundle format.
+            .arg("--new-bundle-format")
+            .arg("--yes")
+            .status()
+            .with_context(|| format!("failed to invoke {}", cosign_binary.display()))?;
+        if !status.success() {
+            anyhow::bail!("failed to invoke cosign (exited with status {status})");
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_write_checksums() -> Result<(), Error> {
+        let package_dir = TempDir::new()?;
+        let create_file = |path: &str, contents: &[u8]| {
+            let path = package_dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(path, contents)
+        };
+
+        create_file("bin/rustc", b"hello world")?;
+        create_file("lib/rustlib/x86_64-unknown-linux-gnu/lib/libstd.rlib", b"")?;
+        create_file("share/ferrocene/checksums/other.sha256", b"not included")?;
+
+        write_checksums("demo-package", package_dir.path(), None)?;
+
+        assert_eq!(
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9  bin/rustc\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  \
+             lib/rustlib/x86_64-unknown-linux-gnu/lib/libstd.rlib\n",
+            std::fs::read_to_string(
+                package_dir.path().join("share/ferrocene/checksums/demo-package.sha256")
+            )?
+        );
+        Ok(())
+    }
+}
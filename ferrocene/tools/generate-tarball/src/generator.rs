@@ -6,6 +6,7 @@
 
 use anyhow::Result;
 
+use crate::checksums::write_checksums;
 use crate::compression::{CompressionFormats, CompressionProfile};
 use crate::signatures::{SignatureContext, sign_manifest_with_aws_kms};
 use crate::tarballer::Tarballer;
@@ -76,6 +77,9 @@ pub struct Generator {
     /// The ARN of the AWS KMS key used to sign the criticalup manifest
     #[clap(long, value_name = "ARN")]
     ferrocene_signing_kms_key_arn: Option<String>,
+    /// Path of the cosign binary used to sign the checksums of the package
+    #[clap(long, value_name = "PATH")]
+    ferrocene_cosign_binary: Option<PathBuf>,
     /// Path prefix that should only contain Ferrocene files.
     #[clap(long, value_name = "PATH")]
     ferrocene_managed_prefix: Vec<String>,
@@ -111,6 +115,7 @@ pub fn run(self) -> Result<()> {
             override_file_mtime,
             ferrocene_commit_sha,
             ferrocene_signing_kms_key_arn,
+            ferrocene_cosign_binary,
             ferrocene_managed_prefix,
             ferrocene_proxied_binary,
             ferrocene_component,
@@ -154,6 +159,8 @@ pub fn run(self) -> Result<()> {
             )?;
         }
 
+        write_checksums(&ferrocene_component, &package_dir, ferrocene_cosign_binary.as_deref())?;
+
         // Make the tarballs
         create_dir_all(&output_dir)?;
         let output = Path::new(&output_dir).join(&package_name);
@@ -5,6 +5,7 @@
 mod signatures;
 mod util;
 
+mod checksums;
 mod compression;
 mod generator;
 mod tarballer;
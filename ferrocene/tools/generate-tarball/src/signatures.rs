@@ -108,7 +108,7 @@ fn collect_files(
     Ok(())
 }
 
-fn hash_file(path: &Path) -> Result<Vec<u8>, Error> {
+pub(crate) fn hash_file(path: &Path) -> Result<Vec<u8>, Error> {
     let mut sha256 = Sha256::new();
     let mut contents = File::open(path)?;
     std::io::copy(&mut contents, &mut sha256)?;
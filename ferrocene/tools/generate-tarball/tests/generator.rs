@@ -48,7 +48,15 @@ fn works() -> Result<(), anyhow::Error> {
         .collect::<Result<Vec<_>, _>>()?;
     res.sort();
 
-    let expected = vec!["bar".to_string(), "bar/bar".to_string(), "foo".to_string()];
+    let expected = vec![
+        "bar".to_string(),
+        "bar/bar".to_string(),
+        "foo".to_string(),
+        "share".to_string(),
+        "share/ferrocene".to_string(),
+        "share/ferrocene/checksums".to_string(),
+        "share/ferrocene/checksums/foo.sha256".to_string(),
+    ];
     assert_eq!(res, expected);
 
     Ok(())
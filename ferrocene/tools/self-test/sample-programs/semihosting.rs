@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+// Bare-metal sample program, executed under `qemu-system-*`. It prints its output and reports
+// whether it succeeded through semihosting, as there is no operating system to do so. It is linked
+// with the linker scripts of `semihosting-runner`, which expect a `_start` function.
+
+#![no_std]
+#![no_main]
+
+use addition::add;
+
+fn main() -> bool {
+    if add(4, 5) != 9 {
+        return false;
+    }
+    write0(b"123456789\0");
+    true
+}
+
+/// `SYS_WRITE0`: write a NUL-terminated string to the debugger's console.
+fn write0(message: &[u8]) {
+    unsafe { semihosting(0x04, message.as_ptr() as usize) };
+}
+
+/// `SYS_EXIT`: stop the emulator, with an exit status depending on `success`.
+fn exit(success: bool) -> ! {
+    // ADP_Stopped_ApplicationExit and ADP_Stopped_RunTimeErrorUnknown.
+    let reason: usize = if success { 0x20026 } else { 0x20023 };
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        semihosting(0x18, reason)
+    };
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        // On 64-bit targets the parameter is a pointer to the reason and the exit code.
+        let block = [reason, 0];
+        semihosting(0x18, block.as_ptr() as usize)
+    };
+    loop {}
+}
+
+#[no_mangle]
+extern "C" fn selftest_entry() -> ! {
+    exit(main())
+}
+
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+    exit(false)
+}
+
+#[cfg(target_arch = "arm")]
+unsafe fn semihosting(operation: usize, parameter: usize) -> usize {
+    let result;
+    core::arch::asm!(
+        "bkpt #0xab",
+        inout("r0") operation => result,
+        in("r1") parameter,
+        options(nostack),
+    );
+    result
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn semihosting(operation: usize, parameter: usize) -> usize {
+    let result;
+    core::arch::asm!(
+        "hlt #0xf000",
+        inout("x0") operation => result,
+        in("x1") parameter,
+        options(nostack),
+    );
+    result
+}
+
+// Cortex-M: the reset vector, called with the stack set up, which the linker script places in
+// the vector table at the start of the flash.
+#[cfg(target_arch = "arm")]
+#[no_mangle]
+unsafe extern "C" fn _start() -> ! {
+    // Enable the FPU (CP10 and CP11), which is disabled at reset.
+    #[cfg(target_abi = "eabihf")]
+    {
+        let cpacr = 0xE000_ED88 as *mut u32;
+        cpacr.write_volatile(cpacr.read_volatile() | (0xF << 20));
+        core::arch::asm!("dsb", "isb");
+    }
+    selftest_entry()
+}
+
+// AArch64: QEMU jumps to the ELF entry point at EL1, without a stack.
+#[cfg(target_arch = "aarch64")]
+core::arch::global_asm!(
+    ".section .text._start, \"ax\"",
+    ".global _start",
+    "_start:",
+    "    ldr x30, =__stack_top",
+    "    mov sp, x30",
+    // Don't trap floating point and SIMD instructions, which the compiler may use.
+    "    mov x0, #0x300000",
+    "    msr cpacr_el1, x0",
+    "    isb",
+    "    bl selftest_entry",
+);
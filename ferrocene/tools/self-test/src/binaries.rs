@@ -37,6 +37,7 @@ fn check_binary(
 
     check_file(&bin, &bin_dir, name)?;
     let version = get_version(&bin, name)?;
+    reporter.binary_version(name, &version.release, &version.commit_hash);
     check_version(version, hash, name)?;
 
     reporter.success(&format!("binary {name} is valid"));
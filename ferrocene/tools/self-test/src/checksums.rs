@@ -0,0 +1,401 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Verification of the installed files against the checksums shipped in every tarball.
+//!
+//! `generate-tarball` writes the SHA-256 of every file of a component to
+//! `share/ferrocene/checksums/{component}.sha256`, and in release builds signs it with cosign
+//! into `{component}.sha256.cosign-bundle`.
+//!
+//! Every component signed by `generate-tarball` also ships its manifest in
+//! `share/criticaltrust/ferrocene/{component}.json`, which is used to detect components whose
+//! checksums were removed.
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use document_signatures::{Identity, RawCosignBundle, SIGSTORE, TrustedRoot, verify_blob};
+use sha2::{Digest, Sha256};
+
+use crate::env;
+use crate::error::Error;
+use crate::report::Reporter;
+
+const CHECKSUMS_DIR: &str = "share/ferrocene/checksums";
+const MANIFESTS_DIR: &str = "share/criticaltrust/ferrocene";
+const EXTENSION: &str = "sha256";
+
+/// Identity the checksums are expected to be signed by, and the Sigstore instance vouching for it.
+#[derive(Clone, Copy)]
+struct Signer<'a> {
+    identity: Identity<'a>,
+    root: &'a TrustedRoot,
+}
+
+pub(crate) fn check(reporter: &dyn Reporter, sysroot: &Path) -> Result<(), Error> {
+    let signer = match (env::SELFTEST_SIGNING_IDENTITY, env::SELFTEST_SIGNING_OIDC_ISSUER) {
+        (Some(email), Some(oidc_issuer)) => {
+            Some(Signer { identity: Identity { email, oidc_issuer }, root: &SIGSTORE })
+        }
+        _ => None,
+    };
+    check_inner(reporter, sysroot, signer)
+}
+
+fn check_inner(
+    reporter: &dyn Reporter,
+    sysroot: &Path,
+    signer: Option<Signer<'_>>,
+) -> Result<(), Error> {
+    let dir = sysroot.join(CHECKSUMS_DIR);
+    let checksums = find_files(&dir, EXTENSION)?;
+    for manifest in find_files(&sysroot.join(MANIFESTS_DIR), "json")? {
+        let component = manifest.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+        let path = dir.join(format!("{component}.{EXTENSION}"));
+        if !checksums.contains(&path) {
+            return Err(Error::ComponentChecksumsMissing { component: component.into(), path });
+        }
+    }
+    if checksums.is_empty() {
+        if signer.is_some() {
+            return Err(Error::NoChecksums { path: dir });
+        }
+        reporter.skipped("verifying the checksums of the installed files (no checksums found)");
+        return Ok(());
+    }
+
+    let mut covered = BTreeSet::new();
+    for path in checksums {
+        let component = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+
+        // The signature is verified on the same contents that are then parsed, so that the file
+        // can't be replaced in between.
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|error| Error::ChecksumsReadFailed { path: path.clone(), error })?;
+        if let Some(signer) = signer {
+            verify_signature(reporter, &path, contents.as_bytes(), &component, signer)?;
+        }
+        let mut verified = 0;
+        for (idx, line) in contents.lines().enumerate() {
+            let invalid = || Error::InvalidChecksums { path: path.clone(), line: idx + 1 };
+            let (expected, file) = line.split_once("  ").ok_or_else(invalid)?;
+            if expected.len() != 64 || file.is_empty() || file.split('/').any(|p| p == "..") {
+                return Err(invalid());
+            }
+
+            let file_path = file.split('/').fold(sysroot.to_path_buf(), |path, p| path.join(p));
+            let found = match hash_file(&file_path) {
+                Ok(hash) => hash,
+                Err(error) if error.kind() == ErrorKind::NotFound => {
+                    return Err(Error::InstalledFileMissing { path: file_path, component });
+                }
+                Err(error) => return Err(Error::ChecksumsReadFailed { path: file_path, error }),
+            };
+            if !found.eq_ignore_ascii_case(expected) {
+                return Err(Error::InstalledFileModified { path: file_path, component });
+            }
+            verified += 1;
+            covered.insert(file_path);
+        }
+
+        reporter.success(&format!(
+            "verified the checksums of the {verified} files installed by {component}"
+        ));
+    }
+
+    // Files not listed in any checksums were not installed by Ferrocene: they are not an error,
+    // but they are not verified either, so let the user know about them.
+    let mut installed = Vec::new();
+    find_installed_files(sysroot, &dir, &mut installed)?;
+    installed.sort();
+    for file in installed.into_iter().filter(|file| !covered.contains(file)) {
+        reporter.note(&format!("file {} is not covered by any checksums", file.display()));
+    }
+
+    Ok(())
+}
+
+/// Return the files in `dir` with the `extension`, sorted to make the output deterministic.
+fn find_files(dir: &Path, extension: &str) -> Result<Vec<PathBuf>, Error> {
+    let map_err = |error| Error::ChecksumsReadFailed { path: dir.into(), error };
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(map_err(error)),
+    };
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(map_err)?.path();
+        if path.extension().is_some_and(|ext| ext == extension) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Recursively collect the files in `dir`, skipping the checksums directory itself.
+fn find_installed_files(dir: &Path, skip: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+    let map_err = |error| Error::ChecksumsReadFailed { path: dir.into(), error };
+    for entry in std::fs::read_dir(dir).map_err(map_err)? {
+        let entry = entry.map_err(map_err)?;
+        let path = entry.path();
+        if entry.file_type().map_err(map_err)?.is_dir() {
+            if path != skip {
+                find_installed_files(&path, skip, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn verify_signature(
+    reporter: &dyn Reporter,
+    path: &Path,
+    contents: &[u8],
+    component: &str,
+    signer: Signer<'_>,
+) -> Result<(), Error> {
+    let mut bundle = path.as_os_str().to_os_string();
+    bundle.push(".cosign-bundle");
+    let bundle = PathBuf::from(bundle);
+    if !bundle.is_file() {
+        return Err(Error::ChecksumsSignatureMissing { component: component.into(), bundle });
+    }
+
+    // The bundle is verified offline against the embedded Sigstore trust anchors, so that neither
+    // cosign nor network access are needed to verify the installation.
+    RawCosignBundle::load(&bundle)
+        .and_then(|bundle| verify_blob(&bundle, contents, &signer.identity, signer.root))
+        .map_err(|error| Error::ChecksumsSignatureInvalid {
+            component: component.into(),
+            error: error.into(),
+        })?;
+
+    reporter.success(&format!(
+        "verified the signature of the checksums of {component} (signed by {})",
+        signer.identity.email
+    ));
+    Ok(())
+}
+
+fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut sha256 = Sha256::new();
+    std::io::copy(&mut File::open(path)?, &mut sha256)?;
+    Ok(sha256.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestUtils;
+
+    const HELLO_WORLD: &str = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+
+    // Signed by the test instance of Fulcio and Rekor of document-signatures.
+    const CHECKSUMS: &str = include_str!("../../document-signatures/test_data/checksums.sha256");
+    const BUNDLE: &str =
+        include_str!("../../document-signatures/test_data/checksums.sha256.cosign-bundle");
+    const TEST_ROOT: TrustedRoot = TrustedRoot {
+        fulcio_root: include_str!("../../document-signatures/test_data/fulcio-root.pem"),
+        fulcio_intermediate: include_str!(
+            "../../document-signatures/test_data/fulcio-intermediate.pem"
+        ),
+        rekor_public_key: include_str!("../../document-signatures/test_data/rekor.pub"),
+    };
+    const SIGNER: Signer<'static> = Signer {
+        identity: Identity {
+            email: "signer@ferrous-systems.com",
+            oidc_issuer: "https://login.microsoftonline.com",
+        },
+        root: &TEST_ROOT,
+    };
+
+    fn write(utils: &TestUtils, path: &str, contents: &str) {
+        let path = utils.sysroot().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    fn check(utils: &TestUtils, signer: Option<Signer<'_>>) -> Result<(), Error> {
+        check_inner(utils.reporter(), utils.sysroot(), signer)
+    }
+
+    #[test]
+    fn test_no_checksums() {
+        let utils = TestUtils::new();
+        check(&utils, None).unwrap();
+        utils.assert_report_skipped(
+            "verifying the checksums of the installed files (no checksums found)",
+        );
+        utils.assert_no_reports();
+
+        match check(&utils, Some(SIGNER)) {
+            Err(Error::NoChecksums { .. }) => {}
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_valid_checksums() {
+        let utils = TestUtils::new();
+        write(&utils, "lib/librustc_driver.so", "hello world");
+        write(
+            &utils,
+            "share/ferrocene/checksums/rustc.sha256",
+            &format!("{HELLO_WORLD}  lib/librustc_driver.so\n"),
+        );
+
+        check(&utils, None).unwrap();
+        utils.assert_report_success("verified the checksums of the 1 files installed by rustc");
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_modified_file() {
+        let utils = TestUtils::new();
+        write(&utils, "lib/librustc_driver.so", "tampered");
+        write(
+            &utils,
+            "share/ferrocene/checksums/rustc.sha256",
+            &format!("{HELLO_WORLD}  lib/librustc_driver.so\n"),
+        );
+
+        match check(&utils, None) {
+            Err(Error::InstalledFileModified { path, component }) => {
+                assert_eq!(utils.sysroot().join("lib").join("librustc_driver.so"), path);
+                assert_eq!("rustc", component);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let utils = TestUtils::new();
+        write(
+            &utils,
+            "share/ferrocene/checksums/rustc.sha256",
+            &format!("{HELLO_WORLD}  lib/librustc_driver.so\n"),
+        );
+
+        match check(&utils, None) {
+            Err(Error::InstalledFileMissing { component, .. }) => assert_eq!("rustc", component),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_uncovered_files() {
+        let utils = TestUtils::new();
+        write(&utils, "lib/librustc_driver.so", "hello world");
+        write(&utils, "lib/extra.so", "not from ferrocene");
+        write(
+            &utils,
+            "share/ferrocene/checksums/rustc.sha256",
+            &format!("{HELLO_WORLD}  lib/librustc_driver.so\n"),
+        );
+
+        check(&utils, None).unwrap();
+        utils.assert_report_note(&format!(
+            "file {} is not covered by any checksums",
+            utils.sysroot().join("lib").join("extra.so").display()
+        ));
+        utils.assert_report_success("verified the checksums of the 1 files installed by rustc");
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_missing_component_checksums() {
+        let utils = TestUtils::new();
+        write(&utils, "share/criticaltrust/ferrocene/rustc.json", "{}");
+        write(&utils, "share/criticaltrust/ferrocene/rust-std.json", "{}");
+        write(
+            &utils,
+            "share/ferrocene/checksums/rustc.sha256",
+            "0000000000000000000000000000000000000000000000000000000000000000  \
+             share/criticaltrust/ferrocene/rustc.json\n",
+        );
+
+        match check(&utils, None) {
+            Err(Error::ComponentChecksumsMissing { component, path }) => {
+                assert_eq!("rust-std", component);
+                assert_eq!(utils.sysroot().join(CHECKSUMS_DIR).join("rust-std.sha256"), path);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_checksums() {
+        for contents in ["foo", "abcd  lib/foo", &format!("{HELLO_WORLD}  ../outside")] {
+            let utils = TestUtils::new();
+            write(&utils, "share/ferrocene/checksums/rustc.sha256", contents);
+
+            match check(&utils, None) {
+                Err(Error::InvalidChecksums { line: 1, .. }) => {}
+                other => panic!("unexpected result for {contents:?}: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_missing_signature() {
+        let utils = TestUtils::new();
+        write(&utils, "share/ferrocene/checksums/rustc.sha256", "");
+
+        match check(&utils, Some(SIGNER)) {
+            Err(Error::ChecksumsSignatureMissing { component, .. }) => {
+                assert_eq!("rustc", component)
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_valid_signature() {
+        let utils = TestUtils::new();
+        write(&utils, "lib/librustc_driver.so", "hello world");
+        write(&utils, "share/ferrocene/checksums/rustc.sha256", CHECKSUMS);
+        write(&utils, "share/ferrocene/checksums/rustc.sha256.cosign-bundle", BUNDLE);
+
+        check(&utils, Some(SIGNER)).unwrap();
+        utils.assert_report_success("verified the checksums of the 1 files installed by rustc");
+        utils.assert_report_success(
+            "verified the signature of the checksums of rustc (signed by signer@ferrous-systems.com)",
+        );
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_invalid_signature() {
+        let tampered = CHECKSUMS.replace("librustc_driver", "libstd");
+        let wrong_signer = Signer {
+            identity: Identity { email: "someone-else@example.com", ..SIGNER.identity },
+            ..SIGNER
+        };
+        for (checksums, bundle, signer) in [
+            (CHECKSUMS, "{}", SIGNER),
+            (tampered.as_str(), BUNDLE, SIGNER),
+            (CHECKSUMS, BUNDLE, wrong_signer),
+            (CHECKSUMS, BUNDLE, Signer { root: &SIGSTORE, ..SIGNER }),
+        ] {
+            let utils = TestUtils::new();
+            write(&utils, "lib/librustc_driver.so", "hello world");
+            write(&utils, "share/ferrocene/checksums/rustc.sha256", checksums);
+            write(&utils, "share/ferrocene/checksums/rustc.sha256.cosign-bundle", bundle);
+
+            match check(&utils, Some(signer)) {
+                Err(Error::ChecksumsSignatureInvalid { component, .. }) => {
+                    assert_eq!("rustc", component)
+                }
+                other => panic!("unexpected result: {other:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Command line options.
+//!
+//! The parser is hand-written to avoid adding dependencies to the self-test tool, which only
+//! supports a handful of options anyway.
+
+use crate::error::Error;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Cli {
+    pub(crate) format: Format,
+    /// Only check these targets, instead of every installed target.
+    pub(crate) targets: Vec<String>,
+    /// Run the sample programs of the targets the host can't execute under QEMU.
+    pub(crate) qemu: bool,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+impl Cli {
+    pub(crate) fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, Error> {
+        let mut cli = Cli::default();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let has_value = inline_value.is_some();
+            let value = || {
+                inline_value
+                    .or_else(|| args.next())
+                    .ok_or_else(|| invalid(format!("missing value for {name}")))
+            };
+
+            match name.as_str() {
+                "--format" => {
+                    cli.format = match value()?.as_str() {
+                        "text" => Format::Text,
+                        "json" => Format::Json,
+                        other => return Err(invalid(format!("unknown format `{other}`"))),
+                    }
+                }
+                "--target" => cli.targets.push(value()?),
+                "--qemu" if !has_value => cli.qemu = true,
+                "--qemu" => return Err(invalid("`--qemu` doesn't take a value".into())),
+                _ => return Err(invalid(format!("unknown argument `{name}`"))),
+            }
+        }
+
+        Ok(cli)
+    }
+}
+
+fn invalid(reason: String) -> Error {
+    Error::InvalidCliArgs { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, Error> {
+        Cli::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_no_args() {
+        assert_eq!(Cli::default(), parse(&[]).unwrap());
+    }
+
+    #[test]
+    fn test_format_and_targets() {
+        let expected = Cli {
+            format: Format::Json,
+            targets: vec!["aarch64-unknown-none".into(), "thumbv7em-none-eabihf".into()],
+            qemu: true,
+        };
+        assert_eq!(
+            expected,
+            parse(&[
+                "--format",
+                "json",
+                "--target",
+                "aarch64-unknown-none",
+                "--target=thumbv7em-none-eabihf",
+                "--qemu",
+            ])
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        for args in [
+            &["some-arg"][..],
+            &["--flag"],
+            &["--format"],
+            &["--format", "xml"],
+            &["--target"],
+            &["--qemu=yes"],
+        ] {
+            assert!(matches!(parse(args), Err(Error::InvalidCliArgs { .. })), "{args:?}");
+        }
+    }
+}
@@ -8,8 +8,9 @@
 
 use tempfile::TempDir;
 
-use crate::env;
+use crate::env::{self, Env};
 use crate::error::Error;
+use crate::qemu::{self, Emulator, EmulatorSpec};
 use crate::report::Reporter;
 use crate::targets::Target;
 use crate::utils::run_command;
@@ -63,16 +64,53 @@
     },
 ];
 
+/// Sample program for bare-metal targets, only compiled when executing it under QEMU's system
+/// emulation, as it needs to be linked for the emulated board. It must be compiled after
+/// `addition.rs`.
+static SEMIHOSTING_PROGRAM: SampleProgram = SampleProgram {
+    name: "semihosting.rs",
+    contents: include_bytes!("../sample-programs/semihosting.rs"),
+    rustflags: &["--crate-type", "bin", "--edition", "2021", "--extern", "addition"],
+    expected_executables: &["semihosting"],
+    expected_libraries: &[],
+    expected_rlibs: &[],
+    // See semihosting.rs
+    executable_output: Some(b"123456789"),
+};
+
 /// Compile the [`SAMPLE_PROGRAMS`] for all `targets`.
 ///
 /// Also execute the sample programs for the platform specified by the
-/// `SELFTEST_TARGET` compile-time environment variable.
+/// `SELFTEST_TARGET` compile-time environment variable. When `qemu` is set, the sample programs
+/// of the other targets are executed under QEMU, if it is installed.
 pub(crate) fn check(
     reporter: &dyn Reporter,
+    env: &Env,
     sysroot: &Path,
     targets: &[Target],
+    qemu: bool,
 ) -> Result<(), Error> {
-    targets.iter().try_for_each(|target| check_target(reporter, sysroot, target, SAMPLE_PROGRAMS))
+    targets.iter().try_for_each(|target| {
+        let emulator = if qemu && target.tuple != env::SELFTEST_TARGET {
+            find_emulator(reporter, env, target)
+        } else {
+            None
+        };
+        check_target(reporter, sysroot, target, SAMPLE_PROGRAMS, emulator.as_ref())
+    })
+}
+
+fn find_emulator(reporter: &dyn Reporter, env: &Env, target: &Target) -> Option<Emulator> {
+    let reason = match qemu::find(env, target.spec) {
+        Ok(Some(emulator)) => return Some(emulator),
+        Ok(None) => "no emulator is configured for it".to_string(),
+        Err(binary) => format!("{binary} is not installed"),
+    };
+    reporter.skipped(&format!(
+        "executing sample programs for target {} under QEMU ({reason})",
+        target.tuple
+    ));
+    None
 }
 
 fn check_target(
@@ -80,6 +118,7 @@ fn check_target(
     sysroot: &Path,
     target: &Target,
     programs: &[SampleProgram],
+    emulator: Option<&Emulator>,
 ) -> Result<(), Error> {
     let temp = tempfile::Builder::new()
         .prefix("fst-")
@@ -91,6 +130,10 @@ fn check_target(
     create_tmp_compilation_dir(&ctx.output_dir)?;
     let mut expected_artifacts = ExpectedFiles::new(&ctx.output_dir);
 
+    // Programs built for targets with an operating system can be executed as-is by QEMU.
+    let user_emulator =
+        emulator.filter(|emulator| matches!(emulator.spec, EmulatorSpec::User { .. }));
+
     for program in programs {
         let expected_binary_paths = program
             .expected_executables
@@ -121,23 +164,72 @@ fn check_target(
             .collect::<Vec<_>>();
         expected_artifacts.add(expected_rlib_paths);
 
-        compile(&ctx, program)?;
+        compile(&ctx, program, &[])?;
         expected_artifacts.check(program.name)?;
 
-        let should_run = (ctx.target.tuple == env::SELFTEST_TARGET)
+        let should_run = (ctx.target.tuple == env::SELFTEST_TARGET || user_emulator.is_some())
             .then_some(program.executable_output)
             .flatten();
         if let Some(expected_output) = should_run {
-            run(&ctx, program, expected_output)?
+            run(&ctx, program, expected_output, user_emulator)?
         }
 
         reporter.success(&format!(
-            "compiled {}sample program `{}` for target {}",
+            "compiled {}sample program `{}` for target {}{}",
             if should_run.is_some() { "and ran " } else { "" },
             program.name,
-            target.tuple
+            target.tuple,
+            match user_emulator {
+                Some(emulator) if should_run.is_some() =>
+                    format!(" under {}", emulator.spec.binary()),
+                _ => String::new(),
+            }
         ));
     }
+
+    if let Some(emulator) = emulator
+        && let EmulatorSpec::System(board) = &emulator.spec
+    {
+        check_semihosting(reporter, &ctx, &mut expected_artifacts, emulator, board.linker_script)?;
+    }
+    Ok(())
+}
+
+/// Compile [`SEMIHOSTING_PROGRAM`] for the board emulated by `emulator`, and execute it.
+fn check_semihosting(
+    reporter: &dyn Reporter,
+    ctx: &Context<'_>,
+    expected_artifacts: &mut ExpectedFiles,
+    emulator: &Emulator,
+    linker_script: &str,
+) -> Result<(), Error> {
+    let program = &SEMIHOSTING_PROGRAM;
+
+    let linker_script_path = ctx.source_dir.join("semihosting.ld");
+    std::fs::write(&linker_script_path, linker_script).map_err(|error| {
+        Error::WritingSampleProgramFailed {
+            name: program.name.into(),
+            dest: linker_script_path.clone(),
+            error,
+        }
+    })?;
+    let mut link_arg = OsString::from("-Clink-arg=-T");
+    link_arg.push(&linker_script_path);
+
+    expected_artifacts.add(program.expected_executables.iter().map(|name| name.to_string()));
+    compile(ctx, program, &[link_arg])?;
+    expected_artifacts.check(program.name)?;
+
+    if let Some(expected_output) = program.executable_output {
+        run(ctx, program, expected_output, Some(emulator))?;
+    }
+
+    reporter.success(&format!(
+        "compiled and ran sample program `{}` for target {} under {}",
+        program.name,
+        ctx.target.tuple,
+        emulator.spec.binary()
+    ));
     Ok(())
 }
 
@@ -146,7 +238,11 @@ fn create_tmp_compilation_dir(path: &Path) -> Result<(), Error> {
         .map_err(|error| Error::TemporaryCompilationDirectoryCreationFailed { error })
 }
 
-fn compile(ctx: &Context<'_>, program: &SampleProgram) -> Result<(), Error> {
+fn compile(
+    ctx: &Context<'_>,
+    program: &SampleProgram,
+    extra_args: &[OsString],
+) -> Result<(), Error> {
     let program_path = ctx.source_dir.join(program.name);
     std::fs::write(&program_path, program.contents).map_err(|error| {
         Error::WritingSampleProgramFailed {
@@ -170,6 +266,7 @@ fn compile(ctx: &Context<'_>, program: &SampleProgram) -> Result<(), Error> {
     }
     cmd.args(program.rustflags);
     cmd.args(&ctx.target.rustflags);
+    cmd.args(extra_args);
     cmd.arg(&program_path);
 
     run_command(&mut cmd)
@@ -178,15 +275,22 @@ fn compile(ctx: &Context<'_>, program: &SampleProgram) -> Result<(), Error> {
     Ok(())
 }
 
-fn run(ctx: &Context<'_>, program: &SampleProgram, expected_output: &[u8]) -> Result<(), Error> {
+fn run(
+    ctx: &Context<'_>,
+    program: &SampleProgram,
+    expected_output: &[u8],
+    emulator: Option<&Emulator>,
+) -> Result<(), Error> {
     // where is it
     let bin_name = program.name.replace(".rs", "");
     let bin_path = ctx.output_dir.join(bin_name);
     // now try and execute it
-    let mut cmd = Command::new(bin_path);
-    let output = cmd
-        .output()
-        .map_err(|error| Error::RunningSampleProgramFailed { name: program.name.into(), error })?;
+    let output = match emulator {
+        Some(emulator) => emulator.run(program.name, &bin_path)?,
+        None => Command::new(bin_path).output().map_err(|error| {
+            Error::RunningSampleProgramFailed { name: program.name.into(), error }
+        })?,
+    };
     if output.stdout != expected_output {
         Err(Error::SampleProgramOutputWrong {
             name: program.name.into(),
@@ -296,7 +400,11 @@ fn test_all_sample_program_source_files_are_registered() {
             .expect("sample-programs directory not found")
             .map(|entry| entry.unwrap().path().file_name().unwrap().to_str().unwrap().to_string())
             .collect::<Vec<_>>();
-        let mut registered = SAMPLE_PROGRAMS.iter().map(|p| p.name.to_string()).collect::<Vec<_>>();
+        let mut registered = SAMPLE_PROGRAMS
+            .iter()
+            .chain([&SEMIHOSTING_PROGRAM])
+            .map(|p| p.name.to_string())
+            .collect::<Vec<_>>();
 
         actual.sort();
         registered.sort();
@@ -382,7 +490,99 @@ fn main() {
         let utils = TestUtils::new();
         utils.bin("rustc").program_source(RUSTC_SOURCE).create();
 
-        check_target(utils.reporter(), utils.sysroot(), &target, TEST_PROGRAMS).unwrap();
+        check_target(utils.reporter(), utils.sysroot(), &target, TEST_PROGRAMS, None).unwrap();
+    }
+
+    #[test]
+    fn test_check_target_under_system_emulator() {
+        const RUSTC_SOURCE: &str = r#"
+            fn main() {
+                let args = std::env::args().skip(1).collect::<Vec<_>>();
+                let out_dir = &args[args.iter().position(|a| a == "--out-dir").unwrap() + 1];
+                let source = args.last().unwrap();
+                if source.ends_with("foo.rs") {
+                    std::fs::write(format!("{out_dir}/libfoo.rlib"), b"").unwrap();
+                } else if source.ends_with("semihosting.rs") {
+                    assert!(args.iter().any(|a| a.starts_with("-Clink-arg=-T")));
+                    std::fs::write(format!("{out_dir}/semihosting"), b"").unwrap();
+                } else {
+                    panic!("unexpected args: {args:?}");
+                }
+            }
+        "#;
+        const QEMU_SOURCE: &str = r#"
+            fn main() {
+                let args = std::env::args().skip(1).collect::<Vec<_>>();
+                assert_eq!(["-M", "mps2-an386"], args[..2]);
+                assert!(args.iter().any(|a| a == "-kernel"));
+                // Like QEMU, write the semihosting console to stderr unless it is sent to a
+                // character device.
+                let config = &args[args.iter().position(|a| a == "-semihosting-config").unwrap() + 1];
+                if config.contains("chardev=console")
+                    && args.windows(2).any(|w| w == ["-chardev", "stdio,id=console"])
+                {
+                    print!("123456789");
+                } else {
+                    eprint!("123456789");
+                }
+            }
+        "#;
+
+        const TEST_PROGRAMS: &[SampleProgram] = &[SampleProgram {
+            name: "foo.rs",
+            contents: b"pub fn foo() {}",
+            rustflags: &["--crate-type", "lib"],
+            expected_rlibs: &["foo"],
+            expected_executables: &[],
+            expected_libraries: &[],
+            executable_output: None,
+        }];
+
+        let target = Target {
+            spec: &TargetSpec {
+                tuple: "thumbv7em-none-eabihf",
+                std: false,
+                linker: Linker::BundledLld,
+            },
+            rustflags: Vec::new(),
+        };
+
+        let utils = TestUtils::new();
+        utils.bin("rustc").program_source(RUSTC_SOURCE).create();
+        utils.bin("qemu-system-arm").program_source(QEMU_SOURCE).create();
+
+        let emulator = find_emulator(utils.reporter(), utils.env(), &target);
+        check_target(utils.reporter(), utils.sysroot(), &target, TEST_PROGRAMS, emulator.as_ref())
+            .unwrap();
+
+        utils.assert_report_success(
+            "compiled and ran sample program `semihosting.rs` for target thumbv7em-none-eabihf \
+             under qemu-system-arm",
+        );
+        utils.assert_report_success(
+            "compiled sample program `foo.rs` for target thumbv7em-none-eabihf",
+        );
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_find_emulator_not_installed() {
+        let target = Target {
+            spec: &TargetSpec {
+                tuple: "aarch64-unknown-none",
+                std: false,
+                linker: Linker::BundledLld,
+            },
+            rustflags: Vec::new(),
+        };
+
+        let utils = TestUtils::new();
+        assert!(find_emulator(utils.reporter(), utils.env(), &target).is_none());
+        utils.assert_report_skipped(
+            "executing sample programs for target aarch64-unknown-none under QEMU \
+             (qemu-system-aarch64 is not installed)",
+        );
+        utils.assert_no_reports();
     }
 
     #[test]
@@ -461,7 +661,7 @@ fn test_compile_failed_to_write_program() {
             executable_output: None,
         };
 
-        match compile(&context, &program) {
+        match compile(&context, &program, &[]) {
             Err(Error::WritingSampleProgramFailed { name, dest, error }) => {
                 assert_eq!("example.rs", name);
                 assert_eq!(tempdir.path().join("missing").join("example.rs"), dest);
@@ -508,7 +708,7 @@ fn test_compile_failed_invocation() {
             executable_output: None,
         };
 
-        match compile(&context, &program) {
+        match compile(&context, &program, &[]) {
             Err(Error::SampleProgramCompilationFailed { name, error }) => {
                 assert_eq!("example.rs", name);
                 assert_eq!(rustc, error.path);
@@ -589,6 +789,6 @@ fn compile_success_inner(std: bool, extra_arg: &[&str]) {
             executable_output: None,
         };
 
-        compile(&context, &program).unwrap();
+        compile(&context, &program, &[]).unwrap();
     }
 }
@@ -18,6 +18,10 @@
 pub(crate) const SELFTEST_TARGET: &str = env!("SELFTEST_TARGET");
 pub(crate) const SELFTEST_RUST_HASH: Option<&str> = option_env!("SELFTEST_RUST_HASH");
 pub(crate) const SELFTEST_CARGO_HASH: Option<&str> = option_env!("SELFTEST_CARGO_HASH");
+/// The cosign identity the checksums of the installed files are signed by, if they are signed
+pub(crate) const SELFTEST_SIGNING_IDENTITY: Option<&str> = option_env!("SELFTEST_SIGNING_IDENTITY");
+pub(crate) const SELFTEST_SIGNING_OIDC_ISSUER: Option<&str> =
+    option_env!("SELFTEST_SIGNING_OIDC_ISSUER");
 
 /// Run-time environment variables
 #[derive(Debug, Default, PartialEq)]
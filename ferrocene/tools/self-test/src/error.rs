@@ -10,8 +10,8 @@
 
 #[derive(Debug, ThisError)]
 pub(crate) enum Error {
-    #[error("CLI args are not used, so should not be provided")]
-    CliArgsProvided,
+    #[error("invalid CLI arguments: {reason}")]
+    InvalidCliArgs { reason: String },
     #[error("could not detect the sysroot of the Ferrocene installation")]
     NoSysroot,
     #[error("binary {name} expected (inside {}), but is not there", directory.display())]
@@ -98,6 +98,36 @@ pub(crate) enum Error {
     },
     #[error("sample program {name} should have produced {expected:?}, actually produced {found:?}")]
     SampleProgramOutputWrong { name: String, expected: Vec<u8>, found: Vec<u8> },
+    #[error("target {target} is not supported by Ferrocene")]
+    UnsupportedTarget { target: String },
+    #[error("target {target} was requested, but is not installed")]
+    TargetNotInstalled { target: String },
+    #[error("sample program {name} failed when executed with {emulator}: {reason}")]
+    EmulatedSampleProgramFailed { name: String, emulator: String, reason: String },
+    #[error("no checksums of the installed files found in {}", path.display())]
+    NoChecksums { path: PathBuf },
+    #[error("failed to read {} while verifying the checksums of the installed files", path.display())]
+    ChecksumsReadFailed {
+        path: PathBuf,
+        #[source]
+        error: std::io::Error,
+    },
+    #[error("line {line} of the checksums file {} is invalid", path.display())]
+    InvalidChecksums { path: PathBuf, line: usize },
+    #[error("file {} installed by {component} is missing", path.display())]
+    InstalledFileMissing { path: PathBuf, component: String },
+    #[error("file {} installed by {component} was modified after installation", path.display())]
+    InstalledFileModified { path: PathBuf, component: String },
+    #[error("the checksums of {component} are not signed (missing {})", bundle.display())]
+    ChecksumsSignatureMissing { component: String, bundle: PathBuf },
+    #[error("the signature of the checksums of {component} is invalid")]
+    ChecksumsSignatureInvalid {
+        component: String,
+        #[source]
+        error: Box<dyn std::error::Error + Send + Sync>,
+    },
+    #[error("component {component} is installed, but its checksums are missing ({})", path.display())]
+    ComponentChecksumsMissing { component: String, path: PathBuf },
 }
 
 impl Error {
@@ -115,7 +145,7 @@ pub(crate) fn code(&self) -> u8 {
             Error::DuplicateTargetLibrary { .. } => 9,
             Error::TargetLibraryDiscoveryFailed { .. } => 10,
             Error::CCompilerNotFound { .. } => 11,
-            Error::CliArgsProvided => 12,
+            Error::InvalidCliArgs { .. } => 12,
             Error::BundledLinkerMissing(_) => 15,
             Error::NonUtf8Path { .. } => 16,
             Error::TemporaryCompilationDirectoryCreationFailed { .. } => 17,
@@ -128,6 +158,17 @@ pub(crate) fn code(&self) -> u8 {
             Error::WrongLinkerArgs { .. } => 24,
             Error::RunningSampleProgramFailed { .. } => 25,
             Error::SampleProgramOutputWrong { .. } => 26,
+            Error::UnsupportedTarget { .. } => 27,
+            Error::TargetNotInstalled { .. } => 28,
+            Error::EmulatedSampleProgramFailed { .. } => 29,
+            Error::NoChecksums { .. } => 30,
+            Error::ChecksumsReadFailed { .. } => 31,
+            Error::InvalidChecksums { .. } => 32,
+            Error::InstalledFileMissing { .. } => 33,
+            Error::InstalledFileModified { .. } => 34,
+            Error::ChecksumsSignatureMissing { .. } => 35,
+            Error::ChecksumsSignatureInvalid { .. } => 36,
+            Error::ComponentChecksumsMissing { .. } => 37,
         }
     }
 
@@ -60,6 +60,7 @@ pub(crate) fn check_and_add_rustflags(
             Linker::BundledLld => {
                 reporter
                     .skipped(&format!("Target `{}` does not require a C compiler", target.tuple));
+                reporter.linker(target.tuple, LINKER_NAME);
                 continue 'target_loop;
             }
             Linker::HostCc => &[""],
@@ -119,6 +120,7 @@ pub(crate) fn check_and_add_rustflags(
                                 "Found C compiler `{}` for target `{}`",
                                 compiler_name, target.tuple
                             ));
+                            reporter.linker(target.tuple, &compiler_name);
                             target.rustflags.push(format!("-Clinker={compiler_name}"));
                             for cc_arg in cc_args {
                                 target.rustflags.push(format!("-Clink-arg={cc_arg}"));
@@ -442,6 +444,7 @@ fn find_bundled_lld_wrapper(reporter: &dyn Reporter, sysroot: &Path) -> Result<P
 
 pub(crate) fn report_linker_flags(reporter: &dyn Reporter, targets: &[Target]) {
     for target in targets {
+        reporter.rustflags(target.tuple, &target.rustflags);
         if target.rustflags.is_empty() {
             reporter.info(&format!("Target '{}' requires no special linker flags", target.tuple));
         } else {
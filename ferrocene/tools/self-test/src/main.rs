@@ -2,10 +2,13 @@
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
 mod binaries;
+mod checksums;
+mod cli;
 mod compile;
 mod env;
 mod error;
 mod linkers;
+mod qemu;
 mod report;
 mod targets;
 mod utils;
@@ -15,24 +18,24 @@
 
 use std::io::{self, IsTerminal};
 
+use crate::cli::{Cli, Format};
 use crate::env::Env;
 use crate::error::Error;
-use crate::report::{Reporter, StderrReporter};
+use crate::report::{JsonReporter, Reporter, StderrReporter};
 use crate::utils::get_sysroot;
 
-fn main_inner(reporter: &dyn Reporter) -> Result<(), Error> {
-    if std::env::args().len() > 1 {
-        return Err(Error::CliArgsProvided);
-    }
+fn main_inner(reporter: &dyn Reporter, cli: &Cli) -> Result<(), Error> {
+    targets::check_supported(&cli.targets)?;
     let env = Env::gather()?;
 
     let sysroot = get_sysroot().ok_or(Error::NoSysroot)?;
     reporter.info(&format!("using sysroot {}", sysroot.display()));
 
+    checksums::check(reporter, &sysroot)?;
     binaries::check(reporter, &sysroot)?;
-    let mut targets = targets::check(reporter, &sysroot)?;
+    let mut targets = targets::check(reporter, &sysroot, &cli.targets)?;
     linkers::check_and_add_rustflags(reporter, &env, &sysroot, &mut targets)?;
-    compile::check(reporter, &sysroot, &targets)?;
+    compile::check(reporter, &env, &sysroot, &targets, cli.qemu)?;
     linkers::report_linker_flags(reporter, &targets);
 
     reporter.success("Ferrocene self-check completed!");
@@ -40,14 +43,35 @@ fn main_inner(reporter: &dyn Reporter) -> Result<(), Error> {
 }
 
 fn main() {
-    let reporter =
+    let stderr_reporter =
         if io::stderr().is_terminal() { StderrReporter::color() } else { StderrReporter::plain() };
 
-    match main_inner(&reporter) {
-        Ok(()) => {}
-        Err(err) => {
-            reporter.error(&err);
-            std::process::exit(err.code() as i32);
+    let cli = match Cli::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(err) => exit_with_error(&stderr_reporter, err),
+    };
+
+    match cli.format {
+        Format::Text => {
+            if let Err(err) = main_inner(&stderr_reporter, &cli) {
+                exit_with_error(&stderr_reporter, err);
+            }
+        }
+        Format::Json => {
+            let reporter = JsonReporter::default();
+            let result = main_inner(&reporter, &cli);
+            if let Err(err) = &result {
+                reporter.error(err);
+            }
+            print!("{}", reporter.render());
+            if let Err(err) = result {
+                std::process::exit(err.code() as i32);
+            }
         }
     }
 }
+
+fn exit_with_error(reporter: &dyn Reporter, err: Error) -> ! {
+    reporter.error(&err);
+    std::process::exit(err.code() as i32);
+}
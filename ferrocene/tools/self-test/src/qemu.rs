@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Execution of the sample programs under QEMU, for targets that can't run on the host.
+//!
+//! Targets with an operating system run the same sample programs as the host under QEMU's user
+//! mode emulation (`qemu-<arch>`). Bare-metal targets instead run a dedicated sample program under
+//! QEMU's system emulation (`qemu-system-<arch>`), on a board with the same CPU architecture,
+//! which reports its output and outcome through semihosting. The boards and their linker scripts
+//! are the ones of `semihosting-runner`, which runs the tests of these targets.
+
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use semihosting_runner::Board;
+
+use crate::env::Env;
+use crate::error::Error;
+use crate::linkers::Linker;
+use crate::targets::TargetSpec;
+use crate::utils::find_binary_in_path;
+
+/// How long a sample program may run under emulation before being considered stuck.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum EmulatorSpec {
+    User { binary: &'static str },
+    System(Board),
+}
+
+impl EmulatorSpec {
+    pub(crate) fn for_target(tuple: &str) -> Option<Self> {
+        let user = |binary| Some(EmulatorSpec::User { binary });
+        match tuple {
+            "aarch64-unknown-linux-gnu" | "aarch64-unknown-linux-musl" => user("qemu-aarch64"),
+            "x86_64-unknown-linux-gnu" | "x86_64-unknown-linux-musl" => user("qemu-x86_64"),
+            "riscv64gc-unknown-linux-gnu" => user("qemu-riscv64"),
+            "powerpc64le-unknown-linux-gnu" => user("qemu-ppc64le"),
+            "s390x-unknown-linux-gnu" => user("qemu-s390x"),
+            _ => Board::for_target(tuple).map(EmulatorSpec::System),
+        }
+    }
+
+    pub(crate) fn binary(&self) -> &'static str {
+        match self {
+            EmulatorSpec::User { binary } => binary,
+            EmulatorSpec::System(board) => board.qemu,
+        }
+    }
+}
+
+/// An emulator found on the system for a target.
+#[derive(Debug)]
+pub(crate) struct Emulator {
+    pub(crate) spec: EmulatorSpec,
+    pub(crate) path: PathBuf,
+    /// Where the dynamic loader and system libraries of the target are, for user mode emulation
+    /// of dynamically linked programs.
+    ld_prefix: Option<PathBuf>,
+}
+
+/// Find the emulator for a target, returning `Err` with the name of the missing emulator if it is
+/// not installed, or `Ok(None)` if the target has no emulator configured.
+pub(crate) fn find(env: &Env, target: &TargetSpec) -> Result<Option<Emulator>, &'static str> {
+    let Some(spec) = EmulatorSpec::for_target(target.tuple) else { return Ok(None) };
+    let path = find_binary_in_path(env, spec.binary()).map_err(|_| spec.binary())?;
+
+    // Cross compilers for glibc targets install the target's system root in `/usr/<prefix>`,
+    // e.g. `/usr/aarch64-linux-gnu`.
+    let ld_prefix = match target.linker {
+        Linker::CrossCc(prefixes) => prefixes
+            .iter()
+            .map(|prefix| Path::new("/usr").join(prefix.trim_end_matches('-')))
+            .find(|path| path.is_dir()),
+        Linker::HostCc | Linker::BundledLld => None,
+    };
+
+    Ok(Some(Emulator { spec, path, ld_prefix }))
+}
+
+impl Emulator {
+    /// Run `program` under the emulator, returning its output.
+    pub(crate) fn run(&self, name: &str, program: &Path) -> Result<Output, Error> {
+        let mut cmd = Command::new(&self.path);
+        match &self.spec {
+            EmulatorSpec::User { .. } => {
+                if let Some(ld_prefix) = &self.ld_prefix {
+                    cmd.env("QEMU_LD_PREFIX", ld_prefix);
+                }
+                cmd.arg(program);
+            }
+            EmulatorSpec::System(board) => {
+                cmd.args(board.args(program, &[]));
+            }
+        }
+
+        let running_failed = |error| Error::RunningSampleProgramFailed { name: name.into(), error };
+        let mut child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(running_failed)?;
+
+        // Drain both pipes while waiting, otherwise a program writing more than the pipe buffer
+        // blocks forever.
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+
+        let start = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(running_failed)? {
+                break status;
+            }
+            if start.elapsed() > TIMEOUT {
+                // The program is stuck, most likely in a fault handler.
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::EmulatedSampleProgramFailed {
+                    name: name.into(),
+                    emulator: self.spec.binary().into(),
+                    reason: format!("timed out after {} seconds", TIMEOUT.as_secs()),
+                });
+            }
+            thread::sleep(Duration::from_millis(50));
+        };
+
+        let stdout = stdout.join().expect("reading stdout panicked").map_err(running_failed)?;
+        let stderr = stderr.join().expect("reading stderr panicked").map_err(running_failed)?;
+        let output = Output { status, stdout, stderr };
+        if output.status.success() {
+            Ok(output)
+        } else {
+            Err(Error::EmulatedSampleProgramFailed {
+                name: name.into(),
+                emulator: self.spec.binary().into(),
+                reason: format!(
+                    "exited with {}, stderr:\n{}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr)
+                ),
+            })
+        }
+    }
+}
+
+/// Read `pipe` to the end on a separate thread.
+fn drain(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<io::Result<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            pipe.read_to_end(&mut buf)?;
+        }
+        Ok(buf)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestUtils;
+
+    #[test]
+    fn test_for_target() {
+        assert_eq!(
+            Some(EmulatorSpec::User { binary: "qemu-aarch64" }),
+            EmulatorSpec::for_target("aarch64-unknown-linux-gnu")
+        );
+        assert!(matches!(
+            EmulatorSpec::for_target("thumbv7em-none-eabihf"),
+            Some(EmulatorSpec::System(Board { qemu: "qemu-system-arm", .. }))
+        ));
+        assert_eq!(None, EmulatorSpec::for_target("x86_64-pc-windows-msvc"));
+    }
+
+    #[test]
+    fn test_find_missing_emulator() {
+        let utils = TestUtils::new();
+        let target =
+            TargetSpec { tuple: "aarch64-unknown-none", std: false, linker: Linker::BundledLld };
+        assert_eq!("qemu-system-aarch64", find(utils.env(), &target).unwrap_err());
+    }
+
+    #[test]
+    fn test_find_no_emulator() {
+        let utils = TestUtils::new();
+        let target =
+            TargetSpec { tuple: "armv7r-none-eabihf", std: false, linker: Linker::BundledLld };
+        assert!(find(utils.env(), &target).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_run_failure() {
+        let utils = TestUtils::new();
+        // Empty expected args will result in the emulator failing.
+        let path = utils.bin("qemu-aarch64").expected_args(&[]).create();
+        let emulator =
+            Emulator { spec: EmulatorSpec::User { binary: "qemu-aarch64" }, path, ld_prefix: None };
+
+        match emulator.run("assertion.rs", Path::new("assertion")) {
+            Err(Error::EmulatedSampleProgramFailed { name, emulator, .. }) => {
+                assert_eq!("assertion.rs", name);
+                assert_eq!("qemu-aarch64", emulator);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_run_large_output() {
+        let utils = TestUtils::new();
+        // More than fits in the pipe buffers, on both stdout and stderr.
+        let path = utils
+            .bin("qemu-aarch64")
+            .program_source(
+                r#"
+                fn main() {
+                    let line = "x".repeat(1023);
+                    for _ in 0..1024 {
+                        println!("{line}");
+                        eprintln!("{line}");
+                    }
+                }
+                "#,
+            )
+            .create();
+        let emulator =
+            Emulator { spec: EmulatorSpec::User { binary: "qemu-aarch64" }, path, ld_prefix: None };
+
+        let output = emulator.run("output.rs", Path::new("output")).unwrap();
+        assert_eq!(1024 * 1024, output.stdout.len());
+        assert_eq!(1024 * 1024, output.stderr.len());
+    }
+}
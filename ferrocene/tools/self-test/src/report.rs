@@ -1,8 +1,11 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
+use std::cell::RefCell;
 use std::error::Error as _;
+use std::fmt::Write as _;
 
+use crate::env;
 use crate::error::{CommandError, CommandErrorKind, Error};
 
 const TERMINAL_WIDTH: usize = 79;
@@ -13,6 +16,16 @@ pub(crate) trait Reporter {
     fn note(&self, message: &str);
     fn info(&self, message: &str);
     fn error(&self, error: &Error);
+
+    // The following methods record structured information about the installation. The messages
+    // reported by the checks already contain it, so only machine-readable reporters need them.
+
+    /// Record the version of a binary of the installation.
+    fn binary_version(&self, _binary: &str, _release: &str, _commit_hash: &str) {}
+    /// Record the linker used for a target.
+    fn linker(&self, _target: &str, _linker: &str) {}
+    /// Record the flags `rustc` needs to compile for a target.
+    fn rustflags(&self, _target: &str, _rustflags: &[String]) {}
 }
 
 pub(crate) struct StderrReporter {
@@ -119,3 +132,195 @@ fn error(&self, error: &Error) {
         }
     }
 }
+
+/// Reporter recording everything it is told, to print it as a JSON document at the end, for
+/// installation records that must be archived.
+#[derive(Default)]
+pub(crate) struct JsonReporter {
+    report: RefCell<JsonReport>,
+}
+
+#[derive(Default)]
+struct JsonReport {
+    checks: Vec<(&'static str, String)>,
+    binaries: Vec<(String, String, String)>,
+    targets: Vec<JsonTarget>,
+    error: Option<(u8, String, Vec<String>)>,
+}
+
+struct JsonTarget {
+    tuple: String,
+    linker: Option<String>,
+    rustflags: Vec<String>,
+}
+
+impl JsonReporter {
+    fn target<R>(&self, tuple: &str, f: impl FnOnce(&mut JsonTarget) -> R) -> R {
+        let targets = &mut self.report.borrow_mut().targets;
+        let index = match targets.iter().position(|target| target.tuple == tuple) {
+            Some(index) => index,
+            None => {
+                targets.push(JsonTarget {
+                    tuple: tuple.into(),
+                    linker: None,
+                    rustflags: Vec::new(),
+                });
+                targets.len() - 1
+            }
+        };
+        f(&mut targets[index])
+    }
+
+    /// Render the report, once all the checks were executed.
+    pub(crate) fn render(&self) -> String {
+        let report = self.report.borrow();
+        let mut out = String::new();
+
+        let status = if report.error.is_some() { "error" } else { "success" };
+        writeln!(out, "{{").unwrap();
+        writeln!(out, "  \"status\": {},", json_string(status)).unwrap();
+        writeln!(out, "  \"release\": {},", json_string(env::CFG_RELEASE)).unwrap();
+        writeln!(out, "  \"host\": {},", json_string(env::SELFTEST_TARGET)).unwrap();
+
+        let checks = report.checks.iter().map(|(kind, message)| {
+            format!("{{\"kind\": {}, \"message\": {}}}", json_string(kind), json_string(message))
+        });
+        writeln!(out, "  \"checks\": {},", json_array(checks)).unwrap();
+
+        let binaries = report.binaries.iter().map(|(name, release, commit_hash)| {
+            format!(
+                "{{\"name\": {}, \"release\": {}, \"commit_hash\": {}}}",
+                json_string(name),
+                json_string(release),
+                json_string(commit_hash)
+            )
+        });
+        writeln!(out, "  \"binaries\": {},", json_array(binaries)).unwrap();
+
+        let targets = report.targets.iter().map(|target| {
+            format!(
+                "{{\"tuple\": {}, \"linker\": {}, \"rustflags\": {}}}",
+                json_string(&target.tuple),
+                target.linker.as_deref().map(json_string).unwrap_or_else(|| "null".into()),
+                json_array(target.rustflags.iter().map(|flag| json_string(flag)))
+            )
+        });
+        writeln!(out, "  \"targets\": {},", json_array(targets)).unwrap();
+
+        let error = match &report.error {
+            Some((code, message, causes)) => format!(
+                "{{\"code\": {}, \"message\": {}, \"causes\": {}}}",
+                json_string(&format!("FST_{code:0>3}")),
+                json_string(message),
+                json_array(causes.iter().map(|cause| json_string(cause)))
+            ),
+            None => "null".into(),
+        };
+        writeln!(out, "  \"error\": {error}").unwrap();
+        writeln!(out, "}}").unwrap();
+        out
+    }
+}
+
+impl Reporter for JsonReporter {
+    fn success(&self, message: &str) {
+        self.report.borrow_mut().checks.push(("success", message.into()));
+    }
+
+    fn skipped(&self, message: &str) {
+        self.report.borrow_mut().checks.push(("skipped", message.into()));
+    }
+
+    fn note(&self, message: &str) {
+        self.report.borrow_mut().checks.push(("note", message.into()));
+    }
+
+    fn info(&self, message: &str) {
+        self.report.borrow_mut().checks.push(("info", message.into()));
+    }
+
+    fn error(&self, error: &Error) {
+        let mut causes = Vec::new();
+        let mut source = error.source();
+        while let Some(s) = source {
+            causes.push(s.to_string());
+            source = s.source();
+        }
+        self.report.borrow_mut().error = Some((error.code(), error.to_string(), causes));
+    }
+
+    fn binary_version(&self, binary: &str, release: &str, commit_hash: &str) {
+        self.report.borrow_mut().binaries.push((binary.into(), release.into(), commit_hash.into()));
+    }
+
+    fn linker(&self, target: &str, linker: &str) {
+        self.target(target, |target| target.linker = Some(linker.into()));
+    }
+
+    fn rustflags(&self, target: &str, rustflags: &[String]) {
+        self.target(target, |target| target.rustflags = rustflags.to_vec());
+    }
+}
+
+fn json_array(items: impl Iterator<Item = String>) -> String {
+    format!("[{}]", items.collect::<Vec<_>>().join(", "))
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(r#""plain""#, json_string("plain"));
+        assert_eq!(
+            r#""a \"quoted\" \\ path\n\t\u0001""#,
+            json_string("a \"quoted\" \\ path\n\t\u{1}")
+        );
+    }
+
+    #[test]
+    fn test_json_reporter() {
+        let reporter = JsonReporter::default();
+        reporter.success("binary rustc is valid");
+        reporter.binary_version("rustc", "1.0.0", "0123456789abcdef");
+        reporter.linker("x86_64-unknown-linux-gnu", "cc");
+        reporter.rustflags("x86_64-unknown-linux-gnu", &["-Clinker=cc".into()]);
+        reporter.rustflags("aarch64-unknown-none", &[]);
+        reporter.error(&Error::TargetNotInstalled { target: "thumbv7em-none-eabihf".into() });
+
+        let expected = format!(
+            r#"{{
+  "status": "error",
+  "release": "{}",
+  "host": "{}",
+  "checks": [{{"kind": "success", "message": "binary rustc is valid"}}],
+  "binaries": [{{"name": "rustc", "release": "1.0.0", "commit_hash": "0123456789abcdef"}}],
+  "targets": [{{"tuple": "x86_64-unknown-linux-gnu", "linker": "cc", "rustflags": ["-Clinker=cc"]}}, {{"tuple": "aarch64-unknown-none", "linker": null, "rustflags": []}}],
+  "error": {{"code": "FST_028", "message": "target thumbv7em-none-eabihf was requested, but is not installed", "causes": []}}
+}}
+"#,
+            env::CFG_RELEASE,
+            env::SELFTEST_TARGET,
+        );
+        assert_eq!(expected, reporter.render());
+    }
+}
@@ -133,15 +133,38 @@ fn deref(&self) -> &Self::Target {
     }
 }
 
+/// Check that the targets requested on the command line are supported, before doing anything.
+pub(crate) fn check_supported(targets: &[String]) -> Result<(), Error> {
+    match targets.iter().find(|target| !SUPPORTED_TARGETS.iter().any(|spec| spec.tuple == *target))
+    {
+        Some(target) => Err(Error::UnsupportedTarget { target: target.clone() }),
+        None => Ok(()),
+    }
+}
+
 /// Check which of the supported targets are installed.
-pub(crate) fn check(reporter: &dyn Reporter, sysroot: &Path) -> Result<Vec<Target>, Error> {
-    SUPPORTED_TARGETS.iter().try_fold(Vec::new(), |mut found, target| {
-        match check_target(reporter, sysroot, target)? {
-            CheckTargetOutcome::Missing => {}
-            CheckTargetOutcome::Found => found.push(Target { spec: target, rustflags: Vec::new() }),
-        }
-        Ok(found)
-    })
+///
+/// If `only` is not empty, only those targets are checked, and all of them must be installed.
+pub(crate) fn check(
+    reporter: &dyn Reporter,
+    sysroot: &Path,
+    only: &[String],
+) -> Result<Vec<Target>, Error> {
+    SUPPORTED_TARGETS
+        .iter()
+        .filter(|target| only.is_empty() || only.iter().any(|tuple| tuple == target.tuple))
+        .try_fold(Vec::new(), |mut found, target| {
+            match check_target(reporter, sysroot, target)? {
+                CheckTargetOutcome::Missing if !only.is_empty() => {
+                    return Err(Error::TargetNotInstalled { target: target.tuple.into() });
+                }
+                CheckTargetOutcome::Missing => {}
+                CheckTargetOutcome::Found => {
+                    found.push(Target { spec: target, rustflags: Vec::new() })
+                }
+            }
+            Ok(found)
+        })
 }
 
 fn check_target(
@@ -73,6 +73,14 @@ pub(crate) fn assert_report_skipped(&self, message: &str) {
         );
     }
 
+    #[track_caller]
+    pub(crate) fn assert_report_note(&self, message: &str) {
+        assert_eq!(
+            Report::Note(message.into()),
+            self.reports.reports.borrow_mut().pop().expect("no reports left")
+        );
+    }
+
     #[track_caller]
     pub(crate) fn assert_no_reports(&self) {
         assert!(self.reports.reports.borrow_mut().is_empty());
@@ -6,9 +6,28 @@
 #[test]
 fn invoke_with_args() {
     let exe = env!("CARGO_BIN_EXE_ferrocene-self-test");
-    for arg in ["some-arg", "two args", "--flag", "--option value"] {
-        let output = Command::new(exe).arg(arg).output().unwrap();
+    for args in [
+        &["some-arg"][..],
+        &["two args"],
+        &["--flag"],
+        &["--option value"],
+        &["--format", "xml"],
+        &["--target"],
+    ] {
+        let output = Command::new(exe).args(args).output().unwrap();
         assert!(!output.status.success());
         assert!(String::from_utf8_lossy(&output.stderr).contains("FST_012"));
     }
 }
+
+#[test]
+fn invoke_with_unsupported_target() {
+    let exe = env!("CARGO_BIN_EXE_ferrocene-self-test");
+    let output =
+        Command::new(exe).args(["--format", "json", "--target", "unknown"]).output().unwrap();
+    assert!(!output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(r#""status": "error""#));
+    assert!(stdout.contains(r#""code": "FST_027""#));
+}
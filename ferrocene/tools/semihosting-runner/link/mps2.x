@@ -0,0 +1,32 @@
+/* SPDX-License-Identifier: MIT OR Apache-2.0 */
+/* SPDX-FileCopyrightText: The Ferrocene Developers */
+
+/* Memory layout of the MPS2-AN385 (Cortex-M3) and MPS2-AN386 (Cortex-M4F) boards: the vector
+ * table at the start of the code memory, and the stack at the end of the data memory. The reset
+ * vector is `_start`, which the program provides, and which has to enable the FPU before using it
+ * on targets with hardware floating point.
+ *
+ * QEMU loads every section at its own address, so `.data` doesn't need to be copied from the code
+ * memory and `.bss` is already zeroed. */
+
+MEMORY
+{
+    FLASH : ORIGIN = 0x00000000, LENGTH = 4M
+    RAM : ORIGIN = 0x20000000, LENGTH = 4M
+}
+
+ENTRY(_start);
+
+SECTIONS
+{
+    .vector_table ORIGIN(FLASH) :
+    {
+        LONG(ORIGIN(RAM) + LENGTH(RAM));
+        LONG(_start);
+    } > FLASH
+    .text : { *(.text .text.*) } > FLASH
+    .rodata : { *(.rodata .rodata.*) } > FLASH
+    .data : { *(.data .data.*) } > RAM
+    .bss : { *(.bss .bss.*) } > RAM
+    /DISCARD/ : { *(.ARM.exidx .ARM.exidx.*) }
+}
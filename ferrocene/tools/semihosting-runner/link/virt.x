@@ -0,0 +1,21 @@
+/* SPDX-License-Identifier: MIT OR Apache-2.0 */
+/* SPDX-FileCopyrightText: The Ferrocene Developers */
+
+/* Memory layout of the QEMU `virt` board (Cortex-A53): the RAM starts at 0x40000000, where QEMU
+ * also places the device tree, so the program is loaded a bit after it. QEMU jumps to `_start` at
+ * EL1 without a stack: the program provides `_start`, which has to set the stack pointer to
+ * `__stack_top` and enable floating point and SIMD instructions before using them. */
+
+ENTRY(_start);
+
+SECTIONS
+{
+    . = 0x40080000;
+    .text : { *(.text .text.*) }
+    .rodata : { *(.rodata .rodata.*) }
+    .data : { *(.data .data.*) }
+    .bss : { *(.bss .bss.*) }
+    . = ALIGN(16);
+    . = . + 0x100000;
+    __stack_top = .;
+}
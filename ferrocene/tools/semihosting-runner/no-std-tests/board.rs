@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Start-up code and Arm semihosting calls, see `../README.md`.
+
+use core::arch::asm;
+
+/// `SYS_WRITEC`: writes `bytes` to the standard output of the runner, one at a time.
+pub(crate) fn write(bytes: &[u8]) {
+    for byte in bytes {
+        unsafe { semihosting(0x03, byte as *const u8 as usize) };
+    }
+}
+
+/// `SYS_GET_CMDLINE`: the arguments of the runner, after the name of the program.
+pub(crate) fn args() -> &'static str {
+    static mut CMDLINE: [u8; 1024] = [0; 1024];
+    // SAFETY: only called once, before the tests run.
+    let buffer = unsafe { &mut *&raw mut CMDLINE };
+    let mut block = [buffer.as_mut_ptr() as usize, buffer.len()];
+    if unsafe { semihosting(0x15, block.as_mut_ptr() as usize) } != 0 {
+        return "";
+    }
+    let cmdline = core::str::from_utf8(&buffer[..block[1]]).unwrap_or("");
+    cmdline.split_once(' ').map_or("", |(_program, args)| args)
+}
+
+/// `SYS_EXIT`: stops the emulator, which exits with a failure unless `success`.
+pub(crate) fn exit(success: bool) -> ! {
+    // ADP_Stopped_ApplicationExit and ADP_Stopped_RunTimeErrorUnknown.
+    let reason: usize = if success { 0x20026 } else { 0x20023 };
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        semihosting(0x18, reason)
+    };
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        // On 64-bit targets the parameter is a pointer to the reason and the exit status.
+        let block = [reason, usize::from(!success)];
+        semihosting(0x18, block.as_ptr() as usize)
+    };
+    loop {}
+}
+
+#[cfg(target_arch = "arm")]
+unsafe fn semihosting(operation: usize, parameter: usize) -> usize {
+    let result;
+    unsafe {
+        asm!(
+            "bkpt #0xab",
+            inout("r0") operation => result,
+            in("r1") parameter,
+            options(nostack),
+        )
+    };
+    result
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn semihosting(operation: usize, parameter: usize) -> usize {
+    let result;
+    unsafe {
+        asm!(
+            "hlt #0xf000",
+            inout("x0") operation => result,
+            in("x1") parameter,
+            options(nostack),
+        )
+    };
+    result
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn semihosting_entry() -> ! {
+    exit(crate::run())
+}
+
+// Cortex-M: the reset vector, called with the stack set up. The FPU is disabled at reset.
+#[cfg(target_arch = "arm")]
+#[unsafe(no_mangle)]
+unsafe extern "C" fn _start() -> ! {
+    #[cfg(target_abi = "eabihf")]
+    unsafe {
+        let cpacr = 0xE000_ED88 as *mut u32;
+        cpacr.write_volatile(cpacr.read_volatile() | (0xF << 20));
+        asm!("dsb", "isb");
+    }
+    semihosting_entry()
+}
+
+// AArch64: QEMU jumps to the ELF entry point at EL1, without a stack.
+#[cfg(target_arch = "aarch64")]
+core::arch::global_asm!(
+    ".section .text._start, \"ax\"",
+    ".global _start",
+    "_start:",
+    "    ldr x30, =__stack_top",
+    "    mov sp, x30",
+    // Don't trap floating point and SIMD instructions, which the compiler may use.
+    "    mov x0, #0x300000",
+    "    msr cpacr_el1, x0",
+    "    isb",
+    "    bl semihosting_entry",
+);
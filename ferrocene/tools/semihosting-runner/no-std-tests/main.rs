@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Tests of `core` run on the emulated board rather than on a facade target, with the harness of
+//! `-Z no-std-test-harness`. Bootstrap builds this file with `--test`, and renders the output of
+//! the harness like the output of libtest:
+//!
+//! ```text
+//! ./x test ferrocene/tools/semihosting-runner/no-std-tests --target <tuple>
+//! ```
+
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks, ferrocene_test)]
+#![reexport_test_harness_main = "run_tests"]
+
+mod board;
+
+use core::ferrocene_test::harness;
+use core::fmt::{self, Write};
+use core::hint::black_box;
+
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo<'_>) -> ! {
+    harness::report_panic(info);
+    board::exit(false)
+}
+
+static OUTPUT: fn(&[u8]) = board::write;
+
+/// Runs the tests, returning whether they all passed.
+fn run() -> bool {
+    harness::set_output(&OUTPUT);
+    if let Err(arg) = harness::set_args(board::args()) {
+        board::write(b"error: unsupported argument `");
+        board::write(arg.as_bytes());
+        board::write(b"`\n");
+        return false;
+    }
+    run_tests();
+    harness::succeeded()
+}
+
+#[test]
+fn integer_division() {
+    // 64-bit division calls into `compiler_builtins` on 32-bit targets.
+    let x = black_box(u64::MAX);
+    assert_eq!(x / 3, 0x5555_5555_5555_5555);
+    assert_eq!(x % 10, 5);
+}
+
+#[test]
+fn checked_arithmetic() {
+    assert_eq!(u8::MAX.checked_add(black_box(1)), None);
+    assert_eq!(i32::MIN.checked_neg(), None);
+    assert_eq!(black_box(7u32).checked_div(2), Some(3));
+}
+
+#[test]
+fn float_arithmetic() {
+    let x = black_box(2.0f32);
+    assert_eq!(x * 1.5 + 0.25, 3.25);
+    assert_eq!(black_box(1.0f64) / 4.0, 0.25);
+}
+
+#[test]
+fn formatting() {
+    let mut buffer = Buffer { bytes: [0; 64], len: 0 };
+    write!(buffer, "{} {:?} {:.2} {:#x}", 42, "a\"b", 1.5f32, 255u8).unwrap();
+    assert_eq!(buffer.as_str(), r#"42 "a\"b" 1.50 0xff"#);
+}
+
+#[test]
+fn sorting() {
+    let mut x = black_box([5, 3, 9, 1, 7]);
+    x.sort_unstable();
+    assert_eq!(x, [1, 3, 5, 7, 9]);
+    assert_eq!(x.binary_search(&7), Ok(3));
+}
+
+struct Buffer {
+    bytes: [u8; 64],
+    len: usize,
+}
+
+impl Buffer {
+    fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap()
+    }
+}
+
+impl Write for Buffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.bytes.get_mut(self.len..end).ok_or(fmt::Error)?.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+use std::ffi::OsString;
+use std::path::Path;
+use std::process::Command;
+
+/// The QEMU board a target's programs run on, also used by `ferrocene-self-test` to run its
+/// bare-metal sample program.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Board {
+    /// The name of the QEMU binary emulating the board.
+    pub qemu: &'static str,
+    /// The arguments selecting the board and its CPU.
+    pub machine: &'static [&'static str],
+    /// The file name of the linker script in `link/` programs are linked with.
+    pub linker_script_name: &'static str,
+    /// The contents of that linker script.
+    pub linker_script: &'static str,
+}
+
+const MPS2_AN385: Board = Board {
+    qemu: "qemu-system-arm",
+    machine: &["-M", "mps2-an385"],
+    linker_script_name: "mps2.x",
+    linker_script: include_str!("../link/mps2.x"),
+};
+
+const MPS2_AN386: Board = Board {
+    qemu: "qemu-system-arm",
+    machine: &["-M", "mps2-an386"],
+    linker_script_name: "mps2.x",
+    linker_script: include_str!("../link/mps2.x"),
+};
+
+const VIRT_CORTEX_A53: Board = Board {
+    qemu: "qemu-system-aarch64",
+    machine: &["-M", "virt", "-cpu", "cortex-a53"],
+    linker_script_name: "virt.x",
+    linker_script: include_str!("../link/virt.x"),
+};
+
+impl Board {
+    pub fn for_target(tuple: &str) -> Option<Self> {
+        match tuple {
+            // Cortex-M3, whose instruction set is a superset of ARMv6-M.
+            "thumbv6m-none-eabi" => Some(MPS2_AN385),
+            // Cortex-M4F.
+            "thumbv7em-none-eabi" | "thumbv7em-none-eabihf" => Some(MPS2_AN386),
+            "aarch64-unknown-none" => Some(VIRT_CORTEX_A53),
+            _ => None,
+        }
+    }
+
+    /// The QEMU invocation booting `program`, whose command line is `program` followed by `args`.
+    pub fn command(&self, program: &Path, args: &[String]) -> Command {
+        let mut cmd = Command::new(self.qemu);
+        cmd.args(self.args(program, args));
+        cmd
+    }
+
+    /// The arguments of [`command`](Self::command), for callers running another QEMU binary than
+    /// the one in the `PATH`.
+    pub fn args(&self, program: &Path, args: &[String]) -> Vec<OsString> {
+        // The console is sent to our standard output: QEMU writes it to its standard error
+        // otherwise.
+        let mut semihosting = "enable=on,target=native,chardev=console".to_string();
+        for arg in std::iter::once(&program.display().to_string()).chain(args) {
+            semihosting.push_str(",arg=");
+            semihosting.push_str(&escape_option(arg));
+        }
+
+        let mut result: Vec<OsString> = self.machine.iter().map(OsString::from).collect();
+        for arg in ["-nographic", "-monitor", "none", "-serial", "null"] {
+            result.push(arg.into());
+        }
+        result.extend(["-chardev".into(), "stdio,id=console".into()]);
+        result.extend(["-semihosting-config".into(), semihosting.into()]);
+        result.extend(["-kernel".into(), program.into()]);
+        result
+    }
+}
+
+/// QEMU separates the values of an option with commas, and a comma inside of a value is written
+/// as two commas.
+fn escape_option(value: &str) -> String {
+    value.replace(',', ",,")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_target() {
+        assert!(matches!(
+            Board::for_target("thumbv7em-none-eabihf"),
+            Some(Board { qemu: "qemu-system-arm", .. })
+        ));
+        assert!(matches!(
+            Board::for_target("thumbv6m-none-eabi"),
+            Some(Board { machine: &["-M", "mps2-an385"], .. })
+        ));
+        assert!(matches!(
+            Board::for_target("aarch64-unknown-none"),
+            Some(Board { qemu: "qemu-system-aarch64", .. })
+        ));
+        assert_eq!(None, Board::for_target("aarch64-unknown-linux-gnu"));
+    }
+
+    #[test]
+    fn test_linker_scripts() {
+        // Bootstrap links the test programs with the scripts in `link/`, and self-test embeds them.
+        let link = Path::new(env!("CARGO_MANIFEST_DIR")).join("link");
+        let mut scripts = 0;
+        for entry in std::fs::read_dir(&link).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap();
+            assert!(
+                [MPS2_AN385, MPS2_AN386, VIRT_CORTEX_A53]
+                    .iter()
+                    .any(|board| board.linker_script_name == name),
+                "no board uses {}",
+                path.display()
+            );
+            scripts += 1;
+        }
+        for board in [MPS2_AN385, MPS2_AN386, VIRT_CORTEX_A53] {
+            let path = link.join(board.linker_script_name);
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), board.linker_script);
+        }
+        assert_eq!(2, scripts);
+    }
+
+    #[test]
+    fn test_command() {
+        let board = Board::for_target("aarch64-unknown-none").unwrap();
+        let cmd = board.command(Path::new("test"), &["--exact".into(), "a,b".into()]);
+        assert_eq!("qemu-system-aarch64", cmd.get_program());
+        assert_eq!(
+            vec![
+                "-M",
+                "virt",
+                "-cpu",
+                "cortex-a53",
+                "-nographic",
+                "-monitor",
+                "none",
+                "-serial",
+                "null",
+                "-chardev",
+                "stdio,id=console",
+                "-semihosting-config",
+                "enable=on,target=native,chardev=console,arg=test,arg=--exact,arg=a,,b",
+                "-kernel",
+                "test",
+            ],
+            cmd.get_args().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,9 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! The boards emulated by `semihosting-runner`, shared with `ferrocene-self-test` so both run
+//! bare-metal programs on the same boards, linked with the same linker scripts.
+
+mod board;
+
+pub use crate::board::Board;
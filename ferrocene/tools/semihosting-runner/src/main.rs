@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Runs the test programs of bare-metal targets under QEMU's system emulation, on a board with
+//! the same CPU as the target. Bootstrap uses it as the runner of compiletest and `cargo test`:
+//!
+//! ```text
+//! semihosting-runner --target <tuple> <program> [args...]
+//! ```
+//!
+//! The program talks to us through Arm semihosting: it reads its arguments with
+//! `SYS_GET_CMDLINE`, its output goes to our standard output, and the status it passes to
+//! `SYS_EXIT` becomes our exit status. Programs that don't exit within the timeout, most often
+//! because they are stuck in a fault handler, are killed.
+
+use std::path::PathBuf;
+use std::process::{ExitCode, Stdio};
+use std::time::{Duration, Instant};
+
+use semihosting_runner::Board;
+
+/// How long a program may run before being killed, unless `SEMIHOSTING_RUNNER_TIMEOUT` sets
+/// another number of seconds.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Exit status when the program timed out, the same as the `timeout` command.
+const TIMED_OUT: u8 = 124;
+
+struct Args {
+    target: String,
+    program: PathBuf,
+    args: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<ExitCode, String> {
+    let args = parse_args(std::env::args().skip(1))?;
+    let board = Board::for_target(&args.target)
+        .ok_or_else(|| format!("running programs of {} is not supported", args.target))?;
+    let timeout = match std::env::var("SEMIHOSTING_RUNNER_TIMEOUT") {
+        Ok(secs) => Duration::from_secs(
+            secs.parse().map_err(|_| format!("invalid SEMIHOSTING_RUNNER_TIMEOUT: {secs}"))?,
+        ),
+        Err(_) => DEFAULT_TIMEOUT,
+    };
+
+    let mut child = board
+        .command(&args.program, &args.args)
+        .stdin(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("failed to start {}: {err}", board.qemu))?;
+
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait().map_err(|err| err.to_string())? {
+            // QEMU exits with the status the program passed to `SYS_EXIT`.
+            return Ok(match status.code() {
+                Some(code) => ExitCode::from(code as u8),
+                None => ExitCode::FAILURE,
+            });
+        }
+        if start.elapsed() > timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            eprintln!(
+                "error: {} timed out after {} seconds",
+                args.program.display(),
+                timeout.as_secs()
+            );
+            return Ok(ExitCode::from(TIMED_OUT));
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    const USAGE: &str = "usage: semihosting-runner --target <tuple> <program> [args...]";
+
+    let target = match (args.next().as_deref(), args.next()) {
+        (Some("--target"), Some(target)) => target,
+        _ => return Err(USAGE.into()),
+    };
+    let program = args.next().ok_or(USAGE)?.into();
+    Ok(Args { target, program, args: args.collect() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse(&["--target", "aarch64-unknown-none", "prog", "--exact", "x"]).unwrap();
+        assert_eq!("aarch64-unknown-none", args.target);
+        assert_eq!(PathBuf::from("prog"), args.program);
+        assert_eq!(vec!["--exact", "x"], args.args);
+
+        assert!(parse(&["--target", "aarch64-unknown-none", "prog"]).unwrap().args.is_empty());
+    }
+
+    #[test]
+    fn test_parse_args_invalid() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["prog"]).is_err());
+        assert!(parse(&["--target", "aarch64-unknown-none"]).is_err());
+    }
+}
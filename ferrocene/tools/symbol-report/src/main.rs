@@ -1,5 +1,6 @@
 #![feature(rustc_private)]
 
+extern crate rustc_ast;
 extern crate rustc_driver;
 extern crate rustc_hir;
 extern crate rustc_interface;
@@ -13,19 +14,23 @@
 use std::process::ExitCode;
 use std::sync::LazyLock;
 
-use build_helper::symbol_report::{Function, SymbolReport};
+use build_helper::symbol_report::{Function, SymbolReport, ValidationStatus};
+use rustc_ast::LitKind;
 use rustc_driver::{Callbacks, Compilation};
+use rustc_hir::attrs::AttributeKind;
 use rustc_hir::def::DefKind;
 use rustc_hir::def_id::LocalDefId;
 use rustc_hir::{AttrId, Attribute, HirId};
 use rustc_interface::interface::Compiler;
-use rustc_middle::ty::TyCtxt;
 use rustc_middle::ty::print::{
     with_no_trimmed_paths, with_no_visible_paths, with_resolve_crate_name,
 };
+use rustc_middle::ty::{GenericParamDefKind, TyCtxt};
 use rustc_session::EarlyDiagCtxt;
 use rustc_session::config::ErrorOutputType;
-use rustc_span::{Span, Symbol};
+use rustc_span::{Span, Symbol, kw};
+
+mod workspace;
 
 static FERROCENE_ANNOTATION_PATH: LazyLock<[Symbol; 2]> =
     LazyLock::new(|| ["ferrocene", "annotation"].map(Symbol::intern));
@@ -34,11 +39,19 @@ struct Vis<'tcx> {
     tcx: TyCtxt<'tcx>,
     report: SymbolReport,
     visited_attrs: BTreeSet<AttrId>,
+    /// The filename, start line and justification of each annotation inside a function body, to
+    /// be attached to the function once all of them are visited.
+    annotation_reasons: Vec<(String, usize, String)>,
 }
 
 impl<'tcx> Vis<'tcx> {
     fn new(tcx: TyCtxt<'tcx>) -> Self {
-        Self { tcx, report: SymbolReport::new(), visited_attrs: BTreeSet::new() }
+        Self {
+            tcx,
+            report: SymbolReport::new(),
+            visited_attrs: BTreeSet::new(),
+            annotation_reasons: Vec::new(),
+        }
     }
 
     fn convert_span(&mut self, span: Span) -> (String, usize, usize) {
@@ -58,8 +71,27 @@ fn find_hir_id_annotations(&mut self, hir_id: HirId, span: Span) {
             .find(|attr| attr.path_matches(FERROCENE_ANNOTATION_PATH.as_slice()))
         {
             let (filename, start, end) = self.convert_span(span);
-            self.report.add_annotation(filename, start, end);
+            self.report.add_annotation(filename.clone(), start, end);
             self.visited_attrs.insert(attr.id());
+            if let Some(reason) = annotation_reason(attr) {
+                self.annotation_reasons.push((filename, start, reason));
+            }
+        }
+    }
+
+    /// Attach the justification of the annotations inside each checked function to it.
+    fn attach_annotation_reasons(&mut self) {
+        for function in &mut self.report.symbols {
+            if !function.status.is_checked() {
+                continue;
+            }
+            function.annotations.extend(self.annotation_reasons.iter().filter_map(
+                |(filename, start, reason)| {
+                    (*filename == function.filename
+                        && (function.start_line..=function.end_line).contains(start))
+                    .then(|| reason.clone())
+                },
+            ));
         }
     }
 }
@@ -92,28 +124,43 @@ fn visit_attribute(&mut self, attr: &'v rustc_hir::Attribute) -> Self::Result {
     }
 }
 
-struct LoadCoreSymbols;
+struct LoadCoreSymbols {
+    /// Whether we are running as `RUSTC_WORKSPACE_WRAPPER`, see [`workspace`].
+    wrapper: bool,
+}
 
 impl Callbacks for LoadCoreSymbols {
     fn after_expansion(&mut self, _: &Compiler, tcx: TyCtxt<'_>) -> Compilation {
         // NOTE: this can't be in main because it shouldn't execute when only running
         // --print=file-names
-        let out = match std::env::var("SYMBOL_REPORT_OUT") {
-            Ok(p) => Box::new(File::create(&p).expect(&format!("could not create file {p}")))
-                as Box<dyn Write + Send>,
-            Err(_) => Box::new(io::stdout()) as _,
+        let out = if self.wrapper {
+            match workspace::report_path(tcx) {
+                Some(p) => Box::new(
+                    File::create(&p).expect(&format!("could not create file {}", p.display())),
+                ) as Box<dyn Write + Send>,
+                // Build scripts are not part of the certified subset.
+                None => return Compilation::Continue,
+            }
+        } else {
+            match std::env::var("SYMBOL_REPORT_OUT") {
+                Ok(p) => Box::new(File::create(&p).expect(&format!("could not create file {p}")))
+                    as Box<dyn Write + Send>,
+                Err(_) => Box::new(io::stdout()) as _,
+            }
         };
 
         let vis = Vis::new(tcx);
         let mut vis = extract_all_functions(tcx, vis);
 
         tcx.hir_visit_all_item_likes_in_crate(&mut vis);
+        vis.attach_annotation_reasons();
         // It is very important that we walk the attributes *after* we visit the rest of the items.
         // This allows us to detect unused annotations.
         tcx.hir_walk_attributes(&mut vis);
 
         serde_json::to_writer(out, &vis.report).expect("failed to serialize report");
-        Compilation::Stop
+        // As a wrapper, cargo still needs the compiled crate, e.g. to build its dependents.
+        if self.wrapper { Compilation::Continue } else { Compilation::Stop }
     }
 }
 
@@ -121,9 +168,13 @@ fn main() {
     rustc_driver::install_ice_hook("https://github.com/ferrocene/ferrocene/issues/new", |_| ());
     let handler = EarlyDiagCtxt::new(ErrorOutputType::default());
     rustc_driver::init_rustc_env_logger(&handler);
+    let mut args: Vec<String> = std::env::args().collect();
+    if args.get(1).is_some_and(|arg| arg == "merge") {
+        std::process::exit(workspace::merge(&args[2..]));
+    }
+    let wrapper = workspace::strip_wrapped_rustc(&mut args);
     let exit_code = rustc_driver::catch_with_exit_code(move || {
-        let args: Vec<String> = std::env::args().collect();
-        rustc_driver::run_compiler(&args, &mut LoadCoreSymbols)
+        rustc_driver::run_compiler(&args, &mut LoadCoreSymbols { wrapper })
     });
     let exit_code = if exit_code == ExitCode::SUCCESS {
         rustc_driver::EXIT_SUCCESS
@@ -133,19 +184,37 @@ fn main() {
     std::process::exit(exit_code);
 }
 
-use rustc_middle::middle::codegen_fn_attrs::ferrocene::{ValidatedStatus, item_is_validated};
+use rustc_middle::middle::codegen_fn_attrs::ferrocene::{
+    ValidatedRoot, ValidatedStatus, item_is_validated, validated_root,
+};
 
 fn extract_all_functions<'tcx>(tcx: TyCtxt<'tcx>, mut vis: Vis<'tcx>) -> Vis<'tcx> {
     for def in tcx.hir_crate_items(()).definitions() {
-        match tcx.def_kind(def) {
+        let def_kind = tcx.def_kind(def);
+        match def_kind {
             DefKind::Mod | DefKind::Struct | DefKind::Enum | DefKind::Union => continue,
             _ => {}
         }
-
-        match item_is_validated(tcx, def.into()) {
-            ValidatedStatus::Validated { annotation: Some(_) } => {}
+        let is_fn = matches!(def_kind, DefKind::Fn | DefKind::AssocFn);
+
+        // Unvalidated functions are listed too, so that the report can explain why they are not
+        // part of the certified subset.
+        let status = match item_is_validated(tcx, def.into()) {
+            ValidatedStatus::Validated { annotation: Some(_) } => ValidationStatus::Prevalidated,
+            ValidatedStatus::Validated { annotation: None } if is_fn => ValidationStatus::Implicit,
+            // Entry points are not validated, but their bodies are checked like validated
+            // functions.
+            ValidatedStatus::Unvalidated
+                if let Some(ValidatedRoot::Entrypoint { .. }) = validated_root(tcx, def.into()) =>
+            {
+                ValidationStatus::Entrypoint
+            }
+            ValidatedStatus::Unvalidated | ValidatedStatus::WorkaroundDelegationBugs if is_fn => {
+                ValidationStatus::Unvalidated
+            }
             _ => continue,
-        }
+        };
+        let reason = status_reason(tcx, def, status);
 
         let qualified_name = get_qualified_name(tcx, def);
 
@@ -154,24 +223,82 @@ fn extract_all_functions<'tcx>(tcx: TyCtxt<'tcx>, mut vis: Vis<'tcx>) -> Vis<'tc
         }
 
         let (filename, mut start_line, end_line) = get_span(tcx, &mut vis, def);
+        let mut annotations = vec![];
 
         // We don't check for annotations those inside the `Visitor` implementation so we do it
         // here.
-        if let Some(attr) = has_ferrocene_annotation(tcx, def) {
+        if let Some(attr) = has_ferrocene_annotation(tcx, def)
+            && status.is_checked()
+        {
             vis.report.add_annotation(filename.clone(), start_line, end_line);
             vis.visited_attrs.insert(attr.id());
+            annotations.extend(annotation_reason(attr));
             // Set the start line of the function as the minimum between the start line of the
             // function and the start of the annotation so it can be seen in the coverage report.
             let (_, span_start_line, _) = vis.convert_span(attr.span());
             start_line = start_line.min(span_start_line);
         }
 
-        vis.report.symbols.push(Function { qualified_name, filename, start_line, end_line });
+        let generics = if is_fn { get_generics(tcx, def) } else { vec![] };
+        let cfg = get_cfg(tcx, def);
+
+        vis.report.symbols.push(Function {
+            qualified_name,
+            filename,
+            start_line,
+            end_line,
+            status,
+            annotations,
+            generics,
+            cfg,
+            reason,
+        });
     }
 
     vis
 }
 
+/// Why `def` has `status`, mirroring the checks of `item_is_validated`.
+fn status_reason(tcx: TyCtxt<'_>, def: LocalDefId, status: ValidationStatus) -> String {
+    let owner = tcx.typeck_root_def_id_local(def);
+    let parent = tcx.local_parent(owner);
+    let derived = matches!(tcx.def_kind(parent), DefKind::Impl { .. })
+        && tcx.is_automatically_derived(parent.to_def_id());
+    let reason = match status {
+        ValidationStatus::Prevalidated => {
+            if owner != def {
+                "defined in a prevalidated function"
+            } else if derived {
+                "derived for a prevalidated type"
+            } else {
+                "marked `#[ferrocene::prevalidated]`"
+            }
+        }
+        ValidationStatus::Entrypoint => "marked `#[ferrocene::entrypoint]`",
+        ValidationStatus::Implicit => {
+            if tcx.hir_maybe_body_owned_by(owner).is_none() {
+                "no body"
+            } else if derived {
+                "derived for a validated type"
+            } else if tcx.entry_fn(()).is_some_and(|(main, _)| main == owner.to_def_id()) {
+                "`main` function"
+            } else {
+                "validated by the compiler"
+            }
+        }
+        ValidationStatus::Unvalidated => {
+            if derived && !tcx.is_builtin_derived(parent.to_def_id()) {
+                "derived by a proc-macro"
+            } else if derived {
+                "derived for an unvalidated type"
+            } else {
+                "not marked `#[ferrocene::prevalidated]`"
+            }
+        }
+    };
+    reason.to_string()
+}
+
 fn get_qualified_name(tcx: TyCtxt<'_>, def: LocalDefId) -> String {
     with_no_visible_paths!(with_resolve_crate_name!(with_no_trimmed_paths!(tcx.def_path_str(def))))
 }
@@ -179,12 +306,17 @@ fn get_qualified_name(tcx: TyCtxt<'_>, def: LocalDefId) -> String {
 /// Filter out functions that are marked prevalidated for internal use only
 fn should_filter_out(qualified_name: &str) -> bool {
     const FILTER_LIST: &[&str] = &["core::ferrocene_test", "core::core_arch"];
-
-    FILTER_LIST
-        .iter()
-        // Some symbols start with `<`, some without
-        .flat_map(|s| [s.to_string(), format!("<{s}")])
-        .any(|filter| qualified_name.starts_with(&filter))
+    // The harness of `-Z no-std-test-harness` is public, even if hidden and perma-unstable, so it
+    // is listed like the rest of `core`, as unvalidated.
+    const KEEP_LIST: &[&str] = &["core::ferrocene_test::harness"];
+
+    let matches = |list: &[&str]| {
+        list.iter()
+            // Some symbols start with `<`, some without
+            .flat_map(|s| [s.to_string(), format!("<{s}")])
+            .any(|prefix| qualified_name.starts_with(&prefix))
+    };
+    matches(FILTER_LIST) && !matches(KEEP_LIST)
 }
 
 fn get_span(tcx: TyCtxt<'_>, vis: &mut Vis<'_>, def: LocalDefId) -> (String, usize, usize) {
@@ -196,3 +328,54 @@ fn get_span(tcx: TyCtxt<'_>, vis: &mut Vis<'_>, def: LocalDefId) -> (String, usi
 fn has_ferrocene_annotation(tcx: TyCtxt<'_>, def: LocalDefId) -> Option<&Attribute> {
     tcx.get_attrs_by_path(def.into(), FERROCENE_ANNOTATION_PATH.as_slice()).next()
 }
+
+/// The justification of a `#[ferrocene::annotation("...")]`.
+fn annotation_reason(attr: &Attribute) -> Option<String> {
+    let list = attr.meta_item_list()?;
+    match &list.first()?.lit()?.kind {
+        LitKind::Str(reason, _) => Some(reason.to_string()),
+        _ => None,
+    }
+}
+
+fn get_generics(tcx: TyCtxt<'_>, def: LocalDefId) -> Vec<String> {
+    let mut generics = tcx.generics_of(def);
+    let mut params = vec![];
+    loop {
+        // The parent's parameters come first.
+        let own =
+            generics.own_params.iter().filter(|param| param.name != kw::SelfUpper).map(|param| {
+                match param.kind {
+                    GenericParamDefKind::Const { .. } => format!(
+                        "const {}: {}",
+                        param.name,
+                        tcx.type_of(param.def_id).instantiate_identity()
+                    ),
+                    GenericParamDefKind::Lifetime | GenericParamDefKind::Type { .. } => {
+                        param.name.to_string()
+                    }
+                }
+            });
+        params.splice(0..0, own);
+        match generics.parent {
+            Some(parent) => generics = tcx.generics_of(parent),
+            None => break,
+        }
+    }
+    params
+}
+
+fn get_cfg(tcx: TyCtxt<'_>, def: LocalDefId) -> Vec<String> {
+    let mut cfg = vec![];
+    let mut current = Some(def);
+    while let Some(def) = current {
+        for attr in tcx.hir_attrs(tcx.local_def_id_to_hir_id(def)).iter().rev() {
+            if let Attribute::Parsed(AttributeKind::CfgTrace(cfgs)) = attr {
+                cfg.extend(cfgs.iter().rev().map(|(cfg, _)| cfg.to_string()));
+            }
+        }
+        current = tcx.opt_local_parent(def);
+    }
+    cfg.reverse();
+    cfg
+}
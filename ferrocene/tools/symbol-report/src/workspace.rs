@@ -0,0 +1,106 @@
+//! Running `symbol-report` over a cargo workspace, rather than over the certified core library.
+//!
+//! With `SYMBOL_REPORT_OUT_DIR` set, `symbol-report` can be used as `RUSTC_WORKSPACE_WRAPPER`.
+//! Cargo then runs it instead of `rustc` for each crate of the workspace, passing the path to
+//! `rustc` as the first argument. Each crate is compiled as usual, and its report is written to
+//! `$SYMBOL_REPORT_OUT_DIR/<crate name><extra filename>.json`. Crates outside the workspace are
+//! not part of the certified subset, and cargo doesn't wrap them.
+//!
+//! `symbol-report merge <dir> [<out>]` then merges the reports of every crate into a single
+//! report, which `blanket show --report` accepts like the report of core. It is written to `<out>`,
+//! or to stdout if not given.
+
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use build_helper::symbol_report::SymbolReport;
+use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_middle::ty::TyCtxt;
+
+const OUT_DIR_ENV: &str = "SYMBOL_REPORT_OUT_DIR";
+
+/// If running as a cargo wrapper, remove the path to `rustc` cargo passes as the first argument,
+/// since `symbol-report` is the compiler. Unless `symbol-report` is installed next to `rustc`, it
+/// can't find the sysroot by itself, so it uses the one of the wrapped `rustc`. Returns whether we
+/// are running as a wrapper.
+pub(crate) fn strip_wrapped_rustc(args: &mut Vec<String>) -> bool {
+    if std::env::var_os(OUT_DIR_ENV).is_none() {
+        return false;
+    }
+    let is_rustc = args.get(1).is_some_and(|arg| {
+        let path = Path::new(arg);
+        path.file_stem() == Some(OsStr::new("rustc")) && path.extension() != Some(OsStr::new("rs"))
+    });
+    if !is_rustc {
+        eprintln!("error: {OUT_DIR_ENV} is set, but symbol-report was not run as a cargo wrapper");
+        std::process::exit(1);
+    }
+    let rustc = args.remove(1);
+
+    if !args.iter().any(|arg| arg == "--sysroot" || arg.starts_with("--sysroot=")) {
+        let output = Command::new(&rustc)
+            .args(["--print", "sysroot"])
+            .output()
+            .expect(&format!("could not run {rustc}"));
+        if !output.status.success() {
+            eprintln!("error: `{rustc} --print sysroot` failed");
+            std::process::exit(1);
+        }
+        let sysroot = String::from_utf8(output.stdout).expect("sysroot is not UTF-8");
+        args.push(format!("--sysroot={}", sysroot.trim()));
+    }
+    true
+}
+
+/// Where to write the report of the current crate, or `None` if it doesn't need one.
+pub(crate) fn report_path(tcx: TyCtxt<'_>) -> Option<PathBuf> {
+    let crate_name = tcx.crate_name(LOCAL_CRATE);
+    if crate_name.as_str() == "build_script_build" {
+        return None;
+    }
+    let dir = PathBuf::from(std::env::var_os(OUT_DIR_ENV)?);
+    std::fs::create_dir_all(&dir).expect(&format!("could not create {}", dir.display()));
+    let extra_filename = &tcx.sess.opts.cg.extra_filename;
+    Some(dir.join(format!("{crate_name}{extra_filename}.json")))
+}
+
+/// `symbol-report merge <dir> [<out>]`. Returns the exit code.
+pub(crate) fn merge(args: &[String]) -> i32 {
+    let (dir, out) = match args {
+        [dir] => (Path::new(dir), None),
+        [dir, out] => (Path::new(dir), Some(Path::new(out))),
+        _ => {
+            eprintln!("usage: symbol-report merge <dir> [<out>]");
+            return 1;
+        }
+    };
+
+    let mut entries = std::fs::read_dir(dir)
+        .expect(&format!("could not read {}", dir.display()))
+        .map(|entry| entry.expect("could not read directory entry").path())
+        .filter(|path| path.extension() == Some(OsStr::new("json")))
+        .collect::<Vec<_>>();
+    // So that the merged report doesn't depend on the order of the directory.
+    entries.sort();
+
+    let mut report = SymbolReport::new();
+    for path in entries {
+        let file = File::open(&path).expect(&format!("could not open {}", path.display()));
+        let crate_report: SymbolReport = serde_json::from_reader(file)
+            .expect(&format!("could not parse symbol report {}", path.display()));
+        report.merge(crate_report);
+    }
+
+    let out = match out {
+        Some(p) => {
+            Box::new(File::create(p).expect(&format!("could not create file {}", p.display())))
+                as Box<dyn Write>
+        }
+        None => Box::new(io::stdout()) as _,
+    };
+    serde_json::to_writer(out, &report).expect("failed to serialize report");
+    0
+}
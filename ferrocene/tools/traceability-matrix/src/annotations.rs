@@ -22,6 +22,7 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             AnnotationSource::ParentDirectory { .. } => {
                 write!(f, "{} (from its parent directory)", self.test.display())
             }
+            AnnotationSource::CargoTest { name } => write!(f, "{} ({name})", self.test.display()),
         }
     }
 }
@@ -52,8 +53,14 @@ fn default() -> Self {
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) enum AnnotationSource {
     TestItself,
-    ParentDirectory { bulk_file: PathBuf },
+    ParentDirectory {
+        bulk_file: PathBuf,
+    },
     Rmake,
+    /// A `#[test]` function of a Cargo package, with its libtest name.
+    CargoTest {
+        name: String,
+    },
 }
 
 #[derive(Debug)]
@@ -0,0 +1,440 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Annotations of `#[test]` functions in Cargo packages (e.g. `library/coretests`), which are not
+//! collected by compiletest.
+//!
+//! A test function is annotated with `// ferrocene-annotations: <id>` comments placed right before
+//! or among its attributes, which may span several lines or share a line with the function:
+//!
+//! ```text
+//! // ferrocene-annotations: fls_3xvm61x0t251
+//! #[test]
+//! fn test_initialization() { ... }
+//! ```
+//!
+//! Libtest names tests after their module path within the test crate, which we can't fully know
+//! without Cargo, as the same file could be the root of the crate or one of its modules. Instead,
+//! every suffix of the module path derived from the file path is tried, and a test is matched with
+//! all the outcomes recorded with any of the resulting names.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+
+use crate::annotations::{
+    AnnotatedFile, AnnotationSource, Annotations, DisplayCommaSeparatedSet, Targets,
+};
+use crate::test_outcomes::{TestOutcomes, cargo_test_name};
+
+const ANNOTATION_PREFIX: &str = "// ferrocene-annotations: ";
+
+#[derive(Debug, PartialEq, Eq)]
+struct AnnotatedTest {
+    file: PathBuf,
+    /// Module path of the file within the package, without the `src` or `tests` directory.
+    file_modules: Vec<String>,
+    /// Inline modules containing the test, and the name of the test function.
+    path: Vec<String>,
+    ids: Vec<String>,
+}
+
+impl Annotations {
+    pub(crate) fn load_cargo_package(
+        &mut self,
+        package: &Path,
+        src_base: &Path,
+        test_outcomes: Option<&TestOutcomes>,
+    ) -> anyhow::Result<()> {
+        if test_outcomes.is_none() {
+            self.considers_ignored_tests = false;
+        }
+        let krate = package_name(package)?;
+
+        let mut tests = Vec::new();
+        collect_directory(package, package, &mut tests)?;
+
+        let mut unknown_tests = Vec::new();
+        for test in tests {
+            let names = test.candidate_names(&krate);
+            let default_name = names.last().expect("there is always one candidate").clone();
+
+            let relative_file =
+                test.file.strip_prefix(src_base).unwrap_or(&test.file).to_path_buf();
+            let mut targets = Targets::default();
+            let name = match test_outcomes {
+                Some(outcomes) => {
+                    let executed = outcomes_of(&names, &outcomes.executed_tests);
+                    let ignored = outcomes_of(&names, &outcomes.ignored_tests);
+                    let Some(name) = executed.first().or(ignored.first()).map(|(n, _)| n.clone())
+                    else {
+                        unknown_tests.push(default_name);
+                        continue;
+                    };
+                    if executed.is_empty() {
+                        // Do not consider annotations from ignored tests.
+                        let ignored = ignored.into_iter().flat_map(|(_, on)| on).collect();
+                        self.ignored_tests.insert(name, ignored);
+                        continue;
+                    }
+                    targets.executed = DisplayCommaSeparatedSet(
+                        executed.into_iter().flat_map(|(_, on)| on).collect(),
+                    );
+                    targets.ignored = DisplayCommaSeparatedSet(
+                        ignored.into_iter().flat_map(|(_, on)| on).collect(),
+                    );
+                    name
+                }
+                None => default_name,
+            };
+
+            for id in test.ids {
+                self.ids.entry(id).or_default().insert(AnnotatedFile {
+                    test: relative_file.clone(),
+                    source: AnnotationSource::CargoTest { name: name.clone() },
+                    targets: targets.clone(),
+                });
+            }
+        }
+        if !unknown_tests.is_empty() {
+            eprintln!(
+                "The following Cargo tests have no entries in test_outcomes, \
+                likely due to test_outcomes being stale:"
+            );
+            for test in unknown_tests {
+                eprintln!("  - {test}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl AnnotatedTest {
+    /// Every name libtest could give to this test, from the longest module path to the shortest.
+    fn candidate_names(&self, krate: &str) -> Vec<String> {
+        (0..=self.file_modules.len())
+            .map(|skip| {
+                let path = self.file_modules[skip..].iter().chain(&self.path);
+                cargo_test_name(krate, &path.cloned().collect::<Vec<_>>().join("::"))
+            })
+            .collect()
+    }
+}
+
+/// The outcomes recorded under any of the names, with the name they were recorded under.
+fn outcomes_of<T: Clone>(names: &[String], outcomes: &BTreeMap<String, T>) -> Vec<(String, T)> {
+    names.iter().filter_map(|name| Some((name.clone(), outcomes.get(name)?.clone()))).collect()
+}
+
+/// Read the package name from the `[package]` section of its `Cargo.toml`.
+fn package_name(package: &Path) -> anyhow::Result<String> {
+    let manifest = package.join("Cargo.toml");
+    let contents = std::fs::read_to_string(&manifest)
+        .with_context(|| format!("failed to read {}", manifest.display()))?;
+
+    let mut in_package = false;
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if in_package
+            && let Some((key, value)) = line.split_once('=')
+            && key.trim() == "name"
+        {
+            return Ok(value.trim().trim_matches('"').to_string());
+        }
+    }
+    anyhow::bail!("missing package name in {}", manifest.display());
+}
+
+fn collect_directory(
+    package: &Path,
+    dir: &Path,
+    tests: &mut Vec<AnnotatedTest>,
+) -> anyhow::Result<()> {
+    let mut entries = std::fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.path());
+    for entry in entries {
+        let path = entry.path();
+        if path.is_dir() {
+            // Benchmarks are not tests, and `target` may contain generated sources.
+            if dir == package && (path.ends_with("benches") || path.ends_with("target")) {
+                continue;
+            }
+            collect_directory(package, &path, tests)?;
+        } else if path.extension().and_then(|e| e.to_str()) == Some("rs") {
+            let contents = std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            let file_modules = file_modules(path.strip_prefix(package)?);
+            tests.extend(
+                collect_file(&contents)
+                    .with_context(|| format!("in {}", path.display()))?
+                    .into_iter()
+                    .map(|(path_in_file, ids)| AnnotatedTest {
+                        file: path.clone(),
+                        file_modules: file_modules.clone(),
+                        path: path_in_file,
+                        ids,
+                    }),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Module path of a file relative to the package root, following Rust's module file conventions.
+fn file_modules(relative: &Path) -> Vec<String> {
+    let mut modules = relative
+        .with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    if matches!(modules.first().map(String::as_str), Some("src" | "tests")) {
+        modules.remove(0);
+    }
+    if matches!(modules.last().map(String::as_str), Some("lib" | "main" | "mod")) {
+        modules.pop();
+    }
+    modules
+}
+
+/// Find the annotated test functions in a file, returning the path of each function (including
+/// the inline modules containing it) and its annotations.
+fn collect_file(contents: &str) -> anyhow::Result<Vec<(Vec<String>, Vec<String>)>> {
+    let mut found = Vec::new();
+    // Inline modules we are in, with the brace depth their body started at.
+    let mut modules: Vec<(String, usize)> = Vec::new();
+    let mut depth = 0;
+
+    let mut pending_ids = Vec::new();
+    let mut pending_test = false;
+    // Unclosed brackets of an attribute spanning several lines.
+    let mut attribute_depth = 0;
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if let Some(id) = trimmed.strip_prefix(ANNOTATION_PREFIX) {
+            if !id.is_empty() {
+                pending_ids.push(id.trim().to_string());
+            }
+            continue;
+        } else if trimmed.starts_with("// ferrocene-annotation: ") {
+            // Prevent common typos
+            anyhow::bail!(
+                "attribute is called 'ferrocene-annotations', not 'ferrocene-annotation'"
+            );
+        }
+
+        let code = trimmed.split("//").next().unwrap_or_default();
+        let code = skip_attributes(code, &mut attribute_depth, &mut pending_test);
+        if let Some(name) = item_name(code, "fn ") {
+            if pending_test && !pending_ids.is_empty() {
+                let mut path = modules.iter().map(|(m, _)| m.clone()).collect::<Vec<_>>();
+                path.push(name.to_string());
+                found.push((path, std::mem::take(&mut pending_ids)));
+            }
+            pending_ids.clear();
+            pending_test = false;
+        } else if !code.is_empty() || trimmed.is_empty() {
+            if let Some(name) = item_name(code, "mod ")
+                && code.ends_with('{')
+            {
+                modules.push((name.to_string(), depth + 1));
+            }
+            pending_ids.clear();
+            pending_test = false;
+        }
+
+        for c in code.chars() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    if modules.last().is_some_and(|(_, start)| *start == depth) {
+                        modules.pop();
+                    }
+                    depth = depth.saturating_sub(1);
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Skip the attributes at the start of `code`, returning the code after them. `depth` is the number
+/// of unclosed brackets of an attribute started on a previous line, and is updated for the next
+/// line. `is_test` is set if one of the attributes is `#[test]`.
+fn skip_attributes<'a>(mut code: &'a str, depth: &mut usize, is_test: &mut bool) -> &'a str {
+    loop {
+        if *depth == 0 {
+            let Some(attribute) = code.strip_prefix("#[") else {
+                return code;
+            };
+            *is_test |= attribute.starts_with("test]");
+            *depth = 1;
+            code = attribute;
+        }
+        let mut end = None;
+        for (i, c) in code.char_indices() {
+            match c {
+                '[' => *depth += 1,
+                ']' => {
+                    *depth -= 1;
+                    if *depth == 0 {
+                        end = Some(i);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        match end {
+            Some(end) => code = code[end + 1..].trim_start(),
+            // The attribute continues on the next line.
+            None => return "",
+        }
+    }
+}
+
+/// Name of the item declared by `code` with the given keyword, skipping visibility and qualifiers.
+fn item_name<'a>(code: &'a str, keyword: &str) -> Option<&'a str> {
+    let mut rest = code;
+    loop {
+        if let Some(after) = rest.strip_prefix(keyword) {
+            let end = after.find(|c: char| !(c.is_alphanumeric() || c == '_'))?;
+            return (end > 0).then(|| &after[..end]);
+        }
+        let (word, after) = rest.split_once(' ')?;
+        if !(word.starts_with("pub") || matches!(word, "async" | "const" | "unsafe" | "extern")) {
+            return None;
+        }
+        rest = after.trim_start();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::test_outcomes::On;
+
+    #[test]
+    fn test_collect_file() -> anyhow::Result<()> {
+        let contents = "\
+// ferrocene-annotations: fls_top
+#[test]
+fn top_level() {}
+
+// ferrocene-annotations: fls_not_a_test
+fn not_a_test() {}
+
+#[test]
+fn not_annotated() {}
+
+mod inner {
+    #[test]
+    // ferrocene-annotations: fls_a
+    // ferrocene-annotations: fls_b
+    #[should_panic]
+    pub fn nested() {
+        if true { panic!() }
+    }
+}
+
+// ferrocene-annotations: fls_after
+#[test]
+fn after_module() {}
+
+// ferrocene-annotations: fls_one_line
+#[test] #[should_panic] fn one_line() { panic!() }
+
+// ferrocene-annotations: fls_multi_line_attribute
+#[test]
+#[cfg_attr(
+    miri,
+    ignore = \"too slow\"
+)]
+fn multi_line_attribute() {}
+
+// ferrocene-annotations: fls_not_a_test_either
+#[cfg_attr(
+    test,
+    derive(Debug)
+)]
+fn not_a_test_either() {}
+";
+        assert_eq!(
+            vec![
+                (vec!["top_level".to_string()], vec!["fls_top".to_string()]),
+                (
+                    vec!["inner".to_string(), "nested".to_string()],
+                    vec!["fls_a".to_string(), "fls_b".to_string()]
+                ),
+                (vec!["after_module".to_string()], vec!["fls_after".to_string()]),
+                (vec!["one_line".to_string()], vec!["fls_one_line".to_string()]),
+                (
+                    vec!["multi_line_attribute".to_string()],
+                    vec!["fls_multi_line_attribute".to_string()]
+                ),
+            ],
+            collect_file(contents)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_modules() {
+        assert_eq!(Vec::<String>::new(), file_modules(Path::new("lib.rs")));
+        assert_eq!(vec!["num"], file_modules(Path::new("tests/num/mod.rs")));
+        assert_eq!(vec!["num", "int"], file_modules(Path::new("tests/num/int.rs")));
+        assert_eq!(vec!["foo"], file_modules(Path::new("src/foo.rs")));
+    }
+
+    #[test]
+    fn test_load_cargo_package() -> anyhow::Result<()> {
+        let dir = TempDir::new()?;
+        let package = dir.path().join("library/coretests");
+        std::fs::create_dir_all(package.join("tests/num"))?;
+        std::fs::create_dir_all(package.join("benches"))?;
+        std::fs::write(package.join("Cargo.toml"), "[package]\nname = \"coretests\"\n")?;
+        std::fs::write(
+            package.join("tests/num/mod.rs"),
+            "// ferrocene-annotations: executed\n#[test]\nfn add() {}\n\n\
+            // ferrocene-annotations: ignored\n#[test]\nfn sub() {}\n",
+        )?;
+        std::fs::write(
+            package.join("benches/lib.rs"),
+            "// ferrocene-annotations: bench\n#[test]\nfn bench() {}\n",
+        )?;
+
+        let on = BTreeSet::from([On { target: "x86_64".into(), variant: "empty".into() }]);
+        let outcomes = TestOutcomes {
+            executed_tests: BTreeMap::from([("coretests::num::add".into(), on.clone())]),
+            ignored_tests: BTreeMap::from([("coretests::num::sub".into(), on.clone())]),
+        };
+
+        let mut annotations = Annotations::new();
+        annotations.load_cargo_package(&package, dir.path(), Some(&outcomes))?;
+
+        assert_eq!(
+            BTreeMap::from([(
+                "executed".into(),
+                BTreeSet::from([AnnotatedFile {
+                    test: "library/coretests/tests/num/mod.rs".into(),
+                    source: AnnotationSource::CargoTest { name: "coretests::num::add".into() },
+                    targets: Targets {
+                        executed: DisplayCommaSeparatedSet(on.clone()),
+                        ignored: Default::default(),
+                    },
+                }])
+            )]),
+            annotations.ids,
+        );
+        assert_eq!(BTreeMap::from([("coretests::num::sub".into(), on)]), annotations.ignored_tests);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Configuration file listing the requirement sources to trace the tests to.
+//!
+//! ```json
+//! {
+//!     "documentations": [
+//!         { "name": "FLS", "ids": "paragraph-ids.json", "url": "https://spec.ferrocene.dev" },
+//!         { "name": "SRS", "ids": "srs/ids.json", "url": "https://example.com/srs/{link}" }
+//!     ]
+//! }
+//! ```
+//!
+//! Relative `ids` paths are resolved from the directory containing the configuration file.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use serde::Deserialize;
+
+use crate::documentations::{self, Documentation};
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Config {
+    pub(crate) documentations: Vec<DocumentationConfig>,
+}
+
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct DocumentationConfig {
+    /// Name shown in the report, e.g. `FLS`.
+    pub(crate) name: String,
+    /// Path to the [`TraceabilityIds`](documentations::TraceabilityIds) of the documentation.
+    pub(crate) ids: PathBuf,
+    /// URL of the documentation, see [`Documentation::link`].
+    pub(crate) url: String,
+}
+
+impl Config {
+    pub(crate) fn load(path: &Path) -> anyhow::Result<Self> {
+        let mut config: Config = serde_json::from_slice(&std::fs::read(path)?)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+
+        if config.documentations.is_empty() {
+            anyhow::bail!("{} doesn't list any documentation", path.display());
+        }
+        let mut names = HashSet::new();
+        for documentation in &mut config.documentations {
+            if !names.insert(documentation.name.clone()) {
+                anyhow::bail!("documentation {} is listed multiple times", documentation.name);
+            }
+            if let Some(base) = path.parent() {
+                documentation.ids = base.join(&documentation.ids);
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub(crate) fn load_documentations(&self) -> anyhow::Result<Vec<Documentation>> {
+        self.documentations
+            .iter()
+            .map(|doc| {
+                documentations::load(&doc.name, &doc.ids, &doc.url).with_context(|| {
+                    format!("failed to load the {} ids from {}", doc.name, doc.ids.display())
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_load() -> anyhow::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("config.json");
+        std::fs::write(
+            &path,
+            r#"{"documentations": [
+                {"name": "FLS", "ids": "/abs/paragraph-ids.json", "url": "https://spec"},
+                {"name": "SRS", "ids": "srs/ids.json", "url": "https://srs/{link}"}
+            ]}"#,
+        )?;
+
+        assert_eq!(
+            Config {
+                documentations: vec![
+                    DocumentationConfig {
+                        name: "FLS".into(),
+                        ids: "/abs/paragraph-ids.json".into(),
+                        url: "https://spec".into(),
+                    },
+                    DocumentationConfig {
+                        name: "SRS".into(),
+                        ids: dir.path().join("srs/ids.json"),
+                        url: "https://srs/{link}".into(),
+                    },
+                ],
+            },
+            Config::load(&path)?,
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_duplicate_names() -> anyhow::Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("config.json");
+        std::fs::write(
+            &path,
+            r#"{"documentations": [
+                {"name": "FLS", "ids": "a.json", "url": "https://a"},
+                {"name": "FLS", "ids": "b.json", "url": "https://b"}
+            ]}"#,
+        )?;
+
+        assert!(Config::load(&path).is_err());
+        Ok(())
+    }
+}
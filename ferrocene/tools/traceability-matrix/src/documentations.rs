@@ -12,6 +12,18 @@ pub(crate) struct Documentation {
     pub(crate) ids: TraceabilityIds,
 }
 
+impl Documentation {
+    /// URL of a link relative to the documentation. The URL of the documentation is either a
+    /// template containing `{link}`, or the base URL the link is appended to.
+    pub(crate) fn link(&self, link: &str) -> String {
+        if self.url.contains("{link}") {
+            self.url.replace("{link}", link)
+        } else {
+            format!("{}/{link}", self.url)
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub(crate) struct TraceabilityIds {
     pub(crate) documents: Vec<Document>,
@@ -59,3 +71,22 @@ pub(crate) fn load(name: &str, path: &Path, url: &str) -> anyhow::Result<Documen
         ids: serde_json::from_slice(&std::fs::read(path)?)?,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link() {
+        let documentation = |url: &str| Documentation {
+            name: "FLS".into(),
+            url: url.into(),
+            ids: TraceabilityIds { documents: Vec::new() },
+        };
+        assert_eq!("../fls/foo.html#bar", documentation("../fls").link("foo.html#bar"));
+        assert_eq!(
+            "https://example.com/view?doc=foo.html#bar",
+            documentation("https://example.com/view?doc={link}").link("foo.html#bar")
+        );
+    }
+}
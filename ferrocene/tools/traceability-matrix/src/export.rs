@@ -0,0 +1,330 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Machine-readable exports of the [`TraceabilityMatrix`], for importing it into requirements
+//! management tools instead of scraping the HTML report.
+//!
+//! The JSON export contains one entry per requirement (specification section or command line
+//! option), with every test linked to it and the targets each test was executed or ignored on.
+//! The CSV export has the same information, with one row per requirement and test (or a single
+//! row without a test for unlinked requirements).
+
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::annotations::{AnnotatedFile, AnnotationSource};
+use crate::matrix::{LinkTest, Requirement, TraceabilityMatrix};
+use crate::test_outcomes::On;
+
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    requirements: Vec<JsonRequirement<'a>>,
+    unknown_annotations: Vec<JsonUnknownAnnotation<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonRequirement<'a> {
+    id: &'a str,
+    kind: &'a str,
+    documentation: &'a str,
+    page: &'a str,
+    name: String,
+    link: &'a str,
+    status: &'a str,
+    tests: Vec<JsonTest<'a>>,
+    untested_targets: Vec<&'a str>,
+}
+
+#[derive(Serialize)]
+struct JsonTest<'a> {
+    test: String,
+    annotated_in: String,
+    executed: Vec<&'a On>,
+    ignored: Vec<&'a On>,
+}
+
+#[derive(Serialize)]
+struct JsonUnknownAnnotation<'a> {
+    annotation: &'a str,
+    test: String,
+}
+
+pub(crate) fn json(matrix: &TraceabilityMatrix) -> anyhow::Result<String> {
+    let requirements = matrix
+        .requirements()
+        .map(|Requirement { element, status, tests, untested_targets }| JsonRequirement {
+            id: &element.id,
+            kind: element.kind.id,
+            documentation: &element.page.documentation,
+            page: &element.page.name,
+            name: element.name(),
+            link: &element.link,
+            status: status.as_str(),
+            tests: tests
+                .iter()
+                .filter_map(|test| match test {
+                    LinkTest::File(file) => Some(JsonTest {
+                        test: test_name(file),
+                        annotated_in: annotated_in(file),
+                        executed: file.targets.executed.0.iter().collect(),
+                        ignored: file.targets.ignored.0.iter().collect(),
+                    }),
+                    LinkTest::NoParagraphsInSection | LinkTest::Informational => None,
+                })
+                .collect(),
+            untested_targets: untested_targets.into_iter().flatten().map(String::as_str).collect(),
+        })
+        .collect();
+    let unknown_annotations = matrix
+        .unknown_annotations
+        .iter()
+        .map(|unknown| JsonUnknownAnnotation {
+            annotation: &unknown.annotation,
+            test: unknown.file.test.display().to_string(),
+        })
+        .collect();
+
+    Ok(serde_json::to_string_pretty(&JsonExport { requirements, unknown_annotations })?)
+}
+
+pub(crate) fn csv(matrix: &TraceabilityMatrix) -> String {
+    let mut out = String::new();
+    write_row(
+        &mut out,
+        &[
+            "id",
+            "kind",
+            "documentation",
+            "page",
+            "name",
+            "status",
+            "test",
+            "annotated_in",
+            "executed_on",
+            "ignored_on",
+        ],
+    );
+    for Requirement { element, status, tests, .. } in matrix.requirements() {
+        let name = element.name();
+        let requirement = [
+            element.id.as_str(),
+            element.kind.id,
+            &element.page.documentation,
+            &element.page.name,
+            &name,
+            status.as_str(),
+        ];
+
+        let files = tests.iter().filter_map(|test| match test {
+            LinkTest::File(file) => Some(file),
+            LinkTest::NoParagraphsInSection | LinkTest::Informational => None,
+        });
+        let mut wrote_test = false;
+        for file in files {
+            let test = test_name(file);
+            let executed = targets(&file.targets.executed.0);
+            let ignored = targets(&file.targets.ignored.0);
+            let test_columns = [test.as_str(), &annotated_in(file), &executed, &ignored];
+            write_row(&mut out, &[&requirement[..], &test_columns[..]].concat());
+            wrote_test = true;
+        }
+        if !wrote_test {
+            write_row(&mut out, &[&requirement[..], &["", "", "", ""][..]].concat());
+        }
+    }
+    out
+}
+
+/// Path of the test, or its libtest name for tests of Cargo packages.
+fn test_name(file: &AnnotatedFile) -> String {
+    match &file.source {
+        AnnotationSource::CargoTest { name } => name.clone(),
+        _ => file.test.display().to_string(),
+    }
+}
+
+/// Where the annotation linking the test comes from.
+fn annotated_in(file: &AnnotatedFile) -> String {
+    match &file.source {
+        AnnotationSource::TestItself => file.test.display().to_string(),
+        AnnotationSource::ParentDirectory { bulk_file } => bulk_file.display().to_string(),
+        AnnotationSource::Rmake => file.test.join("rmake.rs").display().to_string(),
+        AnnotationSource::CargoTest { .. } => file.test.display().to_string(),
+    }
+}
+
+/// Targets as `target/variant`, separated by spaces.
+fn targets<'a>(targets: impl IntoIterator<Item = &'a On>) -> String {
+    targets
+        .into_iter()
+        .map(|on| format!("{}/{}", on.target, on.variant))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn write_row(out: &mut String, columns: &[&str]) {
+    for (i, column) in columns.iter().enumerate() {
+        if i != 0 {
+            out.push(',');
+        }
+        if column.contains([',', '"', '\n', '\r']) {
+            write!(out, "\"{}\"", column.replace('"', "\"\"")).unwrap();
+        } else {
+            out.push_str(column);
+        }
+    }
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+    use crate::annotations::{Annotations, DisplayCommaSeparatedSet, Targets};
+    use crate::documentations::{
+        CliOption, Document, Documentation, Paragraph, Section, TraceabilityIds,
+    };
+    use crate::matrix::prepare;
+
+    fn matrix() -> TraceabilityMatrix {
+        let documentations = [Documentation {
+            name: "FLS".into(),
+            url: "../fls".into(),
+            ids: TraceabilityIds {
+                documents: vec![Document {
+                    title: "Values".into(),
+                    link: "values.html".into(),
+                    informational: false,
+                    sections: vec![
+                        Section {
+                            id: "fls_01".into(),
+                            number: "15.2".into(),
+                            title: "Initialization".into(),
+                            link: "values.html#fls_01".into(),
+                            informational: false,
+                            paragraphs: vec![Paragraph {}],
+                        },
+                        Section {
+                            id: "fls_02".into(),
+                            number: "15.3".into(),
+                            title: "Constants, \"statics\"".into(),
+                            link: "values.html#fls_02".into(),
+                            informational: false,
+                            paragraphs: vec![Paragraph {}],
+                        },
+                    ],
+                    options: vec![CliOption {
+                        id: "um_rustc_crate_name".into(),
+                        program: "rustc".into(),
+                        option: "--crate-name".into(),
+                        link: "values.html#um_rustc_crate_name".into(),
+                    }],
+                }],
+            },
+        }];
+        let on = |target: &str| On { target: target.into(), variant: "default".into() };
+        let test = AnnotatedFile {
+            test: "tests/ui/init.rs".into(),
+            source: AnnotationSource::TestItself,
+            targets: Targets {
+                executed: DisplayCommaSeparatedSet(BTreeSet::from([on(
+                    "x86_64-unknown-linux-gnu",
+                )])),
+                ignored: DisplayCommaSeparatedSet(BTreeSet::from([on("aarch64-unknown-none")])),
+            },
+        };
+        let annotations = Annotations {
+            ids: BTreeMap::from([
+                ("fls_01".into(), BTreeSet::from([test.clone()])),
+                ("um_rustc_crate_name".into(), BTreeSet::from([test.clone()])),
+                ("fls_99".into(), BTreeSet::from([test])),
+            ]),
+            ignored_tests: BTreeMap::new(),
+            considers_ignored_tests: true,
+        };
+        prepare(&documentations, &annotations).unwrap()
+    }
+
+    #[test]
+    fn test_json() {
+        let json: serde_json::Value = serde_json::from_str(&json(&matrix()).unwrap()).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "requirements": [
+                    {
+                        "id": "fls_01",
+                        "kind": "section",
+                        "documentation": "FLS",
+                        "page": "Values",
+                        "name": "15.2 Initialization",
+                        "link": "../fls/values.html#fls_01",
+                        "status": "partially-linked",
+                        "tests": [{
+                            "test": "tests/ui/init.rs",
+                            "annotated_in": "tests/ui/init.rs",
+                            "executed": [
+                                { "target": "x86_64-unknown-linux-gnu", "variant": "default" },
+                            ],
+                            "ignored": [{ "target": "aarch64-unknown-none", "variant": "default" }],
+                        }],
+                        "untested_targets": ["aarch64-unknown-none with variant default"],
+                    },
+                    {
+                        "id": "fls_02",
+                        "kind": "section",
+                        "documentation": "FLS",
+                        "page": "Values",
+                        "name": "15.3 Constants, \"statics\"",
+                        "link": "../fls/values.html#fls_02",
+                        "status": "unlinked",
+                        "tests": [],
+                        "untested_targets": [],
+                    },
+                    {
+                        "id": "um_rustc_crate_name",
+                        "kind": "cli-option",
+                        "documentation": "FLS",
+                        "page": "Values",
+                        "name": "rustc --crate-name",
+                        "link": "../fls/values.html#um_rustc_crate_name",
+                        "status": "partially-linked",
+                        "tests": [{
+                            "test": "tests/ui/init.rs",
+                            "annotated_in": "tests/ui/init.rs",
+                            "executed": [
+                                { "target": "x86_64-unknown-linux-gnu", "variant": "default" },
+                            ],
+                            "ignored": [{ "target": "aarch64-unknown-none", "variant": "default" }],
+                        }],
+                        "untested_targets": ["aarch64-unknown-none with variant default"],
+                    },
+                ],
+                "unknown_annotations": [{ "annotation": "fls_99", "test": "tests/ui/init.rs" }],
+            }),
+            json
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(
+            "id,kind,documentation,page,name,status,test,annotated_in,executed_on,ignored_on\n\
+            fls_01,section,FLS,Values,15.2 Initialization,partially-linked,tests/ui/init.rs,\
+            tests/ui/init.rs,x86_64-unknown-linux-gnu/default,aarch64-unknown-none/default\n\
+            fls_02,section,FLS,Values,\"15.3 Constants, \"\"statics\"\"\",unlinked,,,,\n\
+            um_rustc_crate_name,cli-option,FLS,Values,rustc --crate-name,partially-linked,\
+            tests/ui/init.rs,tests/ui/init.rs,x86_64-unknown-linux-gnu/default,\
+            aarch64-unknown-none/default\n",
+            csv(&matrix())
+        );
+    }
+
+    #[test]
+    fn test_write_row() {
+        let mut out = String::new();
+        write_row(&mut out, &["plain", "with, comma", "with \"quotes\"", ""]);
+        assert_eq!("plain,\"with, comma\",\"with \"\"quotes\"\"\",\n", out);
+    }
+}
@@ -2,7 +2,10 @@
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
 mod annotations;
+mod cargo_packages;
+mod config;
 mod documentations;
+mod export;
 mod matrix;
 mod report;
 mod test_outcomes;
@@ -11,6 +14,7 @@
 use std::path::PathBuf;
 
 use crate::annotations::Annotations;
+use crate::config::Config;
 use crate::matrix::TraceabilityMatrix;
 use crate::report::Urls;
 use crate::test_outcomes::TestOutcomes;
@@ -19,26 +23,35 @@
 fn main() -> anyhow::Result<()> {
     let annotations_path = env_path("ANNOTATIONS");
     let html_out = env_path("HTML_OUT");
+    let json_out = maybe_env_path("JSON_OUT");
+    let csv_out = maybe_env_path("CSV_OUT");
     let src_base = env_path("SRC_BASE");
     let test_outcomes_dir = maybe_env_path("TEST_OUTCOMES_DIR");
+    let cargo_packages = maybe_env_path("CARGO_PACKAGES");
 
     let urls = Urls { src: env_str("SRC_URL") };
 
-    let documentations = [
-        documentations::load("FLS", &env_path("FLS_IDS"), &env_str("FLS_URL"))?,
-        documentations::load("UM", &env_path("UM_IDS"), &env_str("UM_URL"))?,
-    ];
+    let documentations = Config::load(&env_path("CONFIG"))?.load_documentations()?;
 
     let test_outcomes = test_outcomes_dir.map(|dir| TestOutcomes::load(&dir)).transpose()?;
 
     let mut annotations = Annotations::new();
     annotations.load_directory(&annotations_path, &src_base, test_outcomes.as_ref())?;
+    for package in cargo_packages.iter().flat_map(std::env::split_paths) {
+        annotations.load_cargo_package(&package, &src_base, test_outcomes.as_ref())?;
+    }
 
     let matrix = matrix::prepare(&documentations, &annotations)?;
 
     let incomplete = cli_summary(&matrix);
     let report = report::generate(&annotations, &matrix, urls)?;
     std::fs::write(&html_out, report.as_bytes())?;
+    if let Some(json_out) = &json_out {
+        std::fs::write(json_out, export::json(&matrix)?)?;
+    }
+    if let Some(csv_out) = &csv_out {
+        std::fs::write(csv_out, export::csv(&matrix))?;
+    }
 
     // The file:// link is hopefully clickable in terminals.
     eprintln!("Full report: file://{}", std::fs::canonicalize(&html_out)?.display());
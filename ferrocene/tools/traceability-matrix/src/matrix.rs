@@ -10,12 +10,14 @@
 use crate::test_outcomes::On;
 
 pub(crate) const ELEMENT_KIND_SECTION: ElementKind = ElementKind {
+    id: "section",
     singular: "specification section",
     plural: "specification sections",
     include_title_when_copying: true,
 };
 
 pub(crate) const ELEMENT_KIND_CLI_OPTION: ElementKind = ElementKind {
+    id: "cli-option",
     singular: "command line option",
     plural: "command line options",
     include_title_when_copying: false,
@@ -65,12 +67,37 @@ pub(crate) fn analyses_by_kind(&self) -> impl Iterator<Item = &MatrixAnalysis> {
         [&self.sections, &self.cli_options].into_iter()
     }
 
+    /// Every requirement of every analysis, with the tests linked to it.
+    pub(crate) fn requirements(&self) -> impl Iterator<Item = Requirement<'_>> {
+        self.analyses_by_kind().flat_map(|analysis| {
+            let linked = analysis.linked.iter().map(|link| {
+                let status = if link.informational() {
+                    RequirementStatus::Informational
+                } else {
+                    RequirementStatus::Linked
+                };
+                Requirement::linked(link, status)
+            });
+            let partially_linked = analysis
+                .partially_linked
+                .iter()
+                .map(|link| Requirement::linked(link, RequirementStatus::PartiallyLinked));
+            let unlinked = analysis.unlinked.iter().map(|element| Requirement {
+                element,
+                status: RequirementStatus::Unlinked,
+                tests: &[],
+                untested_targets: None,
+            });
+            linked.chain(partially_linked).chain(unlinked)
+        })
+    }
+
     fn analyze_document<'a>(
         &mut self,
         annotations: &Annotations,
         documentation: &'a Documentation,
     ) -> HashSet<&'a String> {
-        let to_url = |url: &str| format!("{}/{url}", documentation.url);
+        let to_url = |url: &str| documentation.link(url);
 
         let mut seen_ids = HashSet::new();
         for page in &documentation.ids.documents {
@@ -110,6 +137,45 @@ fn analyze_document<'a>(
     }
 }
 
+/// A single requirement of the matrix, regardless of which analysis it belongs to.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Requirement<'a> {
+    pub(crate) element: &'a Element,
+    pub(crate) status: RequirementStatus,
+    pub(crate) tests: &'a [LinkTest],
+    pub(crate) untested_targets: Option<&'a BTreeSet<String>>,
+}
+
+impl<'a> Requirement<'a> {
+    fn linked(link: &'a Link, status: RequirementStatus) -> Self {
+        Requirement {
+            element: &link.element,
+            status,
+            tests: &link.tests,
+            untested_targets: Some(&link.untested_targets),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum RequirementStatus {
+    Linked,
+    Informational,
+    PartiallyLinked,
+    Unlinked,
+}
+
+impl RequirementStatus {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            RequirementStatus::Linked => "linked",
+            RequirementStatus::Informational => "informational",
+            RequirementStatus::PartiallyLinked => "partially-linked",
+            RequirementStatus::Unlinked => "unlinked",
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) struct UnknownAnnotation {
     pub(crate) annotation: String,
@@ -258,6 +324,8 @@ fn section(matrix_page: &Page, section: &Section, link: String) -> Self {
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash)]
 pub(crate) struct ElementKind {
+    /// Stable identifier of the kind, used in the machine-readable exports.
+    pub(crate) id: &'static str,
     pub(crate) singular: &'static str,
     pub(crate) plural: &'static str,
     pub(crate) include_title_when_copying: bool,
@@ -5,6 +5,7 @@
 use std::path::Path;
 
 use anyhow::Context;
+use serde::Serialize;
 
 const EXPECTED_FORMAT_VERSION: usize = 1;
 
@@ -53,11 +54,26 @@ pub(crate) fn load(directory: &Path) -> anyhow::Result<Self> {
                             test_outcomes.insert(outcome, name, &target, &ferrocene_variant.id);
                         }
                     }
-                    // Ignore test results from Cargo packages, as we don't consider those in the
-                    // traceability matrix (yet?).
+                    // Libtest names don't include the crate, so prefix them with it to match the
+                    // names of the tests found by `cargo_packages`. When a suite tests multiple
+                    // crates at once we can't know which crate each test comes from, so the
+                    // outcome is recorded for all of them.
                     MetricsNode::TestSuite {
-                        metadata: TestSuiteMetadata::CargoPackage, ..
-                    } => {}
+                        tests,
+                        metadata:
+                            TestSuiteMetadata::CargoPackage { crates, ferrocene_variant, target },
+                    } => {
+                        for Test { name, outcome } in tests {
+                            for krate in &crates {
+                                test_outcomes.insert(
+                                    outcome,
+                                    cargo_test_name(krate, &name),
+                                    &target,
+                                    &ferrocene_variant.id,
+                                );
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -77,10 +93,10 @@ fn insert(&mut self, outcome: MetricsTestOutcome, name: String, target: &str, va
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash, Serialize)]
 pub(crate) struct On {
-    target: String,
-    variant: String,
+    pub(crate) target: String,
+    pub(crate) variant: String,
 }
 
 impl std::fmt::Display for On {
@@ -89,6 +105,11 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     }
 }
 
+/// Name of a libtest test of a Cargo package, in the `crate::path::to::test` format.
+pub(crate) fn cargo_test_name(krate: &str, name: &str) -> String {
+    format!("{krate}::{name}")
+}
+
 /// Compiletest test names are in the `[suite] path/to/test.rs#revision`
 /// format, with the revision being optional.
 fn parse_name(name: String) -> Option<String> {
@@ -121,7 +142,7 @@ enum MetricsNode {
 #[derive(serde::Deserialize)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 enum TestSuiteMetadata {
-    CargoPackage,
+    CargoPackage { crates: Vec<String>, ferrocene_variant: TestSuiteVariant, target: String },
     Compiletest { ferrocene_variant: TestSuiteVariant, target: String },
 }
 
@@ -136,7 +157,7 @@ struct Test {
     outcome: MetricsTestOutcome,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
 // Failed is missing so as to trigger a deserialize failure,
 // because we should not reach this far if any test fails.
@@ -169,8 +190,9 @@ fn test_load_outcomes() -> anyhow::Result<()> {
                     "tests/ui/bar.rs",
                     "tests/run-make/foo.rs",
                     "tests/codegen/foo.rs",
+                    "coretests::num::test_add",
                 ]),
-                ignored_tests: arrange_tests(["tests/ui/baz.rs"])
+                ignored_tests: arrange_tests(["tests/ui/baz.rs", "coretests::num::test_sub"])
             },
             outcomes,
         );
@@ -283,6 +305,35 @@ fn content_1() -> serde_json::Result<Vec<u8>> {
                                 },
                             ],
                         },
+                        {
+                            "kind": "rustbuild_step",
+                            "type": "bootstrap::test::Crate",
+                            "children": [
+                                {
+                                    "kind": "test_suite",
+                                    "metadata": {
+                                        "kind": "cargo_package",
+                                        "crates": ["coretests"],
+                                        "target": "aarch64-unknown-linux-gnu",
+                                        "host": "aarch64-unknown-linux-gnu",
+                                        "stage": 1,
+                                        "ferrocene_variant": {
+                                            "id": "empty",
+                                        },
+                                    },
+                                    "tests": [
+                                        {
+                                            "name": "num::test_add",
+                                            "outcome": "passed",
+                                        },
+                                        {
+                                            "name": "num::test_sub",
+                                            "outcome": "ignored",
+                                        },
+                                    ],
+                                },
+                            ],
+                        },
                     ],
                 },
             ],
@@ -1,3 +1,6 @@
+#[cfg(target_has_atomic_load_store = "ptr")]
+pub mod harness;
+
 use crate::ops::IndexRange;
 use crate::panic::{Location, PanicInfo};
 use crate::slice::SliceIndex;
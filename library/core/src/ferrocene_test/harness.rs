@@ -0,0 +1,438 @@
+//! Ferrocene addition: the test harness of `-Z no-std-test-harness`, for running tests where
+//! libtest can't, as it needs the standard library.
+//!
+//! With that flag, `--test` collects the `#[test]` functions into a static table of
+//! [`TestDescAndFn`]s, and the generated `main` function passes it to [`test_main_static`], or to
+//! the `#![test_runner]` of the crate. The crate provides its own entry point, which calls `main`
+//! (renamed with `#![reexport_test_harness_main]`), and the function receiving the output of the
+//! harness: the same JSON events as libtest's `--format json`, one per line.
+//!
+//! ```ignore (needs a bare-metal target)
+//! #![no_std]
+//! #![no_main]
+//! #![feature(custom_test_frameworks, ferrocene_test)]
+//! #![reexport_test_harness_main = "test_main"]
+//!
+//! use core::ferrocene_test::harness;
+//!
+//! static OUTPUT: fn(&[u8]) = uart_write;
+//!
+//! #[unsafe(no_mangle)]
+//! extern "C" fn _start() -> ! {
+//!     harness::set_output(&OUTPUT);
+//!     // Optional, e.g. from semihosting's `SYS_GET_CMDLINE`.
+//!     harness::set_args("--skip slow_").unwrap();
+//!     test_main();
+//!     exit(harness::succeeded())
+//! }
+//!
+//! #[panic_handler]
+//! fn panic(info: &core::panic::PanicInfo<'_>) -> ! {
+//!     harness::report_panic(info);
+//!     exit(false)
+//! }
+//! ```
+//!
+//! Without the standard library there is no unwinding, so a panic ends the run: the panic handler
+//! reports it as the failure of the running test. For the same reason, `#[should_panic]` tests
+//! are ignored. Tests run one after the other in the order of their names.
+//!
+//! The crate can pass the arguments of the run to [`set_args`]. Like libtest, the harness only runs
+//! the tests whose name contains one of the filters, if any, and none of the `--skip` filters, or
+//! that are equal to them with `--exact`. It rejects any other option, except for the ones asking
+//! for the JSON output.
+
+pub use TestFn::StaticTestFn;
+pub use TestName::StaticTestName;
+
+use crate::fmt::{self, Display, Write};
+use crate::panic::PanicInfo;
+use crate::ptr;
+use crate::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+/// A `#[test]` function, with its description.
+#[derive(Debug)]
+pub struct TestDescAndFn {
+    pub desc: TestDesc,
+    pub testfn: TestFn,
+}
+
+/// The description of a test, the same as libtest's.
+#[derive(Debug)]
+pub struct TestDesc {
+    pub name: TestName,
+    pub ignore: bool,
+    pub ignore_message: Option<&'static str>,
+    pub source_file: &'static str,
+    pub start_line: usize,
+    pub start_col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+    pub should_panic: ShouldPanic,
+    pub compile_fail: bool,
+    pub no_run: bool,
+    pub test_type: TestType,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestName {
+    StaticTestName(&'static str),
+}
+
+impl TestName {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            StaticTestName(name) => name,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShouldPanic {
+    No,
+    Yes,
+    YesWithMessage(&'static str),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestType {
+    UnitTest,
+    IntegrationTest,
+    Unknown,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum TestFn {
+    StaticTestFn(fn() -> Result<(), TestError>),
+}
+
+/// Why a test returning an error failed. The message is cut short if it is too long.
+pub struct TestError {
+    message: [u8; 256],
+    len: usize,
+}
+
+impl TestError {
+    fn new() -> Self {
+        TestError { message: [0; _], len: 0 }
+    }
+
+    pub fn message(&self) -> &str {
+        // SAFETY: `write_str` only appends whole characters.
+        unsafe { crate::str::from_utf8_unchecked(&self.message[..self.len]) }
+    }
+}
+
+impl Write for TestError {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut len = s.len().min(self.message.len() - self.len);
+        while !s.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.message[self.len..self.len + len].copy_from_slice(&s.as_bytes()[..len]);
+        self.len += len;
+        // Stop the formatting once the message is cut short, for it to only keep a prefix.
+        if len == s.len() { Ok(()) } else { Err(fmt::Error) }
+    }
+}
+
+impl fmt::Debug for TestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TestError").field(&self.message()).finish()
+    }
+}
+
+/// The return types of `#[test]` functions, like `std::process::Termination` for libtest.
+pub trait Termination {
+    fn report(self) -> Result<(), TestError>;
+}
+
+impl Termination for () {
+    fn report(self) -> Result<(), TestError> {
+        Ok(())
+    }
+}
+
+impl<T: Termination, E: fmt::Debug> Termination for Result<T, E> {
+    fn report(self) -> Result<(), TestError> {
+        match self {
+            Ok(value) => value.report(),
+            Err(error) => {
+                let mut test_error = TestError::new();
+                let _ = write!(test_error, "Error: {error:?}");
+                Err(test_error)
+            }
+        }
+    }
+}
+
+/// Called by the code generated by `#[test]` with the value returned by the test.
+pub fn assert_test_result<T: Termination>(result: T) -> Result<(), TestError> {
+    result.report()
+}
+
+/// Where the events are written, see [`set_output`]. Either null or a `&'static fn(&[u8])`.
+static OUTPUT: AtomicPtr<fn(&[u8])> = AtomicPtr::new(ptr::null_mut());
+/// The arguments of the run, see [`set_args`].
+static ARGS: AtomicPtr<u8> = AtomicPtr::new(ptr::null_mut());
+static ARGS_LEN: AtomicUsize = AtomicUsize::new(0);
+static EXACT: AtomicBool = AtomicBool::new(false);
+/// The test being run, for [`report_panic`]. Either null or a `&'static TestDescAndFn`.
+static RUNNING: AtomicPtr<TestDescAndFn> = AtomicPtr::new(ptr::null_mut());
+static PASSED: AtomicUsize = AtomicUsize::new(0);
+static FAILED: AtomicUsize = AtomicUsize::new(0);
+static IGNORED: AtomicUsize = AtomicUsize::new(0);
+static FILTERED_OUT: AtomicUsize = AtomicUsize::new(0);
+static SUCCEEDED: AtomicBool = AtomicBool::new(false);
+
+/// Sets the function the harness writes its output to, for example over a UART or semihosting.
+///
+/// The function is stored in a `static` of the crate, as the harness can only keep a pointer to
+/// it: there is no atomic function pointer.
+pub fn set_output(output: &'static fn(&[u8])) {
+    OUTPUT.store(ptr::from_ref(output).cast_mut(), Ordering::Relaxed);
+}
+
+/// Sets the arguments of the run, separated by whitespace, in the same format as libtest's: the
+/// filters, `--skip FILTER` and `--exact`. `--format json` and `-Z unstable-options` are accepted,
+/// as the output is always in JSON.
+///
+/// Returns the first argument the harness doesn't support, in which case the arguments are left
+/// unchanged.
+pub fn set_args(args: &'static str) -> Result<(), &'static str> {
+    let exact = parse_args(args, |_| {})?;
+    ARGS.store(args.as_ptr().cast_mut(), Ordering::Relaxed);
+    ARGS_LEN.store(args.len(), Ordering::Relaxed);
+    EXACT.store(exact, Ordering::Relaxed);
+    Ok(())
+}
+
+/// The arguments set by [`set_args`].
+fn args() -> &'static str {
+    let args = ARGS.load(Ordering::Relaxed);
+    if args.is_null() {
+        return "";
+    }
+    let len = ARGS_LEN.load(Ordering::Relaxed);
+    // SAFETY: `ARGS` and `ARGS_LEN` are only set by `set_args`, from a `&'static str`.
+    unsafe { crate::str::from_utf8_unchecked(crate::slice::from_raw_parts(args, len)) }
+}
+
+/// A test name filter of the arguments.
+enum Filter<'a> {
+    Include(&'a str),
+    Skip(&'a str),
+}
+
+/// Calls `filter` with every filter of `args`, and returns whether `--exact` was passed.
+fn parse_args<'a>(args: &'a str, mut filter: impl FnMut(Filter<'a>)) -> Result<bool, &'a str> {
+    let mut exact = false;
+    let mut args = args.split_ascii_whitespace();
+    while let Some(arg) = args.next() {
+        match arg {
+            "--exact" => exact = true,
+            "--skip" => filter(Filter::Skip(args.next().ok_or(arg)?)),
+            "--format=json" | "-Zunstable-options" => {}
+            "--format" if args.next() == Some("json") => {}
+            "-Z" if args.next() == Some("unstable-options") => {}
+            _ if arg.starts_with('-') => return Err(arg),
+            _ => filter(Filter::Include(arg)),
+        }
+    }
+    Ok(exact)
+}
+
+/// Whether the arguments exclude the test named `name`.
+fn is_filtered_out(name: &str) -> bool {
+    let exact = EXACT.load(Ordering::Relaxed);
+    let matches = |filter: &str| if exact { name == filter } else { name.contains(filter) };
+    let mut has_filters = false;
+    let mut included = false;
+    let mut skipped = false;
+    // The arguments were checked by `set_args`.
+    let _ = parse_args(args(), |filter| match filter {
+        Filter::Include(filter) => {
+            has_filters = true;
+            included |= matches(filter);
+        }
+        Filter::Skip(filter) => skipped |= matches(filter),
+    });
+    (has_filters && !included) || skipped
+}
+
+/// The runner called by the generated `main` function, when the crate has no `#![test_runner]`.
+pub fn test_main_static(tests: &[&'static TestDescAndFn]) {
+    run(tests);
+}
+
+/// Runs `tests`, returning whether all of them passed or were ignored.
+///
+/// Tests run on the current thread, and only one run can happen at a time.
+pub fn run(tests: &[&'static TestDescAndFn]) -> bool {
+    PASSED.store(0, Ordering::Relaxed);
+    FAILED.store(0, Ordering::Relaxed);
+    IGNORED.store(0, Ordering::Relaxed);
+    let filtered_out = tests.iter().filter(|test| is_filtered_out(test.desc.name.as_str())).count();
+    FILTERED_OUT.store(filtered_out, Ordering::Relaxed);
+    event(format_args!(
+        r#"{{ "type": "suite", "event": "started", "test_count": {} }}"#,
+        tests.len() - filtered_out
+    ));
+
+    for &test in tests {
+        if is_filtered_out(test.desc.name.as_str()) {
+            continue;
+        }
+        let name = Json(test.desc.name.as_str());
+        event(format_args!(r#"{{ "type": "test", "event": "started", "name": "{name}" }}"#));
+
+        if test.desc.ignore || test.desc.should_panic != ShouldPanic::No {
+            let message = match test.desc.ignore {
+                true => test.desc.ignore_message,
+                false => Some("`should_panic` tests need unwinding, which the harness lacks"),
+            };
+            increment(&IGNORED);
+            match message {
+                Some(message) => event(format_args!(
+                    concat!(
+                        r#"{{ "type": "test", "name": "{name}", "event": "ignored", "#,
+                        r#""message": "{message}" }}"#
+                    ),
+                    name = name,
+                    message = Json(message),
+                )),
+                None => event(format_args!(
+                    r#"{{ "type": "test", "name": "{name}", "event": "ignored" }}"#
+                )),
+            }
+            continue;
+        }
+
+        RUNNING.store(ptr::from_ref(test).cast_mut(), Ordering::Relaxed);
+        let StaticTestFn(testfn) = test.testfn;
+        let result = testfn();
+        RUNNING.store(ptr::null_mut(), Ordering::Relaxed);
+
+        match result {
+            Ok(()) => {
+                increment(&PASSED);
+                event(format_args!(r#"{{ "type": "test", "name": "{name}", "event": "ok" }}"#));
+            }
+            Err(error) => {
+                increment(&FAILED);
+                event(format_args!(
+                    concat!(
+                        r#"{{ "type": "test", "name": "{name}", "event": "failed", "#,
+                        r#""message": "{message}" }}"#
+                    ),
+                    name = name,
+                    message = Json(error.message()),
+                ));
+            }
+        }
+    }
+
+    finish()
+}
+
+/// Whether all the tests of the last run passed or were ignored.
+pub fn succeeded() -> bool {
+    SUCCEEDED.load(Ordering::Relaxed)
+}
+
+/// Reports the failure of the running test and the end of the run, to be called by the panic
+/// handler. Does nothing when no test is running.
+pub fn report_panic(info: &PanicInfo<'_>) {
+    let test = RUNNING.load(Ordering::Relaxed);
+    // SAFETY: `RUNNING` is either null or comes from a `&'static TestDescAndFn`.
+    let Some(test) = (unsafe { test.as_ref() }) else { return };
+    RUNNING.store(ptr::null_mut(), Ordering::Relaxed);
+
+    increment(&FAILED);
+    let name = test.desc.name.as_str();
+    event(format_args!(
+        r#"{{ "type": "test", "name": "{}", "event": "failed", "stdout": "{}" }}"#,
+        Json(name),
+        Json(format_args!("thread '{name}' {info}\n"))
+    ));
+    finish();
+}
+
+/// Writes the outcome of the run.
+fn finish() -> bool {
+    let failed = FAILED.load(Ordering::Relaxed);
+    event(format_args!(
+        concat!(
+            r#"{{ "type": "suite", "event": "{}", "passed": {}, "failed": {}, "#,
+            r#""ignored": {}, "measured": 0, "filtered_out": {} }}"#
+        ),
+        if failed == 0 { "ok" } else { "failed" },
+        PASSED.load(Ordering::Relaxed),
+        failed,
+        IGNORED.load(Ordering::Relaxed),
+        FILTERED_OUT.load(Ordering::Relaxed),
+    ));
+    SUCCEEDED.store(failed == 0, Ordering::Relaxed);
+    failed == 0
+}
+
+/// Without compare-and-swap on every target, and as there is only one run at a time.
+fn increment(counter: &AtomicUsize) {
+    counter.store(counter.load(Ordering::Relaxed) + 1, Ordering::Relaxed);
+}
+
+/// Writes an event to the output, on its own line.
+fn event(args: fmt::Arguments<'_>) {
+    let output = OUTPUT.load(Ordering::Relaxed);
+    // SAFETY: `OUTPUT` is either null or comes from a `&'static fn(&[u8])`.
+    let Some(&output) = (unsafe { output.as_ref() }) else { return };
+    let _ = Output(output).write_fmt(format_args!("{args}\n"));
+}
+
+struct Output(fn(&[u8]));
+
+impl Write for Output {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        (self.0)(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Displays a value inside of a JSON string, escaping the same characters as libtest.
+struct Json<T>(T);
+
+impl<T: Display> Display for Json<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(Escape(f), "{}", self.0)
+    }
+}
+
+struct Escape<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+impl Write for Escape<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut start = 0;
+        for (i, byte) in s.bytes().enumerate() {
+            let escaped = match byte {
+                b'"' => "\\\"",
+                b'\\' => "\\\\",
+                b'\x08' => "\\b",
+                b'\t' => "\\t",
+                b'\n' => "\\n",
+                b'\x0c' => "\\f",
+                b'\r' => "\\r",
+                b'\x00'..=b'\x1f' | b'\x7f' => {
+                    self.0.write_str(&s[start..i])?;
+                    write!(self.0, "\\u{byte:04x}")?;
+                    start = i + 1;
+                    continue;
+                }
+                _ => continue,
+            };
+            self.0.write_str(&s[start..i])?;
+            self.0.write_str(escaped)?;
+            start = i + 1;
+        }
+        self.0.write_str(&s[start..])
+    }
+}
@@ -1796,7 +1796,8 @@ macro_rules! trace_macros {
     ///
     /// [the reference]: ../../../reference/attributes/testing.html#the-test-attribute
     #[stable(feature = "rust1", since = "1.0.0")]
-    #[allow_internal_unstable(test, rustc_attrs, coverage_attribute)]
+    // Ferrocene addition: `ferrocene_test` for the harness of `-Z no-std-test-harness`
+    #[allow_internal_unstable(test, rustc_attrs, coverage_attribute, ferrocene_test)]
     #[rustc_builtin_macro]
     pub macro test($item:item) {
         /* compiler built-in */
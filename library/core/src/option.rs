@@ -2295,6 +2295,7 @@ pub const fn transpose(self) -> Result<Option<T>, E> {
 #[cfg_attr(panic = "immediate-abort", inline)]
 #[cold]
 #[track_caller]
+#[rustc_diagnostic_item = "option_unwrap_failed"]
 #[ferrocene::prevalidated]
 const fn unwrap_failed() -> ! {
     panic("called `Option::unwrap()` on a `None` value")
@@ -2305,6 +2306,7 @@ const fn unwrap_failed() -> ! {
 #[cfg_attr(panic = "immediate-abort", inline)]
 #[cold]
 #[track_caller]
+#[rustc_diagnostic_item = "option_expect_failed"]
 #[ferrocene::prevalidated]
 const fn expect_failed(msg: &str) -> ! {
     panic_display(&msg)
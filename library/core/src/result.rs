@@ -1914,6 +1914,7 @@ pub const fn flatten(self) -> Result<T, E> {
 #[inline(never)]
 #[cold]
 #[track_caller]
+#[rustc_diagnostic_item = "result_unwrap_failed"]
 #[ferrocene::prevalidated]
 fn unwrap_failed(msg: &str, error: &dyn fmt::Debug) -> ! {
     panic!("{msg}: {error:?}");
@@ -1927,6 +1928,7 @@ fn unwrap_failed(msg: &str, error: &dyn fmt::Debug) -> ! {
 #[inline]
 #[cold]
 #[track_caller]
+#[rustc_diagnostic_item = "result_unwrap_failed"]
 const fn unwrap_failed<T>(_msg: &str, _error: &T) -> ! {
     panic!()
 }
@@ -38,6 +38,7 @@ fn index_mut(&mut self, index: I) -> &mut I::Output {
 #[cfg_attr(not(panic = "immediate-abort"), inline(never), cold)]
 #[cfg_attr(panic = "immediate-abort", inline)]
 #[track_caller]
+#[rustc_diagnostic_item = "slice_index_fail"]
 #[ferrocene::prevalidated]
 const fn slice_index_fail(start: usize, end: usize, len: usize) -> ! {
     if start > len {
@@ -69,12 +69,14 @@
 #[track_caller]
 #[rustc_allow_const_fn_unstable(const_eval_select)]
 #[cfg(not(panic = "immediate-abort"))]
+#[rustc_diagnostic_item = "str_slice_error_fail"]
 #[ferrocene::prevalidated]
 const fn slice_error_fail(s: &str, begin: usize, end: usize) -> ! {
     crate::intrinsics::const_eval_select((s, begin, end), slice_error_fail_ct, slice_error_fail_rt)
 }
 
 #[cfg(panic = "immediate-abort")]
+#[rustc_diagnostic_item = "str_slice_error_fail"]
 const fn slice_error_fail(s: &str, begin: usize, end: usize) -> ! {
     slice_error_fail_ct(s, begin, end)
 }
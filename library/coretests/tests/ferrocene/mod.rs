@@ -23,6 +23,7 @@
 mod result;
 mod slice;
 mod str;
+mod test_harness;
 mod time;
 mod tuple;
 mod unicode;
@@ -0,0 +1,159 @@
+use core::ferrocene_test::harness::{self, *};
+use std::sync::Mutex;
+
+// The harness has global state: only one test of this file can use it at a time.
+static LOCK: Mutex<()> = Mutex::new(());
+static OUTPUT: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+fn capture(bytes: &[u8]) {
+    OUTPUT.lock().unwrap().extend_from_slice(bytes);
+}
+
+fn run(tests: &[&'static TestDescAndFn]) -> (bool, String) {
+    run_with_args("", tests)
+}
+
+fn run_with_args(args: &'static str, tests: &[&'static TestDescAndFn]) -> (bool, String) {
+    OUTPUT.lock().unwrap().clear();
+    harness::set_output(capture);
+    harness::set_args(args).unwrap();
+    let result = harness::run(tests);
+    (result, String::from_utf8(OUTPUT.lock().unwrap().clone()).unwrap())
+}
+
+const fn test(name: &'static str, testfn: fn() -> Result<(), TestError>) -> TestDescAndFn {
+    TestDescAndFn {
+        desc: TestDesc {
+            name: StaticTestName(name),
+            ignore: false,
+            ignore_message: None,
+            source_file: "test_harness.rs",
+            start_line: 1,
+            start_col: 1,
+            end_line: 1,
+            end_col: 1,
+            should_panic: ShouldPanic::No,
+            compile_fail: false,
+            no_run: false,
+            test_type: TestType::UnitTest,
+        },
+        testfn: StaticTestFn(testfn),
+    }
+}
+
+static PASSING: TestDescAndFn = test("passing", || assert_test_result(()));
+static FAILING: TestDescAndFn = test("failing", || assert_test_result(Err::<(), _>("oh \"no\"")));
+static IGNORED: TestDescAndFn = {
+    let mut test = test("ignored", || assert_test_result(()));
+    test.desc.ignore = true;
+    test.desc.ignore_message = Some("slow");
+    test
+};
+static SHOULD_PANIC: TestDescAndFn = {
+    let mut test = test("should_panic", || assert_test_result(()));
+    test.desc.should_panic = ShouldPanic::Yes;
+    test
+};
+static PANICKING: TestDescAndFn = test("panicking", || {
+    let message = "boom";
+    let args = format_args!("{message}");
+    harness::report_panic(&core::ferrocene_test::create_panic_info(&args));
+    assert_test_result(())
+});
+
+#[test]
+fn test_harness_passing() {
+    let _lock = LOCK.lock().unwrap();
+    let (result, output) = run(&[&PASSING, &IGNORED]);
+    assert!(result);
+    assert!(harness::succeeded());
+    assert_eq!(
+        output,
+        concat!(
+            "{ \"type\": \"suite\", \"event\": \"started\", \"test_count\": 2 }\n",
+            "{ \"type\": \"test\", \"event\": \"started\", \"name\": \"passing\" }\n",
+            "{ \"type\": \"test\", \"name\": \"passing\", \"event\": \"ok\" }\n",
+            "{ \"type\": \"test\", \"event\": \"started\", \"name\": \"ignored\" }\n",
+            "{ \"type\": \"test\", \"name\": \"ignored\", \"event\": \"ignored\", ",
+            "\"message\": \"slow\" }\n",
+            "{ \"type\": \"suite\", \"event\": \"ok\", \"passed\": 1, \"failed\": 0, ",
+            "\"ignored\": 1, \"measured\": 0, \"filtered_out\": 0 }\n",
+        )
+    );
+}
+
+#[test]
+fn test_harness_failing() {
+    let _lock = LOCK.lock().unwrap();
+    let (result, output) = run(&[&FAILING, &SHOULD_PANIC]);
+    assert!(!result);
+    assert!(!harness::succeeded());
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines[2],
+        concat!(
+            r#"{ "type": "test", "name": "failing", "event": "failed", "#,
+            r#""message": "Error: \"oh \\\"no\\\"\"" }"#,
+        )
+    );
+    assert!(lines[4].contains(r#""event": "ignored""#));
+    assert!(lines[4].contains("`should_panic` tests need unwinding"));
+    assert!(
+        lines[5].starts_with(r#"{ "type": "suite", "event": "failed", "passed": 0, "failed": 1"#)
+    );
+}
+
+#[test]
+fn test_harness_report_panic() {
+    let _lock = LOCK.lock().unwrap();
+
+    // Outside of a run, the panic isn't a test failure.
+    OUTPUT.lock().unwrap().clear();
+    let args = format_args!("boom");
+    harness::report_panic(&core::ferrocene_test::create_panic_info(&args));
+    assert!(OUTPUT.lock().unwrap().is_empty());
+
+    let (_, output) = run(&[&PANICKING]);
+    let lines = output.lines().collect::<Vec<_>>();
+    assert!(lines[2].starts_with(concat!(
+        r#"{ "type": "test", "name": "panicking", "event": "failed", "#,
+        r#""stdout": "thread 'panicking' panicked at "#,
+    )));
+    assert!(lines[2].ends_with(r#":\nboom\n" }"#));
+    assert!(
+        lines[3].starts_with(r#"{ "type": "suite", "event": "failed", "passed": 0, "failed": 1"#)
+    );
+    assert!(!harness::succeeded());
+}
+
+#[test]
+fn test_harness_filters() {
+    let _lock = LOCK.lock().unwrap();
+    static PASSING_TOO: TestDescAndFn = test("passing_too", || assert_test_result(()));
+    let tests = [&PASSING, &PASSING_TOO, &FAILING];
+
+    let (result, output) = run_with_args("pass --skip too", &tests);
+    assert!(result);
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], r#"{ "type": "suite", "event": "started", "test_count": 1 }"#);
+    assert!(lines[1].contains(r#""name": "passing""#));
+    assert!(lines[3].ends_with(r#""filtered_out": 2 }"#));
+
+    let (_, output) = run_with_args("-Z unstable-options --format json --exact passing", &tests);
+    assert!(output.contains(r#""test_count": 1"#));
+    assert!(!output.contains("passing_too"));
+
+    assert_eq!(harness::set_args("--test-threads 1"), Err("--test-threads"));
+    assert_eq!(harness::set_args("--format pretty"), Err("--format"));
+    assert_eq!(harness::set_args("--skip"), Err("--skip"));
+}
+
+#[test]
+fn test_harness_error_message() {
+    let long = format!("a{}", "é".repeat(200));
+    let full = format!("Error: {long:?}");
+    let Err(error) = assert_test_result(Err::<(), _>(long)) else { panic!() };
+    // Cut short at a character boundary.
+    assert!(full.starts_with(error.message()));
+    assert!((255..=256).contains(&error.message().len()));
+}
@@ -44,6 +44,7 @@
 use crate::ferrocene::code_coverage::{instrument_coverage, measure_coverage};
 use crate::ferrocene::doc::code_coverage::AllCoverageReports;
 use crate::ferrocene::secret_sauce::SecretSauceArtifacts;
+use crate::ferrocene::test::semihosting;
 use crate::ferrocene::test_variants::{TestCondition, TestVariant};
 use crate::ferrocene::tool::SymbolReport;
 use crate::utils::build_stamp::{self, BuildStamp};
@@ -2573,6 +2574,9 @@ fn run(self, builder: &Builder<'_>) {
         for flag in hostflags {
             cmd.arg("--host-rustcflags").arg(flag);
         }
+        // Ferrocene addition
+        targetflags.extend(semihosting::rustflags(builder, target));
+
         for flag in targetflags {
             cmd.arg("--target-rustcflags").arg(flag);
         }
@@ -2804,6 +2808,9 @@ fn run(self, builder: &Builder<'_>) {
             cmd.arg("--remote-test-client").arg(builder.tool_exe(Tool::RemoteTestClient));
         } else if let Some(tool) = builder.runner(target) {
             cmd.arg("--runner").arg(tool);
+        } else if let Some(tool) = semihosting::runner(builder, target) {
+            // Ferrocene addition
+            cmd.arg("--runner").arg(tool);
         }
 
         if suite != "mir-opt" {
@@ -3370,6 +3377,11 @@ fn prepare_cargo_test(
     builder: &Builder<'_>,
 ) -> BootstrapCommand {
     let compiler = cargo.compiler();
+    // Ferrocene addition: link the test programs for the board they run on.
+    let mut cargo = cargo;
+    for flag in semihosting::rustflags(builder, target) {
+        cargo.rustflag(&flag);
+    }
     let mut cargo: BootstrapCommand = cargo.into();
 
     // Propagate `--bless` if it has not already been set/unset
@@ -3427,6 +3439,9 @@ fn prepare_cargo_test(
         );
     } else if let Some(tool) = builder.runner(target) {
         cargo.env(format!("CARGO_TARGET_{}_RUNNER", envify(&target.triple)), tool);
+    } else if let Some(tool) = semihosting::runner(builder, target) {
+        // Ferrocene addition
+        cargo.env(format!("CARGO_TARGET_{}_RUNNER", envify(&target.triple)), tool);
     }
 
     cargo
@@ -3485,6 +3500,19 @@ fn run(self, builder: &Builder<'_>) {
         let target = self.target;
         let mode = self.mode;
 
+        // Ferrocene addition: the tests of the library crates need std and libtest, which the
+        // programs run by the semihosting runner don't have. Test `core` there with the
+        // `#![no_std]` tests built with `-Z no-std-test-harness` instead.
+        if mode == Mode::Std && builder.kind != Kind::Miri && semihosting::is_used(builder, target)
+        {
+            builder.info(&format!(
+                "Testing core on {target} with the no_std test harness, the other library crates \
+                need std"
+            ));
+            builder.ensure(semihosting::NoStdTests::new(build_compiler, target));
+            return;
+        }
+
         // Prepare sysroot
         // See [field@compile::Std::force_recompile].
         builder.ensure(Std::new(build_compiler, build_compiler.host).force_recompile(true));
@@ -493,6 +493,7 @@ fn metadata(&self) -> Option<StepMetadata> {
     FerroceneTraceabilityMatrix, "ferrocene/tools/traceability-matrix", "traceability-matrix";
     FerroceneDocumentSignatures, "ferrocene/tools/document-signatures", "document-signatures";
     FerroceneGenerateTarball, "ferrocene/tools/generate-tarball", "generate-tarball";
+    FerroceneSemihostingRunner, "ferrocene/tools/semihosting-runner", "semihosting-runner";
 
     // This is marked as an external tool because it includes dependencies
     // from submodules. Trying to keep the lints in sync between all the repos
@@ -858,6 +858,7 @@ macro_rules! describe {
                 crate::ferrocene::tool::flip_link::FlipLink,
                 tool::FerroceneDocumentSignatures,
                 tool::FerroceneGenerateTarball,
+                tool::FerroceneSemihostingRunner,
                 tool::FerroceneTraceabilityMatrix,
                 tool::RustdocGUITest,
                 tool::OptimizedDist,
@@ -931,6 +932,8 @@ macro_rules! describe {
                 crate::ferrocene::test::SelfTest,
                 crate::ferrocene::test::CheckDocumentSignatures,
                 crate::ferrocene::test::GenerateTarball,
+                crate::ferrocene::test::semihosting::SemihostingRunner,
+                crate::ferrocene::test::semihosting::NoStdTests,
                 crate::ferrocene::test::DiffUpstream,
                 crate::ferrocene::test::certified_core_symbols::CertifiedCoreSymbols,
                 crate::ferrocene::test::flip_link::FlipLink,
@@ -350,6 +350,8 @@ pub struct Config {
     pub ferrocene_coverage_outcomes: FerroceneCoverageOutcomes,
     pub ferrocene_oxidos_src: Option<String>,
     pub ferrocene_tarball_signing_kms_key_arn: Option<String>,
+    pub ferrocene_tarball_signing_cosign_identity: Option<String>,
+    pub ferrocene_tarball_signing_cosign_oidc_issuer: Option<String>,
     pub ferrocene_document_signatures: FerroceneDocumentSignatures,
     pub ferrocene_allow_dev_signing: bool,
     pub ferrocene_compiler_technical_report_url: Option<String>,
@@ -1188,6 +1190,8 @@ pub(crate) fn parse_inner(
         let mut ferrocene_coverage_outcomes = Default::default();
         let mut ferrocene_oxidos_src = Default::default();
         let mut ferrocene_tarball_signing_kms_key_arn = Default::default();
+        let mut ferrocene_tarball_signing_cosign_identity = Default::default();
+        let mut ferrocene_tarball_signing_cosign_oidc_issuer = Default::default();
         let mut ferrocene_document_signatures = Default::default();
         let mut ferrocene_compiler_technical_report_url = Default::default();
         let mut ferrocene_core_technical_report_url = Default::default();
@@ -1212,6 +1216,16 @@ pub(crate) fn parse_inner(
             ferrocene_aws_profile = f.aws_profile;
             ferrocene_oxidos_src = f.oxidos_src;
             ferrocene_tarball_signing_kms_key_arn = f.tarball_signing_kms_key_arn;
+            if f.tarball_signing_cosign_identity.is_some()
+                != f.tarball_signing_cosign_oidc_issuer.is_some()
+            {
+                panic!(
+                    "ferrocene.tarball-signing-cosign-identity and \
+                     ferrocene.tarball-signing-cosign-oidc-issuer must be set together"
+                );
+            }
+            ferrocene_tarball_signing_cosign_identity = f.tarball_signing_cosign_identity;
+            ferrocene_tarball_signing_cosign_oidc_issuer = f.tarball_signing_cosign_oidc_issuer;
             ferrocene_compiler_technical_report_url = f.compiler_technical_report_url;
             ferrocene_core_technical_report_url = f.core_technical_report_url;
 
@@ -1832,6 +1846,8 @@ pub(crate) fn parse_inner(
             ferrocene_coverage_outcomes,
             ferrocene_oxidos_src,
             ferrocene_tarball_signing_kms_key_arn,
+            ferrocene_tarball_signing_cosign_identity,
+            ferrocene_tarball_signing_cosign_oidc_issuer,
             ferrocene_document_signatures,
             ferrocene_compiler_technical_report_url,
             ferrocene_core_technical_report_url,
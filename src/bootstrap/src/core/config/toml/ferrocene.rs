@@ -17,6 +17,8 @@ struct Ferrocene {
         coverage_outcomes_dir: Option<PathBuf> = "coverage-outcomes-dir",
         oxidos_src: Option<String> = "oxidos-src",
         tarball_signing_kms_key_arn: Option<String> = "tarball-signing-kms-key-arn",
+        tarball_signing_cosign_identity: Option<String> = "tarball-signing-cosign-identity",
+        tarball_signing_cosign_oidc_issuer: Option<String> = "tarball-signing-cosign-oidc-issuer",
         document_signatures: Option<String> = "document-signatures",
         document_signatures_s3_bucket: Option<String> = "document-signatures-s3-bucket",
         document_signatures_tarball: Option<PathBuf> = "document-signatures-tarball",
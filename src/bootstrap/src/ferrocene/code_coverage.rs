@@ -32,6 +32,9 @@ pub(crate) fn instrument_coverage(
     // Tell the compiler to instrument these crates.
     cargo.rustdocflag("-Cinstrument-coverage");
     cargo.rustflag("-Cinstrument-coverage");
+    // Also record branch regions, so that blanket can report branch coverage.
+    cargo.rustdocflag("-Zcoverage-options=branch");
+    cargo.rustflag("-Zcoverage-options=branch");
     // Tell the crate that it's being instrumented.
     cargo.rustflag("--cfg=ferrocene_coverage");
     // Coverage tests must run with panic=abort, we don't certify unwinding.
@@ -152,13 +155,9 @@ pub(crate) fn generate_coverage_report(builder: &Builder<'_>) {
     };
 
     let paths = Paths::find(builder, state.target, state.coverage_for);
-    let llvm_bin_dir = match builder.llvm_config(builder.host_target) {
-        None => builder.llvm_out(builder.host_target).join("bin"),
-        Some(system_llvm) => system_llvm.parent().unwrap().into(),
-    };
 
     builder.info("Merging together code coverage measurements");
-    let mut cmd = BootstrapCommand::new(llvm_bin_dir.join("llvm-profdata"));
+    let mut cmd = BootstrapCommand::new(llvm_bin_dir(builder).join("llvm-profdata"));
     cmd.arg("merge").arg("--sparse").arg("-o").arg(&paths.profdata_file).arg(&paths.profraw_dir);
     cmd.fail_fast().run(builder);
 
@@ -187,6 +186,14 @@ pub(crate) fn generate_coverage_report(builder: &Builder<'_>) {
     }
 }
 
+/// Where `llvm-profdata` and `llvm-cov` can be found.
+pub(super) fn llvm_bin_dir(builder: &Builder<'_>) -> PathBuf {
+    match builder.llvm_config(builder.host_target) {
+        None => builder.llvm_out(builder.host_target).join("bin"),
+        Some(system_llvm) => system_llvm.parent().unwrap().into(),
+    }
+}
+
 fn coverage_dir(builder: &Builder<'_>, t: TargetSelection) -> PathBuf {
     builder.doc_out(t).join("coverage")
 }
@@ -22,7 +22,7 @@
 use crate::ferrocene::uv_command;
 use crate::utils::exec::command;
 use crate::utils::tarball::{GeneratedTarball, Tarball};
-use crate::{FileType, t};
+use crate::{FileType, exe, t};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) struct Docs {
@@ -397,11 +397,21 @@ fn is_default_step(_: &Builder<'_>) -> bool {
 
     fn run(self, builder: &Builder<'_>) -> Self::Output {
         let self_test = builder.ensure(crate::ferrocene::tool::SelfTest { target: self.target });
+        let document_signatures =
+            builder.ensure(crate::ferrocene::tool::DocumentSignatures { target: self.target });
 
         let mut tarball = Tarball::new(builder, "ferrocene-self-test", &self.target.triple);
         tarball.add_file(self_test, "bin", FileType::Executable);
+        // Lets auditors verify the signatures of the qualification documents offline.
+        tarball.add_renamed_file(
+            document_signatures,
+            "bin",
+            &exe("ferrocene-document-signatures", self.target),
+            FileType::Executable,
+        );
 
         tarball.ferrocene_proxied_binary("bin/ferrocene-self-test");
+        tarball.ferrocene_proxied_binary("bin/ferrocene-document-signatures");
         tarball.generate()
     }
 }
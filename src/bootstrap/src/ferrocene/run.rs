@@ -92,13 +92,41 @@ fn run(self, builder: &Builder<'_>) -> Self::Output {
             }
         }
 
+        let config = test_annotations_base.with_file_name("traceability-matrix-config.json");
+        builder.create_dir(config.parent().unwrap());
+        builder.create(
+            &config,
+            &serde_json::json!({
+                "documentations": [
+                    {
+                        "name": "FLS",
+                        "ids": specification.join("paragraph-ids.json"),
+                        "url": spec_url,
+                    },
+                    {
+                        "name": "UM",
+                        "ids": user_manual.join("traceability-ids.json"),
+                        "url": user_manual_url,
+                    },
+                ],
+            })
+            .to_string(),
+        );
+
         let mut cmd = builder.tool_cmd(Tool::FerroceneTraceabilityMatrix);
-        cmd.env("TRACEABILITY_MATRIX_FLS_IDS", specification.join("paragraph-ids.json"))
-            .env("TRACEABILITY_MATRIX_FLS_URL", spec_url)
-            .env("TRACEABILITY_MATRIX_UM_IDS", user_manual.join("traceability-ids.json"))
-            .env("TRACEABILITY_MATRIX_UM_URL", user_manual_url)
+        cmd.env("TRACEABILITY_MATRIX_CONFIG", &config)
             .env("TRACEABILITY_MATRIX_ANNOTATIONS", test_annotations_base)
             .env("TRACEABILITY_MATRIX_HTML_OUT", &html_output)
+            .env("TRACEABILITY_MATRIX_JSON_OUT", html_output.with_extension("json"))
+            .env("TRACEABILITY_MATRIX_CSV_OUT", html_output.with_extension("csv"))
+            .env(
+                "TRACEABILITY_MATRIX_CARGO_PACKAGES",
+                std::env::join_paths(
+                    ["coretests", "alloctests"]
+                        .map(|package| builder.src.join("library").join(package)),
+                )
+                .expect("paths should not contain the path separator"),
+            )
             .env("TRACEABILITY_MATRIX_SRC_BASE", &builder.src)
             .env("TRACEABILITY_MATRIX_SRC_URL", src_url);
 
@@ -235,6 +263,12 @@ fn make_run(run: RunConfig<'_>) {
     fn run(self, builder: &Builder<'_>) -> Self::Output {
         let src = to_str(&builder.src);
         let html_out = coverage_file(&builder, self.certified_target);
+        // For CI dashboards that can't read the HTML report.
+        let lcov_out = html_out.with_extension("lcov");
+        let cobertura_out = html_out.with_extension("xml");
+        // So that later runs can be compared with `blanket diff`.
+        let json_out = html_out.with_extension("json");
+        let branch_export = html_out.with_extension("branches.json");
         let sha_buf;
         let sha = match builder.rust_info() {
             GitInfo::Absent => panic!(
@@ -258,6 +292,17 @@ fn to_str(p: &Path) -> &str {
             p.as_os_str().to_str().expect("invalid utf8 in path")
         }
 
+        // Blanket can't read branch regions from the profiles, so they are exported by `llvm-cov`,
+        // with the same path remapping.
+        let mut llvm_cov = exec::command(code_coverage::llvm_bin_dir(builder).join("llvm-cov"));
+        llvm_cov.args(&["export", "-format=text", "-skip-functions", "-instr-profile"]);
+        llvm_cov.arg(&self.profdata).arg(format!("-path-equivalence={remap_path}"));
+        for bin in &self.instrumented_binaries {
+            llvm_cov.arg("-object").arg(bin);
+        }
+        let export = llvm_cov.run_capture_stdout(builder).stdout();
+        builder.create(&branch_export, &export);
+
         let mut blanket = exec::command(builder.ensure(Blanket {}));
         blanket.args(&[
             "show",
@@ -271,6 +316,14 @@ fn to_str(p: &Path) -> &str {
             src,
             "--html-out",
             to_str(&html_out),
+            "--lcov-out",
+            to_str(&lcov_out),
+            "--cobertura-out",
+            to_str(&cobertura_out),
+            "--json-out",
+            to_str(&json_out),
+            "--branch-export",
+            to_str(&branch_export),
         ]);
         for bin in self.instrumented_binaries {
             blanket.args(&["--object", to_str(&bin)]);
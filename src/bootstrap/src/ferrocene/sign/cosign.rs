@@ -31,7 +31,7 @@
 ];
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub(super) struct CosignBinary;
+pub(crate) struct CosignBinary;
 
 impl Step for CosignBinary {
     type Output = PathBuf;
@@ -5,7 +5,7 @@
 // generated, and they all invoke the SignDocument step behind the scenes to actually perform the
 // signature of the documentation.
 
-mod cosign;
+pub(crate) mod cosign;
 pub(crate) mod signature_files;
 
 use std::path::{Path, PathBuf};
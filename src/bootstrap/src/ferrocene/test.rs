@@ -3,6 +3,7 @@
 
 pub(crate) mod certified_core_symbols;
 pub(crate) mod flip_link;
+pub(crate) mod semihosting;
 
 use crate::builder::{Builder, CommandLineStep, RunConfig, ShouldRun};
 use crate::core::build_steps::test::failed_tests::SetupFailedTestsFile;
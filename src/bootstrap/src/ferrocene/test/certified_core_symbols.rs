@@ -41,43 +41,35 @@ fn run(self, builder: &Builder<'_>) -> Self::Output {
             return;
         }
 
-        // load the expected list of qualified functions
         let expected_path = Path::new(TRACKED_FILE);
-        let mut expected: Vec<String> = Default::default();
         let expected_content = builder.read(expected_path);
-        for qualified_name in expected_content.lines() {
-            expected.push(qualified_name.to_string());
-        }
 
-        // generate the actual list of qualified functions
+        // generate the actual list of functions, with their validation status
         let actual_symbol_report_content = builder.read(&actual_symbol_report_path);
         let actual_symbol_report =
             serde_json::from_str::<SymbolReport>(&actual_symbol_report_content).unwrap();
-        let actual: Vec<String> = actual_symbol_report.to_qualified_fn_list();
+        let actual_content = actual_symbol_report.to_csv();
 
         // compare the two
-        if actual == expected {
+        if actual_content.lines().eq(expected_content.lines()) {
             builder.info(&format!("{TRACKED_FILE} is up to date."));
+        } else if builder.config.cmd.bless() {
+            std::fs::write(TRACKED_FILE, actual_content).unwrap();
+            builder.info(&format!("Updated {TRACKED_FILE}"));
         } else {
-            let actual_content = actual.join("\n");
-            if builder.config.cmd.bless() {
-                std::fs::write(TRACKED_FILE, actual_content).unwrap();
-                builder.info(&format!("Updated {TRACKED_FILE}"));
-            } else {
-                builder.info(&format!(
-                    "Diff of {} and {}:",
-                    expected_path.display(),
-                    actual_symbol_report_path.display(),
-                ));
-
-                diff_text(&expected_content, &actual_content);
-
-                builder.info(&format!(
-                    "The certified core symbol report is out of date. \
-                    Run `./x test {TRACKED_FILE} --bless` to update it."
-                ));
-                crate::exit!(1);
-            }
+            builder.info(&format!(
+                "Diff of {} and {}:",
+                expected_path.display(),
+                actual_symbol_report_path.display(),
+            ));
+
+            diff_text(&expected_content, &actual_content);
+
+            builder.info(&format!(
+                "The certified core symbol report is out of date. \
+                Run `./x test {TRACKED_FILE} --bless` to update it."
+            ));
+            crate::exit!(1);
         }
     }
 }
@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Running the tests of bare-metal targets under QEMU with `ferrocene/tools/semihosting-runner`,
+//! for the targets it has a board for.
+
+use std::path::PathBuf;
+
+use crate::builder::{Builder, CommandLineStep, RunConfig, ShouldRun};
+use crate::core::build_steps::compile::Std;
+use crate::core::build_steps::test::failed_tests::SetupFailedTestsFile;
+use crate::core::build_steps::tool::Tool;
+use crate::core::config::TargetSelection;
+use crate::ferrocene::test::{SourceType, tool};
+use crate::ferrocene::test_variants::TestVariant;
+use crate::utils::exec::command;
+use crate::utils::render_tests::try_run_tests;
+use crate::{Compiler, Kind, Mode};
+
+const PATH: &str = "ferrocene/tools/semihosting-runner";
+const NO_STD_TESTS_PATH: &str = "ferrocene/tools/semihosting-runner/no-std-tests";
+
+/// The linker script of `target`'s test programs, if they run under the semihosting runner: it
+/// is only used when the target is not tested remotely and has no other runner configured.
+fn linker_script(builder: &Builder<'_>, target: TargetSelection) -> Option<PathBuf> {
+    if builder.remote_tested(target) || builder.runner(target).is_some() {
+        return None;
+    }
+    // The boards of `Board::for_target` in the runner, which self-test also runs its bare-metal
+    // sample program on.
+    let script = match &*target.triple {
+        "thumbv6m-none-eabi" | "thumbv7em-none-eabi" | "thumbv7em-none-eabihf" => "mps2.x",
+        "aarch64-unknown-none" => "virt.x",
+        _ => return None,
+    };
+    Some(builder.src.join(PATH).join("link").join(script))
+}
+
+/// Whether `target`'s test programs run under the semihosting runner.
+pub(crate) fn is_used(builder: &Builder<'_>, target: TargetSelection) -> bool {
+    linker_script(builder, target).is_some()
+}
+
+/// The command running `target`'s test programs, to pass as compiletest's `--runner` or as
+/// cargo's `CARGO_TARGET_<TARGET>_RUNNER`.
+pub(crate) fn runner(builder: &Builder<'_>, target: TargetSelection) -> Option<String> {
+    linker_script(builder, target)?;
+    let runner = builder.tool_exe(Tool::FerroceneSemihostingRunner);
+    Some(format!("{} --target {}", runner.display(), target.triple))
+}
+
+/// The flags to build `target`'s test programs with, for them to boot on the emulated board.
+pub(crate) fn rustflags(builder: &Builder<'_>, target: TargetSelection) -> Vec<String> {
+    match linker_script(builder, target) {
+        Some(script) => vec![format!("-Clink-arg=-T{}", script.display())],
+        None => vec![],
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct SemihostingRunner {
+    target: TargetSelection,
+}
+
+impl CommandLineStep for SemihostingRunner {
+    type Output = ();
+    const IS_HOST: bool = true;
+
+    fn is_default_step(_: &Builder<'_>) -> bool {
+        true
+    }
+
+    fn run(self, builder: &Builder<'_>) {
+        builder.info(&format!("Testing {PATH}"));
+
+        let compiler = builder.compiler(0, self.target);
+        let cargo = tool::prepare_tool_cargo(
+            builder,
+            compiler,
+            Mode::ToolBootstrap,
+            self.target,
+            Kind::Test,
+            PATH,
+            SourceType::InTree,
+            &[],
+        );
+        crate::core::build_steps::test::run_cargo_test(
+            cargo,
+            &[],
+            &[],
+            "semihosting-runner",
+            self.target,
+            builder,
+            builder.ensure(SetupFailedTestsFile),
+        );
+    }
+
+    fn should_run(run: ShouldRun<'_>) -> ShouldRun<'_> {
+        run.path(PATH)
+    }
+
+    fn make_run(run: RunConfig<'_>) {
+        run.builder.ensure(Self { target: run.target });
+    }
+}
+
+/// Runs the `#![no_std]` tests of `core` in `ferrocene/tools/semihosting-runner/no-std-tests` on
+/// the emulated board, with the harness of `-Z no-std-test-harness`. The harness writes the same
+/// JSON as libtest, so the results are rendered and recorded in the build metrics like the results
+/// of any other test suite.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct NoStdTests {
+    compiler: Compiler,
+    target: TargetSelection,
+}
+
+impl NoStdTests {
+    pub(crate) fn new(compiler: Compiler, target: TargetSelection) -> Self {
+        Self { compiler, target }
+    }
+}
+
+impl CommandLineStep for NoStdTests {
+    type Output = ();
+
+    fn is_default_step(_: &Builder<'_>) -> bool {
+        true
+    }
+
+    fn run(self, builder: &Builder<'_>) {
+        let target = self.target;
+        builder.ensure(Std::new(self.compiler, target));
+
+        let out_dir = builder.test_out(target).join("no-std-tests");
+        builder.create_dir(&out_dir);
+        let program = out_dir.join("no-std-tests");
+        let mut rustc = builder.rustc_cmd(self.compiler);
+        rustc
+            .env("RUSTC_BOOTSTRAP", "1")
+            .args(["--test", "-Zno-std-test-harness", "-Cpanic=abort"])
+            .arg("--target")
+            .arg(target.rustc_target_arg())
+            .args(rustflags(builder, target))
+            .arg("-o")
+            .arg(&program)
+            .arg(builder.src.join(NO_STD_TESTS_PATH).join("main.rs"));
+
+        // --test-variant
+        let variant = TestVariant::current(builder, target);
+        if let Some(edition) = variant.edition() {
+            edition.apply(&mut rustc);
+        }
+        // The runner emulates the CPU of the board, and `core` is built by `Std`.
+        if let Some(qemu_cpu) = variant.qemu_cpu() {
+            qemu_cpu.mark_unused();
+        }
+        if let Some(target_cpu) = variant.target_cpu() {
+            target_cpu.mark_unused();
+        }
+
+        builder.info(&format!("Building {NO_STD_TESTS_PATH} for {target}"));
+        rustc.run(builder);
+
+        let _group = builder.msg_test(NO_STD_TESTS_PATH, target, self.compiler.stage);
+        #[cfg(feature = "build-metrics")]
+        builder.metrics.begin_test_suite(
+            build_helper::metrics::TestSuiteMetadata::CargoPackage {
+                crates: vec!["no-std-tests".into()],
+                target: target.triple.to_string(),
+                host: self.compiler.host.triple.to_string(),
+                stage: self.compiler.stage,
+                ferrocene_variant: variant.for_metrics(),
+            },
+            builder,
+        );
+
+        let mut cmd = command(builder.tool_exe(Tool::FerroceneSemihostingRunner));
+        cmd.arg("--target").arg(&target.triple).arg(&program);
+        cmd.args(builder.config.test_args());
+        cmd.args(["-Z", "unstable-options", "--format", "json"]);
+        try_run_tests(builder, &mut cmd, false, builder.ensure(SetupFailedTestsFile));
+    }
+
+    fn should_run(run: ShouldRun<'_>) -> ShouldRun<'_> {
+        run.path(NO_STD_TESTS_PATH)
+    }
+
+    fn make_run(run: RunConfig<'_>) {
+        // Only targets whose test programs run under the semihosting runner can run the tests.
+        if is_used(run.builder, run.target) {
+            let compiler = run.builder.compiler(run.builder.top_stage, run.builder.host_target);
+            run.builder.ensure(Self { compiler, target: run.target });
+        }
+    }
+}
@@ -5,7 +5,7 @@
 
 use std::path::PathBuf;
 
-use crate::builder::{Builder, CommandLineStep, RunConfig, ShouldRun};
+use crate::builder::{Builder, CommandLineStep, RunConfig, ShouldRun, Step};
 use crate::core::build_steps::tool::{
     RustcPrivateCompilers, SourceType, ToolArtifactKind, ToolBuild, prepare_tool_cargo,
 };
@@ -31,6 +31,15 @@ pub(super) fn update_command(
         if let Some(hash) = builder.cargo_info.sha() {
             cmd.env("SELFTEST_CARGO_HASH", hash);
         }
+        // Identity the checksums of the installed files are signed with, see generate-tarball.
+        let config = &builder.config;
+        if let (Some(identity), Some(oidc_issuer)) = (
+            &config.ferrocene_tarball_signing_cosign_identity,
+            &config.ferrocene_tarball_signing_cosign_oidc_issuer,
+        ) {
+            cmd.env("SELFTEST_SIGNING_IDENTITY", identity);
+            cmd.env("SELFTEST_SIGNING_OIDC_ISSUER", oidc_issuer);
+        }
     }
 }
 
@@ -75,6 +84,33 @@ fn run(self, builder: &Builder<'_>) -> Self::Output {
     }
 }
 
+/// `document-signatures` built for a target, to ship it to verify the signatures of the
+/// qualification documents offline. Bootstrap itself uses the host build of `tool::Tool`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct DocumentSignatures {
+    pub(crate) target: TargetSelection,
+}
+
+impl Step for DocumentSignatures {
+    type Output = PathBuf;
+
+    fn run(self, builder: &Builder<'_>) -> Self::Output {
+        let tool_build = ToolBuild {
+            build_compiler: builder.compiler(0, builder.host_target),
+            target: self.target,
+            tool: "document-signatures",
+            mode: Mode::ToolBootstrap,
+            path: "ferrocene/tools/document-signatures",
+            source_type: SourceType::InTree,
+            extra_features: vec![],
+            allow_features: "",
+            cargo_args: Vec::new(),
+            artifact_kind: ToolArtifactKind::Binary,
+        };
+        builder.ensure(tool_build).tool_path
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct SymbolReport {
     pub(crate) target_compiler: Compiler,
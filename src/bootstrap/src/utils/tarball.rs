@@ -11,6 +11,7 @@
 use crate::core::build_steps::dist::distdir;
 use crate::core::builder::{Builder, Kind};
 use crate::core::config::BUILDER_CONFIG_FILENAME;
+use crate::ferrocene::sign::cosign::CosignBinary;
 use crate::utils::exec::BootstrapCommand;
 use crate::utils::helpers::{move_file, t};
 use crate::utils::{channel, helpers};
@@ -376,6 +377,10 @@ fn non_bare_args(&self, cmd: &mut BootstrapCommand) {
         if let Some(kms_key_arn) = &self.builder.config.ferrocene_tarball_signing_kms_key_arn {
             cmd.arg("--ferrocene-signing-kms-key-arn").arg(kms_key_arn);
         }
+        if self.builder.config.ferrocene_tarball_signing_cosign_identity.is_some() {
+            let cosign = self.builder.ensure(CosignBinary);
+            cmd.arg("--ferrocene-cosign-binary").arg(cosign);
+        }
         for proxied_binary in &self.proxied_binaries {
             cmd.arg("--ferrocene-proxied-binary").arg(proxied_binary);
         }
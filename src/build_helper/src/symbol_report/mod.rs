@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+use std::collections::{BTreeMap, BTreeSet};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Eq, PartialEq, serde_derive::Deserialize, serde_derive::Serialize)]
+pub struct SymbolReport {
+    pub symbols: Vec<Function>,
+    pub annotations: BTreeMap<String, BTreeSet<(usize, usize)>>,
+}
+
+impl SymbolReport {
+    pub fn add_annotation(&mut self, filename: String, start: usize, end: usize) {
+        self.annotations.entry(filename).or_default().insert((start, end));
+    }
+
+    pub fn new() -> Self {
+        Self { symbols: Vec::new(), annotations: BTreeMap::new() }
+    }
+
+    /// Add the functions and annotations of another report, e.g. of another crate of the same
+    /// workspace. Functions that are in both reports (e.g. a library built both normally and with
+    /// `--test`) are only kept once.
+    pub fn merge(&mut self, other: SymbolReport) {
+        self.symbols.extend(other.symbols);
+        self.symbols.sort_by(|a, b| {
+            (&a.filename, a.start_line, &a.qualified_name).cmp(&(
+                &b.filename,
+                b.start_line,
+                &b.qualified_name,
+            ))
+        });
+        self.symbols.dedup();
+        for (filename, annotations) in other.annotations {
+            self.annotations.entry(filename).or_default().extend(annotations);
+        }
+    }
+
+    /// The functions in the certified subset, i.e. those with [`ValidationStatus::Prevalidated`].
+    pub fn certified(&self) -> impl Iterator<Item = &Function> {
+        self.symbols.iter().filter(|f| f.status == ValidationStatus::Prevalidated)
+    }
+
+    pub fn to_qualified_fn_list(&self) -> Vec<String> {
+        let mut a = self.certified().map(|f| f.qualified_name.clone()).collect::<Vec<_>>();
+        a.sort_unstable();
+        a
+    }
+
+    /// Every function of the report as CSV, sorted by name, with its validation status, the
+    /// justification of its annotations (separated by `; `) and why it has that status. Line
+    /// numbers are left out, so that the file only changes when the subset does.
+    pub fn to_csv(&self) -> String {
+        let mut rows = self
+            .symbols
+            .iter()
+            .map(|f| {
+                [
+                    f.qualified_name.as_str(),
+                    f.status.as_str(),
+                    &f.annotations.join("; "),
+                    f.reason.as_str(),
+                ]
+                .map(csv_field)
+                .join(",")
+            })
+            .collect::<Vec<_>>();
+        rows.sort_unstable();
+        rows.dedup();
+        rows.insert(0, "qualified_name,status,annotation,reason".to_string());
+        rows.join("\n")
+    }
+}
+
+/// Quote `field` if it contains a separator, a quote or a newline.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, serde_derive::Deserialize, serde_derive::Serialize)]
+pub struct Function {
+    /// The fully qualified path to the function, for example:
+    /// - `core::ptr::copy`
+    /// - `core::cmp::impls::<impl core::cmp::PartialEq for u8>::eq`
+    /// - `core::convert::num::ptr_try_from_impls::<impl core::convert::TryFrom<u128> for usize>::try_from`
+    pub qualified_name: String,
+    pub filename: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Whether the function is in the certified subset, and why. Reports written before this
+    /// field existed only listed prevalidated functions.
+    #[serde(default)]
+    pub status: ValidationStatus,
+    /// The justification of each `#[ferrocene::annotation]` on the function or inside its body.
+    #[serde(default)]
+    pub annotations: Vec<String>,
+    /// The early-bound generic parameters of the function, including those of its parent impl or
+    /// trait, for example `["T", "'a", "const N: usize"]`.
+    #[serde(default)]
+    pub generics: Vec<String>,
+    /// The `cfg` predicates the function is gated behind, including those of its parent items,
+    /// outermost first. For example `["target_has_atomic = \"8\""]`.
+    #[serde(default)]
+    pub cfg: Vec<String>,
+    /// Why the function has its [`status`](Function::status), for example
+    /// ``marked `#[ferrocene::prevalidated]` `` or `no body`.
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// Why a function is or isn't part of the certified subset.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    serde_derive::Deserialize,
+    serde_derive::Serialize
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ValidationStatus {
+    /// Marked `#[ferrocene::prevalidated]`. These are the functions that need to be covered by
+    /// tests.
+    #[default]
+    Prevalidated,
+    /// Marked `#[ferrocene::entrypoint]`. Its body is checked like that of a prevalidated function,
+    /// so it needs to be covered by tests too, but the function itself is not validated: calling it
+    /// from a prevalidated function is an error. These are not part of the certified subset.
+    Entrypoint,
+    /// Validated without an attribute, for example because the function has no body or is listed
+    /// in a validation manifest. These are not part of the certified subset.
+    Implicit,
+    /// Not validated. Calling it from a prevalidated function is an error.
+    Unvalidated,
+}
+
+impl ValidationStatus {
+    /// Whether the body of the function is checked like a validated function, and so needs to be
+    /// covered by tests.
+    pub fn is_checked(self) -> bool {
+        matches!(self, ValidationStatus::Prevalidated | ValidationStatus::Entrypoint)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ValidationStatus::Prevalidated => "prevalidated",
+            ValidationStatus::Entrypoint => "entrypoint",
+            ValidationStatus::Implicit => "implicit",
+            ValidationStatus::Unvalidated => "unvalidated",
+        }
+    }
+}
+
+impl From<SerdeFunction> for Function {
+    fn from(SerdeFunction(qualified_name, filename, start_line, end_line): SerdeFunction) -> Self {
+        Self {
+            qualified_name,
+            filename,
+            start_line,
+            end_line,
+            status: ValidationStatus::Prevalidated,
+            annotations: Vec::new(),
+            generics: Vec::new(),
+            cfg: Vec::new(),
+            reason: String::new(),
+        }
+    }
+}
+
+/// A single certified function, identified by its span. This is the format of reports written
+/// before [`Function::status`] was added.
+#[derive(Clone, serde_derive::Deserialize, serde_derive::Serialize)]
+#[serde(from = "Function")]
+pub struct SerdeFunction(String, String, usize, usize);
+
+impl From<Function> for SerdeFunction {
+    fn from(func: Function) -> Self {
+        Self(func.qualified_name, func.filename, func.start_line, func.end_line)
+    }
+}
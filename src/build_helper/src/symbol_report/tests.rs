@@ -0,0 +1,47 @@
+use super::*;
+
+fn function(qualified_name: &str, status: ValidationStatus, reason: &str) -> Function {
+    Function {
+        qualified_name: qualified_name.to_string(),
+        filename: "lib.rs".to_string(),
+        start_line: 1,
+        end_line: 1,
+        status,
+        annotations: vec![],
+        generics: vec![],
+        cfg: vec![],
+        reason: reason.to_string(),
+    }
+}
+
+#[test]
+fn test_to_csv() {
+    let mut annotated = function(
+        "core::b",
+        ValidationStatus::Prevalidated,
+        "marked `#[ferrocene::prevalidated]`",
+    );
+    annotated.annotations =
+        vec!["Never called with \"None\"".to_string(), "Unreachable".to_string()];
+    let report = SymbolReport {
+        symbols: vec![
+            annotated,
+            function(
+                "<u8 as core::convert::From<(u8, u8)>>::from",
+                ValidationStatus::Unvalidated,
+                "not marked `#[ferrocene::prevalidated]`",
+            ),
+            function("core::a", ValidationStatus::Implicit, "no body"),
+            function("core::c", ValidationStatus::Entrypoint, "marked `#[ferrocene::entrypoint]`"),
+        ],
+        annotations: BTreeMap::new(),
+    };
+    assert_eq!(
+        report.to_csv(),
+        "qualified_name,status,annotation,reason\n\
+        \"<u8 as core::convert::From<(u8, u8)>>::from\",unvalidated,,not marked `#[ferrocene::prevalidated]`\n\
+        core::a,implicit,,no body\n\
+        core::b,prevalidated,\"Never called with \"\"None\"\"; Unreachable\",marked `#[ferrocene::prevalidated]`\n\
+        core::c,entrypoint,,marked `#[ferrocene::entrypoint]`"
+    );
+}
@@ -877,7 +877,9 @@ pub(crate) fn has_subprocess_support(&self) -> bool {
         let unsupported_target = self.target_cfg().env == "sgx"
             || matches!(self.target_cfg().arch.as_str(), "wasm32" | "wasm64")
             || self.target_cfg().os == "emscripten"
-            || self.target.contains("ferrocene.facade");
+            || self.target.contains("ferrocene.facade")
+            // ferrocene addition: bare-metal test programs run alone on an emulated board
+            || self.target_cfg().os == "none";
         !unsupported_target
     }
 
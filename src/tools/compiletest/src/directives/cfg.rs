@@ -269,6 +269,12 @@ pub(crate) fn prepare_conditions(config: &Config) -> PreparedConditions {
         config.target.starts_with("armv7r-") || config.target.starts_with("armebv7r-"),
         "when the target is part of the Armv7-R target family",
     );
+    // ferrocene addition
+    builder.cond(
+        "semihosting",
+        config.runner.as_deref().is_some_and(|runner| runner.contains("semihosting-runner")),
+        "when test programs are run under QEMU by the semihosting runner",
+    );
     // FIXME(Zalathar): Ideally this should be configured by a command-line
     // flag, not an environment variable.
     builder.cond(
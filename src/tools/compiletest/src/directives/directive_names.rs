@@ -327,6 +327,7 @@
     "ignore-ferrocene.facade",
     "needs-linker-flavor-lld-cc",
     "only-ferrocene.facade",
+    "only-semihosting",
 ];
 
 pub(crate) const KNOWN_HTMLDOCCK_DIRECTIVE_NAMES: &[&str] = &[
@@ -92,11 +92,26 @@ pub(crate) fn dump_covfun_mappings(
                     }
                     // If the mapping is a branch region, print both of its arms
                     // in resolved form (even if they aren't expressions).
-                    MappingKind::Branch { r#true, r#false }
-                    | MappingKind::MCDCBranch { r#true, r#false, .. } => {
+                    MappingKind::Branch { r#true, r#false } => {
                         println!("    true  = {}", expression_resolver.format_term(r#true));
                         println!("    false = {}", expression_resolver.format_term(r#false));
                     }
+                    // For MC/DC conditions, also print which condition is evaluated next.
+                    MappingKind::MCDCBranch {
+                        r#true,
+                        r#false,
+                        condition_id,
+                        true_next_id,
+                        false_next_id,
+                    } => {
+                        println!("    true  = {}", expression_resolver.format_term(r#true));
+                        println!("    false = {}", expression_resolver.format_term(r#false));
+                        println!(
+                            "    condition {condition_id}: true => {}, false => {}",
+                            format_next_condition(true_next_id),
+                            format_next_condition(false_next_id),
+                        );
+                    }
                     _ => (),
                 }
             }
@@ -121,6 +136,13 @@ pub(crate) fn dump_covfun_mappings(
     Ok(())
 }
 
+fn format_next_condition(next_id: Option<u32>) -> String {
+    match next_id {
+        Some(id) => format!("condition {id}"),
+        None => "decision outcome".to_owned(),
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 struct CovfunLineData {
     is_used: bool,
@@ -217,14 +239,16 @@ fn read_raw_mapping_kind(&mut self) -> anyhow::Result<MappingKind> {
                 5 => {
                     let bitmap_idx = self.read_uleb128_u32()?;
                     let conditions_num = self.read_uleb128_u32()?;
+                    ensure!(conditions_num > 0, "MC/DC decision without conditions");
                     Ok(MappingKind::MCDCDecision { bitmap_idx, conditions_num })
                 }
                 6 => {
                     let r#true = self.read_simple_term()?;
                     let r#false = self.read_simple_term()?;
-                    let condition_id = self.read_uleb128_u32()?;
-                    let true_next_id = self.read_uleb128_u32()?;
-                    let false_next_id = self.read_uleb128_u32()?;
+                    let condition_id =
+                        self.read_condition_id()?.context("MC/DC branch without condition ID")?;
+                    let true_next_id = self.read_condition_id()?;
+                    let false_next_id = self.read_condition_id()?;
                     Ok(MappingKind::MCDCBranch {
                         r#true,
                         r#false,
@@ -239,6 +263,12 @@ fn read_raw_mapping_kind(&mut self) -> anyhow::Result<MappingKind> {
         }
     }
 
+    /// LLVM writes MC/DC condition IDs as their value plus one, so that zero
+    /// means "no condition" (i.e. the outcome of the decision is known).
+    fn read_condition_id(&mut self) -> anyhow::Result<Option<u32>> {
+        Ok(self.read_uleb128_u32()?.checked_sub(1))
+    }
+
     fn read_raw_mapping_region(&mut self) -> anyhow::Result<MappingRegion> {
         let start_line_offset = self.read_uleb128_u32()?;
         let start_column = self.read_uleb128_u32()?;
@@ -253,7 +283,7 @@ fn read_raw_mapping_region(&mut self) -> anyhow::Result<MappingRegion> {
 ///
 /// Terms are used as the operands of coverage-counter expressions, as the arms
 /// of branch mappings, and as the value of code/gap mappings.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum CovTerm {
     Zero,
     Counter(u32),
@@ -261,7 +291,7 @@ pub(crate) enum CovTerm {
 }
 
 /// Operator (addition or subtraction) used by an expression.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum Op {
     Sub,
     Add,
@@ -285,7 +315,7 @@ pub(crate) fn decode(input: u32) -> Option<Self> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 enum MappingKind {
     Code(CovTerm),
     Gap(CovTerm),
@@ -298,16 +328,15 @@ enum MappingKind {
         r#true: CovTerm,
         r#false: CovTerm,
     },
+    /// A condition of an MC/DC decision. The next IDs are the conditions
+    /// evaluated next when this one is true or false, or `None` if the outcome
+    /// of the decision is then known.
     MCDCBranch {
         r#true: CovTerm,
         r#false: CovTerm,
-        // These attributes are printed in Debug but not used directly.
-        #[allow(dead_code)]
         condition_id: u32,
-        #[allow(dead_code)]
-        true_next_id: u32,
-        #[allow(dead_code)]
-        false_next_id: u32,
+        true_next_id: Option<u32>,
+        false_next_id: Option<u32>,
     },
     MCDCDecision {
         // These attributes are printed in Debug but not used directly.
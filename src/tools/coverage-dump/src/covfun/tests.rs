@@ -1,4 +1,5 @@
-use super::{CovfunLineData, parse_covfun_line};
+use super::{CovTerm, CovfunLineData, MappingKind, Op, parse_covfun_line};
+use crate::parser::Parser;
 
 /// Integers in LLVM IR are not inherently signed/unsigned, and the text format tends
 /// to emit them in signed form, so this helper function converts `i64` to `u64`.
@@ -51,3 +52,33 @@ struct Case {
         assert_eq!(line_data.as_ref(), Ok(expected));
     }
 }
+
+#[test]
+fn read_mcdc_mapping_kinds() {
+    // MC/DC decision (kind 5) with bitmap index 4 and 2 conditions.
+    let mut parser = Parser::new(&[0x28, 0x04, 0x02]);
+    assert_eq!(
+        parser.read_raw_mapping_kind().unwrap(),
+        MappingKind::MCDCDecision { bitmap_idx: 4, conditions_num: 2 }
+    );
+    parser.ensure_empty().unwrap();
+
+    // MC/DC branch (kind 6) for condition 0, followed by condition 1 when
+    // true, and deciding the outcome when false. IDs are stored plus one.
+    let mut parser = Parser::new(&[0x30, 0x05, 0x02, 0x01, 0x02, 0x00]);
+    assert_eq!(
+        parser.read_raw_mapping_kind().unwrap(),
+        MappingKind::MCDCBranch {
+            r#true: CovTerm::Counter(1),
+            r#false: CovTerm::Expression(0, Op::Sub),
+            condition_id: 0,
+            true_next_id: Some(1),
+            false_next_id: None,
+        }
+    );
+    parser.ensure_empty().unwrap();
+
+    // Zero is not a valid ID for the condition itself, nor a valid number of conditions.
+    assert!(Parser::new(&[0x30, 0x05, 0x02, 0x00, 0x00, 0x00]).read_raw_mapping_kind().is_err());
+    assert!(Parser::new(&[0x28, 0x04, 0x00]).read_raw_mapping_kind().is_err());
+}
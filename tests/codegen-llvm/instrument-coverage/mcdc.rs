@@ -0,0 +1,26 @@
+// Test that `-Zcoverage-options=mcdc` emits the MC/DC bitmap intrinsics in LLVM IR.
+// (Ferrocene addition)
+
+//@ compile-flags: -Cinstrument-coverage -Zno-profiler-runtime -Zcoverage-options=mcdc
+//@ compile-flags: -Copt-level=0
+
+#![crate_type = "lib"]
+
+// CHECK-LABEL: @two_conditions(
+// CHECK: %mcdc.addr.0 = alloca [4 x i8]
+// CHECK: call void @llvm.instrprof.mcdc.parameters(ptr @__profn_two_conditions, i64 {{-?[0-9]+}}, i32 3)
+// CHECK: store i32 0, ptr %mcdc.addr.0
+// CHECK: call void @llvm.instrprof.mcdc.tvbitmap.update(ptr @__profn_two_conditions, i64 {{-?[0-9]+}}, i32 0, ptr %mcdc.addr.0)
+#[no_mangle]
+pub fn two_conditions(a: bool, b: bool) -> u32 {
+    if a && b { 1 } else { 0 }
+}
+
+// A single condition is not a decision, so no bitmap is needed.
+// CHECK-LABEL: @one_condition(
+// CHECK-NOT: llvm.instrprof.mcdc
+// CHECK: ret
+#[no_mangle]
+pub fn one_condition(a: bool) -> u32 {
+    if a { 1 } else { 0 }
+}
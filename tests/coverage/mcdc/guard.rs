@@ -0,0 +1,50 @@
+#![feature(coverage_attribute)]
+//@ edition: 2024
+//@ compile-flags: -Zcoverage-options=mcdc
+//@ llvm-cov-flags: --show-branches=count --show-mcdc
+
+macro_rules! no_merge {
+    () => {
+        for _ in 0..1 {}
+    };
+}
+
+fn match_guard(x: Option<u32>, b: bool) {
+    no_merge!();
+
+    match x {
+        Some(x) if x > 0 && b => {
+            say("positive and b");
+        }
+        _ => {
+            say("something else");
+        }
+    }
+}
+
+fn if_let_guard(x: Option<u32>, b: bool) {
+    no_merge!();
+
+    match x {
+        Some(x) if let Some(_) = x.checked_sub(1) && b => {
+            say("positive and b");
+        }
+        _ => {
+            say("something else");
+        }
+    }
+}
+
+#[coverage(off)]
+fn say(message: &str) {
+    core::hint::black_box(message);
+}
+
+#[coverage(off)]
+fn main() {
+    for x in [None, Some(0), Some(1), Some(2)] {
+        let b = x != Some(1);
+        match_guard(x, b);
+        if_let_guard(x, b);
+    }
+}
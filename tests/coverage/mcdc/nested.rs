@@ -0,0 +1,38 @@
+#![feature(coverage_attribute)]
+//@ edition: 2021
+//@ compile-flags: -Zcoverage-options=mcdc
+//@ llvm-cov-flags: --show-branches=count --show-mcdc
+
+macro_rules! no_merge {
+    () => {
+        for _ in 0..1 {}
+    };
+}
+
+fn nested_in_condition(a: bool, b: bool, c: bool) {
+    no_merge!();
+
+    if a && check(b && c) {
+        say("a and (b and c)");
+    } else {
+        say("not a, or not (b and c)");
+    }
+}
+
+#[coverage(off)]
+fn check(value: bool) -> bool {
+    core::hint::black_box(value)
+}
+
+#[coverage(off)]
+fn say(message: &str) {
+    core::hint::black_box(message);
+}
+
+#[coverage(off)]
+fn main() {
+    nested_in_condition(false, true, true);
+    nested_in_condition(true, false, true);
+    nested_in_condition(true, true, false);
+    nested_in_condition(true, true, true);
+}
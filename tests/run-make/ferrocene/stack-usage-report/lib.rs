@@ -0,0 +1,45 @@
+#![crate_type = "lib"]
+
+extern crate upstream;
+
+#[inline(never)]
+fn leaf(buf: &mut [u8; 64]) -> u8 {
+    std::hint::black_box(buf)[0]
+}
+
+#[no_mangle]
+pub fn bounded() -> u8 {
+    let mut buf = [0; 64];
+    leaf(&mut buf)
+}
+
+#[no_mangle]
+pub fn recursive(n: u32) -> u32 {
+    if n == 0 { 0 } else { recursive(n - 1) + 1 }
+}
+
+#[no_mangle]
+pub fn indirect(f: fn() -> u8) -> u8 {
+    f()
+}
+
+#[no_mangle]
+pub fn calls_upstream() -> u8 {
+    upstream::leaf(1)
+}
+
+#[no_mangle]
+pub fn copies(dst: &mut [u8; 4096], src: &[u8; 4096]) {
+    // Lowered by LLVM to a call to `memcpy`, which doesn't appear in MIR.
+    *dst = *src;
+}
+
+#[inline(always)]
+fn inlined(x: u8) -> u8 {
+    x ^ 0x5a
+}
+
+#[no_mangle]
+pub fn calls_inlined(x: u8) -> u8 {
+    inlined(x)
+}
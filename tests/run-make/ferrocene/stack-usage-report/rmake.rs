@@ -0,0 +1,74 @@
+//@ needs-target-std
+//@ only-elf
+// Reason: the frame sizes are read from the `.stack_sizes` section, which only exists in ELF.
+//
+// `-Z stack-usage-report` combines the frame sizes emitted by LLVM with the call graph of the
+// crate. Check that the worst-case path goes through the callees, that recursion, indirect calls,
+// calls inserted by the backend and calls into a crate compiled without stack sizes make the stack
+// usage unbounded, and that inlined functions don't.
+
+use run_make_support::serde_json::{self, Value};
+use run_make_support::{rfs, rustc};
+
+fn main() {
+    rustc().input("upstream.rs").opt_level("0").run();
+    // Without optimizations, LLVM lowers even small copies to calls to `memcpy` and `memset`. The
+    // MIR inliner is disabled so that LLVM is the one inlining `inlined`.
+    rustc()
+        .input("lib.rs")
+        .opt_level("1")
+        .arg("-Zinline-mir=no")
+        .arg("-Zstack-usage-report=stack-usage.json")
+        .run();
+
+    let report: Value = serde_json::from_str(&rfs::read_to_string("stack-usage.json")).unwrap();
+    let roots = report["roots"].as_array().unwrap();
+    let find_root = |name: &str| {
+        roots
+            .iter()
+            .find(|root| root["function"] == name)
+            .unwrap_or_else(|| panic!("missing root {name}: {roots:#?}"))
+    };
+
+    let root = find_root("bounded");
+    assert_eq!(root["bounded"], true);
+    // The buffer is on the stack of `bounded`.
+    assert!(root["stack_size"].as_u64().unwrap() >= 64, "{root:#?}");
+    let path = root["worst_case_path"].as_array().unwrap();
+    assert_eq!(path[0]["function"], "bounded");
+    assert!(path[1]["function"].as_str().unwrap().contains("leaf"), "{root:#?}");
+    let frame_sizes = path.iter().map(|entry| entry["frame_size"].as_u64().unwrap());
+    assert_eq!(frame_sizes.sum::<u64>(), root["stack_size"].as_u64().unwrap());
+
+    let root = find_root("recursive");
+    assert_eq!(root["bounded"], false);
+    assert_eq!(root["recursive_functions"], serde_json::json!(["recursive"]));
+
+    let root = find_root("indirect");
+    assert_eq!(root["bounded"], false);
+    assert_eq!(root["indirect_calls"], serde_json::json!(["indirect"]));
+
+    // `upstream::leaf` is codegened in `upstream`, which doesn't have stack sizes.
+    let root = find_root("calls_upstream");
+    assert_eq!(root["bounded"], false);
+    let is_upstream_leaf = |function: &Value| {
+        let function = function.as_str().unwrap();
+        function.contains("upstream") && function.contains("leaf")
+    };
+    for list in ["unknown_callees", "unknown_frame_sizes"] {
+        assert!(root[list].as_array().unwrap().iter().any(is_upstream_leaf), "{root:#?}");
+    }
+
+    // `memcpy` is called by the machine code of `copies` only.
+    let root = find_root("copies");
+    assert_eq!(root["bounded"], false);
+    for list in ["unknown_callees", "unknown_frame_sizes"] {
+        assert!(root[list].as_array().unwrap().iter().any(|f| f == "memcpy"), "{root:#?}");
+    }
+
+    // `inlined` has no code of its own, its frame is part of the frame of `calls_inlined`.
+    let root = find_root("calls_inlined");
+    assert_eq!(root["bounded"], true, "{root:#?}");
+    let is_inlined = |function: &Value| function.as_str().unwrap().contains("inlined");
+    assert!(root["inlined_functions"].as_array().unwrap().iter().any(is_inlined), "{root:#?}");
+}
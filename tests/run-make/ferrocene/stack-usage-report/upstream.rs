@@ -0,0 +1,6 @@
+#![crate_type = "rlib"]
+
+#[inline(never)]
+pub fn leaf(x: u8) -> u8 {
+    std::hint::black_box(x)
+}
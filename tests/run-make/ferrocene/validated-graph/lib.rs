@@ -0,0 +1,26 @@
+#![crate_type = "lib"]
+
+pub struct Unvalidated;
+
+impl Clone for Unvalidated {
+    fn clone(&self) -> Self {
+        Unvalidated
+    }
+}
+
+#[ferrocene::prevalidated]
+fn validated_helper() {}
+
+fn unvalidated_helper() {}
+
+#[ferrocene::prevalidated]
+fn generic<T: Clone>(x: &T) -> T {
+    x.clone()
+}
+
+#[ferrocene::prevalidated]
+pub fn entrypoint() -> fn() {
+    validated_helper();
+    generic(&Unvalidated);
+    unvalidated_helper
+}
@@ -0,0 +1,38 @@
+//@ needs-target-std
+//
+// `-Z ferrocene-validated-graph` writes the call graph walked by the post-mono
+// `ferrocene::unvalidated` pass as JSON. Check that it records each kind of edge, along with
+// whether the callee is validated.
+
+use run_make_support::serde_json::{self, Value};
+use run_make_support::{rfs, rustc};
+
+fn main() {
+    rustc().input("lib.rs").arg("-Zferrocene-validated-graph=graph.json").run();
+
+    let graph: Value = serde_json::from_str(&rfs::read_to_string("graph.json")).unwrap();
+    let roots = graph["roots"].as_array().unwrap();
+    assert!(roots.iter().any(|root| root == "entrypoint"), "missing root: {roots:?}");
+
+    let edges = graph["edges"].as_array().unwrap();
+    let find_edge = |caller: &str, callee: &str| {
+        edges
+            .iter()
+            .find(|edge| edge["caller"] == caller && edge["callee"] == callee)
+            .unwrap_or_else(|| panic!("missing edge {caller} -> {callee}: {edges:#?}"))
+    };
+
+    let edge = find_edge("entrypoint", "validated_helper");
+    assert_eq!(edge["kind"], "call");
+    assert_eq!(edge["validated"], true);
+    assert!(edge["span"].as_str().unwrap().starts_with("lib.rs:"));
+
+    let edge = find_edge("entrypoint", "unvalidated_helper");
+    assert_eq!(edge["kind"], "fn-ptr-cast");
+    assert_eq!(edge["validated"], false);
+
+    // Generic functions are recorded once per instantiation.
+    let edge = find_edge("generic::<Unvalidated>", "<Unvalidated as std::clone::Clone>::clone");
+    assert_eq!(edge["kind"], "call");
+    assert_eq!(edge["validated"], false);
+}
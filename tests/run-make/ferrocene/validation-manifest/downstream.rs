@@ -0,0 +1,8 @@
+#![crate_type = "rlib"]
+#![deny(ferrocene::unvalidated)]
+
+#[ferrocene::prevalidated]
+pub fn entrypoint() {
+    upstream::vetted();
+    upstream::distrusted();
+}
@@ -0,0 +1,77 @@
+//@ needs-target-std
+//
+// `-Z ferrocene-validation-manifest` overrides the validation status of items in upstream crates.
+// Check that it can both validate and unvalidate items, and that a manifest written for a
+// different version of the crate, or naming items that don't exist, is rejected.
+
+use run_make_support::regex::Regex;
+use run_make_support::{rfs, rustc};
+
+fn manifest(hash: &str, validated: &[&str], unvalidated: &[&str]) {
+    let list = |items: &[&str]| {
+        items.iter().map(|item| format!("\"{item}\"")).collect::<Vec<_>>().join(", ")
+    };
+    rfs::write(
+        "manifest.json",
+        format!(
+            r#"{{
+                "upstream": {{
+                    "hash": "{hash}",
+                    "validated": [{}],
+                    "unvalidated": [{}]
+                }},
+                "not_a_dependency": {{ "hash": "0" }}
+            }}"#,
+            list(validated),
+            list(unvalidated),
+        ),
+    );
+}
+
+fn downstream() -> run_make_support::Rustc {
+    let mut rustc = rustc();
+    rustc.input("downstream.rs").arg("-Zferrocene-validation-manifest=manifest.json");
+    rustc
+}
+
+fn main() {
+    rustc().input("upstream.rs").run();
+
+    // Without a manifest, the attributes of `upstream` are used.
+    rustc()
+        .input("downstream.rs")
+        .run_fail()
+        .assert_stderr_contains("vetted")
+        .assert_stderr_not_contains("distrusted");
+
+    // A manifest for another version of `upstream` is rejected.
+    manifest("0123456789abcdef", &["upstream::vetted"], &[]);
+    let output = downstream().run_fail();
+    output.assert_stderr_contains("is out of date for crate `upstream`");
+    let stderr = output.stderr_utf8();
+    let hash =
+        &Regex::new("`upstream` has hash `([0-9a-f]+)`").unwrap().captures(&stderr).unwrap()[1];
+
+    // The manifest takes precedence over the attributes.
+    manifest(hash, &["upstream::vetted"], &["upstream::distrusted"]);
+    downstream()
+        .run_fail()
+        .assert_stderr_contains("distrusted")
+        .assert_stderr_not_contains("vetted");
+
+    // Items not listed in the manifest keep their attributes.
+    manifest(hash, &["upstream::vetted"], &[]);
+    downstream().run();
+
+    // An item can't be both.
+    manifest(hash, &["upstream::vetted"], &["upstream::vetted"]);
+    downstream()
+        .run_fail()
+        .assert_stderr_contains("lists `upstream::vetted` as both validated and unvalidated");
+
+    // A typo in an item path is an error rather than being ignored.
+    manifest(hash, &["upstream::veted"], &[]);
+    downstream().run_fail().assert_stderr_contains(
+        "lists `upstream::veted`, which is not an item in crate `upstream`",
+    );
+}
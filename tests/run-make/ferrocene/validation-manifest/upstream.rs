@@ -0,0 +1,6 @@
+#![crate_type = "rlib"]
+
+pub fn vetted() {}
+
+#[ferrocene::prevalidated]
+pub fn distrusted() {}
@@ -0,0 +1,28 @@
+#![crate_type = "lib"]
+
+#[ferrocene::prevalidated]
+pub fn entrypoint() {
+    helper();
+    drivers::validated_driver();
+}
+
+fn helper() {}
+
+#[ferrocene::entrypoint]
+pub fn isr() {
+    helper();
+}
+
+pub fn unused() {}
+
+pub mod drivers {
+    #[ferrocene::prevalidated]
+    pub fn validated_driver() {
+        let f: fn() = legacy_driver;
+        f();
+    }
+
+    pub fn legacy_driver() {}
+
+    pub fn unused_driver() {}
+}
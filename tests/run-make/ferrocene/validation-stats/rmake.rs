@@ -0,0 +1,44 @@
+//@ needs-target-std
+//
+// `--print=ferrocene-validation-stats` counts, for each module, how many functions are validated,
+// entry points, unvalidated but reachable from a validated function, and unreachable. Check the
+// counts when generating code, when only emitting metadata, and when the post-mono pass is loaded
+// from the incremental cache.
+
+use run_make_support::{rfs, rustc};
+
+const EXPECTED: &str = "\
+module        prevalidated  entrypoint  reachable  unreachable
+lib                      1           1          1            1
+lib::drivers             1           0          1            1
+total                    2           1          2            2
+";
+
+fn main() {
+    rustc()
+        .input("lib.rs")
+        .arg("-Zunstable-options")
+        .print("ferrocene-validation-stats=stats.txt")
+        .run();
+    assert_eq!(rfs::read_to_string("stats.txt"), EXPECTED);
+
+    rustc()
+        .input("lib.rs")
+        .emit("metadata")
+        .arg("-Zunstable-options")
+        .print("ferrocene-validation-stats")
+        .run()
+        .assert_stdout_equals(EXPECTED);
+
+    // The second build doesn't re-run `collect_and_partition_mono_items`.
+    for _ in 0..2 {
+        rfs::remove_file("stats.txt");
+        rustc()
+            .input("lib.rs")
+            .incremental("incr")
+            .arg("-Zunstable-options")
+            .print("ferrocene-validation-stats=stats.txt")
+            .run();
+        assert_eq!(rfs::read_to_string("stats.txt"), EXPECTED);
+    }
+}
@@ -0,0 +1,2 @@
+#[ferrocene::prevalidated]
+pub fn a() {}
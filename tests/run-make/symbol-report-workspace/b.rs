@@ -0,0 +1,4 @@
+#[ferrocene::prevalidated]
+pub fn b() {
+    a::a();
+}
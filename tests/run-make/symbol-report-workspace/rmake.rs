@@ -0,0 +1,42 @@
+/// Tests running symbol-report as `RUSTC_WORKSPACE_WRAPPER`, the way cargo would: each crate is
+/// compiled as usual, its report is written to `SYMBOL_REPORT_OUT_DIR`, and `symbol-report merge`
+/// merges the reports of every crate.
+use std::path::PathBuf;
+
+use run_make_support::path_helpers::build_root;
+use run_make_support::serde_json;
+use run_make_support::symbol_report::SymbolReport;
+use run_make_support::{cmd, cwd, rfs};
+
+fn main() {
+    let rustc_path: PathBuf = std::env::var_os("RUSTC").expect("RUSTC env var is not set").into();
+    let rel_rustc_path = rustc_path.strip_prefix(build_root()).unwrap().to_str().unwrap();
+    let stage = if rel_rustc_path.contains("stage1") { 1 } else { 2 };
+    let symbol_report_path = build_root().join(format!("stage{stage}-tools-bin/symbol-report"));
+
+    let out_dir = cwd().join("reports");
+    for (krate, extra) in [("a", &[][..]), ("b", &["--extern", "a=liba.rlib"][..])] {
+        let mut command = cmd(&symbol_report_path);
+        for (k, maybe_v) in run_make_support::rustc().into_raw_command().get_envs() {
+            if let Some(v) = maybe_v {
+                command.env(k, v);
+            }
+        }
+        // Like cargo, pass the wrapped rustc as the first argument.
+        command
+            .env("SYMBOL_REPORT_OUT_DIR", &out_dir)
+            .arg(&rustc_path)
+            .arg(format!("{krate}.rs"))
+            .arg("--crate-type=rlib")
+            .args(extra)
+            .run();
+    }
+
+    // The crates were compiled, not only analyzed.
+    assert!(cwd().join("liba.rlib").exists());
+    assert!(cwd().join("libb.rlib").exists());
+
+    cmd(&symbol_report_path).arg("merge").arg(&out_dir).arg("merged.json").run();
+    let report: SymbolReport = serde_json::from_str(&rfs::read_to_string("merged.json")).unwrap();
+    assert_eq!(report.to_qualified_fn_list(), ["a::a", "b::b"]);
+}
@@ -75,6 +75,12 @@ fn main() {
                 function.filename =
                     function.filename.strip_prefix(&testcases_path).unwrap().to_owned();
             }
+            actual_report.annotations = std::mem::take(&mut actual_report.annotations)
+                .into_iter()
+                .map(|(filename, lines)| {
+                    (filename.strip_prefix(&testcases_path).unwrap().to_owned(), lines)
+                })
+                .collect();
 
             // Parse the expected file as a symbol report.
             let expected_text = std::fs::read_to_string(expected_path).unwrap();
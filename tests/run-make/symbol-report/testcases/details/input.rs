@@ -0,0 +1,26 @@
+pub struct Wrapper<T>(T);
+
+impl<T> Wrapper<T> {
+    #[ferrocene::prevalidated]
+    pub fn get<const N: usize>(&self) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(not(feature = "nope"))]
+#[ferrocene::prevalidated]
+pub fn gated() {}
+
+#[ferrocene::prevalidated]
+pub fn checked(x: Option<u8>) -> u8 {
+    match x {
+        Some(x) => x,
+        #[ferrocene::annotation("Never called with `None` in tests")]
+        None => 0,
+    }
+}
+
+pub fn unvalidated() {}
+
+#[ferrocene::entrypoint]
+pub fn entry() {}
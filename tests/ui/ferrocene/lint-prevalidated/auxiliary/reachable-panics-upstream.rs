@@ -0,0 +1,19 @@
+#![crate_type = "lib"]
+
+// Neither generic nor inline, so its MIR is not available in other crates.
+#[inline(never)]
+pub fn opaque(x: u32) -> u32 {
+    x.wrapping_add(1)
+}
+
+// Generic, so downstream crates walk its body.
+pub fn generic_calls_opaque<T>(_: T) -> u32 {
+    opaque(1)
+}
+
+// Validated, so it was checked for reachable panics when this crate was compiled.
+#[ferrocene::prevalidated]
+#[inline(never)]
+pub fn validated_opaque(x: u32) -> u32 {
+    x.wrapping_add(1)
+}
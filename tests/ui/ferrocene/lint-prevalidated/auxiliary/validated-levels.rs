@@ -0,0 +1,10 @@
+#![crate_type = "lib"]
+
+#[ferrocene::prevalidated(standard = "ISO 26262", level = "ASIL-B")]
+pub fn asil_b() {}
+
+#[ferrocene::prevalidated(standard = "ISO 26262", level = "ASIL-D")]
+pub fn asil_d() {}
+
+#[ferrocene::prevalidated]
+pub fn any_level() {}
@@ -0,0 +1,79 @@
+// With `-Z ferrocene-fn-ptr-escape-analysis`, casts to function pointers are only linted if the
+// pointer may be called or escape the function.
+
+//@ build-fail
+//@ compile-flags: -Z ferrocene-fn-ptr-escape-analysis
+
+#![crate_type = "lib"]
+#![deny(ferrocene::unvalidated)]
+#![allow(unused_assignments)]
+
+fn unvalidated() {}
+
+#[ferrocene::prevalidated]
+fn validated() {}
+
+#[ferrocene::prevalidated]
+fn takes_ptr(_: fn()) {}
+
+#[ferrocene::prevalidated]
+pub fn overwritten() {
+    let mut handler: fn() = unvalidated; // ok: never called
+    handler = validated;
+    handler();
+}
+
+#[ferrocene::prevalidated]
+pub fn dispatch_table(i: usize) {
+    let mut table: [fn(); 2] = [unvalidated; 2]; // ok: never called
+    table = [validated; 2];
+    table[i]();
+}
+
+#[ferrocene::prevalidated]
+pub fn never_used() {
+    let _unused: fn() = unvalidated; // ok
+}
+
+#[ferrocene::prevalidated]
+pub fn called() {
+    let f: fn() = unvalidated; //~ ERROR unvalidated
+    f();
+}
+
+#[ferrocene::prevalidated]
+pub fn table_called(i: usize) {
+    let table: [fn(); 2] = [validated, unvalidated]; //~ ERROR unvalidated
+    table[i]();
+}
+
+#[ferrocene::prevalidated]
+pub fn returned() -> fn() {
+    unvalidated //~ ERROR unvalidated
+}
+
+#[ferrocene::prevalidated]
+pub fn passed() {
+    takes_ptr(unvalidated); //~ ERROR unvalidated
+}
+
+#[ferrocene::prevalidated]
+pub fn borrowed() {
+    let f: fn() = unvalidated; //~ ERROR unvalidated
+    let r = &f;
+    r();
+}
+
+struct OnDrop(fn());
+
+impl Drop for OnDrop {
+    #[ferrocene::prevalidated]
+    fn drop(&mut self) {
+        (self.0)();
+    }
+}
+
+#[ferrocene::prevalidated]
+pub fn dropped() {
+    let _guard = OnDrop(unvalidated); //~ ERROR unvalidated
+}
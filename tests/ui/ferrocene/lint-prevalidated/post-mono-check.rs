@@ -0,0 +1,25 @@
+// `-Z ferrocene-post-mono-check` runs the post-mono pass without codegen, so that `cargo check`
+// also reports unvalidated calls in instantiations of generic functions.
+
+//@ check-fail
+//@ compile-flags: -Z ferrocene-post-mono-check
+
+#![crate_type = "lib"]
+#![deny(ferrocene::unvalidated)]
+
+struct Unvalidated;
+
+impl Clone for Unvalidated {
+    fn clone(&self) -> Self { Unvalidated }
+}
+
+// has to be checked post-mono
+#[ferrocene::prevalidated]
+fn uninstantiated_generic<T: Clone>(x: T) {
+    x.clone(); //~ ERROR calls an unvalidated
+}
+
+#[ferrocene::prevalidated]
+pub fn instantiates() {
+    uninstantiated_generic(Unvalidated);
+}
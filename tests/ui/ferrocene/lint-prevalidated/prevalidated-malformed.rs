@@ -0,0 +1,20 @@
+// Arguments to `#[ferrocene::prevalidated]` must name a known safety standard and level.
+
+//@ build-fail
+
+#![crate_type = "lib"]
+
+#[ferrocene::prevalidated(standard = "ISO 26262")] //~ ERROR malformed
+pub fn missing_level() {}
+
+#[ferrocene::prevalidated(asil = "D")] //~ ERROR malformed
+pub fn unknown_key() {}
+
+#[ferrocene::prevalidated = "ISO 26262"] //~ ERROR malformed
+pub fn name_value() {}
+
+#[ferrocene::prevalidated(standard = "ISO 9001", level = "A")] //~ ERROR unknown safety standard
+pub fn unknown_standard() {}
+
+#[ferrocene::prevalidated(standard = "ISO 26262", level = "SIL 4")] //~ ERROR unknown ISO 26262 level
+pub fn unknown_level() {}
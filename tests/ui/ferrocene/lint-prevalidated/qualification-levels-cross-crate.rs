@@ -0,0 +1,23 @@
+// The qualification levels of upstream items are read from their metadata.
+
+//@ check-fail
+//@ aux-build: validated-levels.rs
+
+#![crate_type = "lib"]
+#![deny(ferrocene::unvalidated)] //~ NOTE defined here
+
+extern crate validated_levels;
+
+#[ferrocene::prevalidated(standard = "ISO 26262", level = "ASIL-D")] //~ NOTE marked
+pub fn entrypoint() { //~ NOTE is validated
+    validated_levels::asil_d(); // ok
+    validated_levels::any_level(); // ok: bare annotations place no restrictions
+    validated_levels::asil_b(); //~ ERROR function validated for ISO 26262 ASIL-D calls an insufficiently validated function
+    //~^ NOTE everything it uses must be validated for ISO 26262 at ASIL-D or above
+}
+
+#[ferrocene::prevalidated(standard = "ISO 26262", level = "ASIL-B")]
+pub fn lower_level() {
+    validated_levels::asil_b(); // ok
+    validated_levels::asil_d(); // ok
+}
@@ -0,0 +1,46 @@
+// Items validated against a safety standard may only use items validated for the same standard,
+// at the same level or above.
+
+//@ check-fail
+//@ compile-flags: -Z deduplicate-diagnostics=yes
+
+#![crate_type = "lib"]
+#![deny(ferrocene::unvalidated)] //~ NOTE defined here
+
+#[ferrocene::prevalidated(standard = "ISO 26262", level = "ASIL-B")]
+fn asil_b() {} //~ NOTE not validated for ISO 26262 ASIL-D
+
+#[ferrocene::prevalidated(standard = "ISO 26262", level = "ASIL-D")]
+fn asil_d() {} //~ NOTE not validated for IEC 61508 SIL 2
+
+#[ferrocene::prevalidated(standard = "IEC 61508", level = "SIL 4")]
+fn sil_4() {} //~ NOTE not validated for ISO 26262 ASIL-D
+//~| NOTE not validated for ISO 26262 ASIL-A
+
+#[ferrocene::prevalidated]
+fn any_level() {}
+
+#[ferrocene::prevalidated(standard = "ISO 26262", level = "ASIL-D")] //~ NOTE marked
+pub fn entrypoint() { //~ NOTE is validated
+    asil_d(); // ok
+    any_level(); // ok: bare annotations place no restrictions
+    asil_b(); //~ ERROR function validated for ISO 26262 ASIL-D calls an insufficiently validated function
+    //~^ NOTE everything it uses must be validated for ISO 26262 at ASIL-D or above
+    sil_4(); //~ ERROR function validated for ISO 26262 ASIL-D calls an insufficiently validated function
+    //~^ NOTE everything it uses must be validated for ISO 26262 at ASIL-D or above
+}
+
+#[ferrocene::prevalidated]
+pub fn no_requirements() {
+    asil_b(); // ok
+    sil_4(); // ok
+}
+
+#[ferrocene::prevalidated(standard = "ISO 26262", level = "ASIL-A")] //~ NOTE marked
+#[ferrocene::prevalidated(standard = "IEC 61508", level = "SIL 2")]
+pub fn multiple_standards() { //~ NOTE is validated
+    sil_4(); //~ ERROR function validated for ISO 26262 ASIL-A calls an insufficiently validated function
+    //~^ NOTE everything it uses must be validated for ISO 26262 at ASIL-A or above
+    asil_d(); //~ ERROR function validated for IEC 61508 SIL 2 calls an insufficiently validated function
+    //~^ NOTE everything it uses must be validated for IEC 61508 at SIL 2 or above
+}
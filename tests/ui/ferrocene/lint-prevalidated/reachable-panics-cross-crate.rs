@@ -0,0 +1,42 @@
+// `-Z deny-reachable-panics` can't see the body of functions in other crates whose MIR isn't
+// available, so it reports calls to them as possibly panicking, unless they are known not to, or
+// were already checked in their own crate.
+
+//@ build-fail
+//@ aux-build: reachable-panics-upstream.rs
+//@ compile-flags: -Z deny-reachable-panics
+
+#![crate_type = "lib"]
+
+extern crate reachable_panics_upstream;
+
+unsafe extern "C" {
+    safe fn abs(x: i32) -> i32;
+}
+
+#[ferrocene::prevalidated]
+pub fn calls_opaque(x: u32) -> u32 {
+    reachable_panics_upstream::opaque(x) //~ ERROR may panic (body unavailable)
+}
+
+#[ferrocene::prevalidated]
+pub fn calls_generic() -> u32 {
+    reachable_panics_upstream::generic_calls_opaque(()) //~ ERROR body unavailable
+}
+
+#[ferrocene::prevalidated]
+pub fn calls_foreign(x: i32) -> i32 {
+    abs(x) // ok: foreign functions are not Rust code
+}
+
+#[ferrocene::prevalidated]
+pub fn calls_validated_opaque(x: u32) -> u32 {
+    reachable_panics_upstream::validated_opaque(x) // ok: validated, so checked upstream
+}
+
+#[ferrocene::prevalidated]
+pub fn justified(x: u32) -> u32 {
+    #[allow(ferrocene::reachable_panic, reason = "`opaque` is checked in its own crate")]
+    let y = reachable_panics_upstream::opaque(x);
+    y
+}
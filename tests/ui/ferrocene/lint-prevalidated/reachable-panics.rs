@@ -0,0 +1,62 @@
+// `-Z deny-reachable-panics` reports every panic reachable from a validated root, and names the
+// operation it comes from.
+
+//@ build-fail
+//@ compile-flags: -Z deny-reachable-panics -C overflow-checks=on
+// The path through the standard library is an implementation detail.
+//@ normalize-stderr: " -> `[^`]*::[^`]*`( -> `[^`]*`)*" -> " -> ..."
+
+#![crate_type = "lib"]
+
+#[ferrocene::prevalidated]
+pub fn index(x: &[u32], i: usize) -> u32 {
+    x[i] //~ ERROR reachable panic: index out of bounds
+}
+
+#[ferrocene::prevalidated]
+pub fn add(a: u32, b: u32) -> u32 {
+    a + b //~ ERROR reachable panic: arithmetic overflow
+}
+
+#[ferrocene::prevalidated]
+pub fn divide(a: u32, b: u32) -> u32 {
+    a / b //~ ERROR reachable panic: division by zero
+}
+
+#[ferrocene::prevalidated]
+pub fn unwrap(x: Option<u32>) -> u32 {
+    x.unwrap() //~ ERROR reachable panic: `unwrap` or `expect`
+}
+
+#[ferrocene::prevalidated]
+pub fn slice(x: &[u32], n: usize) -> &[u32] {
+    &x[..n] //~ ERROR reachable panic: slice range out of bounds
+}
+
+#[ferrocene::prevalidated]
+pub fn explicit(fail: bool) {
+    if fail {
+        panic!("explicit"); //~ ERROR reachable panic: explicit panic
+    }
+}
+
+fn helper(x: &[u32]) -> u32 {
+    x[1] //~ ERROR reachable panic: index out of bounds
+}
+
+#[ferrocene::prevalidated]
+pub fn calls_helper(x: &[u32]) -> u32 {
+    helper(x)
+}
+
+#[ferrocene::prevalidated]
+pub fn justified(x: &[u32]) -> u32 {
+    #[allow(ferrocene::reachable_panic, reason = "callers check that `x` is not empty")]
+    let first = x[0];
+    first
+}
+
+// Not validated, so not checked.
+pub fn unvalidated(x: &[u32]) -> u32 {
+    x[0]
+}
@@ -0,0 +1,36 @@
+// `-Z ferrocene-validated-roots` treats exported and `extern "C"` functions as validated entry
+// points, and `#[ferrocene::entrypoint]` marks individual functions as entry points.
+
+//@ check-fail
+//@ compile-flags: -Z ferrocene-validated-roots=exported,extern-c
+
+#![crate_type = "lib"]
+#![deny(ferrocene::unvalidated)]
+
+fn unvalidated() {}
+
+#[unsafe(no_mangle)]
+pub fn isr_handler() {
+    unvalidated(); //~ ERROR unvalidated
+}
+
+pub extern "C" fn called_from_c() {
+    unvalidated(); //~ ERROR unvalidated
+}
+
+#[ferrocene::entrypoint]
+pub fn entrypoint() {
+    unvalidated(); //~ ERROR unvalidated
+}
+
+// Not an entry point.
+pub fn not_a_root() {
+    unvalidated();
+}
+
+// Entry points are not validated themselves: calling one from validated code is linted.
+#[ferrocene::prevalidated]
+pub fn validated_caller() {
+    isr_handler(); //~ ERROR unvalidated
+    entrypoint(); //~ ERROR unvalidated
+}
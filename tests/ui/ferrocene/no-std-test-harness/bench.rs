@@ -0,0 +1,11 @@
+// The `no_std` test harness doesn't run benchmarks.
+
+//@ compile-flags: --test -Z no-std-test-harness
+
+#![feature(test)]
+
+extern crate test;
+
+#[bench]
+fn bench(_: &mut test::Bencher) {}
+//~^^ ERROR benchmarks are not supported by the `no_std` test harness
@@ -0,0 +1,48 @@
+// With `-Z no-std-test-harness`, the tests are run by `core::ferrocene_test::harness`, which
+// writes the same JSON events as libtest's `--format json` to the output set by the crate.
+
+//@ compile-flags: --test -Z no-std-test-harness
+//@ run-fail
+//@ check-run-results
+
+#![feature(custom_test_frameworks, ferrocene_test)]
+#![test_runner(crate::runner)]
+
+#[cfg(test)]
+use core::ferrocene_test::harness;
+#[cfg(test)]
+use std::io::Write;
+
+#[cfg(test)]
+static OUTPUT: fn(&[u8]) = |bytes| std::io::stdout().write_all(bytes).unwrap();
+
+#[cfg(test)]
+fn runner(tests: &[&harness::TestDescAndFn]) {
+    harness::set_output(&OUTPUT);
+    if !harness::run(tests) {
+        std::process::exit(101);
+    }
+}
+
+#[test]
+fn a_passing() {}
+
+#[test]
+fn b_returning_ok() -> Result<(), String> {
+    Ok(())
+}
+
+#[test]
+fn c_returning_err() -> Result<(), String> {
+    Err("something \"went\" wrong".into())
+}
+
+#[test]
+#[ignore = "not today"]
+fn d_ignored() {}
+
+#[test]
+#[should_panic]
+fn e_should_panic() {
+    panic!()
+}
@@ -0,0 +1,111 @@
+//@ no-prefer-dynamic
+// Start-up code and Arm semihosting calls for the test programs run by `semihosting-runner`, see
+// `ferrocene/tools/semihosting-runner/README.md`. Test programs provide `test_main`.
+
+#![crate_type = "rlib"]
+#![no_std]
+#![feature(ferrocene_test)]
+
+unsafe extern "Rust" {
+    safe fn test_main();
+}
+
+/// `SYS_WRITE0`: writes a NUL-terminated message to the standard output of the runner.
+pub fn write0(message: &[u8]) {
+    assert_eq!(Some(&0), message.last());
+    unsafe { semihosting(0x04, message.as_ptr() as usize) };
+}
+
+/// `SYS_WRITEC`: writes `bytes` to the standard output of the runner, one at a time.
+pub fn write(bytes: &[u8]) {
+    for byte in bytes {
+        unsafe { semihosting(0x03, byte as *const u8 as usize) };
+    }
+}
+
+/// `SYS_EXIT`: stops the emulator, which exits with `status`.
+pub fn exit(status: usize) -> ! {
+    // ADP_Stopped_ApplicationExit and ADP_Stopped_RunTimeErrorUnknown.
+    let reason: usize = if status == 0 { 0x20026 } else { 0x20023 };
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        semihosting(0x18, reason)
+    };
+    #[cfg(target_arch = "aarch64")]
+    unsafe {
+        // On 64-bit targets the parameter is a pointer to the reason and the exit status.
+        let block = [reason, status];
+        semihosting(0x18, block.as_ptr() as usize)
+    };
+    loop {}
+}
+
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo<'_>) -> ! {
+    // Reports the failure of the running test, for programs using `-Z no-std-test-harness`.
+    core::ferrocene_test::harness::report_panic(info);
+    write0(b"panicked\n\0");
+    exit(1)
+}
+
+#[unsafe(no_mangle)]
+extern "C" fn semihosting_entry() -> ! {
+    test_main();
+    exit(0)
+}
+
+#[cfg(target_arch = "arm")]
+unsafe fn semihosting(operation: usize, parameter: usize) -> usize {
+    let result;
+    unsafe {
+        core::arch::asm!(
+            "bkpt #0xab",
+            inout("r0") operation => result,
+            in("r1") parameter,
+            options(nostack),
+        )
+    };
+    result
+}
+
+#[cfg(target_arch = "aarch64")]
+unsafe fn semihosting(operation: usize, parameter: usize) -> usize {
+    let result;
+    unsafe {
+        core::arch::asm!(
+            "hlt #0xf000",
+            inout("x0") operation => result,
+            in("x1") parameter,
+            options(nostack),
+        )
+    };
+    result
+}
+
+// Cortex-M: the reset vector, called with the stack set up. The FPU is disabled at reset.
+#[cfg(target_arch = "arm")]
+#[unsafe(no_mangle)]
+unsafe extern "C" fn _start() -> ! {
+    #[cfg(target_abi = "eabihf")]
+    unsafe {
+        let cpacr = 0xE000_ED88 as *mut u32;
+        cpacr.write_volatile(cpacr.read_volatile() | (0xF << 20));
+        core::arch::asm!("dsb", "isb");
+    }
+    semihosting_entry()
+}
+
+// AArch64: QEMU jumps to the ELF entry point at EL1, without a stack.
+#[cfg(target_arch = "aarch64")]
+core::arch::global_asm!(
+    ".section .text._start, \"ax\"",
+    ".global _start",
+    "_start:",
+    "    ldr x30, =__stack_top",
+    "    mov sp, x30",
+    // Don't trap floating point and SIMD instructions, which the compiler may use.
+    "    mov x0, #0x300000",
+    "    msr cpacr_el1, x0",
+    "    isb",
+    "    bl semihosting_entry",
+);
@@ -0,0 +1,39 @@
+// `#![no_std]` tests run on the board through the `main` function generated by
+// `-Z no-std-test-harness`, and the panic handler reports the test that panicked.
+
+//@ compile-flags: --test -Z no-std-test-harness
+//@ run-fail
+//@ check-run-results
+//@ only-semihosting
+//@ aux-build: semihosting.rs
+
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks, ferrocene_test)]
+#![reexport_test_harness_main = "run_tests"]
+
+extern crate semihosting;
+
+use core::ferrocene_test::harness;
+
+static OUTPUT: fn(&[u8]) = semihosting::write;
+
+#[unsafe(no_mangle)]
+fn test_main() {
+    harness::set_output(&OUTPUT);
+    run_tests();
+    if !harness::succeeded() {
+        semihosting::exit(101);
+    }
+}
+
+#[test]
+fn a_passing() {}
+
+#[test]
+fn b_panicking() {
+    panic!("oh no");
+}
+
+#[test]
+fn c_never_run() {}
@@ -0,0 +1,16 @@
+// A panicking bare-metal test program makes the runner exit with a failure.
+
+//@ run-fail
+//@ check-run-results
+//@ only-semihosting
+//@ aux-build: semihosting.rs
+
+#![no_std]
+#![no_main]
+
+extern crate semihosting;
+
+#[unsafe(no_mangle)]
+fn test_main() {
+    panic!();
+}
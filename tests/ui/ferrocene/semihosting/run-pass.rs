@@ -0,0 +1,17 @@
+// Bare-metal test programs run under QEMU, and report their output and success through
+// semihosting.
+
+//@ run-pass
+//@ check-run-results
+//@ only-semihosting
+//@ aux-build: semihosting.rs
+
+#![no_std]
+#![no_main]
+
+extern crate semihosting;
+
+#[unsafe(no_mangle)]
+fn test_main() {
+    semihosting::write0(b"hello from the board\n\0");
+}